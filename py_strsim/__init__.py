@@ -0,0 +1,5 @@
+"""Alias of `strsim` under the project's name, so that `python -m
+py_strsim` runs the command-line interface too."""
+
+from strsim import *
+from strsim import __all__
@@ -0,0 +1,4 @@
+from strsim.__main__ import main
+
+if __name__ == '__main__':
+    main(prog='python -m py_strsim')
@@ -5,12 +5,19 @@ setup(
     name='strsim',
     version='0.0.1',
     packages=find_packages(
-        where='strsim',
+        include=['strsim', 'py_strsim'],
     ),
     include_package_data=True,
+    extras_require={
+        'numpy': ['numpy'],
+    },
+    entry_points={
+        'console_scripts': ['py-strsim = strsim.__main__:main'],
+    },
     rust_extensions=[
         RustExtension(
             "strsim._py_strsim",
+            features=["extension-module"],
         )
     ],
 )
\ No newline at end of file
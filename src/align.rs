@@ -0,0 +1,344 @@
+// ------------------------------------------------------------------------
+//  Edit Operations
+// ------------------------------------------------------------------------
+
+/// Kind of a single step of an alignment, named after `difflib` opcodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tag {
+    Equal,
+    Replace,
+    Insert,
+    Delete,
+}
+
+impl Tag {
+    /// Name of the tag as used by `difflib` and `python-Levenshtein`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tag::Equal => "equal",
+            Tag::Replace => "replace",
+            Tag::Insert => "insert",
+            Tag::Delete => "delete",
+        }
+    }
+}
+
+/// A single alignment step. `src` and `dst` are the positions in `a` and `b`
+/// the step applies to.
+pub type Step = (Tag, usize, usize);
+
+/// A `difflib` opcode, `(tag, i1, i2, j1, j2)`, describing how `a[i1:i2]`
+/// turns into `b[j1:j2]`.
+pub type Opcode = (Tag, usize, usize, usize, usize);
+
+/// Computes an optimal Levenshtein alignment of two character slices with the
+/// full dynamic programming matrix. Every character of both inputs is
+/// covered by exactly one step, including `Equal` steps.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First sequence to align
+/// * `b` - Secondary sequence to align to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Alignment steps in order from the start of both sequences
+pub fn alignment_path(a: &[char], b: &[char]) -> Vec<Step> {
+    let width = b.len() + 1;
+    let mut matrix = vec![0usize; (a.len() + 1) * width];
+    for (j, cell) in matrix.iter_mut().take(width).enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        matrix[i * width] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            matrix[i * width + j] = (matrix[(i - 1) * width + j - 1] + cost)
+                .min(matrix[(i - 1) * width + j] + 1)
+                .min(matrix[i * width + j - 1] + 1);
+        }
+    }
+
+    let mut path = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 || j > 0 {
+        let current = matrix[i * width + j];
+        if i > 0 && j > 0 {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            if current == matrix[(i - 1) * width + j - 1] + cost {
+                i -= 1;
+                j -= 1;
+                path.push((if cost == 0 { Tag::Equal } else { Tag::Replace }, i, j));
+                continue;
+            }
+        }
+        if i > 0 && current == matrix[(i - 1) * width + j] + 1 {
+            i -= 1;
+            path.push((Tag::Delete, i, j));
+        } else {
+            j -= 1;
+            path.push((Tag::Insert, i, j));
+        }
+    }
+    path.reverse();
+    path
+}
+
+/// Drops the `Equal` steps of an alignment path, leaving the edit operations
+/// in the format of `python-Levenshtein`'s `editops`.
+pub fn editops(path: &[Step]) -> Vec<Step> {
+    path
+        .iter()
+        .filter(|(tag, _, _)| *tag != Tag::Equal)
+        .copied()
+        .collect()
+}
+
+/// Groups consecutive steps of the same kind of an alignment path into
+/// `difflib`-compatible opcodes.
+pub fn opcodes(path: &[Step]) -> Vec<Opcode> {
+    let mut codes: Vec<Opcode> = Vec::new();
+    for &(tag, i, j) in path {
+        let (di, dj) = match tag {
+            Tag::Equal | Tag::Replace => (1, 1),
+            Tag::Delete => (1, 0),
+            Tag::Insert => (0, 1),
+        };
+        match codes.last_mut() {
+            Some(last) if last.0 == tag => {
+                last.2 = i + di;
+                last.4 = j + dj;
+            }
+            _ => codes.push((tag, i, i + di, j, j + dj)),
+        }
+    }
+    codes
+}
+
+/// Renders an alignment path as two gapped rows of equal length and a marker
+/// row between them. The marker row holds `|` under equal characters, `.`
+/// under substitutions, and a space under insertions and deletions.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First aligned sequence
+/// * `b` - Secondary aligned sequence
+/// * `path` - Alignment path of `a` and `b`
+/// * `gap` - Character used to pad over insertions and deletions
+/// 
+/// # Returns
+/// 
+/// * `output` - Gapped `a`, marker row, and gapped `b`
+pub fn render(a: &[char], b: &[char], path: &[Step], gap: char) -> (String, String, String) {
+    let mut top = String::with_capacity(path.len());
+    let mut markers = String::with_capacity(path.len());
+    let mut bottom = String::with_capacity(path.len());
+    for &(tag, i, j) in path {
+        let (x, marker, y) = match tag {
+            Tag::Equal => (a[i], '|', b[j]),
+            Tag::Replace => (a[i], '.', b[j]),
+            Tag::Delete => (a[i], ' ', gap),
+            Tag::Insert => (gap, ' ', b[j]),
+        };
+        top.push(x);
+        markers.push(marker);
+        bottom.push(y);
+    }
+    (top, markers, bottom)
+}
+
+
+/// Last row of the Levenshtein matrix of `a` against every prefix of `b`, or
+/// against every suffix of `b` when `reverse` is set, in linear memory.
+fn last_row(a: &[char], b: &[char], reverse: bool) -> Vec<usize> {
+    let at = |s: &[char], k: usize| if reverse { s[s.len() - 1 - k] } else { s[k] };
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 0..a.len() {
+        current[0] = i + 1;
+        let x = at(a, i);
+        for j in 0..b.len() {
+            let cost = usize::from(x != at(b, j));
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous
+}
+
+fn hirschberg(a: &[char], b: &[char], a_offset: usize, b_offset: usize, path: &mut Vec<Step>) {
+    if a.is_empty() {
+        path.extend((0..b.len()).map(|j| (Tag::Insert, a_offset, b_offset + j)));
+    } else if b.is_empty() {
+        path.extend((0..a.len()).map(|i| (Tag::Delete, a_offset + i, b_offset)));
+    } else if a.len() == 1 || b.len() == 1 {
+        path.extend(
+            alignment_path(a, b)
+                .into_iter()
+                .map(|(tag, i, j)| (tag, a_offset + i, b_offset + j)),
+        );
+    } else {
+        let mid = a.len() / 2;
+        let forward = last_row(&a[..mid], b, false);
+        let backward = last_row(&a[mid..], b, true);
+        let split = (0..=b.len())
+            .min_by_key(|&j| forward[j] + backward[b.len() - j])
+            .unwrap_or(0);
+
+        hirschberg(&a[..mid], &b[..split], a_offset, b_offset, path);
+        hirschberg(&a[mid..], &b[split..], a_offset + mid, b_offset + split, path);
+    }
+}
+
+/// Computes an optimal Levenshtein alignment with Hirschberg's algorithm,
+/// which needs memory linear in the input lengths instead of the quadratic
+/// matrix of `alignment_path`. The steps have the same format as the ones of
+/// `alignment_path`, and the same cost, but where several alignments are
+/// optimal the two may return different ones.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First sequence to align
+/// * `b` - Secondary sequence to align to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Alignment steps in order from the start of both sequences
+pub fn alignment_path_linear(a: &[char], b: &[char]) -> Vec<Step> {
+    let mut path = Vec::with_capacity(a.len().max(b.len()));
+    hirschberg(a, b, 0, 0, &mut path);
+    path
+}
+
+// ------------------------------------------------------------------------
+//  Alignment Scores
+// ------------------------------------------------------------------------
+
+/// Levenshtein distance of two character slices together with the largest
+/// number of identical characters over all alignments of that distance.
+pub fn distance_and_matches(a: &[char], b: &[char]) -> (usize, usize) {
+    // Cells hold (distance, matches) and prefer a lower distance, then more
+    // matches.
+    let better = |x: (usize, usize), y: (usize, usize)| {
+        if x.0 < y.0 || (x.0 == y.0 && x.1 > y.1) { x } else { y }
+    };
+
+    let mut previous: Vec<(usize, usize)> = (0..=b.len()).map(|j| (j, 0)).collect();
+    let mut current = vec![(0, 0); b.len() + 1];
+    for (i, &x) in a.iter().enumerate() {
+        current[0] = (i + 1, 0);
+        for (j, &y) in b.iter().enumerate() {
+            let diagonal = if x == y {
+                (previous[j].0, previous[j].1 + 1)
+            } else {
+                (previous[j].0 + 1, previous[j].1)
+            };
+            let deletion = (previous[j + 1].0 + 1, previous[j + 1].1);
+            let insertion = (current[j].0 + 1, current[j].1);
+            current[j + 1] = better(better(diagonal, deletion), insertion);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Calculates the LIG3 similarity, `2I / (2I + D)`, where `D` is the
+/// Levenshtein distance and `I` the number of identical characters in an
+/// optimal alignment. The returned value is between 0.0 and 1.0 (higher
+/// value means more similar).
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Similarity between `a` and `b`
+pub fn lig3(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let (distance, matches) = distance_and_matches(&a, &b);
+    if matches == 0 {
+        return 0.0;
+    }
+    (2 * matches) as f64 / (2 * matches + distance) as f64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    /// Checks that `path` turns `a` into `b` step by step and returns its
+    /// number of edits.
+    fn cost(a: &[char], b: &[char], path: &[Step]) -> usize {
+        let (mut i, mut j) = (0, 0);
+        for &(tag, src, dst) in path {
+            assert_eq!((src, dst), (i, j), "{:?}", path);
+            match tag {
+                Tag::Equal | Tag::Replace => {
+                    assert_eq!(a[i] == b[j], tag == Tag::Equal, "{:?}", path);
+                    (i, j) = (i + 1, j + 1);
+                }
+                Tag::Delete => i += 1,
+                Tag::Insert => j += 1,
+            }
+        }
+        assert_eq!((i, j), (a.len(), b.len()), "{:?}", path);
+        editops(path).len()
+    }
+
+    /// Number of distinct optimal alignments of `a` and `b`.
+    fn optimal_alignments(a: &[char], b: &[char]) -> usize {
+        let width = b.len() + 1;
+        // Each cell holds its distance and the number of optimal paths to it.
+        let mut cells = vec![(0usize, 1usize); (a.len() + 1) * width];
+        for i in 0..=a.len() {
+            for j in 0..=b.len() {
+                let mut steps = Vec::new();
+                if i > 0 && j > 0 {
+                    steps.push((cells[(i - 1) * width + j - 1], usize::from(a[i - 1] != b[j - 1])));
+                }
+                if i > 0 {
+                    steps.push((cells[(i - 1) * width + j], 1));
+                }
+                if j > 0 {
+                    steps.push((cells[i * width + j - 1], 1));
+                }
+                if let Some(best) = steps.iter().map(|&((d, _), cost)| d + cost).min() {
+                    let ways = steps.iter().filter(|&&((d, _), cost)| d + cost == best).map(|&((_, n), _)| n).sum();
+                    cells[i * width + j] = (best, ways);
+                }
+            }
+        }
+        cells[a.len() * width + b.len()].1
+    }
+
+    #[test]
+    fn hirschberg_finds_an_optimal_alignment_like_the_full_matrix() {
+        let mut rng = Rng::new(552);
+        let strings = rng.strings(400, 14);
+        let mut unique = 0;
+        let pairs = strings.windows(2).map(|pair| (pair[0].as_str(), pair[1].as_str()));
+        for (x, y) in pairs.chain([("", ""), ("", "abc"), ("a", "b"), ("kitten", "sitting")]) {
+            let (a, b): (Vec<char>, Vec<char>) = (x.chars().collect(), y.chars().collect());
+            let (full, linear) = (alignment_path(&a, &b), alignment_path_linear(&a, &b));
+            let distance = strsim::levenshtein(x, y);
+            assert_eq!(cost(&a, &b, &full), distance);
+            assert_eq!(cost(&a, &b, &linear), distance, "{:?} {:?}", x, y);
+            // Ties between optimal alignments may be broken either way.
+            if optimal_alignments(&a, &b) == 1 {
+                assert_eq!(linear, full, "{:?} {:?}", x, y);
+                unique += 1;
+            }
+        }
+        assert!(unique > 50, "{}", unique);
+    }
+}
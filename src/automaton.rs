@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::error::Result;
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+
+// ------------------------------------------------------------------------
+//  Levenshtein DFA
+// ------------------------------------------------------------------------
+
+/// State every transition of the dead state leads back to.
+const DEAD: usize = 0;
+
+/// Deterministic automaton accepting the strings within Levenshtein
+/// distance `k` of a pattern. Each state is a row of the edit distance
+/// table of the pattern against the characters read so far, with entries
+/// above `k` clipped to `k + 1`. Characters outside the pattern all behave
+/// alike, so the alphabet is the pattern's distinct characters plus one
+/// symbol for everything else.
+struct Dfa {
+    k: usize,
+    symbols: HashMap<char, usize>,
+    /// Transitions of each state, one per symbol, the last for characters
+    /// outside the pattern.
+    transitions: Vec<usize>,
+    /// Distance to the pattern of the strings ending in each state, or
+    /// `None` if it exceeds `k`.
+    distances: Vec<Option<usize>>,
+}
+
+impl Dfa {
+    fn new(pattern: &[char], k: usize) -> Self {
+        let mut symbols: HashMap<char, usize> = HashMap::new();
+        for &c in pattern {
+            let next = symbols.len();
+            symbols.entry(c).or_insert(next);
+        }
+        let alphabet: Vec<Option<char>> = {
+            let mut alphabet = vec![None; symbols.len() + 1];
+            for (&c, &symbol) in &symbols {
+                alphabet[symbol] = Some(c);
+            }
+            alphabet
+        };
+
+        let limit = k + 1;
+        let dead = vec![limit; pattern.len() + 1];
+        let start: Vec<usize> = (0..=pattern.len()).map(|j| j.min(limit)).collect();
+        let mut rows = vec![dead.clone(), start.clone()];
+        let mut ids: HashMap<Vec<usize>, usize> = HashMap::from([(dead, DEAD), (start, 1)]);
+        let mut transitions = vec![DEAD; alphabet.len()];
+
+        let mut state = 1;
+        while state < rows.len() {
+            for &c in &alphabet {
+                let row = &rows[state];
+                let mut next = Vec::with_capacity(row.len());
+                next.push((row[0] + 1).min(limit));
+                for j in 1..row.len() {
+                    let substitution = row[j - 1] + usize::from(Some(pattern[j - 1]) != c);
+                    next.push(substitution.min(row[j] + 1).min(next[j - 1] + 1).min(limit));
+                }
+                let id = match ids.get(&next) {
+                    Some(&id) => id,
+                    None => {
+                        let id = rows.len();
+                        ids.insert(next.clone(), id);
+                        rows.push(next);
+                        id
+                    }
+                };
+                transitions.push(id);
+            }
+            state += 1;
+        }
+
+        let distances = rows
+            .iter()
+            .map(|row| Some(row[pattern.len()]).filter(|&d| d <= k))
+            .collect();
+        Self { k, symbols, transitions, distances }
+    }
+
+    /// Distance between the pattern and `s` if it is at most `k`, reading
+    /// `s` only until no continuation can match.
+    fn distance(&self, s: &str) -> Option<usize> {
+        let width = self.symbols.len() + 1;
+        let mut state = 1;
+        for c in s.chars() {
+            let symbol = self.symbols.get(&c).copied().unwrap_or(width - 1);
+            state = self.transitions[state * width + symbol];
+            if state == DEAD {
+                return None;
+            }
+        }
+        self.distances[state]
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Levenshtein Automaton
+// ------------------------------------------------------------------------
+
+/// Levenshtein automaton for a fixed pattern, compiled once into a DFA.
+/// Checking a candidate then costs one table lookup per character, instead
+/// of filling a row of the edit distance table per character, and stops as
+/// soon as no continuation of the candidate can match.
+/// 
+/// # Arguments
+/// 
+/// * `pattern` - String that candidates are matched against
+/// * `k` - Largest Levenshtein distance of a match
+/// * `preprocessor` - `Preprocessor` applied to the pattern and each
+///   candidate before they are compared, or `None`
+#[cfg_attr(feature = "python", pyclass(module = "strsim", text_signature = "(pattern, k, /, preprocessor=None)"))]
+pub struct LevenshteinAutomaton {
+    pattern: String,
+    preprocessor: Preprocessor,
+    dfa: Dfa,
+}
+
+impl LevenshteinAutomaton {
+    pub fn new(pattern: &str, k: usize) -> Self {
+        Self::with_preprocessor(pattern, k, Preprocessor::default())
+    }
+
+    /// Compiles the automaton for `pattern` after `preprocessor`, which is
+    /// then applied to each candidate too.
+    pub(crate) fn with_preprocessor(pattern: &str, k: usize, preprocessor: Preprocessor) -> Self {
+        let chars: Vec<char> = preprocessor.apply(pattern).chars().collect();
+        Self { pattern: pattern.to_string(), dfa: Dfa::new(&chars, k), preprocessor }
+    }
+
+    /// Whether `candidate` is within distance `k` of the pattern.
+    pub fn matches(&self, candidate: &str) -> bool {
+        self.distance(candidate).is_some()
+    }
+
+    /// Levenshtein distance between the pattern and `candidate` if it is at
+    /// most `k`, and `None` otherwise.
+    pub fn distance(&self, candidate: &str) -> Option<usize> {
+        self.dfa.distance(&self.preprocessor.apply(candidate))
+    }
+
+    /// Finds the candidates within distance `k` of the pattern, in parallel
+    /// on `n_threads` threads, or on all available cores when `None`. Returns
+    /// pairs of an index into `candidates` and its distance, in the order of
+    /// `candidates`.
+    pub fn filter<S: AsRef<str> + Sync>(&self, candidates: &[S], n_threads: Option<usize>) -> Result<Vec<(usize, usize)>> {
+        let pool = thread_pool(n_threads)?;
+        Ok(pool.run_sized(candidates.len(), 1, |counter, min_len| self.filter_batch(counter, 0, candidates, min_len)))
+    }
+
+    /// The pattern candidates are matched against.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Largest distance of a match.
+    pub fn k(&self) -> usize {
+        self.dfa.k
+    }
+
+    /// Number of states of the compiled DFA.
+    pub fn n_states(&self) -> usize {
+        self.dfa.distances.len()
+    }
+
+    fn filter_batch<S: AsRef<str> + Sync>(
+        &self,
+        counter: &Counter,
+        start: usize,
+        candidates: &[S],
+        min_len: usize,
+    ) -> Vec<(usize, usize)> {
+        candidates
+            .par_iter()
+            .with_min_len(min_len)
+            .enumerate()
+            .filter_map(|(i, candidate)| {
+                if counter.is_cancelled() {
+                    return None;
+                }
+                self.distance(candidate.as_ref()).map(|d| (start + i, d))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl LevenshteinAutomaton {
+    #[new]
+    #[args(preprocessor = "None")]
+    fn py_new(pattern: &str, k: usize, preprocessor: Option<Preprocessor>) -> Self {
+        Self::with_preprocessor(pattern, k, preprocessor.unwrap_or_default())
+    }
+
+    /// Whether `candidate` is within distance `k` of the pattern.
+    #[pyo3(name = "matches", text_signature = "($self, candidate, /)")]
+    fn py_matches(&self, candidate: &str) -> bool {
+        self.matches(candidate)
+    }
+
+    /// Levenshtein distance between the pattern and `candidate` if it is at
+    /// most `k`, and `None` otherwise.
+    #[pyo3(name = "distance", text_signature = "($self, candidate, /)")]
+    fn py_distance(&self, candidate: &str) -> Option<usize> {
+        self.distance(candidate)
+    }
+
+    /// Finds the candidates within distance `k` of the pattern.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `candidates` - Strings to check, a sequence or an iterable
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of an index into `candidates` and its distance, in
+    ///   the order of `candidates`
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "filter", text_signature = "($self, candidates, /, n_threads=None)")]
+    fn py_filter(&self, candidates: Strings, n_threads: NThreads) -> PyResult<Vec<(usize, usize)>> {
+        let pool = thread_pool(n_threads)?;
+        let mut output = Vec::new();
+        candidates.for_each_batch(|start, candidates| {
+            let reporter = Reporter::new(None, Some(candidates.len()));
+            let batch = pool.install_sized(&reporter, candidates.len(), 1, |counter, min_len| {
+                self.filter_batch(counter, start, candidates, min_len)
+            })?;
+            output.extend(batch);
+            Ok(())
+        })?;
+        Ok(output)
+    }
+
+    /// The pattern candidates are matched against.
+    #[getter(pattern)]
+    fn py_pattern(&self) -> &str {
+        self.pattern()
+    }
+
+    /// Largest distance of a match.
+    #[getter(k)]
+    fn py_k(&self) -> usize {
+        self.k()
+    }
+
+    /// Number of states of the compiled DFA.
+    #[getter(n_states)]
+    fn py_n_states(&self) -> usize {
+        self.n_states()
+    }
+
+    /// Rebuilds the automaton from its pattern, `k` and preprocessor when
+    /// unpickled.
+    fn __reduce__(slf: PyRef<'_, Self>) -> (PyObject, (String, usize, Preprocessor)) {
+        (slf.py().get_type::<Self>().into(), (slf.pattern.clone(), slf.k(), slf.preprocessor.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    /// Brute-force counterpart of `filter`.
+    fn within(pattern: &str, k: usize, candidates: &[String]) -> Vec<(usize, usize)> {
+        let distances = candidates.iter().map(|c| strsim::levenshtein(pattern, c));
+        distances.enumerate().filter(|&(_, d)| d <= k).collect()
+    }
+
+    #[test]
+    fn filter_matches_brute_force_levenshtein() {
+        let mut rng = Rng::new(590);
+        let candidates = rng.strings(300, 8);
+        for k in 0..=3 {
+            for pattern in candidates.iter().take(40).chain([&String::new()]) {
+                let automaton = LevenshteinAutomaton::new(pattern, k);
+                assert_eq!(automaton.filter(&candidates, Some(2)).unwrap(), within(pattern, k, &candidates), "{:?} {}", pattern, k);
+            }
+        }
+    }
+
+    #[test]
+    fn filter_counts_characters_not_bytes() {
+        let candidates: Vec<String> = ["", "é", "e", "ée", "日本", "日本語", "本語", "nihongo"].map(String::from).into();
+        for (pattern, k) in [("é", 0), ("é", 1), ("日本語", 1), ("日本語", 0), ("", 0), ("", 2)] {
+            let automaton = LevenshteinAutomaton::new(pattern, k);
+            assert_eq!(automaton.filter(&candidates, None).unwrap(), within(pattern, k, &candidates), "{:?} {}", pattern, k);
+        }
+        assert_eq!(LevenshteinAutomaton::new("", 0).filter(&candidates, None).unwrap(), [(0, 0)]);
+        assert_eq!(LevenshteinAutomaton::new("日本語", 1).filter(&candidates, None).unwrap(), [(4, 1), (5, 0), (6, 1)]);
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn preprocessor_applies_to_pattern_and_candidates() {
+        let preprocessor = crate::testing::preprocessor(&["casefold", "strip_accents"]);
+        let automaton = LevenshteinAutomaton::with_preprocessor("Café", 1, preprocessor);
+        assert_eq!(automaton.pattern(), "Café");
+        assert_eq!(automaton.filter(&["CAFE", "cafés", "tea"], None).unwrap(), [(0, 0), (1, 1)]);
+        assert_eq!(LevenshteinAutomaton::new("Café", 1).filter(&["CAFE", "cafés", "tea"], None).unwrap(), []);
+    }
+}
@@ -0,0 +1,828 @@
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::metric::{Kind, Metric};
+use crate::pool::{thread_pool, Pool};
+use crate::progress::Counter;
+use crate::rank::{Ranked, TopK};
+
+// ------------------------------------------------------------------------
+//  Parallel Loops
+// ------------------------------------------------------------------------
+
+/// Scores `a` against each string in `bs`, in tasks of at least `min_len`
+/// strings, or returns `None` once the job is cancelled.
+pub(crate) fn score_each<S, T, F>(counter: &Counter, min_len: usize, f: &F, a: &str, bs: &[S]) -> Option<Vec<T>>
+where
+    S: AsRef<str> + Sync,
+    T: Send,
+    F: Fn(&str, &str) -> T + Sync,
+{
+    bs.par_iter()
+        .with_min_len(min_len)
+        .map(|b| (!counter.is_cancelled()).then(|| f(a, b.as_ref())))
+        .collect()
+}
+
+/// Scores each string in `as_` against the string at the same position in
+/// `bs`, like `score_each`.
+pub(crate) fn score_zipped<S, T, F>(counter: &Counter, min_len: usize, f: &F, as_: &[S], bs: &[S]) -> Option<Vec<T>>
+where
+    S: AsRef<str> + Sync,
+    T: Send,
+    F: Fn(&str, &str) -> T + Sync,
+{
+    as_.par_iter()
+        .zip(bs.par_iter())
+        .with_min_len(min_len)
+        .map(|(a, b)| (!counter.is_cancelled()).then(|| f(a.as_ref(), b.as_ref())))
+        .collect()
+}
+
+/// Rows of a matrix with `columns` columns that a task takes to score at
+/// least `min_len` strings.
+pub(crate) fn rows_per_task(min_len: usize, columns: usize) -> usize {
+    min_len.div_ceil(columns.max(1)).max(1)
+}
+
+pub(crate) fn check_same_length(as_: usize, bs: usize) -> Result<()> {
+    if as_ != bs {
+        return Err(Error::LengthMismatch(format!("as_ and bs must have the same length, got {} and {}", as_, bs)));
+    }
+    Ok(())
+}
+
+/// Scores every string in `as_` against every string in `bs` into a
+/// row-major matrix, in tasks of enough rows to score at least `min_len`
+/// strings. With `symmetric`, only the
+/// upper triangle is scored and then mirrored into the lower one. Cells for
+/// which `mask` is false are not scored and hold `skipped` instead. Without
+/// a mask, `row` scores a whole row at a time where it handles its string.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn score_matrix<S, T, F, M>(
+    counter: &Counter,
+    min_len: usize,
+    score: &F,
+    row: Option<&RowFn<T>>,
+    as_: &[S],
+    bs: &[S],
+    symmetric: bool,
+    mask: Option<&M>,
+    skipped: T,
+) -> Vec<T>
+where
+    S: AsRef<str> + Sync,
+    T: Copy + Default + Send + Sync,
+    F: Fn(&str, &str) -> T + Sync,
+    M: Fn(usize, usize) -> bool + Sync,
+{
+    let columns = bs.len();
+    let wanted = |i: usize, j: usize| match mask {
+        Some(mask) if symmetric => mask(i, j) || mask(j, i),
+        Some(mask) => mask(i, j),
+        None => true,
+    };
+    let mut matrix = vec![T::default(); as_.len() * columns];
+    if columns == 0 {
+        return matrix;
+    }
+    let row = row.filter(|_| mask.is_none());
+    let strs: Vec<&str> = if row.is_some() { bs.iter().map(AsRef::as_ref).collect() } else { Vec::new() };
+    matrix
+        .par_chunks_mut(columns)
+        .zip(as_.par_iter())
+        .with_min_len(rows_per_task(min_len, columns))
+        .enumerate()
+        .for_each(|(i, (cells, a))| {
+            if counter.is_cancelled() {
+                return;
+            }
+            let start = if symmetric { i } else { 0 };
+            if !row.is_some_and(|row| row(a.as_ref(), &strs[start..], &mut cells[start..])) {
+                for (j, (cell, b)) in cells.iter_mut().zip(bs).enumerate().skip(start) {
+                    *cell = if wanted(i, j) { score(a.as_ref(), b.as_ref()) } else { skipped };
+                }
+            }
+            counter.advance(columns - start);
+        });
+    if symmetric {
+        for i in 1..as_.len() {
+            for j in 0..i {
+                matrix[i * columns + j] = matrix[j * columns + i];
+            }
+        }
+        if let Some(mask) = mask {
+            for (k, cell) in matrix.iter_mut().enumerate() {
+                if !mask(k / columns, k % columns) {
+                    *cell = skipped;
+                }
+            }
+        }
+    }
+    matrix
+}
+
+/// A scorer of one string against a batch of others into a row, like
+/// `RowScorer` but for any type of score.
+pub(crate) type RowFn<T> = dyn Fn(&str, &[&str], &mut [T]) -> bool + Sync;
+
+/// Packed kernel of `metric`, for `score_matrix`.
+pub(crate) fn packed(metric: &Metric) -> Option<&RowFn<f64>> {
+    metric.row.as_ref().map(|row| row as &RowFn<f64>)
+}
+
+/// Packed kernel of `metric` with its scores converted by `convert`, for
+/// `score_matrix` of scores other than `f64`.
+#[cfg(feature = "python")]
+pub(crate) fn packed_with<T>(metric: Metric, convert: fn(f64) -> T) -> Option<impl Fn(&str, &[&str], &mut [T]) -> bool + Sync> {
+    let row = metric.row?;
+    Some(move |a: &str, bs: &[&str], output: &mut [T]| {
+        let mut scores = vec![0.0; bs.len()];
+        if !row(a, bs, &mut scores) {
+            return false;
+        }
+        for (cell, score) in output.iter_mut().zip(scores) {
+            *cell = convert(score);
+        }
+        true
+    })
+}
+
+/// Scores every pair of distinct strings in `xs` in the condensed order of
+/// `scipy.spatial.distance.pdist`, in tasks of rows of the upper triangle,
+/// like `score_matrix`.
+pub(crate) fn score_condensed<S, T, F>(counter: &Counter, min_len: usize, score: &F, xs: &[S]) -> Vec<T>
+where
+    S: AsRef<str> + Sync,
+    T: Copy + Send,
+    F: Fn(&str, &str) -> T + Sync,
+{
+    let rows: Vec<Vec<T>> = (0..xs.len())
+        .into_par_iter()
+        .with_min_len(rows_per_task(min_len, xs.len()))
+        .map(|i| {
+            if counter.is_cancelled() {
+                return Vec::new();
+            }
+            let row = xs[i + 1..].iter().map(|x| score(xs[i].as_ref(), x.as_ref())).collect();
+            counter.advance(xs.len() - i - 1);
+            row
+        })
+        .collect();
+    rows.concat()
+}
+
+/// The `k` best matches for `a` among `bs`, whose indices start at `start`,
+/// in tasks of at least `min_len` strings.
+pub(crate) fn best_k<S: AsRef<str> + Sync>(
+    counter: &Counter,
+    min_len: usize,
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    start: usize,
+    k: usize,
+) -> TopK {
+    // An empty batch asks the packed kernel whether it handles `a`.
+    if metric.row.is_some_and(|row| row(a, &[], &mut [])) {
+        return best_k_packed(counter, min_len, metric, a, bs, start, k);
+    }
+    let len_a = metric.char_length(a);
+    bs.par_iter()
+        .with_min_len(min_len)
+        .enumerate()
+        .fold(
+            || TopK::new(k),
+            |mut best, (i, b)| {
+                if counter.is_cancelled() {
+                    return best;
+                }
+                counter.advance(1);
+                let b = b.as_ref();
+                let score = match best.cutoff() {
+                    Some(cutoff) => metric.score_cutoff_sized(a, len_a, b, metric.char_length(b), cutoff),
+                    None => Some((metric.score)(a, b)),
+                };
+                if let Some(score) = score {
+                    best.push(Ranked::new(metric.kind, start + i, score));
+                }
+                best
+            },
+        )
+        .reduce(|| TopK::new(k), TopK::merge)
+}
+
+/// Number of strings scored by the packed kernel of a metric per call.
+const PACKED_BATCH: usize = 256;
+
+/// `best_k` with the packed kernel of `metric`, which scores batches of
+/// strings in full, so that only the heap of the best matches prunes them.
+fn best_k_packed<S: AsRef<str> + Sync>(
+    counter: &Counter,
+    min_len: usize,
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    start: usize,
+    k: usize,
+) -> TopK {
+    bs.par_chunks(PACKED_BATCH)
+        .with_min_len(min_len.div_ceil(PACKED_BATCH))
+        .enumerate()
+        .fold(
+            || TopK::new(k),
+            |mut best, (batch, chunk)| {
+                if counter.is_cancelled() {
+                    return best;
+                }
+                counter.advance(chunk.len());
+                let strs: Vec<&str> = chunk.iter().map(AsRef::as_ref).collect();
+                let mut scores = vec![0.0; chunk.len()];
+                metric.score_row(a, &strs, &mut scores);
+                for (i, score) in scores.into_iter().enumerate() {
+                    best.push(Ranked::new(metric.kind, start + batch * PACKED_BATCH + i, score));
+                }
+                best
+            },
+        )
+        .reduce(|| TopK::new(k), TopK::merge)
+}
+
+/// The best match for `a` among `bs`, whose indices start at `start`, in
+/// tasks of at least `min_len` strings.
+pub(crate) fn best_one<S: AsRef<str> + Sync>(
+    counter: &Counter,
+    min_len: usize,
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    start: usize,
+) -> Option<Ranked> {
+    bs.par_iter()
+        .with_min_len(min_len)
+        .enumerate()
+        .filter(|_| !counter.is_cancelled())
+        .map(|(i, b)| {
+            counter.advance(1);
+            Ranked::new(metric.kind, start + i, (metric.score)(a, b.as_ref()))
+        })
+        .max()
+}
+
+/// Appends to `output` the strings of `bs`, whose indices start at `start`,
+/// whose scores against `a` pass `cutoff`, in tasks of at least `min_len`
+/// strings.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn extend_passing<S: AsRef<str> + Sync>(
+    counter: &Counter,
+    min_len: usize,
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    start: usize,
+    cutoff: f64,
+    output: &mut Vec<(usize, f64)>,
+) {
+    let len_a = metric.char_length(a);
+    output.par_extend(
+        bs.par_iter()
+            .with_min_len(min_len)
+            .enumerate()
+            .filter(|_| !counter.is_cancelled())
+            .filter_map(|(i, b)| {
+                counter.advance(1);
+                let b = b.as_ref();
+                metric.score_cutoff_sized(a, len_a, b, metric.char_length(b), cutoff).map(|score| (start + i, score))
+            }),
+    )
+}
+
+/// Sorts pairs of an index and a score best match first with a parallel
+/// sort, breaking ties by the lower index.
+pub(crate) fn sort_best_first(kind: Kind, pool: &Pool, pairs: &mut [(usize, f64)]) {
+    pool.install(|| {
+        pairs.par_sort_unstable_by(|&(i, x), &(j, y)| Ranked::new(kind, j, y).cmp(&Ranked::new(kind, i, x)))
+    });
+}
+
+/// For each key of `left`, the matches among `right` passing `threshold`,
+/// at most `top_k` of them and best first. The keys of `right` are sorted
+/// by length, so that each key of `left` is only compared to the run of
+/// keys whose length lets them pass `threshold` at all. Tasks take enough
+/// keys of `left` to be compared to at least `min_len` keys of `right`.
+pub(crate) fn join_matches<S: AsRef<str> + Sync>(
+    counter: &Counter,
+    min_len: usize,
+    metric: Metric,
+    left: &[S],
+    right: &[S],
+    threshold: f64,
+    top_k: Option<usize>,
+) -> Vec<Vec<Ranked>> {
+    let lengths: Vec<usize> = right.iter().map(|b| b.as_ref().chars().count()).collect();
+    let mut by_length: Vec<usize> = (0..right.len()).collect();
+    by_length.sort_by_key(|&j| lengths[j]);
+    let sorted_lengths: Vec<usize> = by_length.iter().map(|&j| lengths[j]).collect();
+
+    left.par_iter()
+        .with_min_len(rows_per_task(min_len, right.len()))
+        .map(|a| {
+            if counter.is_cancelled() {
+                return Vec::new();
+            }
+            let a = a.as_ref();
+            let len_a = a.chars().count();
+            let start = sorted_lengths
+                .partition_point(|&len_b| len_b < len_a && !metric.lengths_pass(len_a, len_b, threshold));
+            let end = sorted_lengths
+                .partition_point(|&len_b| len_b <= len_a || metric.lengths_pass(len_a, len_b, threshold));
+            let mut best = TopK::new(top_k.unwrap_or(usize::MAX));
+            for &j in &by_length[start..end] {
+                let cutoff = match best.cutoff() {
+                    Some(cutoff) if metric.passes(cutoff, threshold) => cutoff,
+                    _ => threshold,
+                };
+                let b = right[j].as_ref();
+                if let Some(score) = metric.score_cutoff_sized(a, Some(len_a), b, Some(lengths[j]), cutoff) {
+                    best.push(Ranked::new(metric.kind, j, score));
+                }
+            }
+            counter.advance(right.len());
+            best.into_sorted_vec()
+        })
+        .collect()
+}
+
+
+// ------------------------------------------------------------------------
+//  Condensed Matrices
+// ------------------------------------------------------------------------
+
+/// Number of strings whose condensed matrix holds `len` scores.
+fn condensed_side(len: usize) -> Result<usize> {
+    let side = ((1.0 + (1.0 + 8.0 * len as f64).sqrt()) / 2.0).round() as usize;
+    if side * (side - 1) / 2 != len {
+        return Err(Error::Value(format!(
+            "{} scores are not the condensed form of any square matrix",
+            len
+        )));
+    }
+    Ok(side)
+}
+
+/// Expands a condensed matrix, in the order of `pdist`, into a row-major
+/// square one with `diagonal` on its diagonal.
+pub fn square_from_condensed<T: Copy>(condensed: &[T], diagonal: T) -> Result<(usize, Vec<T>)> {
+    let side = condensed_side(condensed.len())?;
+    let mut matrix = vec![diagonal; side * side];
+    let mut values = condensed.iter();
+    for i in 0..side {
+        for j in i + 1..side {
+            let value = *values.next().expect("condensed length matches its side");
+            matrix[i * side + j] = value;
+            matrix[j * side + i] = value;
+        }
+    }
+    Ok((side, matrix))
+}
+
+/// Condenses a row-major square matrix into its upper triangle, in the
+/// order of `pdist`. With `checks`, the matrix must be symmetric and hold
+/// `diagonal` on its diagonal.
+pub fn condensed_from_square<T: Copy + PartialEq + std::fmt::Display>(
+    shape: (usize, usize),
+    matrix: &[T],
+    diagonal: T,
+    checks: bool,
+) -> Result<Vec<T>> {
+    let (side, columns) = shape;
+    if side != columns {
+        return Err(Error::Value(format!("expected a square matrix, got shape ({}, {})", side, columns)));
+    }
+    if checks {
+        for i in 0..side {
+            if matrix[i * side + i] != diagonal {
+                return Err(Error::Value(format!(
+                    "diagonal entry [{}, {}] is {}, expected {}",
+                    i,
+                    i,
+                    matrix[i * side + i],
+                    diagonal
+                )));
+            }
+            for j in i + 1..side {
+                if matrix[i * side + j] != matrix[j * side + i] {
+                    return Err(Error::Value(format!(
+                        "matrix is not symmetric: [{}, {}] is {} but [{}, {}] is {}",
+                        i,
+                        j,
+                        matrix[i * side + j],
+                        j,
+                        i,
+                        matrix[j * side + i]
+                    )));
+                }
+            }
+        }
+    }
+    Ok((0..side).flat_map(|i| matrix[i * side + i + 1..(i + 1) * side].iter().copied()).collect())
+}
+
+
+// ------------------------------------------------------------------------
+//  Joins
+// ------------------------------------------------------------------------
+
+/// A row of a fuzzy join: an index into the left keys, an index into the
+/// right keys and their score, with `None` for the side and score of an
+/// unmatched key.
+pub type JoinRow = (Option<usize>, Option<usize>, Option<f64>);
+
+/// Which unmatched keys a join keeps, as the `how` of `pandas.merge`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Join {
+    Inner,
+    Left,
+    Right,
+    Outer,
+}
+
+impl Join {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "inner" => Ok(Self::Inner),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "outer" => Ok(Self::Outer),
+            _ => Err(Error::Value(format!(
+                "unknown join '{}', expected 'inner', 'left', 'right' or 'outer'",
+                name
+            ))),
+        }
+    }
+
+    /// Rows of the join of the `matches` of each left key among `right`
+    /// keys, in the order of the left keys, with the unmatched right keys
+    /// last.
+    pub(crate) fn rows(self, matches: Vec<Vec<Ranked>>, right: usize) -> Vec<JoinRow> {
+        let mut joined = Vec::new();
+        let mut matched = vec![false; right];
+        for (i, row) in matches.into_iter().enumerate() {
+            if row.is_empty() && matches!(self, Self::Left | Self::Outer) {
+                joined.push((Some(i), None, None));
+            }
+            for m in row {
+                matched[m.index] = true;
+                joined.push((Some(i), Some(m.index), Some(m.score)));
+            }
+        }
+        if matches!(self, Self::Right | Self::Outer) {
+            joined.extend((0..right).filter(|&j| !matched[j]).map(|j| (None, Some(j), None)));
+        }
+        joined
+    }
+}
+
+pub(crate) fn check_top_k(top_k: Option<usize>) -> Result<()> {
+    if top_k == Some(0) {
+        return Err(Error::value("top_k must be at least 1"));
+    }
+    Ok(())
+}
+
+
+// ------------------------------------------------------------------------
+//  Batch Functions
+// ------------------------------------------------------------------------
+
+/// Calculates the scores between `a` and each string in `bs`.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `a` - First string to compare
+/// * `bs` - Secondary strings to compare to `a`
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Scores between `a` and each `b` in `bs`
+pub fn scores<S: AsRef<str> + Sync>(metric: Metric, a: &str, bs: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+    let output = thread_pool(n_threads)?
+        .run_sized(bs.len(), 1, |counter, min_len| score_each(counter, min_len, &metric.score, a, bs));
+    Ok(output.unwrap_or_default())
+}
+
+/// Calculates the scores between the strings at the same positions in `as_`
+/// and `bs`.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `as_` - First strings to compare
+/// * `bs` - Secondary strings, as many as `as_`
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Scores between each `a` in `as_` and the `b` at its position
+pub fn pairwise<S: AsRef<str> + Sync>(metric: Metric, as_: &[S], bs: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+    check_same_length(as_.len(), bs.len())?;
+    let output = thread_pool(n_threads)?
+        .run_sized(as_.len(), 1, |counter, min_len| score_zipped(counter, min_len, &metric.score, as_, bs));
+    Ok(output.unwrap_or_default())
+}
+
+/// Scores every string in `as_` against every string in `bs`.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `as_` - First strings to compare
+/// * `bs` - Secondary strings to compare to each `a` in `as_`
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Row-major matrix whose row `i` holds the scores between
+///   `as_[i]` and each `b` in `bs`
+pub fn cdist<S: AsRef<str> + Sync>(metric: Metric, as_: &[S], bs: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+    let no_mask: Option<&fn(usize, usize) -> bool> = None;
+    Ok(thread_pool(n_threads)?.install(|| {
+        score_matrix(&Counter::new(false), 1, &metric.score, packed(&metric), as_, bs, false, no_mask, f64::NAN)
+    }))
+}
+
+/// Calculates the scores of every pair of distinct strings in `xs`.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `xs` - Strings to compare with each other
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Scores of the `xs.len() * (xs.len() - 1) / 2` pairs in the
+///   condensed order of `scipy.spatial.distance.pdist`
+pub fn pdist<S: AsRef<str> + Sync>(metric: Metric, xs: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+    Ok(thread_pool(n_threads)?.install(|| score_condensed(&Counter::new(false), 1, &metric.score, xs)))
+}
+
+/// Finds the `k` best matches for `a` among `bs`, higher scores first for
+/// similarities and lower scores first for distances.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `a` - First string to compare
+/// * `bs` - Secondary strings to compare to `a`
+/// * `k` - Number of matches to return
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Up to `k` pairs of an index into `bs` and its score, best
+///   match first
+pub fn top_k<S: AsRef<str> + Sync>(
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    k: usize,
+    n_threads: Option<usize>,
+) -> Result<Vec<(usize, f64)>> {
+    let best = thread_pool(n_threads)?.install(|| best_k(&Counter::new(false), 1, metric, a, bs, 0, k));
+    Ok(best.into_sorted_vec().into_iter().map(|r| (r.index, r.score)).collect())
+}
+
+/// Finds the best match for `a` among `bs`. Ties go to the lowest index.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `a` - First string to compare
+/// * `bs` - Secondary strings to compare to `a`
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Index into `bs` of the best match and its score, or `None`
+///   if `bs` is empty
+pub fn best_match<S: AsRef<str> + Sync>(
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    n_threads: Option<usize>,
+) -> Result<Option<(usize, f64)>> {
+    let best = thread_pool(n_threads)?.install(|| best_one(&Counter::new(false), 1, metric, a, bs, 0));
+    Ok(best.map(|r| (r.index, r.score)))
+}
+
+/// Compares `a` to each string in `bs` and keeps only the scores passing
+/// `cutoff`: at least `cutoff` for similarities and at most `cutoff` for
+/// distances.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `a` - First string to compare
+/// * `bs` - Secondary strings to compare to `a`
+/// * `cutoff` - Worst score to keep
+/// * `sort` - Whether to sort the results best match first
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Pairs of an index into `bs` and its score, in the order of
+///   `bs` or best match first
+pub fn threshold<S: AsRef<str> + Sync>(
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    cutoff: f64,
+    sort: bool,
+    n_threads: Option<usize>,
+) -> Result<Vec<(usize, f64)>> {
+    let pool = thread_pool(n_threads)?;
+    let mut output = Vec::new();
+    pool.install(|| extend_passing(&Counter::new(false), 1, metric, a, bs, 0, cutoff, &mut output));
+    if sort {
+        sort_best_first(metric.kind, &pool, &mut output);
+    }
+    Ok(output)
+}
+
+/// Calculates the scores between `a` and each string in `bs`, sorted best
+/// match first. Ties go to the lowest index.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Metric to score with
+/// * `a` - First string to compare
+/// * `bs` - Secondary strings to compare to `a`
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Pairs of an index into `bs` and its score, best match first
+pub fn ranked<S: AsRef<str> + Sync>(
+    metric: Metric,
+    a: &str,
+    bs: &[S],
+    n_threads: Option<usize>,
+) -> Result<Vec<(usize, f64)>> {
+    let mut output: Vec<(usize, f64)> = scores(metric, a, bs, n_threads)?.into_iter().enumerate().collect();
+    sort_best_first(metric.kind, &thread_pool(n_threads)?, &mut output);
+    Ok(output)
+}
+
+/// Joins two lists of keys on the pairs whose score passes `threshold`.
+/// 
+/// # Arguments
+/// 
+/// * `left` - Keys of the first table
+/// * `right` - Keys of the second table
+/// * `metric` - Metric to score the keys with
+/// * `threshold` - Worst score of two matching keys
+/// * `how` - Which unmatched keys to keep
+/// * `top_k` - Largest number of matches kept per key of `left`, or `None`
+///   to keep all of them
+/// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Rows of the join in the order of `left` and best match
+///   first, with the unmatched keys of `right` last
+pub fn fuzzy_join<S: AsRef<str> + Sync>(
+    left: &[S],
+    right: &[S],
+    metric: Metric,
+    threshold: f64,
+    how: Join,
+    top_k: Option<usize>,
+    n_threads: Option<usize>,
+) -> Result<Vec<JoinRow>> {
+    check_top_k(top_k)?;
+    let matches = thread_pool(n_threads)?
+        .install(|| join_matches(&Counter::new(false), 1, metric, left, right, threshold, top_k));
+    Ok(how.rows(matches, right.len()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::{METRIC_NAMES, SYMMETRIC_NAMES};
+    use crate::testing::Rng;
+
+    /// Scores of `a` against each of `bs`, best first, without any pruning.
+    fn brute_force(metric: Metric, a: &str, bs: &[String]) -> Vec<(usize, f64)> {
+        let mut scores: Vec<(usize, f64)> = bs.iter().map(|b| (metric.score)(a, b)).enumerate().collect();
+        scores.sort_by(|&(i, x), &(j, y)| Ranked::new(metric.kind, j, y).cmp(&Ranked::new(metric.kind, i, x)));
+        scores
+    }
+
+    fn scores_of(pairs: &[(usize, f64)]) -> Vec<f64> {
+        pairs.iter().map(|&(_, score)| score).collect()
+    }
+
+    #[test]
+    fn cutoff_paths_match_brute_force() {
+        let mut rng = Rng::new(633);
+        let strings = rng.strings(80, 16);
+        let (left, right) = strings.split_at(20);
+        for name in METRIC_NAMES {
+            let metric = Metric::from_name(name).unwrap();
+            let mut joined = Vec::new();
+            let mut joined_top = Vec::new();
+            let cutoff = brute_force(metric, &left[0], right)[right.len() / 4].1;
+            for (i, a) in left.iter().enumerate() {
+                let expected = brute_force(metric, a, right);
+
+                for k in [1, 3, right.len()] {
+                    let best = top_k(metric, a, right, k, Some(1)).unwrap();
+                    assert_eq!(scores_of(&best), scores_of(&expected[..k]), "{} top_k({:?}, {})", name, a, k);
+                }
+
+                for &(_, passing) in expected.iter().step_by(7) {
+                    let mut found = threshold(metric, a, right, passing, false, Some(1)).unwrap();
+                    found.sort_by_key(|&(j, _)| j);
+                    let mut wanted: Vec<_> = expected.iter().copied().filter(|&(_, s)| metric.passes(s, passing)).collect();
+                    wanted.sort_by_key(|&(j, _)| j);
+                    assert_eq!(found, wanted, "{} threshold({:?}, {})", name, a, passing);
+                }
+
+                let passing = expected.iter().filter(|&&(_, s)| metric.passes(s, cutoff));
+                joined.extend(passing.clone().map(|&(j, s)| (Some(i), Some(j), Some(s))));
+                joined_top.extend(passing.take(2).map(|&(_, s)| (i, s)));
+            }
+
+            let mut found = fuzzy_join(left, right, metric, cutoff, Join::Inner, None, Some(1)).unwrap();
+            found.sort_by_key(|&(i, j, _)| (i, j));
+            joined.sort_by_key(|&(i, j, _)| (i, j));
+            assert_eq!(found, joined, "{} fuzzy_join", name);
+
+            let found = fuzzy_join(left, right, metric, cutoff, Join::Inner, Some(2), Some(1)).unwrap();
+            let found: Vec<_> = found.into_iter().map(|(i, _, s)| (i.unwrap(), s.unwrap())).collect();
+            assert_eq!(found, joined_top, "{} fuzzy_join top 2", name);
+        }
+    }
+
+    #[test]
+    fn symmetric_matrices_match_full_scoring() {
+        let mut rng = Rng::new(573);
+        let strings = rng.strings(60, 12);
+        let counter = Counter::new(false);
+        let mask: fn(usize, usize) -> bool = |i, j| (i * 7 + j * 3) % 5 != 0;
+        for name in METRIC_NAMES {
+            let metric = Metric::from_name(name).unwrap();
+            let run = |symmetric: bool, masked: bool| {
+                let matrix =
+                    score_matrix(&counter, 1, &metric.score, packed(&metric), &strings, &strings, symmetric, masked.then_some(&mask), f64::NAN);
+                matrix.into_iter().map(f64::to_bits).collect::<Vec<_>>()
+            };
+            if SYMMETRIC_NAMES.contains(&name) {
+                assert!(run(true, false) == run(false, false), "{}", name);
+                assert!(run(true, true) == run(false, true), "{} with a mask", name);
+            } else {
+                assert!(run(true, false) != run(false, false), "{} is symmetric", name);
+            }
+        }
+    }
+
+    #[test]
+    fn chunked_tasks_match_one_string_per_task() {
+        assert_eq!([rows_per_task(1, 10), rows_per_task(25, 10), rows_per_task(30, 10), rows_per_task(5, 0)], [1, 3, 3, 5]);
+
+        let mut rng = Rng::new(584);
+        let strings = rng.strings(300, 12);
+        let (left, right) = strings.split_at(40);
+        let pool = thread_pool(4).unwrap();
+        let counter = Counter::new(false);
+        let no_mask: Option<&fn(usize, usize) -> bool> = None;
+        for name in ["levenshtein", "jaro_winkler"] {
+            let metric = Metric::from_name(name).unwrap();
+            let a = &left[0];
+            let cutoff = brute_force(metric, a, right)[right.len() / 4].1;
+            let run = |min_len: usize| {
+                pool.install(|| {
+                    let mut passing = Vec::new();
+                    extend_passing(&counter, min_len, metric, a, right, 0, cutoff, &mut passing);
+                    passing.sort_by_key(|&(j, _)| j);
+                    (
+                        score_matrix(&counter, min_len, &metric.score, packed(&metric), left, right, false, no_mask, f64::NAN),
+                        score_condensed(&counter, min_len, &metric.score, left),
+                        best_k(&counter, min_len, metric, a, right, 0, 5).into_sorted_vec(),
+                        best_one(&counter, min_len, metric, a, right, 0),
+                        passing,
+                        join_matches(&counter, min_len, metric, left, right, cutoff, Some(3)),
+                    )
+                })
+            };
+            let expected = run(1);
+            for min_len in [7, 256, 10_000] {
+                assert!(run(min_len) == expected, "{} with min_len {}", name, min_len);
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::time::Instant;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+
+use crate::batch;
+use crate::error::{Error, Result};
+use crate::metric::Metric;
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+#[cfg(feature = "python")]
+use crate::input::Strings;
+#[cfg(feature = "python")]
+use crate::metric::METRIC_NAMES;
+
+// ------------------------------------------------------------------------
+//  Workloads
+// ------------------------------------------------------------------------
+
+/// Letters generated workloads are drawn from by default.
+pub const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+/// Generates `n_strings` random strings of `min_len` to `max_len` characters
+/// of `alphabet`, the same for the same seed. Every other string is a copy
+/// of an earlier one with one to three characters substituted, inserted or
+/// deleted, so that the workload holds near-duplicates as real data does and
+/// cutoffs let some pairs through.
+pub fn workload(n_strings: usize, min_len: usize, max_len: usize, alphabet: &str, seed: u64) -> Result<Vec<String>> {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    if alphabet.is_empty() {
+        return Err(Error::value("alphabet must not be empty"));
+    }
+    if min_len > max_len {
+        return Err(Error::value(format!("min_len {} exceeds max_len {}", min_len, max_len)));
+    }
+    let mut state = seed;
+    let mut next = |n: usize| {
+        // SplitMix64, so that a seed always yields the same workload.
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        (crate::simhash::mix(state) % n as u64) as usize
+    };
+    let mut strings: Vec<String> = Vec::with_capacity(n_strings);
+    for i in 0..n_strings {
+        if i % 2 == 0 {
+            let len = min_len + next(max_len - min_len + 1);
+            strings.push((0..len).map(|_| alphabet[next(alphabet.len())]).collect());
+            continue;
+        }
+        let mut chars: Vec<char> = strings[next(i)].chars().collect();
+        for _ in 0..1 + next(3) {
+            let position = next(chars.len() + 1);
+            match next(3) {
+                0 if position < chars.len() => chars[position] = alphabet[next(alphabet.len())],
+                1 if position < chars.len() && chars.len() > min_len => {
+                    chars.remove(position);
+                }
+                _ if chars.len() < max_len => chars.insert(position, alphabet[next(alphabet.len())]),
+                _ => {}
+            }
+        }
+        strings.push(chars.into_iter().collect());
+    }
+    Ok(strings)
+}
+
+// ------------------------------------------------------------------------
+//  Timings
+// ------------------------------------------------------------------------
+
+/// How long one metric took to score a workload on a number of threads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing {
+    pub metric: String,
+    pub n_threads: usize,
+    pub pairs: usize,
+    pub seconds: f64,
+}
+
+impl Timing {
+    /// Pairs scored per second of wall-clock time.
+    pub fn pairs_per_second(&self) -> f64 {
+        self.pairs as f64 / self.seconds.max(f64::MIN_POSITIVE)
+    }
+}
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Thread counts tried when none are given: the powers of two below the
+/// number of available cores, and that number.
+pub fn default_thread_counts() -> Vec<usize> {
+    let available = available_threads();
+    let mut counts: Vec<usize> = std::iter::successors(Some(1), |&n| Some(n * 2)).take_while(|&n| n < available).collect();
+    counts.push(available);
+    counts
+}
+
+/// Times `cdist` of `queries` against `choices` under each metric on each
+/// number of threads, keeping the fastest of `repeat` runs as `timeit` does,
+/// since slower runs only measure interference from the rest of the system.
+/// The thread pool of each size is built before it is timed. `between` is
+/// called before each run, and stops the benchmark if it returns an error.
+pub fn run_with<S: AsRef<str> + Sync>(
+    metrics: &[&str],
+    queries: &[S],
+    choices: &[S],
+    thread_counts: &[usize],
+    repeat: usize,
+    mut between: impl FnMut() -> Result<()>,
+) -> Result<Vec<Timing>> {
+    if repeat == 0 {
+        return Err(Error::value("repeat must be at least 1"));
+    }
+    let metrics: Vec<(&str, Metric)> =
+        metrics.iter().map(|&name| Ok((name, Metric::from_name(name)?))).collect::<Result<_>>()?;
+    let mut timings = Vec::with_capacity(metrics.len() * thread_counts.len());
+    for &(name, metric) in &metrics {
+        for &n_threads in thread_counts {
+            thread_pool(Some(n_threads))?;
+            let mut seconds = f64::INFINITY;
+            for _ in 0..repeat {
+                between()?;
+                let start = Instant::now();
+                batch::cdist(metric, queries, choices, Some(n_threads))?;
+                seconds = seconds.min(start.elapsed().as_secs_f64());
+            }
+            let pairs = queries.len() * choices.len();
+            timings.push(Timing { metric: name.to_owned(), n_threads, pairs, seconds });
+        }
+    }
+    Ok(timings)
+}
+
+/// `run_with` without a callback between runs.
+/// 
+/// # Arguments
+/// 
+/// * `metrics` - Names of the metrics to time
+/// * `queries` - First strings to compare
+/// * `choices` - Secondary strings to compare to each query
+/// * `thread_counts` - Numbers of threads to time each metric on
+/// * `repeat` - Number of runs of each, of which the fastest is kept
+/// 
+/// # Returns
+/// 
+/// * `output` - One timing per metric and number of threads, in that order
+pub fn run<S: AsRef<str> + Sync>(
+    metrics: &[&str],
+    queries: &[S],
+    choices: &[S],
+    thread_counts: &[usize],
+    repeat: usize,
+) -> Result<Vec<Timing>> {
+    run_with(metrics, queries, choices, thread_counts, repeat, || Ok(()))
+}
+
+// ------------------------------------------------------------------------
+//  Python
+// ------------------------------------------------------------------------
+
+/// Generates random strings to benchmark with, the same for the same seed.
+/// Every other string is a copy of an earlier one with one to three
+/// characters substituted, inserted or deleted, so that the workload holds
+/// near-duplicates as real data does.
+/// 
+/// # Arguments
+/// 
+/// * `n_strings` - Number of strings to generate
+/// * `min_len` - Fewest characters of a string
+/// * `max_len` - Most characters of a string
+/// * `alphabet` - Characters to draw from
+/// * `seed` - Seed of the generator
+/// 
+/// # Returns
+/// 
+/// * `output` - The generated strings
+#[cfg(feature = "python")]
+#[pyfunction(min_len = "4", max_len = "16", alphabet = "ALPHABET", seed = "1")]
+#[pyo3(name = "workload", text_signature = "(n_strings, /, min_len=4, max_len=16, alphabet='abcdefghijklmnopqrstuvwxyz', seed=1)")]
+pub fn py_workload(n_strings: usize, min_len: usize, max_len: usize, alphabet: &str, seed: u64) -> PyResult<Vec<String>> {
+    Ok(workload(n_strings, min_len, max_len, alphabet, seed)?)
+}
+
+/// Times `vectorized.cdist` of `queries` against `choices` under each metric
+/// on each number of threads, keeping the fastest of `repeat` runs. Without
+/// samples, generated workloads of 100 queries and 1000 choices are used.
+/// The GIL is released while timing, and Ctrl-C stops the benchmark between
+/// runs.
+/// 
+/// # Arguments
+/// 
+/// * `metrics` - Names of the metrics to time, or `None` for all of them
+/// * `queries` - First strings to compare, or `None` to generate them
+/// * `choices` - Secondary strings to compare to each query, or `None` to
+///   generate them
+/// * `n_threads` - Numbers of threads to time each metric on, 0 standing for
+///   all available cores, or `None` for the powers of two up to the number
+///   of available cores
+/// * `repeat` - Number of runs of each, of which the fastest is kept
+/// 
+/// # Returns
+/// 
+/// * `output` - One dict per metric and number of threads, with the keys
+///   `metric`, `n_threads`, `pairs`, `seconds` and `pairs_per_second`,
+///   ready for `pandas.DataFrame`
+#[cfg(feature = "python")]
+#[pyfunction(metrics = "None", queries = "None", choices = "None", n_threads = "None", repeat = "3")]
+#[pyo3(name = "run", text_signature = "(metrics=None, queries=None, choices=None, n_threads=None, repeat=3)")]
+pub fn py_run(
+    py: Python<'_>,
+    metrics: Option<Vec<&str>>,
+    queries: Option<Strings>,
+    choices: Option<Strings>,
+    n_threads: Option<Vec<NThreads>>,
+    repeat: usize,
+) -> PyResult<Vec<PyObject>> {
+    let metrics = metrics.unwrap_or_else(|| METRIC_NAMES.to_vec());
+    let thread_counts = match n_threads {
+        // 0 times the pool of one thread per available core, as elsewhere.
+        Some(counts) => counts.into_iter().map(|n| n.get().unwrap_or_else(available_threads)).collect(),
+        None => default_thread_counts(),
+    };
+    let samples = |strings: Option<Strings>, n_strings: usize, seed: u64| -> PyResult<Vec<String>> {
+        Ok(match strings {
+            Some(strings) => strings.as_strs()?.into_iter().map(str::to_owned).collect(),
+            None => workload(n_strings, 4, 16, ALPHABET, seed)?,
+        })
+    };
+    let (queries, choices) = (samples(queries, 100, 1)?, samples(choices, 1000, 2)?);
+    let timings = py.allow_threads(|| {
+        run_with(&metrics, &queries, &choices, &thread_counts, repeat, || {
+            Ok(Python::with_gil(|py| py.check_signals())?)
+        })
+    })?;
+    timings
+        .into_iter()
+        .map(|timing| {
+            let row = PyDict::new(py);
+            row.set_item("metric", &timing.metric)?;
+            row.set_item("n_threads", timing.n_threads)?;
+            row.set_item("pairs", timing.pairs)?;
+            row.set_item("seconds", timing.seconds)?;
+            row.set_item("pairs_per_second", timing.pairs_per_second())?;
+            Ok(row.into())
+        })
+        .collect()
+}
@@ -0,0 +1,407 @@
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+
+// ------------------------------------------------------------------------
+//  Tree Metrics
+// ------------------------------------------------------------------------
+
+/// Edit distances that satisfy the triangle inequality, which metric trees
+/// need to prune their searches.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Distance {
+    Levenshtein,
+    DamerauLevenshtein,
+}
+
+impl Distance {
+    pub(crate) fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "levenshtein" => Ok(Self::Levenshtein),
+            "damerau_levenshtein" => Ok(Self::DamerauLevenshtein),
+            _ => Err(Error::value(format!(
+                "metric trees need a metric obeying the triangle inequality, expected one of: \
+                 levenshtein, damerau_levenshtein, got '{}'",
+                name
+            ))),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Levenshtein => "levenshtein",
+            Self::DamerauLevenshtein => "damerau_levenshtein",
+        }
+    }
+
+    pub(crate) fn distance(self, a: &[char], b: &[char]) -> usize {
+        match self {
+            Self::Levenshtein => {
+                crate::cutoff::levenshtein_within(a, b, usize::MAX).expect("no distance exceeds usize::MAX")
+            }
+            Self::DamerauLevenshtein => strsim::generic_damerau_levenshtein(a, b),
+        }
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  BK-Tree
+// ------------------------------------------------------------------------
+
+struct Node {
+    word: String,
+    chars: Vec<char>,
+    /// Distance to each child and the child's index.
+    children: Vec<(usize, usize)>,
+}
+
+/// Burkhard-Keller tree over a set of words under an edit distance. Each
+/// child sits at a fixed distance from its parent, so by the triangle
+/// inequality a search within `max_distance` of a query only descends into
+/// the children whose distance to the parent is within `max_distance` of the
+/// query's own, and visits a fraction of the words.
+/// 
+/// # Arguments
+/// 
+/// * `words` - Words to index, or `None` to start empty
+/// * `metric` - `"levenshtein"` or `"damerau_levenshtein"`
+/// * `preprocessor` - `Preprocessor` applied to the words and queries before
+///   they are compared, or `None`. Matches are returned as they were added
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(words=None, /, metric='levenshtein', preprocessor=None)")
+)]
+pub struct BKTree {
+    distance: Distance,
+    preprocessor: Preprocessor,
+    nodes: Vec<Node>,
+}
+
+impl BKTree {
+    /// Makes an empty tree under the metric named `metric`,
+    /// `"levenshtein"` or `"damerau_levenshtein"`.
+    pub fn new(metric: &str) -> Result<Self> {
+        Ok(Self { distance: Distance::from_name(metric)?, preprocessor: Preprocessor::default(), nodes: Vec::new() })
+    }
+
+    /// Adds a word to the tree, returning whether it was added, `false` if
+    /// it was already present.
+    pub fn add(&mut self, word: &str) -> bool {
+        self.insert(word)
+    }
+
+    /// Adds many words to the tree.
+    pub fn extend<I>(&mut self, words: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for word in words {
+            self.insert(word.as_ref());
+        }
+    }
+
+    /// Finds every word within `max_distance` of `query`, as pairs of the
+    /// word and its distance, closest first.
+    pub fn find(&self, query: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        self.words(self.search(query, max_distance))
+    }
+
+    /// Parallel version of `find` for many queries, on `n_threads` threads,
+    /// or on all available cores when `None`.
+    pub fn find_many<S: AsRef<str> + Sync>(
+        &self,
+        queries: &[S],
+        max_distance: usize,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<Vec<(&str, usize)>>> {
+        let found = thread_pool(n_threads)?.run_sized(queries.len(), 1, |counter, min_len| {
+            self.search_many(counter, queries, max_distance, min_len)
+        });
+        Ok(found.unwrap_or_default().into_iter().map(|found| self.words(found)).collect())
+    }
+
+    /// Number of words in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Whether `word` is in the tree.
+    pub fn contains(&self, word: &str) -> bool {
+        !self.search(word, 0).is_empty()
+    }
+
+    fn insert(&mut self, word: &str) -> bool {
+        let chars: Vec<char> = self.preprocessor.apply(word).chars().collect();
+        if self.nodes.is_empty() {
+            self.nodes.push(Node { word: word.to_string(), chars, children: Vec::new() });
+            return true;
+        }
+
+        let mut current = 0;
+        loop {
+            let d = self.distance.distance(&chars, &self.nodes[current].chars);
+            if d == 0 {
+                return false;
+            }
+            match self.nodes[current].children.iter().find(|&&(distance, _)| distance == d) {
+                Some(&(_, child)) => current = child,
+                None => {
+                    let index = self.nodes.len();
+                    self.nodes[current].children.push((d, index));
+                    self.nodes.push(Node { word: word.to_string(), chars, children: Vec::new() });
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Indices of the words within `max_distance` of `query` and their
+    /// distances, closest first and then in insertion order.
+    fn search(&self, query: &str, max_distance: usize) -> Vec<(usize, usize)> {
+        let query: Vec<char> = self.preprocessor.apply(query).chars().collect();
+        let mut found = Vec::new();
+        let mut stack = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+        while let Some(current) = stack.pop() {
+            let node = &self.nodes[current];
+            let d = self.distance.distance(&query, &node.chars);
+            if d <= max_distance {
+                found.push((current, d));
+            }
+            let (low, high) = (d.saturating_sub(max_distance), d.saturating_add(max_distance));
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|&&(distance, _)| low <= distance && distance <= high)
+                    .map(|&(_, child)| child),
+            );
+        }
+        found.sort_unstable_by_key(|&(index, d)| (d, index));
+        found
+    }
+
+    /// `search` for each of `queries` in parallel, or `None` if the job was
+    /// cancelled.
+    fn search_many<S: AsRef<str> + Sync>(
+        &self,
+        counter: &Counter,
+        queries: &[S],
+        max_distance: usize,
+        min_len: usize,
+    ) -> Option<Vec<Vec<(usize, usize)>>> {
+        queries
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|query| (!counter.is_cancelled()).then(|| self.search(query.as_ref(), max_distance)))
+            .collect()
+    }
+
+    fn words(&self, found: Vec<(usize, usize)>) -> Vec<(&str, usize)> {
+        found.into_iter().map(|(index, d)| (self.nodes[index].word.as_str(), d)).collect()
+    }
+}
+
+impl Persist for BKTree {
+    const KIND: &'static str = "BKTree";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.str(self.distance.name());
+        self.preprocessor.encode(encoder);
+        encoder.usize(self.nodes.len());
+        for node in &self.nodes {
+            encoder.str(&node.word);
+            encoder.usize(node.children.len());
+            for &(distance, child) in &node.children {
+                encoder.usize(distance);
+                encoder.usize(child);
+            }
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let distance = Distance::from_name(&decoder.str()?)?;
+        let preprocessor = Preprocessor::decode(decoder)?;
+        let n_nodes = decoder.length()?;
+        let mut nodes = Vec::with_capacity(n_nodes);
+        for _ in 0..n_nodes {
+            let word = decoder.str()?;
+            let children = (0..decoder.length()?)
+                .map(|_| {
+                    let (distance, child) = (decoder.usize()?, decoder.usize()?);
+                    decoder.check(child < n_nodes)?;
+                    Ok((distance, child))
+                })
+                .collect::<Result<_>>()?;
+            nodes.push(Node { chars: preprocessor.apply(&word).chars().collect(), word, children });
+        }
+        Ok(Self { distance, preprocessor, nodes })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BKTree {
+    #[new]
+    #[args(words = "None", metric = "\"levenshtein\"", preprocessor = "None")]
+    fn py_new(words: Option<Strings>, metric: &str, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        let mut tree = Self { preprocessor: preprocessor.unwrap_or_default(), ..Self::new(metric)? };
+        if let Some(words) = words {
+            tree.py_extend(words)?;
+        }
+        Ok(tree)
+    }
+
+    /// Adds a word to the tree.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `word` - Word to add
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Whether the word was added, `False` if it was already present
+    #[pyo3(name = "add", text_signature = "($self, word, /)")]
+    fn py_add(&mut self, word: &str) -> bool {
+        self.insert(word)
+    }
+
+    /// Adds many words to the tree.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `words` - Words to add
+    #[pyo3(name = "extend", text_signature = "($self, words, /)")]
+    fn py_extend(&mut self, words: Strings) -> PyResult<()> {
+        words.for_each_batch(|_, words| {
+            self.extend(words);
+            Ok(())
+        })
+    }
+
+    /// Finds every word within `max_distance` of `query`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - Word to look up
+    /// * `max_distance` - Largest distance of a match
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of a matching word and its distance, closest first
+    #[pyo3(name = "find", text_signature = "($self, query, max_distance, /)")]
+    fn py_find(&self, query: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        self.find(query, max_distance)
+    }
+
+    /// Parallel version of `find` for many queries.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `queries` - Words to look up
+    /// * `max_distance` - Largest distance of a match
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each query, pairs of a matching word and its distance,
+    ///   closest first
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "find_many", text_signature = "($self, queries, max_distance, /, n_threads=None)")]
+    fn py_find_many(
+        &self,
+        queries: Strings,
+        max_distance: usize,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<Vec<(&str, usize)>>> {
+        let queries = queries.as_strs()?;
+        let reporter = Reporter::new(None, Some(queries.len().saturating_mul(self.nodes.len())));
+        let found = thread_pool(n_threads)?.install_sized(&reporter, queries.len(), 1, |counter, min_len| {
+            self.search_many(counter, &queries, max_distance, min_len)
+        })?;
+        Ok(found.unwrap_or_default().into_iter().map(|found| self.words(found)).collect())
+    }
+
+    /// Saves the tree to a file in a compact binary format, to be restored
+    /// by `BKTree.load` without inserting the words again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads a tree written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved tree
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of words in the tree.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    /// Whether `word` is in the tree.
+    fn __contains__(&self, word: &str) -> bool {
+        self.contains(word)
+    }
+}
+
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preprocessor_compares_preprocessed_words_and_returns_them_as_added() {
+        let preprocessor = crate::testing::preprocessor(&["casefold", "strip_accents"]);
+        let mut tree = BKTree { preprocessor, ..BKTree::new("levenshtein").unwrap() };
+        tree.extend(["Café", "CAFE", "tea"]);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.find("cafe", 0), [("Café", 0)]);
+        assert_eq!(tree.find("TEAS", 1), [("tea", 1)]);
+        let loaded = BKTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(loaded.find("cafe", 0), [("Café", 0)]);
+    }
+}
@@ -0,0 +1,49 @@
+// ------------------------------------------------------------------------
+//  Byte Sequences
+// ------------------------------------------------------------------------
+
+/// A slice that `strsim`'s generic functions can iterate by reference.
+pub(crate) struct Seq<'a, T>(pub &'a [T]);
+
+impl<'a, T> IntoIterator for &Seq<'a, T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Number of positions at which two equal-length byte strings differ, or
+/// `None` if their lengths differ.
+pub fn hamming(a: &[u8], b: &[u8]) -> Option<usize> {
+    strsim::generic_hamming(a, b).ok()
+}
+
+/// Levenshtein distance between two byte strings.
+pub fn levenshtein(a: &[u8], b: &[u8]) -> usize {
+    strsim::generic_levenshtein(&Seq(a), &Seq(b))
+}
+
+/// Levenshtein similarity between two byte strings, between 0.0 and 1.0.
+pub fn normalized_levenshtein(a: &[u8], b: &[u8]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / a.len().max(b.len()) as f64
+}
+
+/// Damerau-Levenshtein distance between two byte strings.
+pub fn damerau_levenshtein(a: &[u8], b: &[u8]) -> usize {
+    strsim::generic_damerau_levenshtein(a, b)
+}
+
+/// Jaro similarity between two byte strings.
+pub fn jaro(a: &[u8], b: &[u8]) -> f64 {
+    strsim::generic_jaro(&Seq(a), &Seq(b))
+}
+
+/// Jaro-Winkler similarity between two byte strings.
+pub fn jaro_winkler(a: &[u8], b: &[u8]) -> f64 {
+    strsim::generic_jaro_winkler(&Seq(a), &Seq(b))
+}
@@ -0,0 +1,675 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyTuple;
+use rayon::prelude::*;
+
+use crate::bytes::Seq;
+use crate::error::Result;
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::input::Strings;
+
+// ------------------------------------------------------------------------
+//  Query Characters
+// ------------------------------------------------------------------------
+
+/// Lowercases `s` if `case_insensitive`.
+pub(crate) fn fold(s: &str, case_insensitive: bool) -> Cow<'_, str> {
+    if case_insensitive {
+        Cow::Owned(s.to_lowercase())
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// `s` after `preprocessor`, lowercased if `case_insensitive`.
+fn prepare<'a>(preprocessor: &Preprocessor, s: &'a str, case_insensitive: bool) -> Cow<'a, str> {
+    match preprocessor.apply(s) {
+        Cow::Borrowed(s) => fold(s, case_insensitive),
+        Cow::Owned(s) if case_insensitive => Cow::Owned(s.to_lowercase()),
+        s => s,
+    }
+}
+
+/// Numbers the distinct characters of a query, so that per-character state
+/// can be kept in a flat array. ASCII characters are found without hashing.
+#[derive(Clone)]
+struct Alphabet {
+    ascii: [u32; 128],
+    other: HashMap<char, u32>,
+    len: usize,
+}
+
+impl Alphabet {
+    const ABSENT: u32 = u32::MAX;
+
+    /// Numbers the characters of `chars`, returning the number of each one.
+    fn new(chars: &[char]) -> (Self, Vec<u32>) {
+        let mut alphabet = Self { ascii: [Self::ABSENT; 128], other: HashMap::new(), len: 0 };
+        let slots = chars
+            .iter()
+            .map(|&c| match alphabet.slot(c) {
+                Some(slot) => slot,
+                None => {
+                    let slot = alphabet.len as u32;
+                    if c.is_ascii() {
+                        alphabet.ascii[c as usize] = slot;
+                    } else {
+                        alphabet.other.insert(c, slot);
+                    }
+                    alphabet.len += 1;
+                    slot
+                }
+            })
+            .collect();
+        (alphabet, slots)
+    }
+
+    /// Number of `c`, or `None` if the query does not hold it.
+    fn slot(&self, c: char) -> Option<u32> {
+        let slot = if c.is_ascii() { self.ascii[c as usize] } else { *self.other.get(&c)? };
+        (slot != Self::ABSENT).then_some(slot)
+    }
+}
+
+/// Scores `score` against each candidate in tasks of at least `min_len`, or
+/// returns `None` once the job is cancelled.
+fn score_each<S, T, F>(counter: &Counter, min_len: usize, score: F, candidates: &[S]) -> Option<Vec<T>>
+where
+    S: AsRef<str> + Sync,
+    T: Send,
+    F: Fn(&str) -> T + Sync,
+{
+    candidates
+        .par_iter()
+        .with_min_len(min_len)
+        .map(|candidate| (!counter.is_cancelled()).then(|| score(candidate.as_ref())))
+        .collect()
+}
+
+/// Scores `score` against each candidate on `n_threads` threads.
+fn score_all<S, T, F>(score: F, candidates: &[S], n_threads: Option<usize>) -> Result<Vec<T>>
+where
+    S: AsRef<str> + Sync,
+    T: Send,
+    F: Fn(&str) -> T + Sync + Send,
+{
+    let output = thread_pool(n_threads)?
+        .run_sized(candidates.len(), 1, |counter, min_len| score_each(counter, min_len, score, candidates));
+    Ok(output.unwrap_or_default())
+}
+
+/// Like `score_all`, but with progress reporting and interrupts as in the
+/// `vectorized` functions.
+#[cfg(feature = "python")]
+fn py_score_all<T, F>(score: F, candidates: Strings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<T>>
+where
+    T: Send,
+    F: Fn(&str) -> T + Sync + Send,
+{
+    let candidates = candidates.as_strs()?;
+    let min_len = crate::vectorized::min_len(chunk_size)?;
+    let reporter = crate::progress::Reporter::new(None, Some(candidates.len()));
+    let output = thread_pool(n_threads)?.install_sized(&reporter, candidates.len(), min_len, |counter, min_len| {
+        score_each(counter, min_len, score, &candidates)
+    })?;
+    Ok(output.unwrap_or_default())
+}
+
+// ------------------------------------------------------------------------
+//  Levenshtein
+// ------------------------------------------------------------------------
+
+/// A query prepared for Levenshtein distances to many candidates. The
+/// query is split into 64-character words, and the positions of each of
+/// its characters are kept as one bit-vector per word, with which each
+/// candidate is compared a word of the query at a time by the bit-parallel
+/// algorithm of Myers and Hyyrö.
+/// 
+/// # Arguments
+/// 
+/// * `query` - String to compare candidates to
+/// * `case_insensitive` - Whether to lowercase the query and each candidate
+/// * `preprocessor` - `Preprocessor` applied to the query and each candidate
+///   before they are lowercased, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(query, /, case_insensitive=False, preprocessor=None)")
+)]
+#[derive(Clone)]
+pub struct CachedLevenshtein {
+    query: String,
+    case_insensitive: bool,
+    preprocessor: Preprocessor,
+    len: usize,
+    words: usize,
+    alphabet: Alphabet,
+    /// Bit `i % 64` of word `slot * words + i / 64` is set where the query
+    /// holds the character numbered `slot` at position `i`.
+    masks: Vec<u64>,
+}
+
+impl CachedLevenshtein {
+    /// Prepares `query`, lowercased if `case_insensitive`.
+    pub fn new(query: &str, case_insensitive: bool) -> Self {
+        Self::with_preprocessor(query, case_insensitive, Preprocessor::default())
+    }
+
+    /// Prepares `query` after `preprocessor`, which is then applied to each
+    /// candidate too.
+    pub(crate) fn with_preprocessor(query: &str, case_insensitive: bool, preprocessor: Preprocessor) -> Self {
+        let chars: Vec<char> = prepare(&preprocessor, query, case_insensitive).chars().collect();
+        let (alphabet, slots) = Alphabet::new(&chars);
+        let words = chars.len().div_ceil(64);
+        let mut masks = vec![0; alphabet.len * words];
+        for (i, &slot) in slots.iter().enumerate() {
+            masks[slot as usize * words + i / 64] |= 1 << (i % 64);
+        }
+        Self { query: query.to_owned(), case_insensitive, preprocessor, len: chars.len(), words, alphabet, masks }
+    }
+
+    /// Levenshtein distance between the query and `candidate`.
+    pub fn distance(&self, candidate: &str) -> usize {
+        let candidate = prepare(&self.preprocessor, candidate, self.case_insensitive);
+        match self.words {
+            0 => candidate.chars().count(),
+            1 => self.distance_word(&candidate),
+            _ => self.distance_blocks(&candidate),
+        }
+    }
+
+    /// Levenshtein distances between the query and each candidate, on
+    /// `n_threads` threads, or on one per available core when `None`.
+    pub fn batch<S: AsRef<str> + Sync>(&self, candidates: &[S], n_threads: Option<usize>) -> Result<Vec<usize>> {
+        score_all(|candidate| self.distance(candidate), candidates, n_threads)
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    fn mask(&self, c: char, word: usize) -> u64 {
+        match self.alphabet.slot(c) {
+            Some(slot) => self.masks[slot as usize * self.words + word],
+            None => 0,
+        }
+    }
+
+    /// Distance to a query of at most 64 characters, whose column of the
+    /// distance matrix fits in one word.
+    fn distance_word(&self, candidate: &str) -> usize {
+        let last = 1 << (self.len - 1);
+        let (mut vp, mut vn) = (!0u64, 0u64);
+        let mut distance = self.len;
+        for c in candidate.chars() {
+            let x = self.mask(c, 0);
+            let d0 = ((x & vp).wrapping_add(vp) ^ vp) | x | vn;
+            let hp = vn | !(d0 | vp);
+            let hn = d0 & vp;
+            distance += usize::from(hp & last != 0);
+            distance -= usize::from(hn & last != 0);
+            let hp = (hp << 1) | 1;
+            let hn = hn << 1;
+            vp = hn | !(d0 | hp);
+            vn = hp & d0;
+        }
+        distance
+    }
+
+    /// Distance to a longer query, carrying the horizontal differences of
+    /// each word into the next one.
+    fn distance_blocks(&self, candidate: &str) -> usize {
+        let last = 1 << ((self.len - 1) % 64);
+        let mut vp = vec![!0u64; self.words];
+        let mut vn = vec![0u64; self.words];
+        let mut distance = self.len;
+        for c in candidate.chars() {
+            let (mut hp_carry, mut hn_carry) = (1u64, 0u64);
+            for word in 0..self.words {
+                let x = self.mask(c, word) | hn_carry;
+                let d0 = ((x & vp[word]).wrapping_add(vp[word]) ^ vp[word]) | x | vn[word];
+                let hp = vn[word] | !(d0 | vp[word]);
+                let hn = d0 & vp[word];
+                let (hp_in, hn_in) = (hp_carry, hn_carry);
+                if word + 1 < self.words {
+                    (hp_carry, hn_carry) = (hp >> 63, hn >> 63);
+                } else {
+                    (hp_carry, hn_carry) = (u64::from(hp & last != 0), u64::from(hn & last != 0));
+                }
+                let hp = (hp << 1) | hp_in;
+                let hn = (hn << 1) | hn_in;
+                vp[word] = hn | !(d0 | hp);
+                vn[word] = hp & d0;
+            }
+            distance += hp_carry as usize;
+            distance -= hn_carry as usize;
+        }
+        distance
+    }
+}
+
+// ------------------------------------------------------------------------
+//  Jaro-Winkler
+// ------------------------------------------------------------------------
+
+/// A query prepared for Jaro-Winkler similarities to many candidates. The
+/// characters of the query are numbered once, so that the positions of
+/// each one in a candidate of up to 64 characters can be gathered into a
+/// bit-vector in one pass, and each character of the query then matched
+/// with a few bit operations. Longer candidates are compared character by
+/// character. Scores are exactly those of `jaro_winkler`.
+/// 
+/// # Arguments
+/// 
+/// * `query` - String to compare candidates to
+/// * `case_insensitive` - Whether to lowercase the query and each candidate
+/// * `preprocessor` - `Preprocessor` applied to the query and each candidate
+///   before they are lowercased, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(query, /, case_insensitive=False, preprocessor=None)")
+)]
+#[derive(Clone)]
+pub struct CachedJaroWinkler {
+    query: String,
+    case_insensitive: bool,
+    preprocessor: Preprocessor,
+    chars: Vec<char>,
+    alphabet: Alphabet,
+    slots: Vec<u32>,
+}
+
+impl CachedJaroWinkler {
+    /// Prepares `query`, lowercased if `case_insensitive`.
+    pub fn new(query: &str, case_insensitive: bool) -> Self {
+        Self::with_preprocessor(query, case_insensitive, Preprocessor::default())
+    }
+
+    /// Prepares `query` after `preprocessor`, which is then applied to each
+    /// candidate too.
+    pub(crate) fn with_preprocessor(query: &str, case_insensitive: bool, preprocessor: Preprocessor) -> Self {
+        let chars: Vec<char> = prepare(&preprocessor, query, case_insensitive).chars().collect();
+        let (alphabet, slots) = Alphabet::new(&chars);
+        Self { query: query.to_owned(), case_insensitive, preprocessor, chars, alphabet, slots }
+    }
+
+    /// Jaro-Winkler similarity between the query and `candidate`.
+    pub fn similarity(&self, candidate: &str) -> f64 {
+        let candidate: Vec<char> = prepare(&self.preprocessor, candidate, self.case_insensitive).chars().collect();
+        let jaro = match candidate.len() {
+            0..=64 => self.jaro_word(&candidate),
+            _ => strsim::generic_jaro(&Seq(&self.chars), &Seq(&candidate)),
+        };
+        let prefix = self.chars.iter().zip(&candidate).take_while(|(a, b)| a == b).count();
+        (jaro + 0.1 * prefix as f64 * (1.0 - jaro)).min(1.0)
+    }
+
+    /// Jaro-Winkler similarities between the query and each candidate, on
+    /// `n_threads` threads, or on one per available core when `None`.
+    pub fn batch<S: AsRef<str> + Sync>(&self, candidates: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+        score_all(|candidate| self.similarity(candidate), candidates, n_threads)
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Jaro similarity to a candidate of at most 64 characters, matching
+    /// the characters of the query in order with the first free position in
+    /// the window around them, as `strsim::jaro` does.
+    fn jaro_word(&self, candidate: &[char]) -> f64 {
+        let (a_len, b_len) = (self.chars.len(), candidate.len());
+        if a_len == 0 && b_len == 0 {
+            return 1.0;
+        } else if a_len == 0 || b_len == 0 {
+            return 0.0;
+        } else if a_len == 1 && b_len == 1 {
+            return if self.chars[0] == candidate[0] { 1.0 } else { 0.0 };
+        }
+
+        let mut positions = vec![0u64; self.alphabet.len];
+        for (j, &c) in candidate.iter().enumerate() {
+            if let Some(slot) = self.alphabet.slot(c) {
+                positions[slot as usize] |= 1 << j;
+            }
+        }
+        let search_range = a_len.max(b_len) / 2 - 1;
+        let mut consumed = 0u64;
+        let mut matches = 0.0;
+        let mut transpositions = 0.0;
+        let mut b_match_index = 0;
+        for (i, &slot) in self.slots.iter().enumerate() {
+            let (low, high) = (i.saturating_sub(search_range), (b_len - 1).min(i + search_range));
+            if low > high {
+                continue;
+            }
+            let window = (u64::MAX >> (63 - high)) & (u64::MAX << low);
+            let free = positions[slot as usize] & !consumed & window;
+            if free == 0 {
+                continue;
+            }
+            let j = free.trailing_zeros() as usize;
+            consumed |= 1 << j;
+            matches += 1.0;
+            if j < b_match_index {
+                transpositions += 1.0;
+            }
+            b_match_index = j;
+        }
+
+        if matches == 0.0 {
+            0.0
+        } else {
+            (1.0 / 3.0) * ((matches / a_len as f64) + (matches / b_len as f64) + ((matches - transpositions) / matches))
+        }
+    }
+}
+
+// ------------------------------------------------------------------------
+//  Sørensen–Dice
+// ------------------------------------------------------------------------
+
+/// A query prepared for Sørensen–Dice similarities to many candidates: its
+/// text without whitespace and its bigram counts sorted by bigram, which
+/// each candidate's bigrams are merged with. Scores are exactly those of
+/// `sorensen_dice`.
+/// 
+/// # Arguments
+/// 
+/// * `query` - String to compare candidates to
+/// * `case_insensitive` - Whether to lowercase the query and each candidate
+/// * `preprocessor` - `Preprocessor` applied to the query and each candidate
+///   before they are lowercased, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(query, /, case_insensitive=False, preprocessor=None)")
+)]
+#[derive(Clone)]
+pub struct CachedDice {
+    query: String,
+    case_insensitive: bool,
+    preprocessor: Preprocessor,
+    text: String,
+    bigrams: crate::dice::Bigrams,
+}
+
+impl CachedDice {
+    /// Prepares `query`, lowercased if `case_insensitive`.
+    pub fn new(query: &str, case_insensitive: bool) -> Self {
+        Self::with_preprocessor(query, case_insensitive, Preprocessor::default())
+    }
+
+    /// Prepares `query` after `preprocessor`, which is then applied to each
+    /// candidate too.
+    pub(crate) fn with_preprocessor(query: &str, case_insensitive: bool, preprocessor: Preprocessor) -> Self {
+        let (text, bigrams) = crate::dice::bigram_counts(&prepare(&preprocessor, query, case_insensitive));
+        Self { query: query.to_owned(), case_insensitive, preprocessor, text, bigrams }
+    }
+
+    /// Sørensen–Dice similarity between the query and `candidate`.
+    pub fn similarity(&self, candidate: &str) -> f64 {
+        let (text, bigrams) = crate::dice::bigram_counts(&prepare(&self.preprocessor, candidate, self.case_insensitive));
+        crate::dice::bigram_dice(&self.text, &self.bigrams, &text, &bigrams)
+    }
+
+    /// Sørensen–Dice similarities between the query and each candidate, on
+    /// `n_threads` threads, or on one per available core when `None`.
+    pub fn batch<S: AsRef<str> + Sync>(&self, candidates: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+        score_all(|candidate| self.similarity(candidate), candidates, n_threads)
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+}
+
+// ------------------------------------------------------------------------
+//  Python Methods
+// ------------------------------------------------------------------------
+
+/// Arguments that rebuild a cached metric when unpickled.
+#[cfg(feature = "python")]
+fn reduce<T: pyo3::PyTypeInfo>(
+    py: Python<'_>,
+    query: &str,
+    case_insensitive: bool,
+    preprocessor: &Preprocessor,
+) -> (PyObject, Py<PyTuple>) {
+    let args = [query.into_py(py), case_insensitive.into_py(py), preprocessor.clone().into_py(py)];
+    (py.get_type::<T>().into(), PyTuple::new(py, args).into())
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CachedLevenshtein {
+    #[new]
+    #[args(case_insensitive = "false", preprocessor = "None")]
+    fn py_new(query: &str, case_insensitive: bool, preprocessor: Option<Preprocessor>) -> Self {
+        Self::with_preprocessor(query, case_insensitive, preprocessor.unwrap_or_default())
+    }
+
+    /// Calculates the Levenshtein distance between the query and a
+    /// candidate.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `candidate` - String to compare to the query
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between the query and `candidate`
+    #[pyo3(name = "distance", text_signature = "($self, candidate, /)")]
+    fn py_distance(&self, candidate: &str) -> usize {
+        self.distance(candidate)
+    }
+
+    /// Calculates the Levenshtein distances between the query and each
+    /// candidate.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `candidates` - Strings to compare to the query
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between the query and each candidate
+    #[args(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "batch", text_signature = "($self, candidates, /, chunk_size=None, n_threads=None)")]
+    fn py_batch(&self, candidates: Strings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<usize>> {
+        py_score_all(|candidate| self.distance(candidate), candidates, chunk_size, n_threads)
+    }
+
+    #[getter(query)]
+    fn py_query(&self) -> &str {
+        self.query()
+    }
+
+    fn __reduce__(slf: PyRef<'_, Self>) -> (PyObject, Py<PyTuple>) {
+        reduce::<Self>(slf.py(), &slf.query, slf.case_insensitive, &slf.preprocessor)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CachedLevenshtein({:?})", self.query)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CachedJaroWinkler {
+    #[new]
+    #[args(case_insensitive = "false", preprocessor = "None")]
+    fn py_new(query: &str, case_insensitive: bool, preprocessor: Option<Preprocessor>) -> Self {
+        Self::with_preprocessor(query, case_insensitive, preprocessor.unwrap_or_default())
+    }
+
+    /// Calculates the Jaro-Winkler similarity between the query and a
+    /// candidate.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `candidate` - String to compare to the query
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between the query and `candidate`
+    #[pyo3(name = "similarity", text_signature = "($self, candidate, /)")]
+    fn py_similarity(&self, candidate: &str) -> f64 {
+        self.similarity(candidate)
+    }
+
+    /// Calculates the Jaro-Winkler similarities between the query and each
+    /// candidate.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `candidates` - Strings to compare to the query
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between the query and each candidate
+    #[args(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "batch", text_signature = "($self, candidates, /, chunk_size=None, n_threads=None)")]
+    fn py_batch(&self, candidates: Strings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        py_score_all(|candidate| self.similarity(candidate), candidates, chunk_size, n_threads)
+    }
+
+    #[getter(query)]
+    fn py_query(&self) -> &str {
+        self.query()
+    }
+
+    fn __reduce__(slf: PyRef<'_, Self>) -> (PyObject, Py<PyTuple>) {
+        reduce::<Self>(slf.py(), &slf.query, slf.case_insensitive, &slf.preprocessor)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CachedJaroWinkler({:?})", self.query)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CachedDice {
+    #[new]
+    #[args(case_insensitive = "false", preprocessor = "None")]
+    fn py_new(query: &str, case_insensitive: bool, preprocessor: Option<Preprocessor>) -> Self {
+        Self::with_preprocessor(query, case_insensitive, preprocessor.unwrap_or_default())
+    }
+
+    /// Calculates the Sørensen–Dice similarity between the query and a
+    /// candidate.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `candidate` - String to compare to the query
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between the query and `candidate`
+    #[pyo3(name = "similarity", text_signature = "($self, candidate, /)")]
+    fn py_similarity(&self, candidate: &str) -> f64 {
+        self.similarity(candidate)
+    }
+
+    /// Calculates the Sørensen–Dice similarities between the query and each
+    /// candidate.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `candidates` - Strings to compare to the query
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between the query and each candidate
+    #[args(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "batch", text_signature = "($self, candidates, /, chunk_size=None, n_threads=None)")]
+    fn py_batch(&self, candidates: Strings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        py_score_all(|candidate| self.similarity(candidate), candidates, chunk_size, n_threads)
+    }
+
+    #[getter(query)]
+    fn py_query(&self) -> &str {
+        self.query()
+    }
+
+    fn __reduce__(slf: PyRef<'_, Self>) -> (PyObject, Py<PyTuple>) {
+        reduce::<Self>(slf.py(), &slf.query, slf.case_insensitive, &slf.preprocessor)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CachedDice({:?})", self.query)
+    }
+}
+
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+    use crate::input::Text;
+    use crate::single;
+    use crate::testing::Rng;
+    use crate::units::Unit;
+
+    /// Random strings with non-ASCII, uppercase and empty ones, and some
+    /// longer than a 64-character word of the bit-parallel kernels.
+    fn strings(rng: &mut Rng) -> Vec<String> {
+        let alphabet: Vec<char> = "abcAéÉßẞ日 ".chars().collect();
+        let mut strings = rng.strings(60, 12);
+        strings.extend((0..60).map(|_| rng.string(&alphabet, 12)));
+        strings.extend((0..20).map(|_| rng.string(&alphabet, 150)));
+        strings.extend(["", "é", "ÉÉÉ"].map(String::from));
+        strings
+    }
+
+    #[test]
+    fn batch_equals_the_uncached_scores() {
+        let mut rng = Rng::new(630);
+        let strings = strings(&mut rng);
+        let text = |s: &str| Text::from(s.to_string());
+        for case_insensitive in [false, true] {
+            for query in strings.iter().step_by(7).chain([&String::new()]) {
+                let levenshtein = CachedLevenshtein::new(query, case_insensitive).batch(&strings, Some(2)).unwrap();
+                let jaro_winkler = CachedJaroWinkler::new(query, case_insensitive).batch(&strings, Some(2)).unwrap();
+                let dice = CachedDice::new(query, case_insensitive).batch(&strings, Some(2)).unwrap();
+                for (i, candidate) in strings.iter().enumerate() {
+                    let (a, b, ci) = (|| text(query), || text(candidate), case_insensitive);
+                    let expected = single::levenshtein(a(), b(), Unit::Char, ci, None, None, None, false, false, false, false, false, false, None);
+                    assert_eq!(levenshtein[i], expected, "{:?} {:?} {}", query, candidate, ci);
+                    let expected = single::jaro_winkler(a(), b(), false, Unit::Char, ci, None, None, None, false, false, false, false, false, false, None);
+                    assert_eq!(jaro_winkler[i], expected.unwrap(), "{:?} {:?} {}", query, candidate, ci);
+                    let expected = single::sorensen_dice(a(), b(), 2, false, None, None, ci, None, None, None, false, false, false, false, false, false, None);
+                    assert_eq!(dice[i], expected.unwrap(), "{:?} {:?} {}", query, candidate, ci);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn preprocessor_applies_to_query_and_candidates_before_lowercasing() {
+        let preprocessor = || crate::testing::preprocessor(&["strip_accents"]);
+        let candidates = ["CAFE", "Cafés", "tea"];
+        let levenshtein = CachedLevenshtein::with_preprocessor("Café", true, preprocessor());
+        assert_eq!(levenshtein.batch(&candidates, None).unwrap(), [0, 1, 4]);
+        let jaro_winkler = CachedJaroWinkler::with_preprocessor("Café", true, preprocessor());
+        assert_eq!(jaro_winkler.similarity("CAFE"), 1.0);
+        let dice = CachedDice::with_preprocessor("Café", true, preprocessor());
+        assert_eq!(dice.similarity("CAFE"), 1.0);
+        assert_eq!(levenshtein.query(), "Café");
+    }
+}
@@ -0,0 +1,211 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+// ------------------------------------------------------------------------
+//  Job Fingerprints
+// ------------------------------------------------------------------------
+
+/// Incremental 64-bit FNV-1a hash. Unlike the standard library hashers its
+/// output is fixed across Rust versions and platforms, so it can be stored
+/// on disk and compared by a later process.
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    pub fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Hashes a string together with its length, so that consecutive
+    /// strings cannot run into each other.
+    pub fn write_str(&mut self, s: &str) {
+        self.write(&(s.len() as u64).to_le_bytes());
+        self.write(s.as_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Checkpoint Files
+// ------------------------------------------------------------------------
+
+const MAGIC: &[u8; 8] = b"STRSIMCK";
+const VERSION: u64 = 1;
+
+/// Header fields after the magic bytes: version, fingerprint, rows,
+/// columns, tile rows and tile columns.
+const HEADER_FIELDS: usize = 6;
+const HEADER_LEN: u64 = (MAGIC.len() + 8 * HEADER_FIELDS) as u64;
+
+/// Shape of the tiled matrix a checkpoint belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub rows: usize,
+    pub columns: usize,
+    pub tile_rows: usize,
+    pub tile_columns: usize,
+}
+
+impl Layout {
+    /// Row, column, height and width of every tile in the order they are
+    /// computed: left to right, then top to bottom.
+    pub fn tiles(self) -> impl Iterator<Item = (usize, usize, usize, usize)> {
+        let columns = if self.rows == 0 { 0 } else { self.columns };
+        (0..self.rows).step_by(self.tile_rows).flat_map(move |row| {
+            (0..columns).step_by(self.tile_columns).map(move |column| {
+                let height = self.tile_rows.min(self.rows - row);
+                let width = self.tile_columns.min(self.columns - column);
+                (row, column, height, width)
+            })
+        })
+    }
+}
+
+/// Size in bytes of the record of one tile: its row and column followed by
+/// its scores.
+fn record_len(height: usize, width: usize) -> u64 {
+    16 + 8 * (height * width) as u64
+}
+
+fn read_u64(reader: &mut impl Read) -> std::io::Result<u64> {
+    let mut buffer = [0; 8];
+    reader.read_exact(&mut buffer)?;
+    Ok(u64::from_le_bytes(buffer))
+}
+
+/// Append-only file of the tiles of a matrix job completed so far, so that
+/// an interrupted job can resume where it stopped. The file starts with a
+/// header identifying the job, followed by one record per finished tile in
+/// the order the tiles are computed. Each record is synced to disk before
+/// the tile is handed out, and a record cut short by a crash is discarded
+/// when the file is reopened.
+pub struct Checkpoint {
+    file: File,
+    reader: BufReader<File>,
+    restored: usize,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint at `path`, creating it if it does not exist or
+    /// is empty.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - Location of the checkpoint file
+    /// * `fingerprint` - Hash of the metric and strings of the job
+    /// * `layout` - Shape of the matrix and its tiles
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Checkpoint holding the tiles a previous run completed,
+    ///   or `Error::Value` if the file belongs to a different job
+    pub fn open(path: &Path, fingerprint: u64, layout: Layout) -> Result<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let header = [
+            VERSION,
+            fingerprint,
+            layout.rows as u64,
+            layout.columns as u64,
+            layout.tile_rows as u64,
+            layout.tile_columns as u64,
+        ];
+
+        let len = file.metadata()?.len();
+        if len == 0 {
+            file.write_all(MAGIC)?;
+            for field in header {
+                file.write_all(&field.to_le_bytes())?;
+            }
+            file.sync_data()?;
+        } else {
+            let mismatch = || {
+                Error::Value(format!(
+                    "checkpoint '{}' was written by a different job",
+                    path.display()
+                ))
+            };
+            if len < HEADER_LEN {
+                return Err(mismatch());
+            }
+            let mut magic = [0; 8];
+            file.read_exact(&mut magic)?;
+            if &magic != MAGIC {
+                return Err(mismatch());
+            }
+            for field in header {
+                if read_u64(&mut file)? != field {
+                    return Err(mismatch());
+                }
+            }
+        }
+
+        let mut end = HEADER_LEN;
+        let mut restored = 0;
+        for (_, _, height, width) in layout.tiles() {
+            let next = end + record_len(height, width);
+            if next > len {
+                break;
+            }
+            end = next;
+            restored += 1;
+        }
+        file.set_len(end)?;
+
+        let mut reader = BufReader::new(File::open(path)?);
+        reader.seek(SeekFrom::Start(HEADER_LEN))?;
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(Self { file, reader, restored })
+    }
+
+    /// Number of tiles left to read back from the previous run.
+    pub fn restored(&self) -> usize {
+        self.restored
+    }
+
+    /// Reads back the next tile of the previous run, which must start at
+    /// `row` and `column` and hold `cells` scores.
+    pub fn read_tile(&mut self, row: usize, column: usize, cells: usize) -> Result<Vec<f64>> {
+        if read_u64(&mut self.reader)? != row as u64 || read_u64(&mut self.reader)? != column as u64 {
+            return Err(Error::value("checkpoint is corrupted"));
+        }
+        let mut buffer = vec![0; 8 * cells];
+        self.reader.read_exact(&mut buffer)?;
+        self.restored -= 1;
+        Ok(buffer
+            .chunks_exact(8)
+            .map(|bytes| f64::from_le_bytes(bytes.try_into().expect("chunk of 8 bytes")))
+            .collect())
+    }
+
+    /// Appends a finished tile and syncs it to disk.
+    pub fn write_tile(&mut self, row: usize, column: usize, tile: &[f64]) -> Result<()> {
+        let mut record = Vec::with_capacity(16 + 8 * tile.len());
+        record.extend_from_slice(&(row as u64).to_le_bytes());
+        record.extend_from_slice(&(column as u64).to_le_bytes());
+        for score in tile {
+            record.extend_from_slice(&score.to_le_bytes());
+        }
+        self.file.write_all(&record)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+// ------------------------------------------------------------------------
+//  Disjoint Sets
+// ------------------------------------------------------------------------
+
+/// Union-find over the indices `0..n`, whose sets are always represented by
+/// their smallest member.
+#[derive(Clone, Debug)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    pub fn new(n: usize) -> Self {
+        Self { parent: (0..n).collect() }
+    }
+
+    /// Smallest member of the set containing `x`.
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges the sets containing `x` and `y`.
+    pub fn union(&mut self, x: usize, y: usize) {
+        let (x, y) = (self.find(x), self.find(y));
+        if x < y {
+            self.parent[y] = x;
+        } else {
+            self.parent[x] = y;
+        }
+    }
+
+    /// Smallest member of the set of each index.
+    pub fn into_labels(mut self) -> Vec<usize> {
+        (0..self.parent.len()).map(|x| self.find(x)).collect()
+    }
+}
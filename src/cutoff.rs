@@ -0,0 +1,237 @@
+use crate::scratch::{fill, with_scratch, Scratch};
+
+// ------------------------------------------------------------------------
+//  Bounded Edit Distances
+// ------------------------------------------------------------------------
+
+/// Slack added to score bounds so that rounding never prunes a candidate
+/// whose exact score would pass.
+pub(crate) const EPSILON: f64 = 1e-9;
+
+/// Calculates the Levenshtein distance of two character slices if it is at
+/// most `max`. Common prefixes and suffixes are skipped, candidates whose
+/// length difference alone exceeds `max` are rejected without any work, and
+/// the dynamic program stops as soon as a whole row exceeds `max`.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First sequence to compare
+/// * `b` - Secondary sequence to compare to `a`
+/// * `max` - Largest distance of interest
+/// 
+/// # Returns
+/// 
+/// * `output` - Distance between `a` and `b`, or `None` if it exceeds `max`
+pub fn levenshtein_within(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    levenshtein_within_rows(a, b, max, &mut Vec::new(), &mut Vec::new())
+}
+
+/// `levenshtein_within` with the rows of the dynamic program in `previous`
+/// and `current`, so that callers can reuse them.
+fn levenshtein_within_rows(
+    a: &[char],
+    b: &[char],
+    max: usize,
+    previous: &mut Vec<usize>,
+    current: &mut Vec<usize>,
+) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+    if a.is_empty() || b.is_empty() {
+        return Some(a.len().max(b.len()));
+    }
+
+    previous.clear();
+    previous.extend(0..=b.len());
+    current.clear();
+    current.resize(b.len() + 1, 0);
+    for (i, &x) in a.iter().enumerate() {
+        current[0] = i + 1;
+        let mut row_min = current[0];
+        for (j, &y) in b.iter().enumerate() {
+            let cost = usize::from(x != y);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            row_min = row_min.min(current[j + 1]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(previous, current);
+    }
+    let distance = previous[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+
+// ------------------------------------------------------------------------
+//  Cutoff-Aware Scorers
+// ------------------------------------------------------------------------
+
+/// Largest whole distance allowed by a distance cutoff, or `None` when no
+/// distance can pass.
+fn max_distance(cutoff: f64) -> Option<usize> {
+    if cutoff < 0.0 {
+        None
+    } else {
+        Some(cutoff.floor().min(usize::MAX as f64) as usize)
+    }
+}
+
+/// Levenshtein distance if it is at most `cutoff`.
+pub fn levenshtein(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    let max = max_distance(cutoff)?;
+    with_scratch(|Scratch { a: chars_a, b: chars_b, rows: [previous, current, _], .. }| {
+        fill(chars_a, a.chars());
+        fill(chars_b, b.chars());
+        levenshtein_within_rows(chars_a, chars_b, max, previous, current).map(|d| d as f64)
+    })
+}
+
+/// Normalized Levenshtein similarity if it is at least `cutoff`.
+pub fn normalized_levenshtein(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    with_scratch(|Scratch { a: chars_a, b: chars_b, rows: [previous, current, _], .. }| {
+        fill(chars_a, a.chars());
+        fill(chars_b, b.chars());
+        let max_len = chars_a.len().max(chars_b.len());
+        if max_len == 0 {
+            return (1.0 >= cutoff).then_some(1.0);
+        }
+        let max = max_distance(((1.0 - cutoff) * max_len as f64 + EPSILON).ceil())?;
+        let distance = levenshtein_within_rows(chars_a, chars_b, max, previous, current)?;
+        let similarity = 1.0 - distance as f64 / max_len as f64;
+        (similarity >= cutoff).then_some(similarity)
+    })
+}
+
+/// Runs `f` only if the length difference of the strings is at most
+/// `cutoff`, which every edit distance requires.
+fn length_pruned(a: &str, b: &str, cutoff: f64, f: fn(&str, &str) -> usize) -> Option<f64> {
+    let max = max_distance(cutoff)?;
+    if a.chars().count().abs_diff(b.chars().count()) > max {
+        return None;
+    }
+    let distance = f(a, b);
+    (distance <= max).then_some(distance as f64)
+}
+
+/// Damerau-Levenshtein distance if it is at most `cutoff`.
+pub fn damerau_levenshtein(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    length_pruned(a, b, cutoff, crate::scratch::damerau_levenshtein)
+}
+
+/// Optimal string alignment distance if it is at most `cutoff`.
+pub fn osa_distance(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    length_pruned(a, b, cutoff, crate::scratch::osa_distance)
+}
+
+/// Highest Jaro similarity two non-empty strings of these lengths can reach,
+/// with every character of the shorter one matched and no transpositions.
+fn jaro_bound(len_a: usize, len_b: usize) -> f64 {
+    let m = len_a.min(len_b) as f64;
+    (m / len_a as f64 + m / len_b as f64 + 1.0) / 3.0
+}
+
+/// Runs `f` only if the bound on its score from the string lengths reaches
+/// `cutoff`.
+fn bound_pruned(a: &str, b: &str, cutoff: f64, bound: fn(usize, usize) -> f64, f: fn(&str, &str) -> f64) -> Option<f64> {
+    let (len_a, len_b) = (a.chars().count(), b.chars().count());
+    if len_a > 0 && len_b > 0 && bound(len_a, len_b) + EPSILON < cutoff {
+        return None;
+    }
+    let similarity = f(a, b);
+    (similarity >= cutoff).then_some(similarity)
+}
+
+/// Jaro similarity if it is at least `cutoff`.
+pub fn jaro(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    bound_pruned(a, b, cutoff, jaro_bound, crate::scratch::jaro)
+}
+
+/// Highest Jaro-Winkler similarity two non-empty strings of these lengths
+/// can reach. `strsim` does not cap the common prefix at four characters, so
+/// the bonus can cover the whole shorter string, and the result at 1.
+fn jaro_winkler_bound(len_a: usize, len_b: usize) -> f64 {
+    let jaro = jaro_bound(len_a, len_b);
+    (jaro + 0.1 * len_a.min(len_b) as f64 * (1.0 - jaro)).min(1.0)
+}
+
+/// Jaro-Winkler similarity if it is at least `cutoff`.
+pub fn jaro_winkler(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    bound_pruned(a, b, cutoff, jaro_winkler_bound, crate::scratch::jaro_winkler)
+}
+
+/// `ratio` similarity if it is at least `cutoff`. The longest common
+/// subsequence is at most as long as the shorter string.
+pub fn ratio(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    bound_pruned(
+        a,
+        b,
+        cutoff,
+        |len_a, len_b| 2.0 * len_a.min(len_b) as f64 / (len_a + len_b) as f64,
+        crate::fuzz::ratio,
+    )
+}
+
+
+// ------------------------------------------------------------------------
+//  Length Bounds
+// ------------------------------------------------------------------------
+
+/// Best score two strings of these numbers of characters can reach under
+/// the metric named `name`, for the metrics whose scores the lengths alone
+/// bound: the smallest distance or the largest similarity possible. The
+/// bound only gets worse as the lengths grow apart.
+pub fn length_bound(name: &str) -> Option<fn(usize, usize) -> f64> {
+    let bound: fn(usize, usize) -> f64 = match name {
+        "damerau_levenshtein" | "levenshtein" | "osa_distance" => |len_a, len_b| len_a.abs_diff(len_b) as f64,
+        "normalized_levenshtein" => |len_a, len_b| match len_a.max(len_b) {
+            0 => 1.0,
+            max_len => len_a.min(len_b) as f64 / max_len as f64,
+        },
+        "jaro" => |len_a, len_b| match (len_a.min(len_b), len_a.max(len_b)) {
+            (_, 0) => 1.0,
+            (0, _) => 0.0,
+            _ => jaro_bound(len_a, len_b),
+        },
+        "jaro_winkler" => |len_a, len_b| match (len_a.min(len_b), len_a.max(len_b)) {
+            (_, 0) => 1.0,
+            (0, _) => 0.0,
+            _ => jaro_winkler_bound(len_a, len_b),
+        },
+        "ratio" => |len_a, len_b| match len_a + len_b {
+            0 => 1.0,
+            total => 2.0 * len_a.min(len_b) as f64 / total as f64,
+        },
+        _ => return None,
+    };
+    Some(bound)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jaro_winkler_keeps_long_common_prefixes() {
+        let (a, b) = ("abaabbabb", "abaabbababbbaa");
+        let expected = strsim::jaro_winkler(a, b);
+        assert!(expected > 0.95);
+        assert_eq!(jaro_winkler(a, b, 0.95), Some(expected));
+        assert_eq!(jaro_winkler(a, b, expected), Some(expected));
+    }
+
+    #[test]
+    fn jaro_winkler_length_bound_covers_long_common_prefixes() {
+        let (a, b) = ("abaabbabb", "abaabbababbbaa");
+        let bound = length_bound("jaro_winkler").unwrap();
+        assert!(bound(9, 14) >= strsim::jaro_winkler(a, b));
+    }
+}
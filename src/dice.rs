@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::tokens::Tokenizer;
+
+// ------------------------------------------------------------------------
+//  Sørensen–Dice Coefficient
+// ------------------------------------------------------------------------
+
+/// Sørensen–Dice coefficient of the n-gram multisets of two sequences.
+/// Sequences shorter than `n` share no n-grams and score 0.0 unless equal.
+fn ngram_dice<T: Eq + Hash>(a: &[T], b: &[T], n: usize) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    if a.len() < n || b.len() < n {
+        return 0.0;
+    }
+
+    let mut grams_a: HashMap<&[T], usize> = HashMap::new();
+    for gram in a.windows(n) {
+        *grams_a.entry(gram).or_insert(0) += 1;
+    }
+
+    let mut intersection = 0;
+    for gram in b.windows(n) {
+        if let Some(count) = grams_a.get_mut(gram) {
+            if *count > 0 {
+                *count -= 1;
+                intersection += 1;
+            }
+        }
+    }
+
+    let total = (a.len() - n + 1) + (b.len() - n + 1);
+    (2 * intersection) as f64 / total as f64
+}
+
+/// Calculates the Sørensen–Dice similarity over n-grams of characters or,
+/// given a tokenizer, over n-grams of its lowercase tokens. Whitespace is
+/// ignored in character mode. With `n = 2` in character mode this is the same as
+/// `strsim::sorensen_dice`.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// * `n` - Number of characters or words per gram, at least 1
+/// * `tokens` - Tokenizer to build grams from tokens instead of characters
+/// 
+/// # Returns
+/// 
+/// * `output` - Similarity between `a` and `b`
+pub fn sorensen_dice(a: &str, b: &str, n: usize, tokens: Option<&Tokenizer>) -> f64 {
+    if let Some(tokenizer) = tokens {
+        ngram_dice(&tokenizer.tokenize(a), &tokenizer.tokenize(b), n)
+    } else if n == 2 {
+        crate::scratch::sorensen_dice(a, b)
+    } else {
+        let a: Vec<char> = a.chars().filter(|c| !c.is_whitespace()).collect();
+        let b: Vec<char> = b.chars().filter(|c| !c.is_whitespace()).collect();
+        ngram_dice(&a, &b, n)
+    }
+}
+
+/// Counts of the bigrams of a text, sorted by bigram.
+pub(crate) type Bigrams = Vec<((char, char), usize)>;
+
+/// Text without whitespace and its bigram counts, the form `bigram_dice`
+/// scores.
+pub(crate) fn bigram_counts(s: &str) -> (String, Bigrams) {
+    let text: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut grams: Vec<(char, char)> = text.chars().zip(text.chars().skip(1)).collect();
+    grams.sort_unstable();
+    let mut counts: Bigrams = Vec::new();
+    for gram in grams {
+        match counts.last_mut() {
+            Some((last, count)) if *last == gram => *count += 1,
+            _ => counts.push((gram, 1)),
+        }
+    }
+    (text, counts)
+}
+
+/// `strsim::sorensen_dice` over prepared bigram counts. Like the original,
+/// it measures the texts in bytes.
+pub(crate) fn bigram_dice(text_a: &str, a: &[((char, char), usize)], text_b: &str, b: &[((char, char), usize)]) -> f64 {
+    if text_a.is_empty() && text_b.is_empty() {
+        return 1.0;
+    }
+    if text_a.is_empty() || text_b.is_empty() {
+        return 0.0;
+    }
+    if text_a == text_b {
+        return 1.0;
+    }
+    if text_a.len() < 2 || text_b.len() < 2 {
+        return 0.0;
+    }
+
+    let (mut i, mut j, mut intersection) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                intersection += a[i].1.min(b[j].1);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    (2 * intersection) as f64 / (text_a.len() + text_b.len() - 2) as f64
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use rayon::prelude::*;
+
+use crate::cached::fold;
+use crate::dice::{bigram_counts, Bigrams};
+use crate::error::Result;
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::metric::Kind;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+use crate::rank::{Ranked, TopK};
+
+// ------------------------------------------------------------------------
+//  Dice Index
+// ------------------------------------------------------------------------
+
+/// Choices whose shared bigrams with a query one parallel task counts.
+const MATCH_BLOCK: usize = 1 << 12;
+
+/// Inverted index from bigrams to the choices containing them, for
+/// `sorensen_dice` scoring of many queries against the same choices. The
+/// bigram counts of the choices are built once, and a query only visits the
+/// choices sharing a bigram with it, every other choice scoring 0.0. Scores
+/// match `sorensen_dice` exactly. A query's choices are scored in parallel,
+/// on the thread pool the methods are called from.
+/// 
+/// # Arguments
+/// 
+/// * `choices` - Strings to index, or `None` to start empty
+/// * `case_insensitive` - Whether to lowercase all strings
+/// * `preprocessor` - `Preprocessor` applied to the choices and queries before
+///   they are lowercased and split into bigrams, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(choices=None, /, case_insensitive=False, preprocessor=None)")
+)]
+pub struct DiceIndex {
+    case_insensitive: bool,
+    preprocessor: Preprocessor,
+    /// Each choice as scored: preprocessed, folded and without whitespace.
+    texts: Vec<String>,
+    /// Choices holding each bigram and its count in them, in increasing order.
+    postings: HashMap<(char, char), Vec<(usize, usize)>>,
+    /// Choices of fewer than two characters, which hold no bigram, by text.
+    short: HashMap<String, Vec<usize>>,
+}
+
+impl DiceIndex {
+    /// Makes an empty index.
+    pub fn new(case_insensitive: bool) -> Self {
+        Self {
+            case_insensitive,
+            preprocessor: Preprocessor::default(),
+            texts: Vec::new(),
+            postings: HashMap::new(),
+            short: HashMap::new(),
+        }
+    }
+
+    /// Adds choices to the index. They are numbered after the existing ones.
+    pub fn extend<I>(&mut self, choices: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for choice in choices {
+            let (text, grams) = self.bigrams(choice.as_ref());
+            self.insert(text, grams);
+        }
+    }
+
+    /// `sorensen_dice` similarity between `query` and each choice.
+    pub fn scores(&self, query: &str) -> Vec<f64> {
+        let mut output = vec![0.0; self.len()];
+        for (i, score) in self.matches(query) {
+            output[i] = score;
+        }
+        output
+    }
+
+    /// The `k` choices most similar to `query`, best match first. Ties go to
+    /// the lowest index.
+    pub fn top_k(&self, query: &str, k: usize) -> Vec<(usize, f64)> {
+        let matches = self.matches(query);
+        let mut best = TopK::new(k);
+        for &(i, score) in &matches {
+            best.push(Ranked::new(Kind::Similarity, i, score));
+        }
+        // Choices sharing nothing with the query fill the rest, lowest first.
+        let mut matched = matches.iter().map(|&(i, _)| i).peekable();
+        let unmatched = (0..self.len()).filter(|&i| matched.next_if_eq(&i).is_none());
+        for i in unmatched.take(k.saturating_sub(matches.len())) {
+            best.push(Ranked::new(Kind::Similarity, i, 0.0));
+        }
+        best.into_sorted_vec().into_iter().map(|r| (r.index, r.score)).collect()
+    }
+
+    /// Choices whose similarity to `query` is at least `cutoff`, in the
+    /// order of the choices. Only a cutoff of 0.0 or less lets through the
+    /// choices sharing nothing with the query.
+    pub fn threshold(&self, query: &str, cutoff: f64) -> Vec<(usize, f64)> {
+        if cutoff <= 0.0 {
+            return self.scores(query).into_iter().enumerate().filter(|&(_, score)| score >= cutoff).collect();
+        }
+        self.matches(query).into_iter().filter(|&(_, score)| score >= cutoff).collect()
+    }
+
+    /// `scores` of each query, on `n_threads` threads, or on all available
+    /// cores when `None`.
+    pub fn cdist<S: AsRef<str> + Sync>(&self, queries: &[S], n_threads: Option<usize>) -> Result<Vec<Vec<f64>>> {
+        let output = thread_pool(n_threads)?
+            .run_sized(queries.len(), 1, |counter, min_len| self.scores_each(counter, min_len, queries));
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Number of indexed choices.
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    /// Text of `s` as scored and its bigram counts.
+    fn bigrams(&self, s: &str) -> (String, Bigrams) {
+        bigram_counts(&fold(&self.preprocessor.apply(s), self.case_insensitive))
+    }
+
+    fn insert(&mut self, text: String, grams: Bigrams) {
+        let index = self.texts.len();
+        if grams.is_empty() {
+            self.short.entry(text.clone()).or_default().push(index);
+        }
+        for (gram, count) in grams {
+            self.postings.entry(gram).or_default().push((index, count));
+        }
+        self.texts.push(text);
+    }
+
+    /// Choices scoring above 0.0 against `query` and their scores, in
+    /// increasing order. Their shared bigrams are counted from the postings
+    /// of the query's bigrams, in parallel over blocks of `MATCH_BLOCK`
+    /// choices, whose entries the sorted postings hold in runs.
+    fn matches(&self, query: &str) -> Vec<(usize, f64)> {
+        let (text, grams) = self.bigrams(query);
+        if grams.is_empty() {
+            // Without bigrams, only the choices of the same text score, 1.0.
+            return self.short.get(&text).map_or_else(Vec::new, |same| same.iter().map(|&i| (i, 1.0)).collect());
+        }
+        let postings: Vec<(usize, &[(usize, usize)])> = grams
+            .into_iter()
+            .filter_map(|(gram, count)| Some((count, self.postings.get(&gram)?.as_slice())))
+            .collect();
+        let blocks: Vec<Vec<(usize, f64)>> = (0..self.len().div_ceil(MATCH_BLOCK))
+            .into_par_iter()
+            .map(|block| {
+                let start = block * MATCH_BLOCK;
+                let end = (start + MATCH_BLOCK).min(self.len());
+                let mut shared = vec![0; end - start];
+                for &(count, posting) in &postings {
+                    let from = posting.partition_point(|&(i, _)| i < start);
+                    for &(i, n) in posting[from..].iter().take_while(|&&(i, _)| i < end) {
+                        shared[i - start] += count.min(n);
+                    }
+                }
+                shared
+                    .into_iter()
+                    .enumerate()
+                    .filter(|&(_, shared)| shared > 0)
+                    .map(|(offset, shared)| {
+                        // As `strsim::sorensen_dice` does, texts are measured in bytes.
+                        let other = &self.texts[start + offset];
+                        let score = if *other == text {
+                            1.0
+                        } else {
+                            (2 * shared) as f64 / (text.len() + other.len() - 2) as f64
+                        };
+                        (start + offset, score)
+                    })
+                    .collect()
+            })
+            .collect();
+        blocks.concat()
+    }
+
+    fn scores_each<S: AsRef<str> + Sync>(&self, counter: &Counter, min_len: usize, queries: &[S]) -> Option<Vec<Vec<f64>>> {
+        queries
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|query| (!counter.is_cancelled()).then(|| self.scores(query.as_ref())))
+            .collect()
+    }
+}
+
+impl Persist for DiceIndex {
+    const KIND: &'static str = "DiceIndex";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.bool(self.case_insensitive);
+        self.preprocessor.encode(encoder);
+        encoder.strs(&self.texts);
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let mut index = Self::new(decoder.bool()?);
+        index.preprocessor = Preprocessor::decode(decoder)?;
+        for text in decoder.strs()? {
+            // Texts are saved preprocessed, folded and without whitespace, so
+            // only their bigrams are counted again.
+            let (_, grams) = bigram_counts(&text);
+            index.insert(text, grams);
+        }
+        Ok(index)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl DiceIndex {
+    #[new]
+    #[args(choices = "None", case_insensitive = "false", preprocessor = "None")]
+    fn py_new(choices: Option<Strings>, case_insensitive: bool, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        let mut index = Self { preprocessor: preprocessor.unwrap_or_default(), ..Self::new(case_insensitive) };
+        if let Some(choices) = choices {
+            index.py_extend(choices)?;
+        }
+        Ok(index)
+    }
+
+    /// Adds choices to the index. They are numbered after the existing ones.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `choices` - Strings to add
+    #[pyo3(name = "extend", text_signature = "($self, choices, /)")]
+    fn py_extend(&mut self, choices: Strings) -> PyResult<()> {
+        choices.for_each_batch(|_, choices| {
+            self.extend(choices);
+            Ok(())
+        })
+    }
+
+    /// Calculates the `sorensen_dice` similarity between `query` and each
+    /// choice.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - String to compare to the choices
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `query` and each choice, in the order
+    ///   of the choices
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "scores", text_signature = "($self, query, /, n_threads=None)")]
+    fn py_scores(&self, query: &str, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        Ok(thread_pool(n_threads)?.install(|| self.scores(query)))
+    }
+
+    /// Finds the `k` choices most similar to `query`. Ties go to the lowest
+    /// index.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - String to compare to the choices
+    /// * `k` - Number of matches to return
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Up to `k` pairs of an index into the choices and its
+    ///   similarity, best match first
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "top_k", text_signature = "($self, query, k, /, n_threads=None)")]
+    fn py_top_k(&self, query: &str, k: usize, n_threads: NThreads) -> PyResult<Vec<(usize, f64)>> {
+        Ok(thread_pool(n_threads)?.install(|| self.top_k(query, k)))
+    }
+
+    /// Finds the choices whose similarity to `query` is at least `cutoff`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - String to compare to the choices
+    /// * `cutoff` - Smallest similarity to keep
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of an index into the choices and its similarity, in
+    ///   the order of the choices
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "threshold", text_signature = "($self, query, cutoff, /, n_threads=None)")]
+    fn py_threshold(&self, query: &str, cutoff: f64, n_threads: NThreads) -> PyResult<Vec<(usize, f64)>> {
+        Ok(thread_pool(n_threads)?.install(|| self.threshold(query, cutoff)))
+    }
+
+    /// Calculates the similarity between each query and each choice, the
+    /// queries in parallel.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `queries` - Strings to compare to the choices
+    /// * `chunk_size` - Smallest number of queries each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Rows of similarities, one per query, in the order of the
+    ///   choices
+    #[args(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "cdist", text_signature = "($self, queries, /, chunk_size=None, n_threads=None)")]
+    fn py_cdist(&self, queries: Strings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<Vec<f64>>> {
+        let queries = queries.as_strs()?;
+        let min_len = crate::vectorized::min_len(chunk_size)?;
+        let reporter = Reporter::new(None, Some(queries.len()));
+        let output = thread_pool(n_threads)?.install_sized(&reporter, queries.len(), min_len, |counter, min_len| {
+            self.scores_each(counter, min_len, &queries)
+        })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Saves the index to a file in a compact binary format, to be restored
+    /// by `DiceIndex.load`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads an index written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved index
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of indexed choices.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DiceIndex(<{} choices>, case_insensitive={})", self.len(), if self.case_insensitive { "True" } else { "False" })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    #[test]
+    fn scores_match_sorensen_dice_across_blocks() {
+        let mut rng = Rng::new(637);
+        let choices = rng.strings(2 * MATCH_BLOCK + 100, 8);
+        let mut index = DiceIndex::new(false);
+        index.extend(&choices);
+        for query in rng.strings(20, 8) {
+            let expected: Vec<f64> = choices.iter().map(|c| strsim::sorensen_dice(&query, c)).collect();
+            assert_eq!(index.scores(&query), expected, "{:?}", query);
+            let passing: Vec<_> = expected.iter().copied().enumerate().filter(|&(_, s)| s >= 0.5).collect();
+            assert_eq!(index.threshold(&query, 0.5), passing);
+        }
+    }
+
+    #[test]
+    fn case_insensitive_lowercases_choices_and_queries() {
+        let mut index = DiceIndex::new(true);
+        index.extend(["Night", "NACHT", "a"]);
+        assert_eq!(index.scores("nIGHT"), [1.0, strsim::sorensen_dice("night", "nacht"), 0.0]);
+        assert_eq!(index.scores("A"), [0.0, 0.0, 1.0]);
+        assert_eq!(DiceIndex::new(false).scores("x"), Vec::<f64>::new());
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn preprocessor_applies_to_choices_queries_and_saved_index() {
+        let preprocessor = crate::testing::preprocessor(&["strip_accents", "collapse_whitespace"]);
+        let mut index = DiceIndex { preprocessor, ..DiceIndex::new(true) };
+        index.extend(["Crème  Brûlée", "creme"]);
+        let expected = [1.0, strsim::sorensen_dice("creme brulee", "creme")];
+        assert_eq!(index.scores("CREME BRULEE"), expected);
+        assert_eq!(DiceIndex::from_bytes(&index.to_bytes()).unwrap().scores("CREME BRULEE"), expected);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------
+//  N-Gram Profiles
+// ------------------------------------------------------------------------
+
+/// Relative frequencies of the character n-grams of a string.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    frequencies: HashMap<Vec<char>, f64>,
+}
+
+impl Profile {
+    /// Builds the n-gram frequency profile of `s`. Strings shorter than `n`
+    /// are treated as a single gram.
+    pub fn new(s: &str, n: usize) -> Self {
+        let chars: Vec<char> = s.chars().collect();
+        let mut counts: HashMap<Vec<char>, usize> = HashMap::new();
+        if chars.len() <= n {
+            if !chars.is_empty() {
+                counts.insert(chars, 1);
+            }
+        } else {
+            for gram in chars.windows(n) {
+                *counts.entry(gram.to_vec()).or_insert(0) += 1;
+            }
+        }
+
+        let total = counts.values().sum::<usize>() as f64;
+        Self {
+            frequencies: counts
+                .into_iter()
+                .map(|(gram, count)| (gram, count as f64 / total))
+                .collect(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.frequencies.is_empty()
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Jensen-Shannon Divergence
+// ------------------------------------------------------------------------
+
+/// Jensen-Shannon divergence of two n-gram profiles in bits.
+pub fn profile_divergence(p: &Profile, q: &Profile) -> f64 {
+    if p.is_empty() && q.is_empty() {
+        return 0.0;
+    }
+    if p.is_empty() || q.is_empty() {
+        return 1.0;
+    }
+
+    // Grams found in only one profile contribute half their frequency.
+    let mut divergence = 0.0;
+    for (gram, &pi) in &p.frequencies {
+        match q.frequencies.get(gram) {
+            Some(&qi) => {
+                let mi = 0.5 * (pi + qi);
+                divergence += 0.5 * (pi * (pi / mi).log2() + qi * (qi / mi).log2());
+            }
+            None => divergence += 0.5 * pi,
+        }
+    }
+    for (gram, &qi) in &q.frequencies {
+        if !p.frequencies.contains_key(gram) {
+            divergence += 0.5 * qi;
+        }
+    }
+    divergence.clamp(0.0, 1.0)
+}
+
+/// Calculates the Jensen-Shannon divergence of the character n-gram frequency
+/// profiles of two strings, using base 2 logarithms. The returned value is
+/// between 0.0 and 1.0 (lower value means more similar).
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// * `n` - Number of characters per n-gram, at least 1
+/// 
+/// # Returns
+/// 
+/// * `output` - Divergence between `a` and `b`
+pub fn jensen_shannon(a: &str, b: &str, n: usize) -> f64 {
+    profile_divergence(&Profile::new(a, n), &Profile::new(b, n))
+}
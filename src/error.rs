@@ -0,0 +1,189 @@
+use std::fmt;
+
+#[cfg(feature = "python")]
+use pyo3::once_cell::GILOnceCell;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyDict, PyTuple, PyType};
+
+// ------------------------------------------------------------------------
+//  Errors
+// ------------------------------------------------------------------------
+
+/// Error of the Rust API. With the `python` feature each kind becomes the
+/// Python exception the bindings raise for it.
+#[derive(Debug)]
+pub enum Error {
+    /// An argument out of range or data that cannot be read, raised as
+    /// `InvalidParameterError`.
+    Value(String),
+    /// Sequences that must be as long as each other but are not, raised as
+    /// `LengthMismatchError`.
+    LengthMismatch(String),
+    /// Worker threads that could not be started, raised as `ThreadPoolError`.
+    ThreadPool(String),
+    /// A failed file operation, raised as `OSError`.
+    Io(std::io::Error),
+    /// An exception raised by Python code the library called back into.
+    #[cfg(feature = "python")]
+    Python(pyo3::PyErr),
+}
+
+/// Result of the Rust API.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn value(message: impl Into<String>) -> Self {
+        Self::Value(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(message) | Self::LengthMismatch(message) | Self::ThreadPool(message) => f.write_str(message),
+            Self::Io(error) => error.fmt(f),
+            #[cfg(feature = "python")]
+            Self::Python(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<pyo3::PyErr> for Error {
+    fn from(error: pyo3::PyErr) -> Self {
+        Self::Python(error)
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<Error> for pyo3::PyErr {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Value(message) => InvalidParameterError::new_err(message),
+            Error::LengthMismatch(message) => LengthMismatchError::new_err(message),
+            Error::ThreadPool(message) => ThreadPoolError::new_err(message),
+            Error::Io(error) => error.into(),
+            Error::Python(error) => error,
+        }
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Python Exceptions
+// ------------------------------------------------------------------------
+//  The classes derive from the built-in exceptions raised before they
+//  existed as well as from `StrSimError`, so that `except ValueError` keeps
+//  catching invalid arguments. Python classes of several bases cannot be
+//  declared with `create_exception!`, so they are made with `type` instead.
+
+/// The exception classes of the bindings, made once per interpreter.
+#[cfg(feature = "python")]
+struct Exceptions {
+    base: Py<PyType>,
+    invalid_parameter: Py<PyType>,
+    length_mismatch: Py<PyType>,
+    thread_pool: Py<PyType>,
+}
+
+#[cfg(feature = "python")]
+impl Exceptions {
+    fn get(py: Python<'_>) -> &Self {
+        static EXCEPTIONS: GILOnceCell<Exceptions> = GILOnceCell::new();
+        EXCEPTIONS.get_or_init(py, || Self::new(py).expect("failed to create the exception classes"))
+    }
+
+    fn new(py: Python<'_>) -> PyResult<Self> {
+        let class = |name: &str, doc: &str, bases: Vec<&PyType>| -> PyResult<Py<PyType>> {
+            let namespace = PyDict::new(py);
+            namespace.set_item("__doc__", doc)?;
+            namespace.set_item("__module__", "strsim")?;
+            let class = py.get_type::<PyType>().call1((name, PyTuple::new(py, bases), namespace))?;
+            Ok(class.downcast::<PyType>()?.into())
+        };
+        let base = class(
+            "StrSimError",
+            "Base class of the errors raised by the library.",
+            vec![py.get_type::<pyo3::exceptions::PyException>()],
+        )?;
+        let invalid_parameter = class(
+            "InvalidParameterError",
+            "An argument out of range or data that cannot be read.",
+            vec![base.as_ref(py), py.get_type::<pyo3::exceptions::PyValueError>()],
+        )?;
+        let length_mismatch = class(
+            "LengthMismatchError",
+            "Sequences that must be as long as each other but are not.",
+            vec![invalid_parameter.as_ref(py)],
+        )?;
+        let thread_pool = class(
+            "ThreadPoolError",
+            "Worker threads that could not be started.",
+            vec![base.as_ref(py), py.get_type::<pyo3::exceptions::PyRuntimeError>()],
+        )?;
+        Ok(Self { base, invalid_parameter, length_mismatch, thread_pool })
+    }
+}
+
+/// Raised for `Error::Value`, and by the bindings for invalid arguments.
+#[cfg(feature = "python")]
+pub(crate) struct InvalidParameterError;
+
+#[cfg(feature = "python")]
+impl InvalidParameterError {
+    pub(crate) fn new_err(message: impl Into<String>) -> PyErr {
+        Python::with_gil(|py| PyErr::from_type(Exceptions::get(py).invalid_parameter.as_ref(py), message.into()))
+    }
+}
+
+/// Raised for `Error::LengthMismatch`, and by the bindings for sequences of
+/// different lengths.
+#[cfg(feature = "python")]
+pub(crate) struct LengthMismatchError;
+
+#[cfg(feature = "python")]
+impl LengthMismatchError {
+    pub(crate) fn new_err(message: impl Into<String>) -> PyErr {
+        Python::with_gil(|py| PyErr::from_type(Exceptions::get(py).length_mismatch.as_ref(py), message.into()))
+    }
+}
+
+/// Raised for `Error::ThreadPool`, and by the bindings for threads that
+/// could not be started.
+#[cfg(feature = "python")]
+pub(crate) struct ThreadPoolError;
+
+#[cfg(feature = "python")]
+impl ThreadPoolError {
+    pub(crate) fn new_err(message: impl Into<String>) -> PyErr {
+        Python::with_gil(|py| PyErr::from_type(Exceptions::get(py).thread_pool.as_ref(py), message.into()))
+    }
+}
+
+/// Adds the exception classes to `module`.
+#[cfg(feature = "python")]
+pub(crate) fn register_exceptions(py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    let exceptions = Exceptions::get(py);
+    module.add("StrSimError", &exceptions.base)?;
+    module.add("InvalidParameterError", &exceptions.invalid_parameter)?;
+    module.add("LengthMismatchError", &exceptions.length_mismatch)?;
+    module.add("ThreadPoolError", &exceptions.thread_pool)?;
+    Ok(())
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::path::Path;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyKeyError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::error::{Error, Result};
+use crate::gzip;
+use crate::persist::{Decoder, Encoder, Persist};
+
+// ------------------------------------------------------------------------
+//  Frequency Lists
+// ------------------------------------------------------------------------
+
+/// Reads a word-frequency list with one entry per line, the term in column
+/// `term_index` and the count in column `count_index`, split by `separator`
+/// or by any whitespace when `None`. Gzipped files are decompressed. `add`
+/// is called with each entry; lines without a term or a valid count are
+/// skipped. Returns the number of entries read.
+pub(crate) fn read_entries(
+    path: &Path,
+    term_index: usize,
+    count_index: usize,
+    separator: Option<&str>,
+    mut add: impl FnMut(&str, u64),
+) -> Result<usize> {
+    let mut data = std::fs::read(path)?;
+    if gzip::is_gzip(&data) {
+        data = gzip::decompress(&data)?;
+    }
+    let text = String::from_utf8(data).map_err(|_| Error::value(format!("{} is not UTF-8 text", path.display())))?;
+    let mut read = 0;
+    for line in text.lines() {
+        let column = |index: usize| match separator {
+            Some(separator) => line.split(separator).nth(index),
+            None => line.split_whitespace().nth(index),
+        };
+        let (Some(term), Some(count)) = (column(term_index), column(count_index)) else {
+            continue;
+        };
+        let Ok(count) = count.trim().parse::<u64>() else {
+            continue;
+        };
+        if !term.is_empty() {
+            add(term, count);
+            read += 1;
+        }
+    }
+    Ok(read)
+}
+
+// ------------------------------------------------------------------------
+//  Frequency Dictionaries
+// ------------------------------------------------------------------------
+
+/// Terms and their counts, read once from a word-frequency list and then
+/// handed to any number of `SymSpell` or `SpellCorrector` instances, or
+/// saved in a compact binary format that loads faster than the text.
+/// 
+/// # Arguments
+/// 
+/// * `entries` - Dict or iterable of pairs of a term and its count
+#[cfg_attr(feature = "python", pyclass(module = "strsim", text_signature = "(entries=None, /)"))]
+#[derive(Clone, Default)]
+pub struct FrequencyDictionary {
+    terms: Vec<String>,
+    counts: Vec<u64>,
+    index: HashMap<String, usize>,
+    total_count: u64,
+}
+
+impl FrequencyDictionary {
+    /// Makes an empty dictionary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a word-frequency list, gzipped or not, with one entry per
+    /// line, the term in column `term_index` and the count in column
+    /// `count_index`, split by `separator` or by any whitespace when `None`.
+    /// Lines without a term or a valid count are skipped, and the counts of
+    /// repeated terms are added up.
+    pub fn from_file(path: &Path, term_index: usize, count_index: usize, separator: Option<&str>) -> Result<Self> {
+        let mut dictionary = Self::new();
+        read_entries(path, term_index, count_index, separator, |term, count| {
+            dictionary.add(term, count);
+        })?;
+        Ok(dictionary)
+    }
+
+    /// Adds a term, or adds to its count if present. Returns whether the
+    /// term is new.
+    pub fn add(&mut self, term: &str, count: u64) -> bool {
+        self.total_count = self.total_count.saturating_add(count);
+        match self.index.get(term) {
+            Some(&i) => {
+                self.counts[i] = self.counts[i].saturating_add(count);
+                false
+            }
+            None => {
+                self.index.insert(term.to_owned(), self.terms.len());
+                self.terms.push(term.to_owned());
+                self.counts.push(count);
+                true
+            }
+        }
+    }
+
+    /// Count of a term, or `None` if absent.
+    pub fn count(&self, term: &str) -> Option<u64> {
+        self.index.get(term).map(|&i| self.counts[i])
+    }
+
+    /// Terms and their counts, in the order they were first added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.terms.iter().map(String::as_str).zip(self.counts.iter().copied())
+    }
+
+    /// Sum of the counts of all terms.
+    pub fn total_count(&self) -> u64 {
+        self.total_count
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+impl Persist for FrequencyDictionary {
+    const KIND: &'static str = "FrequencyDictionary";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.strs(&self.terms);
+        for &count in &self.counts {
+            encoder.varint(count);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let mut dictionary = Self::new();
+        let terms = decoder.strs()?;
+        for term in &terms {
+            let count = decoder.varint()?;
+            decoder.check(dictionary.add(term, count))?;
+        }
+        Ok(dictionary)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FrequencyDictionary {
+    #[new]
+    #[args(entries = "None")]
+    fn py_new(entries: Option<&PyAny>) -> PyResult<Self> {
+        let mut dictionary = Self::new();
+        if let Some(entries) = entries {
+            let entries = match entries.downcast::<PyDict>() {
+                Ok(dict) => dict.items().as_ref(),
+                Err(_) => entries,
+            };
+            for entry in entries.iter()? {
+                let (term, count): (&str, u64) = entry?.extract()?;
+                dictionary.add(term, count);
+            }
+        }
+        Ok(dictionary)
+    }
+
+    /// Reads a word-frequency list with one entry per line, such as
+    /// `term<TAB>count`, gzipped or not. Lines without a term or a valid
+    /// count are skipped, and the counts of repeated terms are added up.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - Path of the list, decompressed if gzipped
+    /// * `term_index` - Column of the term
+    /// * `count_index` - Column of the count
+    /// * `separator` - String between columns, or `None` for any whitespace
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The dictionary of the list
+    #[staticmethod]
+    #[args(term_index = "0", count_index = "1", separator = "None")]
+    #[pyo3(name = "from_file", text_signature = "(path, /, term_index=0, count_index=1, separator=None)")]
+    fn py_from_file(path: PathBuf, term_index: usize, count_index: usize, separator: Option<&str>) -> PyResult<Self> {
+        Ok(Self::from_file(&path, term_index, count_index, separator)?)
+    }
+
+    /// Adds a term, or adds to its count if present.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `term` - Term to add
+    /// * `count` - Frequency of the term
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Whether the term is new
+    #[args(count = "1")]
+    #[pyo3(name = "add", text_signature = "($self, term, /, count=1)")]
+    fn py_add(&mut self, term: &str, count: u64) -> bool {
+        self.add(term, count)
+    }
+
+    /// Count of a term, or `default` if absent.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `term` - Term to look up
+    /// * `default` - Value returned for an absent term
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Count of the term
+    #[args(default = "None")]
+    #[pyo3(text_signature = "($self, term, /, default=None)")]
+    fn get(&self, py: Python<'_>, term: &str, default: Option<PyObject>) -> PyObject {
+        match self.count(term) {
+            Some(count) => count.into_py(py),
+            None => default.unwrap_or_else(|| py.None()),
+        }
+    }
+
+    /// Pairs of a term and its count, in the order terms were first added.
+    #[pyo3(text_signature = "($self, /)")]
+    fn items(slf: PyRef<'_, Self>) -> Vec<(String, u64)> {
+        slf.iter().map(|(term, count)| (term.to_owned(), count)).collect()
+    }
+
+    #[getter(total_count)]
+    fn py_total_count(&self) -> u64 {
+        self.total_count()
+    }
+
+    /// Saves the dictionary to a file in a compact binary format, to be
+    /// restored by `FrequencyDictionary.load` without parsing the list again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads a dictionary written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved dictionary
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    fn __getitem__(&self, term: &str) -> PyResult<u64> {
+        self.count(term).ok_or_else(|| PyKeyError::new_err(term.to_owned()))
+    }
+
+    fn __contains__(&self, term: &str) -> bool {
+        self.index.contains_key(term)
+    }
+
+    /// Number of terms in the dictionary.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FrequencyDictionary(<{} terms>)", self.len())
+    }
+}
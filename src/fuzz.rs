@@ -0,0 +1,301 @@
+use crate::tokens::{self, Tokenizer};
+
+// ------------------------------------------------------------------------
+//  Ratio Helpers
+// ------------------------------------------------------------------------
+
+/// Length of the longest common subsequence of two character slices.
+fn lcs_length(a: &[char], b: &[char]) -> usize {
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+    let mut row = vec![0usize; b.len() + 1];
+    for &ca in a {
+        let mut diagonal = 0;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Calculates the normalized Indel similarity, `2 * LCS / (|a| + |b|)`, which
+/// is the `ratio` used by fuzzywuzzy and RapidFuzz scaled to 0.0 - 1.0.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Similarity between `a` and `b`
+pub fn ratio(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    ratio_chars(&a, &b)
+}
+
+fn ratio_chars(a: &[char], b: &[char]) -> f64 {
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 1.0;
+    }
+    2.0 * lcs_length(a, b) as f64 / total as f64
+}
+
+/// Calculates the best `ratio` of the shorter string against every window of
+/// the longer string with the same length as the shorter one.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Similarity between `a` and `b`
+pub fn partial_ratio(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if shorter.is_empty() {
+        return if longer.is_empty() { 1.0 } else { 0.0 };
+    }
+
+    let mut best = 0.0f64;
+    for window in longer.windows(shorter.len()) {
+        best = best.max(ratio_chars(&shorter, window));
+        if best >= 1.0 {
+            break;
+        }
+    }
+    best
+}
+
+
+// ------------------------------------------------------------------------
+//  Token Scorers
+// ------------------------------------------------------------------------
+
+/// Joins `tokens` with single spaces after sorting them.
+fn sorted_joined(mut tokens: Vec<String>) -> String {
+    tokens.sort_unstable();
+    tokens.join(" ")
+}
+
+/// Compares two strings with `ratio` after sorting their tokens, so that word
+/// order does not affect the score.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Similarity between `a` and `b`
+pub fn token_sort_ratio(a: &str, b: &str) -> f64 {
+    token_sort_ratio_with(a, b, &Tokenizer::Words)
+}
+
+/// Like `token_sort_ratio` with the tokens of `tokenizer`.
+pub fn token_sort_ratio_with(a: &str, b: &str, tokenizer: &Tokenizer) -> f64 {
+    ratio(&sorted_joined(tokenizer.tokenize(a)), &sorted_joined(tokenizer.tokenize(b)))
+}
+
+/// Compares two strings with `partial_ratio` after sorting their tokens.
+pub fn partial_token_sort_ratio(a: &str, b: &str) -> f64 {
+    partial_ratio(&sorted_joined(tokens::tokenize(a)), &sorted_joined(tokens::tokenize(b)))
+}
+
+fn token_set_with(mut tokens_a: Vec<String>, mut tokens_b: Vec<String>, scorer: fn(&str, &str) -> f64) -> f64 {
+    tokens_a.sort_unstable();
+    tokens_a.dedup();
+    tokens_b.sort_unstable();
+    tokens_b.dedup();
+    if tokens_a.is_empty() || tokens_b.is_empty() {
+        return 0.0;
+    }
+
+    let intersection: Vec<&str> = tokens_a
+        .iter()
+        .filter(|t| tokens_b.binary_search(t).is_ok())
+        .map(String::as_str)
+        .collect();
+    let diff_ab: Vec<&str> = tokens_a
+        .iter()
+        .filter(|t| tokens_b.binary_search(t).is_err())
+        .map(String::as_str)
+        .collect();
+    let diff_ba: Vec<&str> = tokens_b
+        .iter()
+        .filter(|t| tokens_a.binary_search(t).is_err())
+        .map(String::as_str)
+        .collect();
+
+    if !intersection.is_empty() && (diff_ab.is_empty() || diff_ba.is_empty()) {
+        return 1.0;
+    }
+
+    let sect = intersection.join(" ");
+    let combined_ab = [sect.as_str(), &diff_ab.join(" ")].join(" ").trim().to_string();
+    let combined_ba = [sect.as_str(), &diff_ba.join(" ")].join(" ").trim().to_string();
+
+    let mut best = scorer(&combined_ab, &combined_ba);
+    if !sect.is_empty() {
+        best = best
+            .max(scorer(&sect, &combined_ab))
+            .max(scorer(&sect, &combined_ba));
+    }
+    best
+}
+
+/// Compares the sorted intersection of the token sets of `a` and `b` against
+/// the intersection joined with each remainder, returning the best ratio.
+/// Repeated tokens and extra words in the longer string are not penalized.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Similarity between `a` and `b`
+pub fn token_set_ratio(a: &str, b: &str) -> f64 {
+    token_set_ratio_with(a, b, &Tokenizer::Words)
+}
+
+/// Like `token_set_ratio` with the tokens of `tokenizer`.
+pub fn token_set_ratio_with(a: &str, b: &str, tokenizer: &Tokenizer) -> f64 {
+    token_set_with(tokenizer.tokenize(a), tokenizer.tokenize(b), ratio)
+}
+
+/// Like `token_set_ratio` but compares the token sets with `partial_ratio`.
+pub fn partial_token_set_ratio(a: &str, b: &str) -> f64 {
+    token_set_with(tokens::tokenize(a), tokens::tokenize(b), partial_ratio)
+}
+
+
+// ------------------------------------------------------------------------
+//  Composite Scorers
+// ------------------------------------------------------------------------
+
+/// Weighted ratio combining `ratio`, `partial_ratio`, `token_sort_ratio` and
+/// `token_set_ratio` with the fuzzywuzzy weighting heuristics. Partial scorers
+/// are only consulted when the lengths differ by a factor of 1.5 or more, and
+/// are discounted further when they differ by more than a factor of 8.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Similarity between `a` and `b`
+pub fn wratio(a: &str, b: &str) -> f64 {
+    wratio_with(a, b, &Tokenizer::Words)
+}
+
+/// Like `wratio` with the tokens of `tokenizer`.
+pub fn wratio_with(a: &str, b: &str, tokenizer: &Tokenizer) -> f64 {
+    const UNBASE_SCALE: f64 = 0.95;
+
+    let tokens_a = tokenizer.tokenize(a);
+    let tokens_b = tokenizer.tokenize(b);
+    let a = tokens_a.join(" ");
+    let b = tokens_b.join(" ");
+    let len_a = a.chars().count();
+    let len_b = b.chars().count();
+    if len_a == 0 || len_b == 0 {
+        return 0.0;
+    }
+
+    let base = ratio(&a, &b);
+    let len_ratio = len_a.max(len_b) as f64 / len_a.min(len_b) as f64;
+    let (sorted_a, sorted_b) = (sorted_joined(tokens_a.clone()), sorted_joined(tokens_b.clone()));
+
+    if len_ratio < 1.5 {
+        let token_sort = ratio(&sorted_a, &sorted_b) * UNBASE_SCALE;
+        let token_set = token_set_with(tokens_a, tokens_b, ratio) * UNBASE_SCALE;
+        return base.max(token_sort).max(token_set);
+    }
+
+    let partial_scale = if len_ratio > 8.0 { 0.6 } else { 0.9 };
+    let partial = partial_ratio(&a, &b) * partial_scale;
+    let partial_token_sort = partial_ratio(&sorted_a, &sorted_b) * UNBASE_SCALE * partial_scale;
+    let partial_token_set = token_set_with(tokens_a, tokens_b, partial_ratio) * UNBASE_SCALE * partial_scale;
+    base.max(partial).max(partial_token_sort).max(partial_token_set)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts `score` equals a 0-100 reference score to its two decimals.
+    fn assert_score(score: f64, reference: f64, case: (&str, &str)) {
+        assert!((score * 100.0 - reference).abs() < 0.005, "{:?}: {} != {}", case, score * 100.0, reference);
+    }
+
+    #[test]
+    fn partial_ratio_matches_reference_scores() {
+        // Scores shared by fuzzywuzzy and RapidFuzz `fuzz.partial_ratio`.
+        let cases = [
+            ("this is a test", "this is a test!", 100.0),
+            ("new york mets", "the wonderful new york mets", 100.0),
+            ("physics 2 vid", "study physics physics 2 video", 100.0),
+            ("a certain string", "cetain", 83.33),
+            ("abcd", "XXXbcdXXX", 75.0),
+            ("Á", "ABCD", 0.0),
+            ("", "abc", 0.0),
+        ];
+        for (a, b, reference) in cases {
+            assert_score(partial_ratio(a, b), reference, (a, b));
+            assert_score(partial_ratio(b, a), reference, (b, a));
+        }
+    }
+
+    #[test]
+    fn token_sort_ratio_matches_reference_scores() {
+        // Scores of fuzzywuzzy `fuzz.token_sort_ratio`, and of RapidFuzz with
+        // `processor=utils.default_process`, which lowercases and drops
+        // punctuation as the tokenizer does.
+        let cases = [
+            ("fuzzy wuzzy was a bear", "wuzzy fuzzy was a bear", 100.0),
+            ("New York Mets vs Atlanta Braves", "Atlanta Braves vs New York Mets", 100.0),
+            ("Frodo Baggins", "Baggins, Frodo!", 100.0),
+            ("fuzzy was a bear", "fuzzy fuzzy was a bear", 84.21),
+            ("Mr. Jones", "Jones Mister", 80.0),
+            ("", "abc", 0.0),
+        ];
+        for (a, b, reference) in cases {
+            assert_score(token_sort_ratio(a, b), reference, (a, b));
+            assert_score(token_sort_ratio(b, a), reference, (b, a));
+        }
+    }
+
+    #[test]
+    fn token_set_ratio_matches_reference_scores() {
+        // Scores of fuzzywuzzy `fuzz.token_set_ratio`, and of RapidFuzz with
+        // `processor=utils.default_process`.
+        let cases = [
+            ("fuzzy was a bear", "fuzzy fuzzy was a bear", 100.0),
+            ("New York Mets vs Atlanta Braves", "Atlanta Braves vs New York Mets", 100.0),
+            ("great new york mets", "new york mets vs atlanta braves", 81.25),
+            ("the cat sat", "the dog sat down", 77.78),
+            ("abc", "xyz", 0.0),
+            ("{", "{", 0.0),
+        ];
+        for (a, b, reference) in cases {
+            assert_score(token_set_ratio(a, b), reference, (a, b));
+            assert_score(token_set_ratio(b, a), reference, (b, a));
+        }
+    }
+}
@@ -0,0 +1,629 @@
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyKeyError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyIterator, PyList};
+
+#[cfg(feature = "python")]
+use crate::error::InvalidParameterError;
+use crate::error::Result;
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::metric::Metric;
+use crate::ngram::NGramIndex;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::preprocess::Preprocessor;
+use crate::rank::Ranked;
+
+// ------------------------------------------------------------------------
+//  Fuzzy Keys
+// ------------------------------------------------------------------------
+
+/// Keys of a fuzzy container, looked up through an n-gram index and scored
+/// by a metric. Removed keys stay in the index and are skipped, and a key
+/// added again after its removal gets a new position. The index holds the
+/// keys after preprocessing, and `keys` as they were added.
+struct FuzzyKeys {
+    metric_name: String,
+    metric: Metric,
+    threshold: f64,
+    index: NGramIndex,
+    keys: Vec<String>,
+    positions: HashMap<String, usize>,
+    live: Vec<bool>,
+}
+
+impl FuzzyKeys {
+    fn new(metric: &str, threshold: f64, ngram: usize, preprocessor: Preprocessor) -> Result<Self> {
+        Ok(Self {
+            metric_name: metric.to_string(),
+            metric: Metric::from_name(metric)?,
+            threshold,
+            index: NGramIndex::preprocessed(ngram, preprocessor)?,
+            keys: Vec::new(),
+            positions: HashMap::new(),
+            live: Vec::new(),
+        })
+    }
+
+    /// Position of `key` and whether it was newly added.
+    fn insert(&mut self, key: &str) -> (usize, bool) {
+        if let Some(&position) = self.positions.get(key) {
+            return (position, false);
+        }
+        let position = self.live.len();
+        self.index.insert(key);
+        self.keys.push(key.to_string());
+        self.live.push(true);
+        self.positions.insert(key.to_string(), position);
+        (position, true)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<usize> {
+        let position = self.positions.remove(key)?;
+        self.live[position] = false;
+        Some(position)
+    }
+
+    /// Positions of the keys matching `query` and their scores, best match
+    /// first. The threshold defaults to the container's.
+    fn matches(&self, query: &str, threshold: Option<f64>) -> Vec<(usize, f64)> {
+        let threshold = threshold.unwrap_or(self.threshold);
+        let mut found: Vec<(usize, f64)> = self
+            .index
+            .matches(query, 1, &self.metric, Some(threshold))
+            .into_iter()
+            .filter(|&(position, _)| self.live[position])
+            .collect();
+
+        // A key without n-grams, such as the empty string, is only ever found
+        // by an exact lookup.
+        if let Some(&position) = self.positions.get(query) {
+            let key = self.index.choice(position);
+            let score = (self.metric.score)(key, key);
+            if !found.iter().any(|&(other, _)| other == position) && self.metric.passes(score, threshold) {
+                found.push((position, score));
+                found.sort_unstable_by(|&(i, a), &(j, b)| {
+                    Ranked::new(self.metric.kind, j, b).cmp(&Ranked::new(self.metric.kind, i, a))
+                });
+            }
+        }
+        found
+    }
+
+    #[cfg(feature = "python")]
+    fn best(&self, query: &str) -> Option<usize> {
+        self.matches(query, None).first().map(|&(position, _)| position)
+    }
+
+    fn key(&self, position: usize) -> &str {
+        &self.keys[position]
+    }
+
+    /// Keys still present, in insertion order.
+    fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.positions().map(|position| self.key(position))
+    }
+
+    /// Python iterator over a snapshot of the keys, in insertion order.
+    #[cfg(feature = "python")]
+    fn py_iter<'py>(&self, py: Python<'py>) -> PyResult<&'py PyIterator> {
+        PyIterator::from_object(py, PyList::new(py, self.iter().collect::<Vec<_>>()))
+    }
+
+    /// Positions of the keys still present, in insertion order.
+    fn positions(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.live.len()).filter(|&position| self.live[position])
+    }
+}
+
+impl Persist for FuzzyKeys {
+    const KIND: &'static str = "FuzzyKeys";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.str(&self.metric_name);
+        encoder.f64(self.threshold);
+        self.index.encode(encoder);
+        encoder.strs(&self.keys);
+        for &live in &self.live {
+            encoder.bool(live);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let metric_name = decoder.str()?;
+        let threshold = decoder.f64()?;
+        let index = NGramIndex::decode(decoder)?;
+        let keys = decoder.strs()?;
+        decoder.check(keys.len() == index.len())?;
+        let live = (0..index.len()).map(|_| decoder.bool()).collect::<crate::Result<Vec<bool>>>()?;
+        let mut keys = Self { index, keys, live, ..Self::new(&metric_name, threshold, 1, Preprocessor::default())? };
+        for position in keys.positions().collect::<Vec<_>>() {
+            let key = keys.key(position).to_string();
+            decoder.check(keys.positions.insert(key, position).is_none())?;
+        }
+        Ok(keys)
+    }
+}
+
+// ------------------------------------------------------------------------
+//  Fuzzy Set
+// ------------------------------------------------------------------------
+
+/// Set of strings looked up by similarity. Members sharing an n-gram with
+/// the query are scored by `metric`, and those passing `threshold` match.
+/// Membership tests with `in` are exact.
+/// 
+/// # Arguments
+/// 
+/// * `items` - Strings to add, or `None` to start empty
+/// * `metric` - Name of the metric scoring matches
+/// * `threshold` - Default largest distance or smallest similarity of a match
+/// * `ngram` - Number of characters per n-gram of the candidate index
+/// * `preprocessor` - `Preprocessor` applied to the keys and queries before
+///   they are compared, or `None`. Exact lookups and returned keys use the
+///   keys as they were added
+#[cfg_attr(
+    feature = "python",
+    pyclass(
+        module = "strsim",
+        text_signature = "(items=None, /, metric='normalized_levenshtein', threshold=0.8, ngram=3, preprocessor=None)"
+    )
+)]
+pub struct FuzzySet {
+    keys: FuzzyKeys,
+}
+
+impl FuzzySet {
+    /// Makes an empty set scoring matches by the metric named `metric`, with
+    /// `threshold` as the default largest distance or smallest similarity of
+    /// a match, and candidates sharing an n-gram of `ngram` characters with
+    /// the query.
+    pub fn new(metric: &str, threshold: f64, ngram: usize) -> Result<Self> {
+        Ok(Self { keys: FuzzyKeys::new(metric, threshold, ngram, Preprocessor::default())? })
+    }
+
+    /// Adds `item` to the set. Returns whether it was added, `false` if it
+    /// was already present.
+    pub fn add(&mut self, item: &str) -> bool {
+        self.keys.insert(item).1
+    }
+
+    /// Adds many strings to the set.
+    pub fn extend<I>(&mut self, items: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for item in items {
+            self.keys.insert(item.as_ref());
+        }
+    }
+
+    /// Removes `item` from the set if it is present. Returns whether it was
+    /// present.
+    pub fn discard(&mut self, item: &str) -> bool {
+        self.keys.remove(item).is_some()
+    }
+
+    /// Members matching `query` and their scores, best match first.
+    /// `threshold` is the largest distance or smallest similarity of a match,
+    /// or `None` for the set's threshold.
+    pub fn get(&self, query: &str, threshold: Option<f64>) -> Vec<(&str, f64)> {
+        self.keys
+            .matches(query, threshold)
+            .into_iter()
+            .map(|(position, score)| (self.keys.key(position), score))
+            .collect()
+    }
+
+    /// Whether `item` is exactly a member.
+    pub fn contains(&self, item: &str) -> bool {
+        self.keys.positions.contains_key(item)
+    }
+
+    /// Members in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.keys.iter()
+    }
+
+    /// Number of members.
+    pub fn len(&self) -> usize {
+        self.keys.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.positions.is_empty()
+    }
+}
+
+impl Persist for FuzzySet {
+    const KIND: &'static str = "FuzzySet";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        self.keys.encode(encoder);
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        Ok(Self { keys: FuzzyKeys::decode(decoder)? })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FuzzySet {
+    #[new]
+    #[args(items = "None", metric = "\"normalized_levenshtein\"", threshold = "0.8", ngram = "3", preprocessor = "None")]
+    fn py_new(
+        items: Option<Strings>,
+        metric: &str,
+        threshold: f64,
+        ngram: usize,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<Self> {
+        let mut set = Self { keys: FuzzyKeys::new(metric, threshold, ngram, preprocessor.unwrap_or_default())? };
+        if let Some(items) = items {
+            set.update(items)?;
+        }
+        Ok(set)
+    }
+
+    /// Adds a string to the set.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `item` - String to add
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Whether it was added, `False` if it was already present
+    #[pyo3(name = "add", text_signature = "($self, item, /)")]
+    fn py_add(&mut self, item: &str) -> bool {
+        self.add(item)
+    }
+
+    /// Adds many strings to the set.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `items` - Strings to add
+    #[pyo3(text_signature = "($self, items, /)")]
+    fn update(&mut self, items: Strings) -> PyResult<()> {
+        items.for_each_batch(|_, items| {
+            self.extend(items);
+            Ok(())
+        })
+    }
+
+    /// Removes a string from the set if it is present.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `item` - String to remove
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Whether it was present
+    #[pyo3(name = "discard", text_signature = "($self, item, /)")]
+    fn py_discard(&mut self, item: &str) -> bool {
+        self.discard(item)
+    }
+
+    /// Finds the members matching `query`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - String to look up
+    /// * `threshold` - Largest distance or smallest similarity of a match, or
+    ///   `None` for the set's threshold
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of a matching member and its score, best match first
+    #[args(threshold = "None")]
+    #[pyo3(name = "get", text_signature = "($self, query, /, threshold=None)")]
+    fn py_get(&self, query: &str, threshold: Option<f64>) -> Vec<(String, f64)> {
+        self.get(query, threshold).into_iter().map(|(member, score)| (member.to_string(), score)).collect()
+    }
+
+    /// Saves the set to a file in a compact binary format, to be restored
+    /// by `FuzzySet.load` without indexing the members again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads a set written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved set
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __contains__(&self, item: &str) -> bool {
+        self.contains(item)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyIterator> {
+        self.keys.py_iter(py)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Fuzzy Dict
+// ------------------------------------------------------------------------
+
+/// Mapping from strings to values looked up by similarity. Indexing returns
+/// the value of the exact key if present, and otherwise of the best key
+/// sharing an n-gram with it and passing `threshold` under `metric`, raising
+/// `KeyError` if there is none. Assignment and deletion are exact. As the
+/// values are Python objects, the dict is saved by pickling it rather than
+/// with `save`.
+/// 
+/// # Arguments
+/// 
+/// * `items` - Mapping or iterable of key and value pairs to add, or `None`
+///   to start empty
+/// * `metric` - Name of the metric scoring matches
+/// * `threshold` - Default largest distance or smallest similarity of a match
+/// * `ngram` - Number of characters per n-gram of the candidate index
+/// * `preprocessor` - `Preprocessor` applied to the keys and queries before
+///   they are compared, or `None`. Exact lookups and returned keys use the
+///   keys as they were added
+#[cfg(feature = "python")]
+#[pyclass(
+    module = "strsim",
+    text_signature = "(items=None, /, metric='normalized_levenshtein', threshold=0.8, ngram=3, preprocessor=None)"
+)]
+pub struct FuzzyDict {
+    keys: FuzzyKeys,
+    /// Value at each key position, `None` once the key is removed.
+    values: Vec<Option<PyObject>>,
+}
+
+#[cfg(feature = "python")]
+impl FuzzyDict {
+    fn lookup(&self, key: &str) -> Option<&PyObject> {
+        let position = self.keys.positions.get(key).copied().or_else(|| self.keys.best(key))?;
+        self.values[position].as_ref()
+    }
+
+    fn insert(&mut self, key: &str, value: PyObject) {
+        let (position, added) = self.keys.insert(key);
+        if added {
+            self.values.push(Some(value));
+        } else {
+            self.values[position] = Some(value);
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FuzzyDict {
+    #[new]
+    #[args(items = "None", metric = "\"normalized_levenshtein\"", threshold = "0.8", ngram = "3", preprocessor = "None")]
+    fn new(
+        items: Option<&PyAny>,
+        metric: &str,
+        threshold: f64,
+        ngram: usize,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<Self> {
+        let keys = FuzzyKeys::new(metric, threshold, ngram, preprocessor.unwrap_or_default())?;
+        let mut dict = Self { keys, values: Vec::new() };
+        if let Some(items) = items {
+            dict.update(items)?;
+        }
+        Ok(dict)
+    }
+
+    /// Adds many keys and values, replacing the values of existing keys.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `items` - Mapping or iterable of key and value pairs
+    #[pyo3(text_signature = "($self, items, /)")]
+    fn update(&mut self, items: &PyAny) -> PyResult<()> {
+        let pairs = if items.hasattr("items")? { items.call_method0("items")? } else { items };
+        for pair in pairs.iter()? {
+            let (key, value): (&str, PyObject) = pair?.extract()?;
+            self.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Looks up the value of `key` like indexing, without raising.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` - String to look up
+    /// * `default` - Value returned when no key matches
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Value of the exact or best matching key, or `default`
+    #[args(default = "None")]
+    #[pyo3(text_signature = "($self, key, default=None, /)")]
+    fn get(&self, py: Python<'_>, key: &str, default: Option<PyObject>) -> PyObject {
+        match self.lookup(key) {
+            Some(value) => value.clone_ref(py),
+            None => default.unwrap_or_else(|| py.None()),
+        }
+    }
+
+    /// Finds the keys matching `key`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` - String to look up
+    /// * `threshold` - Largest distance or smallest similarity of a match, or
+    ///   `None` for the dict's threshold
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Triples of a matching key, its value and its score, best
+    ///   match first
+    #[args(threshold = "None")]
+    #[pyo3(text_signature = "($self, key, /, threshold=None)")]
+    fn matches(&self, py: Python<'_>, key: &str, threshold: Option<f64>) -> Vec<(String, PyObject, f64)> {
+        self.keys
+            .matches(key, threshold)
+            .into_iter()
+            .filter_map(|(position, score)| {
+                let value = self.values[position].as_ref()?.clone_ref(py);
+                Some((self.keys.key(position).to_string(), value, score))
+            })
+            .collect()
+    }
+
+    /// Keys in insertion order.
+    #[pyo3(text_signature = "($self, /)")]
+    fn keys(&self) -> Vec<String> {
+        self.keys.positions().map(|position| self.keys.key(position).to_string()).collect()
+    }
+
+    /// Values in the insertion order of their keys.
+    #[pyo3(text_signature = "($self, /)")]
+    fn values(slf: PyRef<'_, Self>) -> Vec<PyObject> {
+        let py = slf.py();
+        slf.keys
+            .positions()
+            .filter_map(|position| slf.values[position].as_ref().map(|value| value.clone_ref(py)))
+            .collect()
+    }
+
+    /// Key and value pairs in insertion order.
+    #[pyo3(text_signature = "($self, /)")]
+    fn items(slf: PyRef<'_, Self>) -> Vec<(String, PyObject)> {
+        let py = slf.py();
+        slf.keys
+            .positions()
+            .filter_map(|position| {
+                let value = slf.values[position].as_ref()?.clone_ref(py);
+                Some((slf.keys.key(position).to_string(), value))
+            })
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.keys.positions.len()
+    }
+
+    fn __contains__(&self, key: &str) -> bool {
+        self.lookup(key).is_some()
+    }
+
+    fn __getitem__(&self, py: Python<'_>, key: &str) -> PyResult<PyObject> {
+        match self.lookup(key) {
+            Some(value) => Ok(value.clone_ref(py)),
+            None => Err(PyKeyError::new_err(key.to_string())),
+        }
+    }
+
+    fn __setitem__(&mut self, key: &str, value: PyObject) {
+        self.insert(key, value);
+    }
+
+    fn __delitem__(&mut self, key: &str) -> PyResult<()> {
+        match self.keys.remove(key) {
+            Some(position) => {
+                self.values[position] = None;
+                Ok(())
+            }
+            None => Err(PyKeyError::new_err(key.to_string())),
+        }
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> (Py<PyBytes>, Vec<Option<PyObject>>) {
+        let py = slf.py();
+        let values = slf.values.iter().map(|value| value.as_ref().map(|value| value.clone_ref(py))).collect();
+        (PyBytes::new(py, &slf.keys.to_bytes()).into(), values)
+    }
+
+    fn __setstate__(&mut self, state: (&[u8], Vec<Option<PyObject>>)) -> PyResult<()> {
+        let (keys, values) = state;
+        let keys = FuzzyKeys::from_bytes(keys)?;
+        if values.len() != keys.live.len() {
+            return Err(InvalidParameterError::new_err("saved FuzzyDict is truncated or corrupted"));
+        }
+        *self = Self { keys, values };
+        Ok(())
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyIterator> {
+        self.keys.py_iter(py)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_matches_members_above_threshold() {
+        let mut set = FuzzySet::new("normalized_levenshtein", 0.7, 3).unwrap();
+        assert!(set.add("apple"));
+        assert!(!set.add("apple"));
+        set.extend(["apply", "banana", ""]);
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.get("appel", Some(0.5)), [("apple", 0.6), ("apply", 0.6)]);
+        let found: Vec<&str> = set.get("apple", None).into_iter().map(|(member, _)| member).collect();
+        assert_eq!(found, ["apple", "apply"]);
+        assert_eq!(set.get("", None), [("", 1.0)]);
+        assert!(set.get("cherry", None).is_empty());
+    }
+
+    #[test]
+    fn discarded_members_are_skipped_and_saved() {
+        let mut set = FuzzySet::new("levenshtein", 1.0, 2).unwrap();
+        set.extend(["kitten", "sitten", "mitten"]);
+        assert!(set.discard("sitten"));
+        assert!(!set.discard("sitten"));
+        assert!(!set.contains("sitten"));
+        assert_eq!(set.get("kitten", None), [("kitten", 0.0), ("mitten", 1.0)]);
+        assert!(set.add("sitten"));
+        assert_eq!(set.iter().collect::<Vec<_>>(), ["kitten", "mitten", "sitten"]);
+
+        let loaded = FuzzySet::from_bytes(&set.to_bytes()).unwrap();
+        assert_eq!(loaded.iter().collect::<Vec<_>>(), ["kitten", "mitten", "sitten"]);
+        assert_eq!(loaded.get("sitting", Some(3.0)), set.get("sitting", Some(3.0)));
+    }
+
+    #[test]
+    fn unknown_metric_is_rejected() {
+        assert!(FuzzySet::new("nope", 0.8, 3).is_err());
+        assert!(FuzzySet::new("levenshtein", 0.8, 0).is_err());
+    }
+}
@@ -0,0 +1,429 @@
+use crate::error::{Error, Result};
+
+// ------------------------------------------------------------------------
+//  Gzip
+// ------------------------------------------------------------------------
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Whether `data` starts like a gzip file.
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Decompresses a gzip file, made of one member or of several concatenated
+/// ones. As with `gzip -d`, bytes after the last member are ignored.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(data.len() * 4);
+    let mut rest = data;
+    while is_gzip(rest) {
+        rest = member(rest, &mut output)?;
+    }
+    Ok(output)
+}
+
+fn corrupt(reason: &str) -> Error {
+    Error::value(format!("invalid gzip data: {}", reason))
+}
+
+/// Decompresses the member at the start of `data` onto `output`, returning
+/// the bytes after it.
+fn member<'a>(data: &'a [u8], output: &mut Vec<u8>) -> Result<&'a [u8]> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    let take = |data: &'a [u8], n: usize| data.get(n..).ok_or_else(|| corrupt("truncated header"));
+    let skip_zero_terminated = |data: &'a [u8]| match data.iter().position(|&byte| byte == 0) {
+        Some(end) => Ok(&data[end + 1..]),
+        None => Err(corrupt("truncated header")),
+    };
+
+    let header = data.get(..10).ok_or_else(|| corrupt("truncated header"))?;
+    if header[2] != 8 {
+        return Err(corrupt("unknown compression method"));
+    }
+    let flags = header[3];
+    let mut rest = &data[10..];
+    if flags & FEXTRA != 0 {
+        let length = match rest {
+            [low, high, ..] => u16::from_le_bytes([*low, *high]) as usize,
+            _ => return Err(corrupt("truncated header")),
+        };
+        rest = take(rest, 2 + length)?;
+    }
+    if flags & FNAME != 0 {
+        rest = skip_zero_terminated(rest)?;
+    }
+    if flags & FCOMMENT != 0 {
+        rest = skip_zero_terminated(rest)?;
+    }
+    if flags & FHCRC != 0 {
+        rest = take(rest, 2)?;
+    }
+
+    let start = output.len();
+    let mut bits = Bits::new(rest);
+    inflate(&mut bits, output)?;
+    let rest = &rest[bits.byte_position()..];
+    let trailer = rest.get(..8).ok_or_else(|| corrupt("truncated trailer"))?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc32_update(0, &output[start..]) != crc || (output.len() - start) as u32 != size {
+        return Err(corrupt("checksum mismatch"));
+    }
+    Ok(&rest[8..])
+}
+
+// ------------------------------------------------------------------------
+//  Deflate
+// ------------------------------------------------------------------------
+
+/// Reads a deflate stream least significant bit first.
+struct Bits<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u64,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0, buffer: 0, count: 0 }
+    }
+
+    /// The next `n` bits without consuming them, padded with zeros past the
+    /// end of the data.
+    fn peek(&mut self, n: u32) -> u32 {
+        while self.count <= 56 && self.position < self.data.len() {
+            self.buffer |= (self.data[self.position] as u64) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        (self.buffer & ((1 << n) - 1)) as u32
+    }
+
+    fn consume(&mut self, n: u32) -> Result<()> {
+        if n > self.count {
+            return Err(corrupt("unexpected end of data"));
+        }
+        self.buffer >>= n;
+        self.count -= n;
+        Ok(())
+    }
+
+    fn bits(&mut self, n: u32) -> Result<u32> {
+        let value = self.peek(n);
+        self.consume(n)?;
+        Ok(value)
+    }
+
+    /// Skips to the next byte boundary.
+    fn align(&mut self) {
+        let partial = self.count % 8;
+        self.buffer >>= partial;
+        self.count -= partial;
+    }
+
+    /// Offset of the first byte not consumed, once aligned.
+    fn byte_position(&self) -> usize {
+        self.position - (self.count / 8) as usize
+    }
+}
+
+/// A canonical Huffman code, decoded with one lookup of its longest code
+/// length. Each entry holds a symbol and the length of its code, or a length
+/// of 0 for bits that no code starts with.
+struct Huffman {
+    max_length: u32,
+    table: Vec<(u16, u8)>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self> {
+        let max_length = lengths.iter().copied().max().unwrap_or(0) as u32;
+        let mut counts = [0u32; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut next = [0u32; 16];
+        for length in 1..16 {
+            next[length] = (next[length - 1] + counts[length - 1]) << 1;
+        }
+        let mut table = vec![(0, 0); 1 << max_length];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length == 0 {
+                continue;
+            }
+            let code = next[length as usize];
+            next[length as usize] += 1;
+            if code >= 1 << length {
+                return Err(corrupt("oversubscribed Huffman code"));
+            }
+            let reversed = code.reverse_bits() >> (32 - length as u32);
+            for entry in table.iter_mut().skip(reversed as usize).step_by(1 << length) {
+                *entry = (symbol as u16, length);
+            }
+        }
+        Ok(Self { max_length, table })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16> {
+        let (symbol, length) = self.table.get(bits.peek(self.max_length) as usize).copied().unwrap_or((0, 0));
+        if length == 0 {
+            return Err(corrupt("invalid Huffman code"));
+        }
+        bits.consume(length as u32)?;
+        Ok(symbol)
+    }
+}
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Decompresses a raw deflate stream onto `output`, leaving `bits` aligned
+/// after its last block.
+fn inflate(bits: &mut Bits, output: &mut Vec<u8>) -> Result<()> {
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored(bits, output)?,
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                codes(bits, output, &Huffman::new(&lengths)?, &Huffman::new(&[5; 30])?)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic(bits)?;
+                codes(bits, output, &literals, &distances)?;
+            }
+            _ => return Err(corrupt("invalid block type")),
+        }
+        if last {
+            bits.align();
+            return Ok(());
+        }
+    }
+}
+
+fn stored(bits: &mut Bits, output: &mut Vec<u8>) -> Result<()> {
+    bits.align();
+    let length = bits.bits(16)?;
+    if bits.bits(16)? != !length & 0xffff {
+        return Err(corrupt("invalid stored block length"));
+    }
+    for _ in 0..length {
+        output.push(bits.bits(8)? as u8);
+    }
+    Ok(())
+}
+
+/// Reads the code lengths of a dynamic block, themselves Huffman coded.
+fn dynamic(bits: &mut Bits) -> Result<(Huffman, Huffman)> {
+    let n_literals = bits.bits(5)? as usize + 257;
+    let n_distances = bits.bits(5)? as usize + 1;
+    let n_code_lengths = bits.bits(4)? as usize + 4;
+    if n_literals > 286 || n_distances > 30 {
+        return Err(corrupt("too many codes"));
+    }
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..n_code_lengths] {
+        code_lengths[symbol] = bits.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(n_literals + n_distances);
+    while lengths.len() < n_literals + n_distances {
+        let (length, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => match lengths.last() {
+                Some(&previous) => (previous, 3 + bits.bits(2)?),
+                None => return Err(corrupt("repeated length with no previous length")),
+            },
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        if lengths.len() + repeat as usize > n_literals + n_distances {
+            return Err(corrupt("too many code lengths"));
+        }
+        lengths.resize(lengths.len() + repeat as usize, length);
+    }
+    if lengths[256] == 0 {
+        return Err(corrupt("missing end-of-block code"));
+    }
+    Ok((Huffman::new(&lengths[..n_literals])?, Huffman::new(&lengths[n_literals..])?))
+}
+
+fn codes(bits: &mut Bits, output: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<()> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        if symbol < 256 {
+            output.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASES.len() {
+            return Err(corrupt("invalid length code"));
+        }
+        let length = LENGTH_BASES[symbol] as usize + bits.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+        let symbol = distances.decode(bits)? as usize;
+        if symbol >= DISTANCE_BASES.len() {
+            return Err(corrupt("invalid distance code"));
+        }
+        let distance = DISTANCE_BASES[symbol] as usize + bits.bits(DISTANCE_EXTRA[symbol] as u32)? as usize;
+        if distance > output.len() {
+            return Err(corrupt("distance too far back"));
+        }
+        // The copy may overlap the bytes it writes, repeating them.
+        let start = output.len() - distance;
+        for i in 0..length {
+            output.push(output[start + i]);
+        }
+    }
+}
+
+// ------------------------------------------------------------------------
+//  Checksums
+// ------------------------------------------------------------------------
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/// CRC-32 of gzip and zip files, of `bytes` following a run whose checksum
+/// is `crc`. A run starts from 0.
+pub(crate) fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!crc, |c, &byte| CRC32_TABLE[((c ^ u32::from(byte)) & 0xff) as usize] ^ (c >> 8))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `gzip` output, with a modification time of 0, of `b"stored block\n"`
+    /// at level 0, in a stored block.
+    const STORED: [u8; 36] = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x01, 0x0d, 0x00, 0xf2, 0xff, 0x73, 0x74, 0x6f,
+        0x72, 0x65, 0x64, 0x20, 0x62, 0x6c, 0x6f, 0x63, 0x6b, 0x0a, 0x6d, 0x75, 0x88, 0xc5, 0x0d, 0x00, 0x00, 0x00,
+    ];
+
+    /// Same of `b"fixed fixed fixed huffman codes\n"` at level 9, in a block
+    /// with the fixed Huffman codes.
+    const FIXED: [u8; 42] = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0x4b, 0xcb, 0xac, 0x48, 0x4d, 0x51, 0x48, 0x43,
+        0x22, 0x33, 0x4a, 0xd3, 0xd2, 0x72, 0x13, 0xf3, 0x14, 0x92, 0xf3, 0x53, 0x52, 0x8b, 0xb9, 0x00, 0x43, 0x66,
+        0x23, 0x97, 0x20, 0x00, 0x00, 0x00,
+    ];
+
+    /// Same of `dynamic_text()` at level 9, in a block with dynamic Huffman
+    /// codes, and with the file name `dynamic.txt` in the header.
+    const DYNAMIC: [u8; 177] = [
+        0x1f, 0x8b, 0x08, 0x08, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0x64, 0x79, 0x6e, 0x61, 0x6d, 0x69, 0x63, 0x2e,
+        0x74, 0x78, 0x74, 0x00, 0xcd, 0x90, 0x89, 0x0d, 0x44, 0x21, 0x08, 0x05, 0x5b, 0x99, 0x12, 0x3e, 0xa0, 0xa8,
+        0xfd, 0x37, 0xb6, 0xa3, 0x55, 0x6c, 0x62, 0x8c, 0xc7, 0x3b, 0xf9, 0x08, 0x06, 0x87, 0x68, 0x72, 0x52, 0xcd,
+        0x38, 0xf4, 0x60, 0x07, 0x45, 0x0e, 0xc6, 0x62, 0x25, 0x49, 0x05, 0x9d, 0x1c, 0x11, 0xc5, 0xfa, 0x88, 0x64,
+        0x16, 0x47, 0xb4, 0x5c, 0x05, 0x3c, 0x0f, 0xe6, 0xc7, 0xa6, 0x27, 0xb9, 0xd8, 0x9b, 0x39, 0xc8, 0x64, 0xab,
+        0xa6, 0xd4, 0x24, 0x82, 0xdd, 0xb4, 0x8c, 0x4d, 0x25, 0x21, 0x92, 0x53, 0xec, 0xc9, 0x3a, 0x2c, 0xf7, 0x7a,
+        0xeb, 0x5d, 0x7d, 0xf4, 0xab, 0x2f, 0x48, 0xa8, 0x04, 0x69, 0x92, 0xe3, 0x09, 0x29, 0xa7, 0xa8, 0xd2, 0x1a,
+        0x68, 0xa3, 0x99, 0x96, 0xfb, 0x9a, 0x1b, 0xe1, 0x06, 0x89, 0x17, 0xaa, 0x6f, 0x40, 0x63, 0x1a, 0xd6, 0xc8,
+        0x06, 0xef, 0x57, 0x22, 0x6f, 0x1d, 0x4b, 0x59, 0xad, 0x6e, 0x49, 0xab, 0x5a, 0xb8, 0x5e, 0x79, 0x47, 0x70,
+        0x9c, 0x45, 0xf0, 0xfd, 0xe5, 0x4c, 0x7e, 0xc5, 0xaa, 0x9b, 0x6b, 0xaa, 0x01, 0x00, 0x00,
+    ];
+
+    fn dynamic_text() -> Vec<u8> {
+        (0..150).map(|i: u32| (i * i % 97).to_string()).collect::<Vec<_>>().join(" ").into_bytes()
+    }
+
+    #[test]
+    fn crc32_matches_check_value() {
+        assert_eq!(crc32_update(0, b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32_update(crc32_update(0, b"1234"), b"56789"), 0xcbf4_3926);
+        assert_eq!(crc32_update(0, b""), 0);
+    }
+
+    #[test]
+    fn decompresses_each_block_type() {
+        assert_eq!(decompress(&STORED).unwrap(), b"stored block\n");
+        assert_eq!(decompress(&FIXED).unwrap(), b"fixed fixed fixed huffman codes\n");
+        assert_eq!(decompress(&DYNAMIC).unwrap(), dynamic_text());
+    }
+
+    #[test]
+    fn decompresses_concatenated_members() {
+        let data = [&STORED[..], &DYNAMIC, &FIXED, b"\0\0 trailing"].concat();
+        let expected = [&b"stored block\n"[..], &dynamic_text(), b"fixed fixed fixed huffman codes\n"].concat();
+        assert_eq!(decompress(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_truncated_members() {
+        for data in [&STORED[..], &FIXED, &DYNAMIC] {
+            for len in 2..data.len() {
+                assert!(decompress(&data[..len]).is_err(), "prefix of {} bytes", len);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_corrupt_members() {
+        for data in [&STORED[..], &FIXED, &DYNAMIC] {
+            let mut bad_crc = data.to_vec();
+            let at = data.len() - 8;
+            bad_crc[at] ^= 1;
+            let err = decompress(&bad_crc).unwrap_err();
+            assert!(err.to_string().contains("checksum mismatch"), "{}", err);
+
+            let mut bad_size = data.to_vec();
+            bad_size[data.len() - 4] ^= 1;
+            assert!(decompress(&bad_size).is_err());
+
+            // A flipped bit of the compressed data is caught, if not by the
+            // decoder then by the checksum, unless it only pads a block.
+            let expected = decompress(data).unwrap();
+            let body = if data[3] & 8 != 0 { 22 } else { 10 };
+            for at in body..data.len() - 8 {
+                for bit in 0..8 {
+                    let mut corrupt = data.to_vec();
+                    corrupt[at] ^= 1 << bit;
+                    if let Ok(output) = decompress(&corrupt) {
+                        assert_eq!(output, expected, "bit {} of byte {}", bit, at);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_compression_method() {
+        let mut data = FIXED;
+        data[2] = 7;
+        assert!(decompress(&data).is_err());
+    }
+}
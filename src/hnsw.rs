@@ -0,0 +1,659 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use rayon::prelude::*;
+
+use crate::checkpoint::Fingerprint;
+use crate::error::{Error, Result};
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+use crate::simhash::mix;
+use crate::tokens::check_ngram;
+use crate::tokens;
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+
+// ------------------------------------------------------------------------
+//  N-Gram Embeddings
+// ------------------------------------------------------------------------
+
+/// Sparse vector of `(bucket, weight)` pairs in increasing bucket order.
+type Embedding = Vec<(u32, f32)>;
+
+/// Embeds `s` as its lowercased character `ngram`-grams hashed into `dim`
+/// buckets, each gram adding one to its bucket or subtracting one, by
+/// another bit of its hash, so that collisions cancel out on average. The
+/// vector is scaled to unit length, so the dot product of two embeddings is
+/// their cosine similarity. Strings without grams embed as the zero vector.
+fn embed(s: &str, ngram: usize, dim: u32) -> Embedding {
+    let mut weights: HashMap<u32, f32> = HashMap::new();
+    for gram in tokens::char_ngrams(s, ngram) {
+        let mut hash = Fingerprint::new();
+        hash.write(gram.as_bytes());
+        let hash = mix(hash.finish());
+        let sign = if hash >> 63 == 1 { -1.0 } else { 1.0 };
+        *weights.entry((hash % u64::from(dim)) as u32).or_insert(0.0) += sign;
+    }
+
+    let mut embedding: Embedding = weights.into_iter().filter(|&(_, weight)| weight != 0.0).collect();
+    embedding.sort_unstable_by_key(|&(bucket, _)| bucket);
+    let norm = embedding.iter().map(|&(_, weight)| weight * weight).sum::<f32>().sqrt();
+    for (_, weight) in &mut embedding {
+        *weight /= norm;
+    }
+    embedding
+}
+
+/// Dot product of two sparse vectors.
+fn dot(a: &[(u32, f32)], b: &[(u32, f32)]) -> f32 {
+    let (mut i, mut j, mut total) = (0, 0, 0.0);
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                total += a[i].1 * b[j].1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    total
+}
+
+
+// ------------------------------------------------------------------------
+//  Hierarchical Navigable Small World Graph
+// ------------------------------------------------------------------------
+
+/// A choice and its similarity to the vector searched for. Greater is
+/// closer, and ties prefer the lower index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Near {
+    similarity: f32,
+    node: usize,
+}
+
+impl Eq for Near {}
+
+impl PartialOrd for Near {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Near {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.similarity
+            .total_cmp(&other.similarity)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// Approximate nearest neighbour index over strings embedded as hashed
+/// character n-gram vectors, ranked by cosine similarity. The choices are
+/// linked in a hierarchical navigable small world graph: every choice joins
+/// layer 0 and, with geometrically falling probability, the sparser layers
+/// above it, each linked to its `m` closest neighbours found so far. Queries
+/// walk greedily down from the top layer and then search layer 0 with a
+/// beam of `ef` candidates, visiting a tiny fraction of the choices. Results
+/// are approximate: a larger `ef` or `ef_construction` finds more of the
+/// true neighbours at the cost of speed.
+/// 
+/// # Arguments
+/// 
+/// * `m` - Number of neighbours each choice is linked to per layer, twice
+///   that on layer 0
+/// * `ef_construction` - Beam width of the searches linking new choices
+/// * `ngram` - Number of characters per n-gram
+/// * `dim` - Number of hash buckets of the embeddings
+/// * `seed` - Seed of the random layer assignment
+/// * `preprocessor` - `Preprocessor` applied to the choices and queries
+///   before they are embedded, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(
+        module = "strsim",
+        text_signature = "(m=16, ef_construction=200, /, ngram=3, dim=65536, seed=1, preprocessor=None)"
+    )
+)]
+pub struct HNSWIndex {
+    m: usize,
+    ef_construction: usize,
+    ngram: usize,
+    dim: u32,
+    seed: u64,
+    preprocessor: Preprocessor,
+    choices: Vec<String>,
+    embeddings: Vec<Embedding>,
+    /// Neighbours of each choice on each of its layers, layer 0 first.
+    links: Vec<Vec<Vec<usize>>>,
+    /// Choice on the top layer where every search starts.
+    entry: Option<usize>,
+}
+
+impl HNSWIndex {
+    /// Makes an empty index linking each choice to its `m` closest
+    /// neighbours per layer, found by searches of beam width
+    /// `ef_construction`, over embeddings of `ngram`-grams hashed into `dim`
+    /// buckets. `seed` drives the random layer assignment.
+    pub fn new(m: usize, ef_construction: usize, ngram: usize, dim: u32, seed: u64) -> Result<Self> {
+        if m < 2 {
+            return Err(Error::value("m must be at least 2"));
+        }
+        if ef_construction == 0 {
+            return Err(Error::value("ef_construction must be at least 1"));
+        }
+        if dim == 0 {
+            return Err(Error::value("dim must be at least 1"));
+        }
+        check_ngram(ngram)?;
+        Ok(Self {
+            m,
+            ef_construction,
+            ngram,
+            dim,
+            seed,
+            preprocessor: Preprocessor::default(),
+            choices: Vec::new(),
+            embeddings: Vec::new(),
+            links: Vec::new(),
+            entry: None,
+        })
+    }
+
+    /// Indexes `choices`, replacing any choices indexed before. The choices
+    /// are embedded in parallel on `n_threads` threads, or on all available
+    /// cores when `None`, and then linked into the graph one by one.
+    pub fn build<S: AsRef<str> + Sync>(&mut self, choices: &[S], n_threads: Option<usize>) -> Result<()> {
+        let mut index = self.emptied();
+        let embeddings = thread_pool(n_threads)?.run_sized(choices.len(), 1, |counter, min_len| {
+            self.embed_all(counter, choices, min_len)
+        });
+        index.embeddings = embeddings.unwrap_or_default();
+        index.choices = choices.iter().map(|s| s.as_ref().to_string()).collect();
+        index.link(&Counter::new(false));
+        *self = index;
+        Ok(())
+    }
+
+    /// Approximately the `k` choices most similar to `s`, as pairs of a
+    /// choice index and its cosine similarity to `s`, most similar first.
+    /// `ef` is the beam width of the search, raised to `k` if smaller, or
+    /// `None` for 64.
+    pub fn query(&self, s: &str, k: usize, ef: Option<usize>) -> Vec<(usize, f64)> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        if k == 0 {
+            return Vec::new();
+        }
+        let embedding = self.embed(s);
+        let mut entry_points = vec![self.near(&embedding, entry)];
+        for layer in (1..self.links[entry].len()).rev() {
+            entry_points = self.search_layer(&embedding, &entry_points, 1, layer);
+        }
+        let ef = ef.unwrap_or(64).max(k);
+        let mut found = self.search_layer(&embedding, &entry_points, ef, 0);
+        found.truncate(k);
+        found.into_iter().map(|near| (near.node, f64::from(near.similarity))).collect()
+    }
+
+    /// Parallel version of `query` for many query strings, on `n_threads`
+    /// threads, or on all available cores when `None`.
+    pub fn query_many<S: AsRef<str> + Sync>(
+        &self,
+        strings: &[S],
+        k: usize,
+        ef: Option<usize>,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<Vec<(usize, f64)>>> {
+        let output = thread_pool(n_threads)?.run_sized(strings.len(), 1, |counter, min_len| {
+            self.query_all(counter, strings, k, ef, min_len)
+        });
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Number of indexed choices.
+    pub fn len(&self) -> usize {
+        self.choices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.choices.is_empty()
+    }
+
+    /// An index with the same parameters and no choices.
+    fn emptied(&self) -> Self {
+        Self {
+            preprocessor: self.preprocessor.clone(),
+            choices: Vec::new(),
+            embeddings: Vec::new(),
+            links: Vec::new(),
+            entry: None,
+            ..*self
+        }
+    }
+
+    fn embed(&self, s: &str) -> Embedding {
+        embed(&self.preprocessor.apply(s), self.ngram, self.dim)
+    }
+
+    fn near(&self, embedding: &[(u32, f32)], node: usize) -> Near {
+        Near { similarity: dot(embedding, &self.embeddings[node]), node }
+    }
+
+    /// Top layer of choice `node`, drawn from a geometric distribution by
+    /// hashing its index with the seed, so that it does not depend on the
+    /// order of insertion.
+    fn level(&self, node: usize) -> usize {
+        let hash = mix(self.seed ^ mix(node as u64));
+        let uniform = ((hash >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        (-uniform.ln() / (self.m as f64).ln()) as usize
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            2 * self.m
+        } else {
+            self.m
+        }
+    }
+
+    /// Beam search of `layer` for the `ef` choices closest to `embedding`,
+    /// starting from `entry_points`. Returns them closest first.
+    fn search_layer(&self, embedding: &[(u32, f32)], entry_points: &[Near], ef: usize, layer: usize) -> Vec<Near> {
+        let mut visited: HashSet<usize> = entry_points.iter().map(|near| near.node).collect();
+        let mut candidates: BinaryHeap<Near> = entry_points.iter().copied().collect();
+        let mut found: BinaryHeap<Reverse<Near>> = entry_points.iter().copied().map(Reverse).collect();
+        while found.len() > ef {
+            found.pop();
+        }
+
+        while let Some(candidate) = candidates.pop() {
+            let farthest = found.peek().expect("there is at least one entry point").0;
+            if found.len() >= ef && candidate < farthest {
+                break;
+            }
+            for &neighbour in &self.links[candidate.node][layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let near = self.near(embedding, neighbour);
+                if found.len() < ef || near > found.peek().expect("the beam is full").0 {
+                    candidates.push(near);
+                    found.push(Reverse(near));
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+        found.into_sorted_vec().into_iter().map(|Reverse(near)| near).collect()
+    }
+
+    /// Chooses up to `m` neighbours among `candidates`, given closest first.
+    /// A candidate closer to an already chosen neighbour than to the target
+    /// is skipped at first, so that the links spread out in different
+    /// directions, and skipped candidates only fill the places left over.
+    fn select_neighbours(&self, candidates: &[Near], m: usize) -> Vec<usize> {
+        let mut chosen: Vec<usize> = Vec::with_capacity(m);
+        let mut skipped: Vec<usize> = Vec::new();
+        for candidate in candidates {
+            if chosen.len() == m {
+                break;
+            }
+            let embedding = &self.embeddings[candidate.node];
+            if chosen.iter().all(|&other| self.near(embedding, other).similarity < candidate.similarity) {
+                chosen.push(candidate.node);
+            } else {
+                skipped.push(candidate.node);
+            }
+        }
+        let missing = m - chosen.len();
+        chosen.extend(skipped.into_iter().take(missing));
+        chosen
+    }
+
+    /// Links the next choice, whose embedding has already been pushed, into
+    /// the graph.
+    fn insert(&mut self, node: usize) {
+        let level = self.level(node);
+        self.links.push(vec![Vec::new(); level + 1]);
+        let Some(entry) = self.entry else {
+            self.entry = Some(node);
+            return;
+        };
+
+        let embedding = self.embeddings[node].clone();
+        let top = self.links[entry].len() - 1;
+        let mut entry_points = vec![self.near(&embedding, entry)];
+        for layer in (level + 1..=top).rev() {
+            entry_points = self.search_layer(&embedding, &entry_points, 1, layer);
+        }
+        for layer in (0..=level.min(top)).rev() {
+            entry_points = self.search_layer(&embedding, &entry_points, self.ef_construction, layer);
+            let neighbours = self.select_neighbours(&entry_points, self.m);
+            for &neighbour in &neighbours {
+                self.links[neighbour][layer].push(node);
+                if self.links[neighbour][layer].len() > self.max_links(layer) {
+                    let own = &self.embeddings[neighbour];
+                    let mut linked: Vec<Near> = self.links[neighbour][layer]
+                        .iter()
+                        .map(|&other| self.near(own, other))
+                        .collect();
+                    linked.sort_unstable_by(|a, b| b.cmp(a));
+                    self.links[neighbour][layer] = self.select_neighbours(&linked, self.max_links(layer));
+                }
+            }
+            self.links[node][layer] = neighbours;
+        }
+        if level > top {
+            self.entry = Some(node);
+        }
+    }
+
+    /// Links the embedded choices into the graph one by one, stopping
+    /// early if the job is cancelled.
+    fn link(&mut self, counter: &Counter) {
+        for node in 0..self.embeddings.len() {
+            if counter.is_cancelled() {
+                break;
+            }
+            self.insert(node);
+        }
+    }
+
+    /// Embeddings of each of `choices`, computed in parallel, or `None` if
+    /// the job was cancelled.
+    fn embed_all<S: AsRef<str> + Sync>(&self, counter: &Counter, choices: &[S], min_len: usize) -> Option<Vec<Embedding>> {
+        choices
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|s| (!counter.is_cancelled()).then(|| self.embed(s.as_ref())))
+            .collect()
+    }
+
+    /// `query` for each of `strings`, in parallel, or `None` if the job was
+    /// cancelled.
+    fn query_all<S: AsRef<str> + Sync>(
+        &self,
+        counter: &Counter,
+        strings: &[S],
+        k: usize,
+        ef: Option<usize>,
+        min_len: usize,
+    ) -> Option<Vec<Vec<(usize, f64)>>> {
+        strings
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|s| (!counter.is_cancelled()).then(|| self.query(s.as_ref(), k, ef)))
+            .collect()
+    }
+}
+
+impl Persist for HNSWIndex {
+    const KIND: &'static str = "HNSWIndex";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.usize(self.m);
+        encoder.usize(self.ef_construction);
+        encoder.usize(self.ngram);
+        encoder.varint(u64::from(self.dim));
+        encoder.varint(self.seed);
+        self.preprocessor.encode(encoder);
+        encoder.strs(&self.choices);
+        for layers in &self.links {
+            encoder.usize(layers.len());
+            for neighbours in layers {
+                encoder.usize(neighbours.len());
+                for &neighbour in neighbours {
+                    encoder.usize(neighbour);
+                }
+            }
+        }
+        encoder.index(self.entry);
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let (m, ef_construction, ngram) = (decoder.usize()?, decoder.usize()?, decoder.usize()?);
+        let dim = u32::try_from(decoder.varint()?).unwrap_or(0);
+        let seed = decoder.varint()?;
+        let mut index = Self { preprocessor: Preprocessor::decode(decoder)?, ..Self::new(m, ef_construction, ngram, dim, seed)? };
+        index.choices = decoder.strs()?;
+        let n_choices = index.choices.len();
+        for _ in 0..n_choices {
+            let n_layers = decoder.length()?;
+            decoder.check(n_layers > 0)?;
+            let layers = (0..n_layers)
+                .map(|_| (0..decoder.length()?).map(|_| decoder.usize()).collect::<crate::Result<Vec<_>>>())
+                .collect::<crate::Result<Vec<_>>>()?;
+            index.links.push(layers);
+        }
+        index.entry = decoder.index(n_choices)?;
+
+        // Every link must lead to a choice present on that layer, and the
+        // search must start on the top layer.
+        let top = index.entry.map(|entry| index.links[entry].len());
+        decoder.check(index.entry.is_some() == (n_choices > 0))?;
+        for layers in &index.links {
+            decoder.check(top.is_some_and(|top| layers.len() <= top))?;
+            for (layer, neighbours) in layers.iter().enumerate() {
+                for &neighbour in neighbours {
+                    decoder.check(neighbour < n_choices && index.links[neighbour].len() > layer)?;
+                }
+            }
+        }
+
+        let embeddings =
+            thread_pool(None)?.install(|| index.choices.par_iter().map(|choice| index.embed(choice)).collect());
+        index.embeddings = embeddings;
+        Ok(index)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl HNSWIndex {
+    #[new]
+    #[args(m = "16", ef_construction = "200", ngram = "3", dim = "65536", seed = "1", preprocessor = "None")]
+    fn py_new(
+        m: usize,
+        ef_construction: usize,
+        ngram: usize,
+        dim: u32,
+        seed: u64,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<Self> {
+        Ok(Self { preprocessor: preprocessor.unwrap_or_default(), ..Self::new(m, ef_construction, ngram, dim, seed)? })
+    }
+
+    /// Indexes `choices`, replacing any choices indexed before. The choices
+    /// are embedded in parallel and then linked into the graph one by one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `choices` - Strings to index
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "build", text_signature = "($self, choices, /, n_threads=None)")]
+    fn py_build(&mut self, choices: Strings, n_threads: NThreads) -> PyResult<()> {
+        let choices = choices.as_strs()?;
+        let pool = thread_pool(n_threads)?;
+        let mut index = self.emptied();
+        let reporter = Reporter::new(None, Some(choices.len()));
+        index.embeddings = pool
+            .install_sized(&reporter, choices.len(), 1, |counter, min_len| {
+                self.embed_all(counter, &choices, min_len)
+            })?
+            .unwrap_or_default();
+        index.choices = choices.iter().map(|&s| s.to_string()).collect();
+
+        let reporter = Reporter::new(None, Some(choices.len()));
+        pool.install_reporting(&reporter, |counter| index.link(counter))?;
+        *self = index;
+        Ok(())
+    }
+
+    /// Finds approximately the `k` choices most similar to `s`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `s` - Query string
+    /// * `k` - Number of neighbours to return
+    /// * `ef` - Beam width of the search, raised to `k` if smaller, or `None`
+    ///   for 64
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Up to `k` pairs of a choice index and its cosine
+    ///   similarity to `s`, most similar first
+    #[args(ef = "None")]
+    #[pyo3(name = "query", text_signature = "($self, s, k, /, ef=None)")]
+    fn py_query(&self, s: &str, k: usize, ef: Option<usize>) -> Vec<(usize, f64)> {
+        self.query(s, k, ef)
+    }
+
+    /// Parallel version of `query` for many query strings.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `strings` - Query strings
+    /// * `k` - Number of neighbours to return per query
+    /// * `ef` - Beam width of the search, raised to `k` if smaller, or `None`
+    ///   for 64
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each query, up to `k` pairs of a choice index and its
+    ///   cosine similarity, most similar first
+    #[args(ef = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "query_many", text_signature = "($self, strings, k, /, ef=None, n_threads=None)")]
+    fn py_query_many(
+        &self,
+        strings: Strings,
+        k: usize,
+        ef: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<Vec<(usize, f64)>>> {
+        let strings = strings.as_strs()?;
+        let reporter = Reporter::new(None, Some(strings.len()));
+        let output = thread_pool(n_threads)?.install_sized(&reporter, strings.len(), 1, |counter, min_len| {
+            self.query_all(counter, &strings, k, ef, min_len)
+        })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Saves the index to a file in a compact binary format, to be restored
+    /// by `HNSWIndex.load` without linking the choices again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads an index written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved index
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of indexed choices.
+    fn __len__(&self) -> usize {
+        self.choices.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    #[test]
+    fn full_beam_search_matches_brute_force() {
+        let mut rng = Rng::new(623);
+        let choices = rng.strings(300, 10);
+        let mut index = HNSWIndex::new(16, 200, 2, 1024, 5).unwrap();
+        index.build(&choices, Some(2)).unwrap();
+        assert_eq!(index.len(), choices.len());
+        for query in rng.strings(20, 10) {
+            let embedding = embed(&query, 2, 1024);
+            let mut expected: Vec<Near> =
+                (0..choices.len()).map(|node| Near { similarity: dot(&embedding, &index.embeddings[node]), node }).collect();
+            expected.sort_unstable_by(|a, b| b.cmp(a));
+            let expected: Vec<_> = expected[..5].iter().map(|near| (near.node, f64::from(near.similarity))).collect();
+            assert_eq!(index.query(&query, 5, Some(choices.len())), expected, "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn recall_against_brute_force_is_high() {
+        let mut rng = Rng::new(624);
+        // Names made of shared syllables, so that most pairs share some
+        // n-grams as real names do and similarities vary smoothly.
+        let syllables = ["ka", "ro", "mi", "len", "ta", "ber", "son", "li", "na", "vor", "el", "dan", "ko", "ri", "sta", "mo"];
+        let mut name = || (0..2 + rng.below(4)).map(|_| syllables[rng.below(syllables.len())]).collect::<String>();
+        let choices: Vec<String> = (0..2000).map(|_| name()).collect();
+        let queries: Vec<String> = (0..100).map(|_| name()).collect();
+        let mut index = HNSWIndex::new(16, 200, 3, 65536, 1).unwrap();
+        index.build(&choices, None).unwrap();
+        let found = index.query_many(&queries, 10, None, None).unwrap();
+        let (mut hits, mut total) = (0, 0);
+        for (query, found) in queries.iter().zip(&found) {
+            let embedding = index.embed(query);
+            let mut similarities: Vec<f32> = index.embeddings.iter().map(|e| dot(&embedding, e)).collect();
+            similarities.sort_unstable_by(|a, b| b.total_cmp(a));
+            // Choices tied with the tenth most similar one are all true
+            // neighbours.
+            let tenth = f64::from(similarities[9]);
+            hits += found.iter().filter(|&&(_, similarity)| similarity >= tenth).count();
+            total += 10;
+        }
+        let recall = hits as f64 / total as f64;
+        assert!(recall >= 0.95, "recall {}", recall);
+        let loaded = HNSWIndex::from_bytes(&index.to_bytes()).unwrap();
+        assert_eq!(loaded.query_many(&queries, 10, None, Some(1)).unwrap(), found);
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        assert!(matches!(HNSWIndex::new(1, 200, 3, 65536, 1), Err(Error::Value(_))));
+        assert!(matches!(HNSWIndex::new(16, 0, 3, 65536, 1), Err(Error::Value(_))));
+        assert!(matches!(HNSWIndex::new(16, 200, 3, 0, 1), Err(Error::Value(_))));
+        assert!(HNSWIndex::new(16, 200, 0, 65536, 1).is_err());
+    }
+}
@@ -0,0 +1,915 @@
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::ffi::CStr;
+use std::mem;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyBytes, PyIterator, PyList, PyString};
+use pyo3::{ffi, AsPyPointer};
+
+use crate::error::{InvalidParameterError, LengthMismatchError};
+use crate::mapped::{CandidateFile, Mapped};
+use crate::preprocess::Preprocess;
+
+// ------------------------------------------------------------------------
+//  Raw Buffers
+// ------------------------------------------------------------------------
+
+/// A contiguous buffer exported by a Python object, without the element type
+/// checks of `pyo3::buffer::PyBuffer`. NumPy exports UCS4 strings with the
+/// `w` format and Arrow exports its buffers as signed bytes, neither of which
+/// `PyBuffer` accepts.
+struct RawBuffer(Box<ffi::Py_buffer>);
+
+impl RawBuffer {
+    fn get(obj: &PyAny) -> PyResult<Self> {
+        // SAFETY: `view` is only read after `PyObject_GetBuffer` succeeded,
+        // which fully initializes it.
+        unsafe {
+            let mut view: Box<ffi::Py_buffer> = Box::new(mem::zeroed());
+            let flags = ffi::PyBUF_FORMAT | ffi::PyBUF_C_CONTIGUOUS;
+            if ffi::PyObject_GetBuffer(obj.as_ptr(), &mut *view, flags) == -1 {
+                return Err(PyErr::fetch(obj.py()));
+            }
+            Ok(Self(view))
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        if self.0.buf.is_null() || self.0.len == 0 {
+            return &[];
+        }
+        // SAFETY: the exporter guarantees `len` readable bytes at `buf` until
+        // the buffer is released in `drop`.
+        unsafe { std::slice::from_raw_parts(self.0.buf as *const u8, self.0.len as usize) }
+    }
+
+    fn format(&self) -> &str {
+        if self.0.format.is_null() {
+            return "B";
+        }
+        // SAFETY: a non-null format is a NUL-terminated string owned by the
+        // exporter.
+        unsafe { CStr::from_ptr(self.0.format) }.to_str().unwrap_or("")
+    }
+
+    fn item_size(&self) -> usize {
+        self.0.itemsize as usize
+    }
+
+    fn dimensions(&self) -> usize {
+        self.0.ndim as usize
+    }
+
+    fn shape(&self) -> Vec<usize> {
+        if self.0.shape.is_null() {
+            return vec![self.0.len as usize / self.item_size().max(1)];
+        }
+        // SAFETY: a non-null shape holds `ndim` lengths owned by the exporter.
+        unsafe { std::slice::from_raw_parts(self.0.shape, self.dimensions()) }
+            .iter()
+            .map(|&n| n as usize)
+            .collect()
+    }
+}
+
+impl Drop for RawBuffer {
+    fn drop(&mut self) {
+        // SAFETY: `view` was filled by a successful `PyObject_GetBuffer` and is
+        // released exactly once, here, holding the GIL.
+        Python::with_gil(|_| unsafe { ffi::PyBuffer_Release(&mut *self.0) });
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  String Inputs
+// ------------------------------------------------------------------------
+
+/// A string passed in from Python. A `str`, or an instance of a subclass
+/// such as `numpy.str_`, is borrowed. Other objects are converted the way
+/// Python code would: path-like objects such as `pathlib.Path` through
+/// `os.fspath`, and objects with a `__str__` of their own through `str`.
+/// Numbers and bytes are not strings, and are rejected rather than spelt out.
+pub struct Text<'py>(Cow<'py, str>);
+
+impl<'py> FromPyObject<'py> for Text<'py> {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if let Ok(s) = obj.downcast::<PyString>() {
+            return Ok(Self(Cow::Borrowed(s.to_str()?)));
+        }
+        let py = obj.py();
+        if obj.hasattr(pyo3::intern!(py, "__fspath__"))? {
+            let path = py.import("os")?.call_method1("fspath", (obj,))?;
+            if let Ok(path) = path.downcast::<PyString>() {
+                return Ok(Self(Cow::Owned(path.to_str()?.to_owned())));
+            }
+        } else if has_own_str(obj) {
+            return Ok(Self(Cow::Owned(obj.str()?.to_str()?.to_owned())));
+        }
+        Err(PyTypeError::new_err(format!("'{}' object is not a string", obj.get_type().name()?)))
+    }
+}
+
+impl From<String> for Text<'_> {
+    fn from(s: String) -> Self {
+        Self(Cow::Owned(s))
+    }
+}
+
+impl Deref for Text<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Text<'_> {
+    pub fn into_owned(self) -> String {
+        self.0.into_owned()
+    }
+
+    /// The text after `pre`, still borrowed if that leaves it unchanged.
+    pub fn preprocessed(self, pre: &Preprocess) -> Self {
+        let preprocessed = match pre.apply(&self) {
+            Cow::Borrowed(s) if s.len() == self.len() => None,
+            s => Some(s.into_owned()),
+        };
+        match preprocessed {
+            Some(s) => Self(Cow::Owned(s)),
+            None => self,
+        }
+    }
+}
+
+/// Whether the type of `obj` defines `__str__` rather than inheriting the
+/// `repr` fallback of `object`, leaving out numbers and bytes, whose `str`
+/// is their `repr`.
+fn has_own_str(obj: &PyAny) -> bool {
+    if obj.is_instance_of::<PyBytes>().unwrap_or(false) || obj.is_instance_of::<PyByteArray>().unwrap_or(false) {
+        return false;
+    }
+    // Both slots are read from type objects in the interpreter's memory,
+    // so their addresses are compared rather than the functions.
+    let slot = |ty: &ffi::PyTypeObject| ty.tp_str.map(|f| f as usize);
+    // SAFETY: the type of a live object and `object` itself are live type
+    // objects, and `PyNumber_Check` only inspects the type of `obj`.
+    unsafe {
+        slot(&*ffi::Py_TYPE(obj.as_ptr())) != slot(&*std::ptr::addr_of!(ffi::PyBaseObject_Type))
+            && ffi::PyNumber_Check(obj.as_ptr()) == 0
+    }
+}
+
+/// Number of strings pulled from a lazy iterable before they are scored.
+pub const BATCH_SIZE: usize = 65536;
+
+enum Source<'py> {
+    Borrowed(Vec<&'py str>),
+    Converted(Vec<Text<'py>>),
+    Decoded(Vec<String>),
+    Arrow(Vec<ArrowChunk>),
+    Mapped(Arc<Mapped>),
+    Lazy {
+        iter: &'py PyIterator,
+        drained: OnceCell<Vec<String>>,
+    },
+    Preprocessed {
+        strings: Box<Strings<'py>>,
+        pre: Preprocess,
+        preprocessed: OnceCell<Vec<String>>,
+    },
+}
+
+/// A list of strings passed in from Python. Besides any sequence of strings,
+/// each read as a `Text`, NumPy unicode arrays and Arrow `StringArray`/`LargeStringArray` objects,
+/// or `ChunkedArray` objects of them, are read straight from their buffers,
+/// and a `CandidateFile` from its memory map, without creating a Python
+/// string per element. Other iterables, such as generators, are consumed
+/// lazily.
+pub struct Strings<'py> {
+    source: Source<'py>,
+}
+
+impl<'py> FromPyObject<'py> for Strings<'py> {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if obj.is_instance_of::<PyList>()? {
+            return Self::sequence(obj);
+        }
+        if let Ok(file) = obj.extract::<PyRef<CandidateFile>>() {
+            return Ok(Self { source: Source::Mapped(file.mapped()) });
+        }
+        if let Some(source) = arrow_source(obj)? {
+            return Ok(Self { source });
+        }
+        if let Some(source) = numpy_source(obj)? {
+            return Ok(Self { source });
+        }
+        if !is_sequence(obj) {
+            if let Ok(iter) = obj.iter() {
+                return Ok(Self { source: Source::Lazy { iter, drained: OnceCell::new() } });
+            }
+        }
+        Self::sequence(obj)
+    }
+}
+
+impl From<Vec<String>> for Strings<'_> {
+    fn from(strings: Vec<String>) -> Self {
+        Self { source: Source::Decoded(strings) }
+    }
+}
+
+impl<'py> Strings<'py> {
+    /// The strings of a sequence, borrowed when they are all `str` and
+    /// converted one by one otherwise.
+    fn sequence(obj: &'py PyAny) -> PyResult<Self> {
+        if let Ok(strings) = obj.extract() {
+            return Ok(Self { source: Source::Borrowed(strings) });
+        }
+        Ok(Self { source: Source::Converted(obj.extract()?) })
+    }
+
+    /// Number of strings, or `None` for a lazy iterable that has not been
+    /// drained.
+    pub fn len_hint(&self) -> Option<usize> {
+        match &self.source {
+            Source::Borrowed(strings) => Some(strings.len()),
+            Source::Converted(strings) => Some(strings.len()),
+            Source::Decoded(strings) => Some(strings.len()),
+            Source::Arrow(chunks) => Some(chunks.iter().map(|chunk| chunk.len).sum()),
+            Source::Mapped(mapped) => mapped.len_hint(),
+            Source::Lazy { drained, .. } => drained.get().map(Vec::len),
+            Source::Preprocessed { strings, .. } => strings.len_hint(),
+        }
+    }
+
+    /// The strings after `pre`, each preprocessed once however many times
+    /// it is scored. Lazy iterables and candidate files are still read a
+    /// batch at a time, each batch preprocessed as it is read.
+    pub fn preprocessed(self, pre: &Preprocess) -> Self {
+        if pre.is_empty() {
+            return self;
+        }
+        let pre = pre.clone();
+        Self { source: Source::Preprocessed { strings: Box::new(self), pre, preprocessed: OnceCell::new() } }
+    }
+
+    /// Slices of the strings, borrowed from wherever they are stored. Lazy
+    /// iterables are drained in full.
+    pub fn as_strs(&self) -> PyResult<Vec<&str>> {
+        match &self.source {
+            Source::Lazy { iter, drained } => {
+                if drained.get().is_none() {
+                    let _ = drained.set(next_batch(iter, usize::MAX)?);
+                }
+                Ok(drained.get().into_iter().flatten().map(String::as_str).collect())
+            }
+            Source::Borrowed(strings) => Ok(strings.clone()),
+            Source::Converted(strings) => Ok(strings.iter().map(|s| &**s).collect()),
+            Source::Decoded(strings) => Ok(strings.iter().map(String::as_str).collect()),
+            Source::Arrow(chunks) => {
+                let mut strings = Vec::with_capacity(chunks.iter().map(|chunk| chunk.len).sum());
+                for chunk in chunks {
+                    chunk.read(&mut strings)?;
+                }
+                Ok(strings)
+            }
+            Source::Mapped(mapped) => mapped.strs(),
+            Source::Preprocessed { strings, pre, preprocessed } => {
+                if preprocessed.get().is_none() {
+                    let _ = preprocessed.set(strings.as_strs()?.iter().map(|s| pre.apply(s).into_owned()).collect());
+                }
+                Ok(preprocessed.get().into_iter().flatten().map(String::as_str).collect())
+            }
+        }
+    }
+
+    /// Calls `f` with the index of the first string of each batch and the
+    /// batch itself. Lazy iterables are pulled and candidate files read
+    /// `BATCH_SIZE` strings at a time, so that only one batch is held in
+    /// memory; everything else is a single batch.
+    pub fn for_each_batch(&self, mut f: impl FnMut(usize, &[&str]) -> PyResult<()>) -> PyResult<()> {
+        match &self.source {
+            Source::Lazy { iter, drained } if drained.get().is_none() => {
+                let mut start = 0;
+                loop {
+                    let batch = next_batch(iter, BATCH_SIZE)?;
+                    if batch.is_empty() {
+                        return Ok(());
+                    }
+                    let strs: Vec<&str> = batch.iter().map(String::as_str).collect();
+                    f(start, &strs)?;
+                    start += batch.len();
+                }
+            }
+            Source::Mapped(mapped) => mapped.for_each_batch(f),
+            Source::Preprocessed { strings, pre, preprocessed } if preprocessed.get().is_none() => {
+                // A trait object, so that the nested call does not instantiate
+                // this function for a new closure type at every level.
+                let f: &mut dyn FnMut(usize, &[&str]) -> PyResult<()> = &mut |start, batch: &[&str]| {
+                    let batch: Vec<String> = batch.iter().map(|s| pre.apply(s).into_owned()).collect();
+                    f(start, &batch.iter().map(String::as_str).collect::<Vec<_>>())
+                };
+                strings.for_each_batch(f)
+            }
+            _ => f(0, &self.as_strs()?),
+        }
+    }
+}
+
+/// Whether `obj` supports the sequence protocol, as lists, tuples, and `str`
+/// do.
+pub(crate) fn is_sequence(obj: &PyAny) -> bool {
+    // SAFETY: `PySequence_Check` only inspects the type of a live object.
+    unsafe { ffi::PySequence_Check(obj.as_ptr()) == 1 }
+}
+
+/// Pulls up to `size` strings from `iter`. Each item is copied out and its
+/// reference released immediately, so that pulling a batch does not keep the
+/// Python objects alive until the end of the call.
+pub fn next_batch(iter: &PyIterator, size: usize) -> PyResult<Vec<String>> {
+    let py = iter.py();
+    let mut batch = Vec::with_capacity(size.min(BATCH_SIZE));
+    while batch.len() < size {
+        // SAFETY: `PyIter_Next` returns a new reference or null, and
+        // `from_owned_ptr_or_opt` takes ownership of it.
+        let item: Option<PyObject> = unsafe { PyObject::from_owned_ptr_or_opt(py, ffi::PyIter_Next(iter.as_ptr())) };
+        match item {
+            Some(item) => batch.push(item.as_ref(py).extract::<Text>()?.into_owned()),
+            None => match PyErr::take(py) {
+                Some(err) => return Err(err),
+                None => break,
+            },
+        }
+    }
+    Ok(batch)
+}
+
+/// The buffers of one Arrow string array: offsets into its UTF-8 data, of
+/// 32 bits or of 64 for a large array, and the slice of them in use.
+struct ArrowChunk {
+    offsets: RawBuffer,
+    data: RawBuffer,
+    start: usize,
+    len: usize,
+    large: bool,
+}
+
+impl ArrowChunk {
+    fn get(array: &PyAny, large: bool) -> PyResult<Self> {
+        let buffers = array.call_method0("buffers")?;
+        Ok(Self {
+            offsets: RawBuffer::get(buffers.get_item(1)?)?,
+            data: match buffers.get_item(2)? {
+                data if data.is_none() => RawBuffer::get(pyo3::types::PyBytes::new(array.py(), b""))?,
+                data => RawBuffer::get(data)?,
+            },
+            start: array.getattr("offset")?.extract()?,
+            len: array.len()?,
+            large,
+        })
+    }
+
+    /// Appends the strings of the array to `strings`.
+    fn read<'a>(&'a self, strings: &mut Vec<&'a str>) -> PyResult<()> {
+        let width = if self.large { 8 } else { 4 };
+        let offsets = self.offsets.bytes();
+        let data = self.data.bytes();
+        let offset = |k: usize| -> PyResult<usize> {
+            let bytes = offsets
+                .get(k * width..(k + 1) * width)
+                .ok_or_else(|| InvalidParameterError::new_err("arrow offsets buffer is too short"))?;
+            let value = if self.large {
+                i64::from_le_bytes(bytes.try_into().expect("8 byte offset"))
+            } else {
+                i64::from(i32::from_le_bytes(bytes.try_into().expect("4 byte offset")))
+            };
+            usize::try_from(value).map_err(|_| InvalidParameterError::new_err("arrow offsets must not be negative"))
+        };
+
+        let mut begin = offset(self.start)?;
+        for k in self.start..self.start + self.len {
+            let end = offset(k + 1)?;
+            let bytes = data
+                .get(begin..end)
+                .ok_or_else(|| InvalidParameterError::new_err("arrow offsets point outside the data buffer"))?;
+            let s = std::str::from_utf8(bytes).map_err(|e| {
+                InvalidParameterError::new_err(format!("arrow string {} is not valid UTF-8: {}", strings.len(), e))
+            })?;
+            strings.push(s);
+            begin = end;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the offsets and data buffers of a `pyarrow` string array, or of
+/// each chunk of a `pyarrow.ChunkedArray` of strings.
+fn arrow_source<'py>(obj: &'py PyAny) -> PyResult<Option<Source<'py>>> {
+    if !obj.hasattr("type")? || !obj.hasattr("null_count")? {
+        return Ok(None);
+    }
+    let chunked = obj.hasattr("chunks")? && obj.hasattr("num_chunks")?;
+    if !chunked && !obj.hasattr("buffers")? {
+        return Ok(None);
+    }
+    let large = match obj.getattr("type")?.str()?.to_str()? {
+        "string" | "utf8" => false,
+        "large_string" | "large_utf8" => true,
+        _ => return Ok(None),
+    };
+    if obj.getattr("null_count")?.extract::<usize>()? > 0 {
+        return Err(InvalidParameterError::new_err("arrow string arrays must not contain nulls"));
+    }
+
+    let chunks = if chunked {
+        obj.getattr("chunks")?
+            .iter()?
+            .map(|chunk| ArrowChunk::get(chunk?, large))
+            .collect::<PyResult<_>>()?
+    } else {
+        vec![ArrowChunk::get(obj, large)?]
+    };
+    Ok(Some(Source::Arrow(chunks)))
+}
+
+/// Decodes a one-dimensional NumPy unicode (`<U`) array from its UCS4
+/// buffer. Other arrays, such as object arrays, are left to the sequence
+/// fallback.
+fn numpy_source<'py>(obj: &'py PyAny) -> PyResult<Option<Source<'py>>> {
+    if !obj.hasattr("dtype")? {
+        return Ok(None);
+    }
+    let buffer = match RawBuffer::get(obj) {
+        Ok(buffer) => buffer,
+        Err(_) => return Ok(None),
+    };
+    let format = buffer.format();
+    if !format.ends_with('w') || format.starts_with(['>', '!']) {
+        return Ok(None);
+    }
+    if buffer.dimensions() != 1 {
+        return Err(PyTypeError::new_err("string arrays must be one-dimensional"));
+    }
+
+    Ok(Some(Source::Decoded(decode_ucs4(buffer.bytes(), buffer.item_size())?)))
+}
+
+/// Decodes strings of `item_size` bytes each, made of native-endian UCS4 code
+/// units padded with trailing zeros.
+fn decode_ucs4(bytes: &[u8], item_size: usize) -> PyResult<Vec<String>> {
+    if !item_size.is_multiple_of(4) {
+        return Err(InvalidParameterError::new_err(format!(
+            "string array items must be whole UCS4 code units, got {} bytes",
+            item_size
+        )));
+    }
+    let mut strings = Vec::new();
+    if item_size > 0 {
+        for item in bytes.chunks_exact(item_size) {
+            let mut s = String::with_capacity(item_size / 4);
+            for unit in item.chunks_exact(4) {
+                let code = u32::from_ne_bytes(unit.try_into().expect("4 byte code unit"));
+                if code == 0 {
+                    break;
+                }
+                s.push(char::from_u32(code).ok_or_else(|| InvalidParameterError::new_err("invalid code point in string array"))?);
+            }
+            strings.push(s);
+        }
+    }
+    Ok(strings)
+}
+
+
+// ------------------------------------------------------------------------
+//  pandas Inputs
+// ------------------------------------------------------------------------
+
+enum SeriesSource<'py> {
+    Arrow(Strings<'py>),
+    Objects(Vec<Option<Text<'py>>>),
+}
+
+/// A pandas Series of strings passed in from Python. Series backed by Arrow
+/// without missing values are read straight from their buffers, and others
+/// from the Python strings they hold, borrowed rather than copied. `None`,
+/// `NaN` and `pandas.NA` are missing values.
+pub struct Series<'py> {
+    index: &'py PyAny,
+    source: SeriesSource<'py>,
+}
+
+impl<'py> FromPyObject<'py> for Series<'py> {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if !obj.hasattr("index")? || !obj.hasattr("array")? {
+            return Err(PyTypeError::new_err(format!(
+                "expected a pandas Series, got '{}'",
+                obj.get_type().name()?
+            )));
+        }
+        let index = obj.getattr("index")?;
+        let array = obj.getattr("array")?;
+        if array.hasattr("__arrow_array__")? && !obj.getattr("hasnans")?.is_true()? {
+            if let Some(source) = arrow_source(array.call_method0("__arrow_array__")?)? {
+                return Ok(Self { index, source: SeriesSource::Arrow(Strings { source }) });
+            }
+        }
+
+        let na = obj.py().import("pandas")?.getattr("NA")?;
+        let values = obj.call_method0("tolist")?.downcast::<PyList>()?;
+        let values = values
+            .iter()
+            .map(|value| {
+                let missing = value.is_none()
+                    || value.as_ptr() == na.as_ptr()
+                    || value.extract::<f64>().is_ok_and(f64::is_nan);
+                if missing {
+                    return Ok(None);
+                }
+                value.extract().map(Some).map_err(|_| {
+                    PyTypeError::new_err(format!(
+                        "series values must be strings or missing, got '{}'",
+                        value.get_type().name().unwrap_or("object")
+                    ))
+                })
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(Self { index, source: SeriesSource::Objects(values) })
+    }
+}
+
+impl<'py> Series<'py> {
+    /// The index of the Series, for results aligned with it.
+    pub fn index(&self) -> &'py PyAny {
+        self.index
+    }
+
+    /// The values of the Series, `None` where they are missing.
+    pub fn values(&self) -> PyResult<Vec<Option<&str>>> {
+        match &self.source {
+            SeriesSource::Arrow(strings) => Ok(strings.as_strs()?.into_iter().map(Some).collect()),
+            SeriesSource::Objects(values) => Ok(values.iter().map(|value| value.as_deref()).collect()),
+        }
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Byte Inputs
+// ------------------------------------------------------------------------
+
+enum ByteSource<'py> {
+    Borrowed(&'py [u8]),
+    Copied(Vec<u8>),
+    Buffer(RawBuffer),
+}
+
+/// A byte string passed in from Python. `bytes` is borrowed, and so is any
+/// other C-contiguous object exporting the buffer protocol, such as a
+/// `memoryview` or a NumPy array, whose raw bytes are read in place.
+/// `bytearray`, which Python code could resize while it is read, is copied.
+pub struct Bytes<'py>(ByteSource<'py>);
+
+impl<'py> FromPyObject<'py> for Bytes<'py> {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if let Ok(bytes) = obj.downcast::<PyBytes>() {
+            return Ok(Self(ByteSource::Borrowed(bytes.as_bytes())));
+        }
+        if let Ok(bytes) = obj.downcast::<PyByteArray>() {
+            return Ok(Self(ByteSource::Copied(bytes.to_vec())));
+        }
+        if !obj.is_instance_of::<PyString>()? {
+            if let Some(buffer) = byte_buffer(obj)? {
+                return Ok(Self(ByteSource::Buffer(buffer)));
+            }
+        }
+        Err(PyTypeError::new_err(format!(
+            "expected bytes, bytearray or a buffer, got {}",
+            obj.get_type().name()?
+        )))
+    }
+}
+
+impl Deref for Bytes<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            ByteSource::Borrowed(bytes) => bytes,
+            ByteSource::Copied(bytes) => bytes,
+            ByteSource::Buffer(buffer) => buffer.bytes(),
+        }
+    }
+}
+
+/// The buffer of `obj`, or `None` if it does not export one. A buffer that
+/// is not C-contiguous, such as a column of an array, raises `BufferError`.
+fn byte_buffer(obj: &PyAny) -> PyResult<Option<RawBuffer>> {
+    match RawBuffer::get(obj) {
+        Ok(buffer) => Ok(Some(buffer)),
+        Err(err) if err.is_instance_of::<pyo3::exceptions::PyBufferError>(obj.py()) => Err(err),
+        Err(_) => Ok(None),
+    }
+}
+
+enum ByteStringsSource<'py> {
+    Items(Vec<Bytes<'py>>),
+    /// The rows of a 2D buffer, each `width` bytes.
+    Rows(RawBuffer, usize),
+    /// The items of a NumPy `bytes_` array, each `width` bytes padded with
+    /// trailing zeros.
+    Padded(RawBuffer, usize),
+}
+
+/// A list of byte strings passed in from Python: a sequence of objects each
+/// read as `Bytes`, the rows of a C-contiguous 2D array such as a NumPy
+/// `uint8` matrix of fixed-width codes, or a NumPy `bytes_` array. Arrays are
+/// read in place, without an object per row.
+pub struct ByteStrings<'py>(ByteStringsSource<'py>);
+
+impl<'py> FromPyObject<'py> for ByteStrings<'py> {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if obj.is_instance_of::<PyList>()? {
+            return Ok(Self(ByteStringsSource::Items(obj.extract()?)));
+        }
+        let buffer = match byte_buffer(obj)? {
+            Some(buffer) => buffer,
+            None => return Ok(Self(ByteStringsSource::Items(obj.extract()?))),
+        };
+        match buffer.shape()[..] {
+            [_] if buffer.format().ends_with('s') => {
+                let width = buffer.item_size();
+                Ok(Self(ByteStringsSource::Padded(buffer, width)))
+            }
+            [_, columns] => {
+                let width = columns * buffer.item_size();
+                Ok(Self(ByteStringsSource::Rows(buffer, width)))
+            }
+            ref shape => Err(PyTypeError::new_err(format!(
+                "expected a 2D array of byte strings, one per row, got {} dimensions",
+                shape.len()
+            ))),
+        }
+    }
+}
+
+impl ByteStrings<'_> {
+    /// Slices of the byte strings, borrowed from wherever they are stored.
+    pub fn as_slices(&self) -> Vec<&[u8]> {
+        match &self.0 {
+            ByteStringsSource::Items(items) => items.iter().map(|item| &**item).collect(),
+            ByteStringsSource::Rows(buffer, width) => Self::split(buffer, *width),
+            ByteStringsSource::Padded(buffer, width) => Self::split(buffer, *width)
+                .into_iter()
+                .map(|item| &item[..item.iter().rposition(|&byte| byte != 0).map_or(0, |i| i + 1)])
+                .collect(),
+        }
+    }
+
+    fn split(buffer: &RawBuffer, width: usize) -> Vec<&[u8]> {
+        match width {
+            0 => vec![&[][..]; buffer.shape()[0]],
+            _ => buffer.bytes().chunks_exact(width).collect(),
+        }
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Masks
+// ------------------------------------------------------------------------
+
+/// A two-dimensional boolean mask passed in from Python, either a NumPy
+/// `bool` array, read from its buffer, or nested sequences of truthy values.
+pub struct Mask {
+    rows: usize,
+    columns: usize,
+    cells: Vec<bool>,
+}
+
+impl<'py> FromPyObject<'py> for Mask {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if obj.hasattr("dtype")? {
+            if let Ok(buffer) = RawBuffer::get(obj) {
+                if buffer.format() == "?" {
+                    let shape = buffer.shape();
+                    if shape.len() != 2 {
+                        return Err(PyTypeError::new_err("mask must be two-dimensional"));
+                    }
+                    let cells = buffer.bytes().iter().map(|&byte| byte != 0).collect();
+                    return Ok(Self { rows: shape[0], columns: shape[1], cells });
+                }
+            }
+        }
+
+        let mut cells = Vec::new();
+        let mut shape: Option<(usize, usize)> = None;
+        for (i, row) in obj.iter()?.enumerate() {
+            let start = cells.len();
+            for cell in row?.iter()? {
+                cells.push(cell?.is_true()?);
+            }
+            let columns = cells.len() - start;
+            match shape {
+                Some((_, expected)) if expected != columns => {
+                    return Err(LengthMismatchError::new_err(format!(
+                        "mask rows must have the same length, row {} has {} instead of {}",
+                        i, columns, expected
+                    )));
+                }
+                _ => shape = Some((i + 1, columns)),
+            }
+        }
+        let (rows, columns) = shape.unwrap_or((0, 0));
+        Ok(Self { rows, columns, cells })
+    }
+}
+
+impl Mask {
+    /// Number of rows and columns.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    /// Raises `InvalidParameterError` unless the mask covers a matrix of `rows` by
+    /// `columns`. An empty mask covers any matrix without rows.
+    pub fn check_shape(&self, rows: usize, columns: usize) -> PyResult<()> {
+        if self.shape() == (rows, columns) || (self.rows == 0 && rows == 0) {
+            return Ok(());
+        }
+        Err(InvalidParameterError::new_err(format!(
+            "mask must have shape ({}, {}), got ({}, {})",
+            rows, columns, self.rows, self.columns
+        )))
+    }
+
+    /// Whether the cell at row `i` and column `j` is set.
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        self.cells[i * self.columns + j]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::exceptions::PyBufferError;
+
+    fn batches(strings: &Strings) -> Vec<(usize, Vec<String>)> {
+        let mut batches = Vec::new();
+        strings
+            .for_each_batch(|start, batch| {
+                batches.push((start, batch.iter().map(|s| s.to_string()).collect()));
+                Ok(())
+            })
+            .unwrap();
+        batches
+    }
+
+    #[test]
+    fn preprocessed_strings_are_read_preprocessed_from_any_source() {
+        Python::with_gil(|py| {
+            let list = PyList::new(py, ["Straße", "ÉTÉ", "abc"]);
+            let lowercase = Preprocess::lowercasing(true);
+            let lowered = vec!["straße".to_string(), "été".to_string(), "abc".to_string()];
+
+            let strings = list.extract::<Strings>().unwrap().preprocessed(&lowercase);
+            assert_eq!(strings.len_hint(), Some(3));
+            assert_eq!(batches(&strings), [(0, lowered.clone())]);
+            assert_eq!(strings.as_strs().unwrap(), lowered);
+
+            let generator = py.eval("(s for s in ['Straße', 'ÉTÉ', 'abc'])", None, None).unwrap();
+            let strings = generator.extract::<Strings>().unwrap().preprocessed(&lowercase);
+            assert_eq!(strings.len_hint(), None);
+            assert_eq!(batches(&strings), [(0, lowered)]);
+
+            let strings = list.extract::<Strings>().unwrap().preprocessed(&Preprocess::lowercasing(false));
+            assert_eq!(strings.as_strs().unwrap(), ["Straße", "ÉTÉ", "abc"]);
+
+            // The steps of a preprocessor run before lowercasing.
+            let pre = lowercase.after(Some(crate::testing::preprocessor(&["casefold", "strip_accents"])));
+            let strings = list.extract::<Strings>().unwrap().preprocessed(&pre);
+            assert_eq!(strings.as_strs().unwrap(), ["strasse", "ete", "abc"]);
+        });
+    }
+
+    #[test]
+    fn preprocessed_text_is_only_copied_when_changed() {
+        let pre = Preprocess { trim: true, ..Preprocess::lowercasing(true) };
+        assert_eq!(&*Text::from("ÀBc".to_string()).preprocessed(&pre), "àbc");
+        assert_eq!(&*Text::from(" abc ".to_string()).preprocessed(&pre), "abc");
+        assert_eq!(&*Text::from("ÀBc".to_string()).preprocessed(&Preprocess::default()), "ÀBc");
+        Python::with_gil(|py| {
+            let text: Text = PyString::new(py, "abc").extract().unwrap();
+            assert!(matches!(text.preprocessed(&pre).0, Cow::Borrowed("abc")));
+        });
+    }
+
+    fn ucs4(s: &str, width: usize) -> Vec<u8> {
+        let mut units: Vec<u32> = s.chars().map(u32::from).collect();
+        units.resize(width, 0);
+        units.iter().flat_map(|unit| unit.to_ne_bytes()).collect()
+    }
+
+    fn slices(strings: &ByteStrings) -> Vec<Vec<u8>> {
+        strings.as_slices().into_iter().map(<[u8]>::to_vec).collect()
+    }
+
+    #[test]
+    fn raw_buffers_are_read_in_place_and_released() {
+        Python::with_gil(|py| {
+            let array = py.eval("__import__('array').array('i', [1, 2, 3])", None, None).unwrap();
+            let buffer = RawBuffer::get(array).unwrap();
+            assert_eq!((buffer.format(), buffer.item_size(), buffer.dimensions()), ("i", 4, 1));
+            assert_eq!(buffer.shape(), [3]);
+            assert_eq!(buffer.bytes().len(), 12);
+
+            let matrix = py.eval("memoryview(bytes(range(12))).cast('H', (2, 3))", None, None).unwrap();
+            let buffer = RawBuffer::get(matrix).unwrap();
+            assert_eq!((buffer.item_size(), buffer.shape()), (2, vec![2, 3]));
+            assert_eq!(buffer.bytes(), (0..12).collect::<Vec<u8>>());
+
+            let empty = RawBuffer::get(PyBytes::new(py, b"")).unwrap();
+            assert_eq!((empty.bytes(), empty.shape()), (&[][..], vec![0]));
+
+            // An exported bytearray cannot be resized until its buffer is released.
+            let bytes = PyByteArray::new(py, b"abc");
+            let buffer = RawBuffer::get(bytes).unwrap();
+            let resized = bytes.call_method1("extend", (b"d".as_slice(),));
+            assert!(matches!(resized, Err(err) if err.is_instance_of::<PyBufferError>(py)));
+            drop(buffer);
+            bytes.call_method1("extend", (b"d".as_slice(),)).unwrap();
+            assert_eq!(bytes.to_vec(), b"abcd");
+        });
+    }
+
+    #[test]
+    fn non_contiguous_buffers_are_rejected() {
+        Python::with_gil(|py| {
+            let strided = py.eval("memoryview(b'abcdef')[::2]", None, None).unwrap();
+            assert!(matches!(RawBuffer::get(strided), Err(err) if err.is_instance_of::<PyBufferError>(py)));
+            assert!(matches!(byte_buffer(strided), Err(err) if err.is_instance_of::<PyBufferError>(py)));
+            assert!(byte_buffer(PyString::new(py, "abc")).unwrap().is_none());
+            assert!(strided.extract::<Bytes>().is_err());
+            assert!(strided.extract::<ByteStrings>().is_err());
+            let list = PyList::new(py, [strided]);
+            assert!(list.extract::<ByteStrings>().is_err());
+        });
+    }
+
+    #[test]
+    fn ucs4_items_are_decoded_by_item_size() {
+        let bytes = [ucs4("ab", 3), ucs4("", 3), ucs4("€😀c", 3)].concat();
+        assert_eq!(decode_ucs4(&bytes, 12).unwrap(), ["ab", "", "€😀c"]);
+        assert_eq!(decode_ucs4(&bytes, 36).unwrap(), ["ab"]);
+        assert_eq!(decode_ucs4(&bytes, 0).unwrap(), Vec::<String>::new());
+        assert!(decode_ucs4(&bytes, 6).is_err());
+        assert!(decode_ucs4(&0xD800u32.to_ne_bytes(), 4).is_err());
+    }
+
+    #[test]
+    fn only_unicode_arrays_are_read_as_numpy_strings() {
+        Python::with_gil(|py| {
+            let module = pyo3::types::PyModule::from_code(
+                py,
+                "import array\nclass Ints(array.array):\n    dtype = 'int32'\n",
+                "arrays.py",
+                "arrays",
+            )
+            .unwrap();
+            let ints = module.getattr("Ints").unwrap().call1(("i", vec![1, 2])).unwrap();
+            assert!(numpy_source(ints).unwrap().is_none());
+            assert!(numpy_source(PyBytes::new(py, b"ab")).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn byte_strings_are_read_from_bytes_bytearray_and_memoryview() {
+        Python::with_gil(|py| {
+            let items = py.eval("[b'ab', bytearray(b'cde'), memoryview(b'xfgx')[1:3], b'']", None, None).unwrap();
+            let strings = items.extract::<ByteStrings>().unwrap();
+            assert_eq!(slices(&strings), [b"ab".to_vec(), b"cde".to_vec(), b"fg".to_vec(), Vec::new()]);
+
+            let tuple = py.eval("(b'ab', bytearray(b'c'))", None, None).unwrap();
+            assert_eq!(slices(&tuple.extract::<ByteStrings>().unwrap()), [b"ab".to_vec(), b"c".to_vec()]);
+
+            let rows = py.eval("memoryview(b'abcdef').cast('B', (2, 3))", None, None).unwrap();
+            assert_eq!(slices(&rows.extract::<ByteStrings>().unwrap()), [b"abc".to_vec(), b"def".to_vec()]);
+            let wide = py.eval("memoryview(bytes(range(8))).cast('H', (2, 2))", None, None).unwrap();
+            assert_eq!(slices(&wide.extract::<ByteStrings>().unwrap()), [vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+
+            let flat = py.eval("memoryview(b'abc')", None, None).unwrap();
+            assert!(matches!(flat.extract::<ByteStrings>(), Err(err) if err.is_instance_of::<PyTypeError>(py)));
+            assert!(py.eval("[1, 2]", None, None).unwrap().extract::<ByteStrings>().is_err());
+            assert!(py.eval("['ab']", None, None).unwrap().extract::<ByteStrings>().is_err());
+
+            let buffer = RawBuffer::get(PyBytes::new(py, b"ab\0\0cdef\0\0\0\0")).unwrap();
+            let padded = ByteStrings(ByteStringsSource::Padded(buffer, 4));
+            assert_eq!(slices(&padded), [b"ab".to_vec(), b"cdef".to_vec(), Vec::new()]);
+        });
+    }
+}
@@ -1,14 +1,143 @@
+// PyO3 0.16 expands `#[pymethods]` into an `impl` nested inside a `const`
+// item, which newer compilers report as a non-local definition.
+#![allow(non_local_definitions)]
+
+#[cfg(feature = "python")]
+use std::borrow::Cow;
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use pyo3::exceptions::PyOSError;
+#[cfg(feature = "python")]
 use rayon::prelude::*;
 
+#[cfg(feature = "python")]
+use collation::Collation;
+#[cfg(feature = "python")]
+use error::{InvalidParameterError, LengthMismatchError};
+#[cfg(feature = "python")]
+use input::{ByteStrings, Bytes, Strings, Text};
+#[cfg(feature = "python")]
+use pool::NThreads;
+#[cfg(feature = "python")]
+use preprocess::{CaseFold, Normalization, Preprocess, Preprocessor};
+#[cfg(feature = "python")]
+use tokens::{Stopwords, Tokenizer};
+#[cfg(feature = "python")]
+use units::Unit;
+
+pub mod align;
+pub mod automaton;
+pub mod batch;
+pub mod benchmark;
+pub mod bktree;
+pub mod bytes;
+pub mod cached;
+pub mod checkpoint;
+pub mod cluster;
+pub mod cutoff;
+pub mod dice;
+pub mod diceindex;
+pub mod divergence;
+pub mod error;
+pub mod frequency;
+pub mod fuzz;
+pub mod fuzzy;
+pub mod gzip;
+pub mod hnsw;
+pub mod jaro;
+pub mod memo;
+pub mod metric;
+pub mod minhash;
+pub mod ngram;
+pub mod persist;
+pub mod phonetic;
+pub mod pool;
+// Without Python, `Preprocessor` has no steps to configure and leaves every
+// string as it is, so the indexes holding one build and save the same way.
+#[cfg_attr(not(feature = "python"), path = "preprocess_bare.rs")]
+pub mod preprocess;
+pub mod progress;
+pub mod rank;
+pub mod scratch;
+pub mod sequence;
+pub mod simd;
+pub mod simhash;
+pub mod symspell;
+pub mod tfidf;
+pub mod tokens;
+pub mod transliterate;
+pub mod vptree;
+pub mod wavefront;
+pub mod weighted;
+
+#[cfg(test)]
+mod testing;
+
+#[cfg(feature = "python")]
+pub mod collation;
+#[cfg(feature = "python")]
+pub mod input;
+#[cfg(feature = "python")]
+pub mod mapped;
+#[cfg(feature = "python")]
+pub mod prepared;
+#[cfg(feature = "python")]
+pub mod sklearn;
+#[cfg(feature = "python")]
+pub mod stream;
+#[cfg(feature = "python")]
+pub mod units;
+#[cfg(feature = "python")]
+pub mod writer;
+
+pub use error::{Error, Result};
+
 // ------------------------------------------------------------------------
 //  Direct `strsim` Bindings
 // ------------------------------------------------------------------------
 
+#[cfg(feature = "python")]
 pub mod single {
     use super::*;
 
+    /// A `difflib` opcode, `(tag, i1, i2, j1, j2)`, as returned to Python.
+    pub type PyOpcode = (&'static str, usize, usize, usize, usize);
+
+    fn py_opcodes(path: &[align::Step]) -> Vec<PyOpcode> {
+        align::opcodes(path)
+            .into_iter()
+            .map(|(tag, i1, i2, j1, j2)| (tag.name(), i1, i2, j1, j2))
+            .collect()
+    }
+
+    pub(crate) use crate::tokens::check_ngram;
+
+    pub(crate) fn check_tokenizer(tokens: bool, tokenizer: &Option<Tokenizer>, stopwords: &Option<Stopwords>) -> PyResult<()> {
+        if tokenizer.is_some() && !tokens {
+            return Err(InvalidParameterError::new_err("tokenizer requires tokens=True"));
+        }
+        if stopwords.is_some() && !tokens {
+            return Err(InvalidParameterError::new_err("stopwords requires tokens=True"));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_decay(decay: f64) -> PyResult<()> {
+        if !(decay > 0.0 && decay <= 1.0) {
+            return Err(InvalidParameterError::new_err("decay must be greater than 0.0 and at most 1.0"));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_weight(weight: f64) -> PyResult<()> {
+        if !(0.0..=1.0).contains(&weight) {
+            return Err(InvalidParameterError::new_err("weight must be between 0.0 and 1.0"));
+        }
+        Ok(())
+    }
+
     /// Like optimal string alignment, but substrings can be edited an unlimited
     /// number of times, and the triangle inequality holds.
     /// 
@@ -16,14 +145,58 @@ pub mod single {
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Distance between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
-        strsim::damerau_levenshtein(a, b)
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn damerau_levenshtein(
+        a: Text,
+        b: Text,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> usize {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        crate::units::damerau_levenshtein(unit)(&a, &b)
     }
 
     /// Calculates the Jaro similarity between two strings. The returned value
@@ -33,30 +206,127 @@ pub mod single {
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Similarity between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn jaro(a: &str, b: &str) -> f64 {
-        strsim::jaro(a, b)
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jaro(
+        a: Text,
+        b: Text,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        crate::units::jaro(unit)(&a, &b)
     }
 
-    /// Like Jaro but gives a boost to strings that have a common prefix.
+    /// Like Jaro but gives a boost to strings that have a common prefix. With
+    /// `long_strings` Winkler's long string adjustment is also applied.
     /// 
     /// # Arguments
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `long_strings` - Whether to apply the long string adjustment, which
+    ///   requires `unit='char'`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Similarity between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn jaro_winkler(a: &str, b: &str) -> f64 {
-        strsim::jaro_winkler(a, b)
+    #[pyfunction(long_strings = "false", unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, long_strings=False, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jaro_winkler(
+        a: Text,
+        b: Text,
+        long_strings: bool,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        if long_strings {
+            crate::units::check_chars(unit, "long_strings")?;
+            Ok(crate::jaro::jaro_winkler_long(&a, &b))
+        } else {
+            Ok(crate::units::jaro_winkler(unit)(&a, &b))
+        }
     }
 
     /// Calculates the minimum number of insertions, deletions, and substitutions
@@ -66,14 +336,58 @@ pub mod single {
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Distance between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn levenshtein(a: &str, b: &str) -> usize {
-        strsim::levenshtein(a, b)
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn levenshtein(
+        a: Text,
+        b: Text,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> usize {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        crate::units::levenshtein(unit)(&a, &b)
     }
 
     /// Calculates a normalized score of the Damerau–Levenshtein algorithm between
@@ -83,14 +397,58 @@ pub mod single {
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Similarity between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn normalized_damerau_levenshtein(a: &str, b: &str) -> f64 {
-        strsim::normalized_damerau_levenshtein(a, b)
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn normalized_damerau_levenshtein(
+        a: Text,
+        b: Text,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        crate::units::normalized_damerau_levenshtein(unit)(&a, &b)
     }
 
     /// Calculates a normalized score of the Levenshtein algorithm between 0.0 and
@@ -100,14 +458,58 @@ pub mod single {
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Similarity between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn normalized_levenshtein(a: &str, b: &str) -> f64 {
-        strsim::normalized_levenshtein(a, b)
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn normalized_levenshtein(
+        a: Text,
+        b: Text,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        crate::units::normalized_levenshtein(unit)(&a, &b)
     }
 
     /// Like Levenshtein but allows for adjacent transpositions. Each substring can
@@ -117,240 +519,7808 @@ pub mod single {
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Distance between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn osa_distance(a: &str, b: &str) -> usize {
-        strsim::osa_distance(a, b)
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn osa_distance(
+        a: Text,
+        b: Text,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> usize {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        crate::units::osa_distance(unit)(&a, &b)
     }
 
-    /// Calculates a Sørensen-Dice similarity distance using bigrams.
+    /// Calculates a Sørensen-Dice similarity distance using n-grams, bigrams by
+    /// default. With `tokens` the n-grams are built from words instead of
+    /// characters.
     /// See http://en.wikipedia.org/wiki/S%C3%B8rensen%E2%80%93Dice_coefficient.
     /// 
     /// # Arguments
     /// 
     /// * `a` - First string to compare
     /// * `b` - Secondary string to compare to `a`
+    /// * `n` - Number of characters (or words) per n-gram
+    /// * `tokens` - Whether to compare word n-grams instead of character n-grams
+    /// * `tokenizer` - How to split the strings into tokens in `tokens` mode:
+    ///   `None` for runs of letters and digits, a delimiter string, a compiled
+    ///   regular expression matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing in `tokens` mode,
+    ///   such as "inc" and "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
     /// * `output` - Similarity between `a` and `b`
-    #[pyfunction]
-    #[pyo3(text_signature = "(a, b, /)")]
-    pub fn sorensen_dice(a: &str, b: &str) -> f64 {
-        strsim::sorensen_dice(a, b)
+    #[pyfunction(n = "2", tokens = "false", tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, n=2, tokens=False, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sorensen_dice(
+        a: Text,
+        b: Text,
+        n: usize,
+        tokens: bool,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        check_ngram(n)?;
+        check_tokenizer(tokens, &tokenizer, &stopwords)?;
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let score = dice::sorensen_dice(&a, &b, n, tokens.then_some(&tokenizer));
+        tokenizer.check()?;
+        Ok(score)
     }
-}
 
+    /// Compares the sorted intersection of the token sets of two strings
+    /// against the intersection joined with each remainder. The returned value
+    /// is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn token_set_ratio(
+        a: Text,
+        b: Text,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let score = fuzz::token_set_ratio_with(&a, &b, &tokenizer);
+        tokenizer.check()?;
+        Ok(score)
+    }
 
-// ------------------------------------------------------------------------
-//  Composite Functions
-// ------------------------------------------------------------------------
+    /// Calculates the best normalized similarity of the shorter string against
+    /// every equal-length window of the longer string. The returned value is
+    /// between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn partial_ratio(
+        a: Text,
+        b: Text,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        fuzz::partial_ratio(&a, &b)
+    }
 
-pub mod vectorized {
-    use super::*;
+    /// Calculates the normalized Indel similarity, `2 * LCS / (|a| + |b|)`. The
+    /// returned value is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ratio(
+        a: Text,
+        b: Text,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        fuzz::ratio(&a, &b)
+    }
 
-    fn create_thread_pool(n: usize) -> PyResult<rayon::ThreadPool> {
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(n)
-            .build()
-            .map_err(|_| PyOSError::new_err("failed to allocate threads"))
+    /// Like `ratio` but sorts the tokens of each string first, so that word order
+    /// does not affect the score.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn token_sort_ratio(
+        a: Text,
+        b: Text,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let score = fuzz::token_sort_ratio_with(&a, &b, &tokenizer);
+        tokenizer.check()?;
+        Ok(score)
     }
 
-    fn vectorize<F: Send + Sync>(f: fn(&str, &str) -> F, n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<F>> {
-        Ok(
-            create_thread_pool(n)?
-                .install(|| {
-                    bs
-                        .par_iter()
-                        .map(|&b| f(a, b))
-                        .collect()
-                })
-        )
+    /// Weighted combination of the full, partial, token-sort, and token-set ratios
+    /// using the fuzzywuzzy heuristics. The returned value is between 0.0 and 1.0
+    /// (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn wratio(
+        a: Text,
+        b: Text,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let score = fuzz::wratio_with(&a, &b, &tokenizer);
+        tokenizer.check()?;
+        Ok(score)
     }
 
-    /// Like optimal string alignment, but substrings can be edited an unlimited
-    /// number of times, and the triangle inequality holds.
+    /// Calculates the Jaro and Jaro-Winkler similarities along with the number
+    /// of matching characters, transpositions, and the common prefix length
+    /// they were computed from.
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
     /// 
     /// # Returns
     /// 
-    /// * `output` - Distances between `a` and each `b` in `bs`
+    /// * `output` - Components of the comparison between `a` and `b`
     #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn damerau_levenshtein(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<usize>> {
-        vectorize::<usize>(strsim::damerau_levenshtein, n, a, bs)
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn jaro_details(a: Text, b: Text) -> crate::jaro::JaroDetails {
+        crate::jaro::jaro_details(&a, &b)
     }
 
-    /// Calculates the Jaro similarity between two strings. The returned value
-    /// is between 0.0 and 1.0 (higher value means more similar).
+    /// Calculates the edit operations of an optimal Levenshtein alignment that
+    /// turn `a` into `b`. Each operation is `(op, src_idx, dst_idx)` where `op`
+    /// is one of `"replace"`, `"insert"`, or `"delete"`.
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
     /// 
     /// # Returns
     /// 
-    /// * `output` - Similarities between `a` and each `b` in `bs`
+    /// * `output` - Edit operations turning `a` into `b`
     #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn jaro(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<f64>> {
-        vectorize::<f64>(strsim::jaro, n, a, bs)
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn levenshtein_editops(a: Text, b: Text) -> Vec<(&'static str, usize, usize)> {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        align::editops(&align::alignment_path(&a, &b))
+            .into_iter()
+            .map(|(tag, i, j)| (tag.name(), i, j))
+            .collect()
     }
 
-    /// Like Jaro but gives a boost to strings that have a common prefix.
+    /// Calculates the `difflib`-compatible opcodes of an optimal Levenshtein
+    /// alignment. Each opcode is `(tag, i1, i2, j1, j2)` describing how
+    /// `a[i1:i2]` turns into `b[j1:j2]`.
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
     /// 
     /// # Returns
     /// 
-    /// * `output` - Similarities between `a` and each `b` in `bs`
+    /// * `output` - Opcodes turning `a` into `b`
     #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn jaro_winkler(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<f64>> {
-        vectorize::<f64>(strsim::jaro_winkler, n, a, bs)
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn levenshtein_opcodes(a: Text, b: Text) -> Vec<PyOpcode> {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        py_opcodes(&align::alignment_path(&a, &b))
     }
 
-    /// Calculates the minimum number of insertions, deletions, and substitutions
-    /// required to change one string into the other.
+    /// Renders an optimal Levenshtein alignment of two strings as two rows of
+    /// equal length, padded with `gap` over insertions and deletions. With
+    /// `markers` a third row is returned between them, holding `|` under equal
+    /// characters and `.` under substitutions.
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
+    /// * `gap` - Character used to pad over insertions and deletions
+    /// * `markers` - Whether to include the marker row
     /// 
     /// # Returns
     /// 
-    /// * `output` - Distances between `a` and each `b` in `bs`
-    #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn levenshtein(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<usize>> {
-        vectorize::<usize>(strsim::levenshtein, n, a, bs)
+    /// * `output` - `(a_row, b_row)` or `(a_row, marker_row, b_row)`
+    #[pyfunction(gap = "'-'", markers = "false")]
+    #[pyo3(text_signature = "(a, b, /, gap='-', markers=False)")]
+    pub fn alignment(py: Python<'_>, a: Text, b: Text, gap: char, markers: bool) -> PyObject {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let path = align::alignment_path(&a, &b);
+        let (top, marker_row, bottom) = align::render(&a, &b, &path, gap);
+        if markers {
+            (top, marker_row, bottom).into_py(py)
+        } else {
+            (top, bottom).into_py(py)
+        }
     }
 
-    /// Calculates a normalized score of the Damerau–Levenshtein algorithm between
-    /// 0.0 and 1.0 (inclusive), where 1.0 means the strings are the same.
+    /// Calculates the U.S. Census Bureau strcmp95 similarity, a Jaro-Winkler
+    /// variant that gives partial credit to similar characters and optionally
+    /// boosts long strings. The returned value is between 0.0 and 1.0 (higher
+    /// value means more similar).
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
+    /// * `long_strings` - Whether to apply the long string adjustment
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
-    /// * `output` - Similarities between `a` and each `b` in `bs`
-    #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn normalized_damerau_levenshtein(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<f64>> {
-        vectorize::<f64>(strsim::normalized_damerau_levenshtein, n, a, bs)
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(long_strings = "false", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, long_strings=False, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn strcmp95(
+        a: Text,
+        b: Text,
+        long_strings: bool,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        crate::jaro::strcmp95(&a, &b, long_strings)
     }
 
-    /// Calculates a normalized score of the Levenshtein algorithm between 0.0 and
-    /// 1.0 (inclusive), where 1.0 means the strings are the same.
+    /// Like Levenshtein but substitutions commonly made by optical character
+    /// recognition, such as `0`/`O`, `1`/`l`/`I`, and `rn`/`m`, cost less than 1.0.
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
-    /// * `output` - Similarities between `a` and each `b` in `bs`
+    /// * `output` - Distance between `a` and `b`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ocr_levenshtein(
+        a: Text,
+        b: Text,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        weighted::ocr_levenshtein(&a, &b)
+    }
+
+    /// Encodes a string with the original Metaphone phonetic algorithm. Words
+    /// are encoded separately and joined with single spaces.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `s` - String to encode
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Metaphone code of `s`
     #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn normalized_levenshtein(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<f64>> {
-        vectorize::<f64>(strsim::normalized_levenshtein, n, a, bs)
+    #[pyo3(text_signature = "(s, /)")]
+    pub fn metaphone(s: Text) -> String {
+        phonetic::metaphone(&s)
     }
 
-    /// Like Levenshtein but allows for adjacent transpositions. Each substring can
-    /// only be edited once.
+    /// Blends the normalized Levenshtein similarity of the Metaphone codes of
+    /// two strings with the Jaro-Winkler similarity of the raw strings. A
+    /// `weight` of 1.0 uses only the phonetic codes and 0.0 only the raw
+    /// strings.
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
+    /// * `weight` - Share of the phonetic similarity in the result
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
-    /// * `output` - Distances between `a` and each `b` in `bs`
-    #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn osa_distance(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<usize>> {
-        vectorize::<usize>(strsim::osa_distance, n, a, bs)
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(weight = "0.5", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, weight=0.5, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn phonetic_hybrid(
+        a: Text,
+        b: Text,
+        weight: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        check_weight(weight)?;
+        Ok(phonetic::phonetic_hybrid(&a, &b, weight))
     }
 
-    /// Calculates a Sørensen-Dice similarity distance using bigrams.
-    /// See http://en.wikipedia.org/wiki/S%C3%B8rensen%E2%80%93Dice_coefficient.
+    /// Like Levenshtein but every edit costs `decay ** k`, where `k` is the
+    /// earlier of the positions of the edit in the two strings, so that errors
+    /// near the start cost more than errors near the end.
     /// 
     /// # Arguments
     /// 
-    /// * `n` - Number of threads to use
     /// * `a` - First string to compare
-    /// * `bs` - Secondary strings to compare to `a`
+    /// * `b` - Secondary string to compare to `a`
+    /// * `decay` - Factor applied to the cost of each later position
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
     /// 
     /// # Returns
     /// 
-    /// * `output` - Similarities between `a` and each `b` in `bs`
+    /// * `output` - Distance between `a` and `b`
+    #[pyfunction(decay = "0.9", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, decay=0.9, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn position_weighted_levenshtein(
+        a: Text,
+        b: Text,
+        decay: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        check_decay(decay)?;
+        Ok(weighted::position_weighted_levenshtein(&a, &b, decay))
+    }
+
+    /// Calculates the Jensen-Shannon divergence of the character n-gram
+    /// frequency profiles of two strings. The returned value is between 0.0 and
+    /// 1.0 (lower value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `ngram` - Number of characters per n-gram
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Divergence between `a` and `b`
+    #[pyfunction(ngram = "3", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, ngram=3, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jensen_shannon(
+        a: Text,
+        b: Text,
+        ngram: usize,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        check_ngram(ngram)?;
+        Ok(divergence::jensen_shannon(&a, &b, ngram))
+    }
+
+    /// Calculates the LIG3 similarity, `2I / (2I + D)`, where `D` is the
+    /// Levenshtein distance and `I` the number of identical characters in an
+    /// optimal alignment. The returned value is between 0.0 and 1.0 (higher value
+    /// means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn lig3(
+        a: Text,
+        b: Text,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> f64 {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        align::lig3(&a, &b)
+    }
+
+    /// Calculates the Levenshtein distance and an optimal alignment of two
+    /// strings with Hirschberg's algorithm, using memory linear in the string
+    /// lengths. The alignment is given as `difflib`-compatible opcodes. Where
+    /// several alignments are optimal, it may differ from the one of
+    /// `levenshtein_opcodes`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and `b` and the opcodes turning `a`
+    ///   into `b`
     #[pyfunction]
-    #[pyo3(text_signature = "(n, a, bs, /)")]
-    pub fn sorensen_dice(n: usize, a: &str, bs: Vec<&str>) -> PyResult<Vec<f64>> {
-        vectorize::<f64>(strsim::sorensen_dice, n, a, bs)
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn levenshtein_alignment_long(a: Text, b: Text) -> (usize, Vec<PyOpcode>) {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let path = align::alignment_path_linear(&a, &b);
+        let distance = path.iter().filter(|(tag, _, _)| *tag != align::Tag::Equal).count();
+        (distance, py_opcodes(&path))
     }
-}
 
+    /// Calculates the Levenshtein distance between two very long strings on
+    /// several threads. The distance matrix is split into tiles, and the tiles
+    /// along each anti-diagonal are computed in parallel, which pays off from
+    /// strings of tens of thousands of characters. Shorter strings are compared
+    /// on one thread, as `levenshtein` does.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and `b`
+    #[pyfunction(unit = "Unit::Char", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', n_threads=None)")]
+    pub fn levenshtein_long(a: Text, b: Text, unit: Unit, n_threads: NThreads) -> PyResult<usize> {
+        let (distance, _) = crate::units::levenshtein_long(unit, &a, &b, n_threads)?;
+        Ok(distance)
+    }
 
-// ------------------------------------------------------------------------
-//  Module Declarations
-// ------------------------------------------------------------------------
+    /// Calculates the normalized Levenshtein similarity between two very long
+    /// strings on several threads, as `levenshtein_long` computes the distance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction(unit = "Unit::Char", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, b, /, unit='char', n_threads=None)")]
+    pub fn normalized_levenshtein_long(a: Text, b: Text, unit: Unit, n_threads: NThreads) -> PyResult<f64> {
+        match crate::units::levenshtein_long(unit, &a, &b, n_threads)? {
+            (_, 0) => Ok(1.0),
+            (distance, len) => Ok(1.0 - distance as f64 / len as f64),
+        }
+    }
 
-#[pymodule]
-#[pyo3(name = "_py_strsim")]
-fn py_strsim(py: Python<'_>, m: &PyModule) -> PyResult<()> {
-    register_child_modules(py, m)?;
-    Ok(())
-}
+    pub(crate) fn check_gap(gap: f64) -> PyResult<()> {
+        if gap > 0.0 {
+            return Err(InvalidParameterError::new_err("gap must be at most 0.0"));
+        }
+        Ok(())
+    }
 
-fn register_child_modules(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
-    let single_module = PyModule::new(py, "single")?;
-    single_module.add_function(wrap_pyfunction!(single::damerau_levenshtein, single_module)?)?;
-    single_module.add_function(wrap_pyfunction!(single::jaro, single_module)?)?;
-    single_module.add_function(wrap_pyfunction!(single::jaro_winkler, single_module)?)?;
-    single_module.add_function(wrap_pyfunction!(single::levenshtein, single_module)?)?;
-    single_module.add_function(wrap_pyfunction!(single::normalized_levenshtein, single_module)?)?;
-    single_module.add_function(wrap_pyfunction!(single::normalized_damerau_levenshtein, single_module)?)?;
-    single_module.add_function(wrap_pyfunction!(single::osa_distance, single_module)?)?;
-    single_module.add_function(wrap_pyfunction!(single::sorensen_dice, single_module)?)?;
+    /// Converts the `matrix` argument of the alignment functions, which is
+    /// `None`, the name of a built-in matrix, or a dict of `(x, y)` pairs to
+    /// scores.
+    pub(crate) fn substitution_matrix(matrix: Option<&PyAny>) -> PyResult<Cow<'static, sequence::SubstitutionMatrix>> {
+        let matrix = match matrix {
+            None => return Ok(Cow::Owned(sequence::SubstitutionMatrix::default())),
+            Some(matrix) => matrix,
+        };
+        if let Ok(name) = matrix.extract::<&str>() {
+            return sequence::SubstitutionMatrix::named(name)
+                .map(Cow::Borrowed)
+                .ok_or_else(|| {
+                    InvalidParameterError::new_err(format!(
+                        "unknown substitution matrix '{}', expected 'blosum62' or 'pam250'",
+                        name
+                    ))
+                });
+        }
+        let entries: HashMap<(char, char), f64> = matrix.extract()?;
+        Ok(Cow::Owned(sequence::SubstitutionMatrix::from_entries(
+            entries.into_iter().map(|((x, y), score)| (x, y, score)),
+            1.0,
+            -1.0,
+        )))
+    }
 
-    let vectorized_module = PyModule::new(py, "vectorized")?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::damerau_levenshtein, vectorized_module)?)?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro, vectorized_module)?)?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro_winkler, vectorized_module)?)?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::levenshtein, vectorized_module)?)?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::normalized_levenshtein, vectorized_module)?)?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::normalized_damerau_levenshtein, vectorized_module)?)?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::osa_distance, vectorized_module)?)?;
-    vectorized_module.add_function(wrap_pyfunction!(vectorized::sorensen_dice, vectorized_module)?)?;
+    /// Calculates the score of an optimal global alignment of two sequences
+    /// with the Needleman-Wunsch algorithm. `matrix` is `None` (matches score
+    /// 1 and mismatches -1), `'blosum62'`, `'pam250'`, or a dict mapping
+    /// `(x, y)` character pairs to scores, where pairs missing from the dict
+    /// fall back to 1 and -1. Every gap position adds `gap` to the score.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First sequence to align
+    /// * `b` - Secondary sequence to align to `a`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Alignment score of `a` and `b`
+    #[pyfunction(matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn needleman_wunsch(
+        a: Text,
+        b: Text,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        check_gap(gap)?;
+        let matrix = substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        Ok(sequence::needleman_wunsch(&a, &b, &scoring).score)
+    }
 
-    parent.add_submodule(single_module)?;
-    parent.add_submodule(vectorized_module)?;
+    /// Calculates the score of the best local alignment of two sequences with
+    /// the Smith-Waterman algorithm. `matrix` and `gap` are interpreted as by
+    /// `needleman_wunsch`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First sequence to align
+    /// * `b` - Secondary sequence to align to `a`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Local alignment score of `a` and `b`
+    #[pyfunction(matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn smith_waterman(
+        a: Text,
+        b: Text,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        check_gap(gap)?;
+        let matrix = substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        Ok(sequence::smith_waterman(&a, &b, &scoring).score)
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    /// Aligns two sequences globally or locally, as selected by `mode`.
+    pub(crate) fn aligner(mode: &str) -> PyResult<sequence::Aligner> {
+        match mode {
+            "global" => Ok(sequence::needleman_wunsch),
+            "local" => Ok(sequence::smith_waterman),
+            _ => Err(InvalidParameterError::new_err(format!(
+                "unknown alignment mode '{}', expected 'global' or 'local'",
+                mode
+            ))),
+        }
+    }
+
+    /// Calculates the fraction of identical positions in an optimal alignment
+    /// of two sequences. Gap columns count towards the alignment length, and
+    /// `mode` selects a global (Needleman-Wunsch) or local (Smith-Waterman)
+    /// alignment. `matrix` and `gap` are interpreted as by `needleman_wunsch`.
+    /// The returned value is between 0.0 and 1.0 (higher value means more
+    /// similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First sequence to align
+    /// * `b` - Secondary sequence to align to `a`
+    /// * `mode` - Either `'global'` or `'local'`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Identity of `a` and `b`
+    #[pyfunction(mode = "\"global\"", matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(a, b, /, mode='global', matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn percent_identity(
+        a: Text,
+        b: Text,
+        mode: &str,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        let align = aligner(mode)?;
+        check_gap(gap)?;
+        let matrix = substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        Ok(align(&a, &b, &scoring).identity())
+    }
+
+    /// Weighted average of several similarity metrics, e.g.
+    /// `[("jaro_winkler", 2.0), ("sorensen_dice", 1.0)]`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metrics` - Pairs of a similarity name and its non-negative weight
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Combined similarity between `a` and `b`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None")]
+    #[pyo3(text_signature = "(metrics, a, b, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ensemble(
+        metrics: Vec<(&str, f64)>,
+        a: Text,
+        b: Text,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<f64> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let (a, b) = (pre.apply(&a), pre.apply(&b));
+        Ok(metric::Ensemble::new(&metrics)?.score(&a, &b))
+    }
+
+    /// Calculates the number of positions at which two equal-length byte
+    /// strings differ, without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `b` - Secondary byte string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and `b`
+    #[pyfunction]
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn hamming_bytes(a: Bytes, b: Bytes) -> PyResult<usize> {
+        bytes::hamming(&a, &b).ok_or_else(|| LengthMismatchError::new_err("a and b must have the same length"))
+    }
+
+    /// Calculates the Levenshtein distance between two byte strings, byte by
+    /// byte and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `b` - Secondary byte string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and `b`
+    #[pyfunction]
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn levenshtein_bytes(a: Bytes, b: Bytes) -> usize {
+        bytes::levenshtein(&a, &b)
+    }
+
+    /// Calculates the normalized Levenshtein similarity between two byte
+    /// strings, between 0.0 and 1.0, without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `b` - Secondary byte string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction]
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn normalized_levenshtein_bytes(a: Bytes, b: Bytes) -> f64 {
+        bytes::normalized_levenshtein(&a, &b)
+    }
+
+    /// Calculates the Damerau-Levenshtein distance between two byte strings,
+    /// byte by byte and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `b` - Secondary byte string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and `b`
+    #[pyfunction]
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn damerau_levenshtein_bytes(a: Bytes, b: Bytes) -> usize {
+        bytes::damerau_levenshtein(&a, &b)
+    }
+
+    /// Calculates the Jaro similarity between two byte strings, byte by byte
+    /// and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `b` - Secondary byte string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction]
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn jaro_bytes(a: Bytes, b: Bytes) -> f64 {
+        bytes::jaro(&a, &b)
+    }
+
+    /// Calculates the Jaro-Winkler similarity between two byte strings, byte
+    /// by byte and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `b` - Secondary byte string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyfunction]
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn jaro_winkler_bytes(a: Bytes, b: Bytes) -> f64 {
+        bytes::jaro_winkler(&a, &b)
+    }
+
+    /// Calculates the 64-bit SimHash fingerprint of a text from its
+    /// lowercased character n-grams. Similar texts get fingerprints a small
+    /// Hamming distance apart, whatever their length.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `text` - Text to fingerprint
+    /// * `n` - Number of characters per n-gram
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Fingerprint of `text`, 0 if it is empty
+    #[pyfunction(n = "3")]
+    #[pyo3(text_signature = "(text, /, n=3)")]
+    pub fn simhash(text: Text, n: usize) -> PyResult<u64> {
+        check_ngram(n)?;
+        Ok(crate::simhash::simhash(&text, n))
+    }
+
+    /// Calculates the number of bits in which two SimHash fingerprints differ.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First fingerprint to compare
+    /// * `b` - Secondary fingerprint to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Hamming distance between `a` and `b`, from 0 to 64
+    #[pyfunction]
+    #[pyo3(text_signature = "(a, b, /)")]
+    pub fn simhash_distance(a: u64, b: u64) -> u32 {
+        crate::simhash::hamming(a, b)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Composite Functions
+// ------------------------------------------------------------------------
+
+#[cfg(feature = "python")]
+pub mod vectorized {
+    use super::*;
+    use pyo3::types::PyDict;
+    pub(crate) use crate::batch::sort_best_first;
+    pub(crate) use crate::pool::thread_pool;
+
+    /// Smallest number of items one parallel task processes, checking that a
+    /// given `chunk_size` is positive.
+    pub(crate) fn min_len(chunk_size: Option<usize>) -> PyResult<usize> {
+        match chunk_size {
+            Some(chunk_size) => stream::check_chunk_size(chunk_size).map(|_| chunk_size),
+            None => Ok(1),
+        }
+    }
+
+    /// Checks that `symmetric` is only asked of a metric whose scores do not
+    /// depend on the order of the strings.
+    pub(crate) fn check_symmetric(metric: &str, symmetric: bool) -> PyResult<()> {
+        if symmetric && !metric::SYMMETRIC_NAMES.contains(&metric) {
+            return Err(InvalidParameterError::new_err(format!(
+                "symmetric=True requires a symmetric metric, '{}' scores (a, b) and (b, a) differently",
+                metric
+            )));
+        }
+        Ok(())
+    }
+
+    fn vectorize<F: Send + Sync>(
+        f: fn(&str, &str) -> F,
+        pre: &Preprocess,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        a: &str,
+        bs: Strings,
+    ) -> PyResult<Vec<F>> {
+        vectorize_with(pre.wrap_second(f), n_threads, chunk_size, &pre.apply(a), bs)
+    }
+
+    pub(crate) fn vectorize_with<T: Send, F: Fn(&str, &str) -> T + Sync>(
+        f: F,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        a: &str,
+        bs: Strings,
+    ) -> PyResult<Vec<T>> {
+        let min_len = min_len(chunk_size)?;
+        let pool = thread_pool(n_threads)?;
+        let mut output = Vec::new();
+        bs.for_each_batch(|_, bs| {
+            let reporter = progress::Reporter::new(None, Some(bs.len()));
+            let batch = pool
+                .install_sized(&reporter, bs.len(), min_len, |counter, min_len| {
+                    batch::score_each(counter, min_len, &f, a, bs)
+                })?;
+            output.extend(batch.unwrap_or_default());
+            Ok(())
+        })?;
+        Ok(output)
+    }
+
+    fn pairwise<F: Send + Sync>(
+        f: fn(&str, &str) -> F,
+        pre: &Preprocess,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        as_: Strings,
+        bs: Strings,
+    ) -> PyResult<Vec<F>> {
+        pairwise_with(pre.wrap(f), n_threads, chunk_size, as_, bs)
+    }
+
+    pub(crate) fn pairwise_with<T: Send, F: Fn(&str, &str) -> T + Sync>(
+        f: F,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        as_: Strings,
+        bs: Strings,
+    ) -> PyResult<Vec<T>> {
+        let min_len = min_len(chunk_size)?;
+        let (as_, bs) = (as_.as_strs()?, bs.as_strs()?);
+        batch::check_same_length(as_.len(), bs.len())?;
+        let reporter = progress::Reporter::new(None, Some(as_.len()));
+        let output = thread_pool(n_threads)?
+            .install_sized(&reporter, as_.len(), min_len, |counter, min_len| {
+                batch::score_zipped(counter, min_len, &f, &as_, &bs)
+            })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Like optimal string alignment, but substrings can be edited an unlimited
+    /// number of times, and the triangle inequality holds.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between `a` and each `b` in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn damerau_levenshtein(
+        a: Text,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<usize>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<usize>(crate::units::damerau_levenshtein(unit), &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Calculates the Jaro similarity between two strings. The returned value
+    /// is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jaro(
+        a: Text,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<f64>(crate::units::jaro(unit), &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Like Jaro but gives a boost to strings that have a common prefix. With
+    /// `long_strings` Winkler's long string adjustment is also applied.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `long_strings` - Whether to apply the long string adjustment, which
+    ///   requires `unit='char'`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(long_strings = "false", unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, long_strings=False, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jaro_winkler(
+        a: Text,
+        bs: Strings,
+        long_strings: bool,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        if long_strings {
+            crate::units::check_chars(unit, "long_strings")?;
+            vectorize::<f64>(crate::jaro::jaro_winkler_long, &pre, n_threads, chunk_size, &a, bs)
+        } else {
+            vectorize::<f64>(crate::units::jaro_winkler(unit), &pre, n_threads, chunk_size, &a, bs)
+        }
+    }
+
+    /// Calculates the minimum number of insertions, deletions, and substitutions
+    /// required to change one string into the other.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between `a` and each `b` in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn levenshtein(
+        a: Text,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<usize>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<usize>(crate::units::levenshtein(unit), &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Calculates a normalized score of the Damerau–Levenshtein algorithm between
+    /// 0.0 and 1.0 (inclusive), where 1.0 means the strings are the same.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn normalized_damerau_levenshtein(
+        a: Text,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<f64>(crate::units::normalized_damerau_levenshtein(unit), &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Calculates a normalized score of the Levenshtein algorithm between 0.0 and
+    /// 1.0 (inclusive), where 1.0 means the strings are the same.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn normalized_levenshtein(
+        a: Text,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<f64>(crate::units::normalized_levenshtein(unit), &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Like Levenshtein but allows for adjacent transpositions. Each substring can
+    /// only be edited once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between `a` and each `b` in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn osa_distance(
+        a: Text,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<usize>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<usize>(crate::units::osa_distance(unit), &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Calculates a Sørensen-Dice similarity distance using n-grams, bigrams by
+    /// default. With `tokens` the n-grams are built from words instead of
+    /// characters.
+    /// See http://en.wikipedia.org/wiki/S%C3%B8rensen%E2%80%93Dice_coefficient.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `n` - Number of characters (or words) per n-gram
+    /// * `tokens` - Whether to compare word n-grams instead of character n-grams
+    /// * `tokenizer` - How to split the strings into tokens in `tokens` mode:
+    ///   `None` for runs of letters and digits, a delimiter string, a compiled
+    ///   regular expression matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing in `tokens` mode,
+    ///   such as "inc" and "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(n = "2", tokens = "false", tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, n=2, tokens=False, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sorensen_dice(
+        a: Text,
+        bs: Strings,
+        n: usize,
+        tokens: bool,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        single::check_ngram(n)?;
+        single::check_tokenizer(tokens, &tokenizer, &stopwords)?;
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = vectorize_with(pre.wrap_second(|a, b| dice::sorensen_dice(a, b, n, tokens.then_some(&tokenizer))), n_threads, chunk_size, &a, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Compares the sorted intersection of the token sets of two strings
+    /// against the intersection joined with each remainder. The returned value
+    /// is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn token_set_ratio(
+        a: Text,
+        bs: Strings,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = vectorize_with(pre.wrap_second(|a, b| fuzz::token_set_ratio_with(a, b, &tokenizer)), n_threads, chunk_size, &a, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Calculates the best normalized similarity of the shorter string against
+    /// every equal-length window of the longer string. The returned value is
+    /// between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn partial_ratio(
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<f64>(fuzz::partial_ratio, &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Calculates the normalized Indel similarity, `2 * LCS / (|a| + |b|)`. The
+    /// returned value is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ratio(
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<f64>(fuzz::ratio, &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Like `ratio` but sorts the tokens of each string first, so that word order
+    /// does not affect the score.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn token_sort_ratio(
+        a: Text,
+        bs: Strings,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = vectorize_with(pre.wrap_second(|a, b| fuzz::token_sort_ratio_with(a, b, &tokenizer)), n_threads, chunk_size, &a, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Weighted combination of the full, partial, token-sort, and token-set ratios
+    /// using the fuzzywuzzy heuristics. The returned value is between 0.0 and 1.0
+    /// (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn wratio(
+        a: Text,
+        bs: Strings,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = vectorize_with(pre.wrap_second(|a, b| fuzz::wratio_with(a, b, &tokenizer)), n_threads, chunk_size, &a, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Calculates the U.S. Census Bureau strcmp95 similarity, a Jaro-Winkler
+    /// variant that gives partial credit to similar characters and optionally
+    /// boosts long strings. The returned value is between 0.0 and 1.0 (higher
+    /// value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `long_strings` - Whether to apply the long string adjustment
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(long_strings = "false", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, long_strings=False, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn strcmp95(
+        a: Text,
+        bs: Strings,
+        long_strings: bool,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        vectorize_with(pre.wrap_second(|a, b| crate::jaro::strcmp95(a, b, long_strings)), n_threads, chunk_size, &a, bs)
+    }
+
+    /// Like Levenshtein but substitutions commonly made by optical character
+    /// recognition, such as `0`/`O`, `1`/`l`/`I`, and `rn`/`m`, cost less than 1.0.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between `a` and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ocr_levenshtein(
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<f64>(weighted::ocr_levenshtein, &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Blends the normalized Levenshtein similarity of the Metaphone codes of
+    /// two strings with the Jaro-Winkler similarity of the raw strings. A
+    /// `weight` of 1.0 uses only the phonetic codes and 0.0 only the raw
+    /// strings.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `weight` - Share of the phonetic similarity in the result
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(weight = "0.5", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, weight=0.5, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn phonetic_hybrid(
+        a: Text,
+        bs: Strings,
+        weight: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        single::check_weight(weight)?;
+        vectorize_with(pre.wrap_second(|a, b| phonetic::phonetic_hybrid(a, b, weight)), n_threads, chunk_size, &a, bs)
+    }
+
+    /// Like Levenshtein but every edit costs `decay ** k`, where `k` is the
+    /// earlier of the positions of the edit in the two strings, so that errors
+    /// near the start cost more than errors near the end.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `decay` - Factor applied to the cost of each later position
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between `a` and each `b` in `bs`
+    #[pyfunction(decay = "0.9", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, decay=0.9, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn position_weighted_levenshtein(
+        a: Text,
+        bs: Strings,
+        decay: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        single::check_decay(decay)?;
+        vectorize_with(pre.wrap_second(|a, b| weighted::position_weighted_levenshtein(a, b, decay)), n_threads, chunk_size, &a, bs)
+    }
+
+    /// Calculates the Jensen-Shannon divergence of the character n-gram
+    /// frequency profiles of two strings. The returned value is between 0.0 and
+    /// 1.0 (lower value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `ngram` - Number of characters per n-gram
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Divergences between `a` and each `b` in `bs`
+    #[pyfunction(ngram = "3", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, ngram=3, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jensen_shannon(
+        a: Text,
+        bs: Strings,
+        ngram: usize,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        single::check_ngram(ngram)?;
+        let profile_a = divergence::Profile::new(&a, ngram);
+        vectorize_with(
+            pre.wrap_second(|_, b| divergence::profile_divergence(&profile_a, &divergence::Profile::new(b, ngram))),
+            n_threads,
+            chunk_size,
+            &a,
+            bs,
+        )
+    }
+
+    /// Calculates the LIG3 similarity, `2I / (2I + D)`, where `D` is the
+    /// Levenshtein distance and `I` the number of identical characters in an
+    /// optimal alignment. The returned value is between 0.0 and 1.0 (higher value
+    /// means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn lig3(
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        vectorize::<f64>(align::lig3, &pre, n_threads, chunk_size, &a, bs)
+    }
+
+    /// Calculates the score of an optimal global alignment of two sequences
+    /// with the Needleman-Wunsch algorithm. `matrix` and `gap` are interpreted
+    /// as by `single.needleman_wunsch`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First sequence to align
+    /// * `bs` - Secondary sequences to align to `a`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Alignment scores of `a` and each `b` in `bs`
+    #[pyfunction(matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn needleman_wunsch(
+        a: Text,
+        bs: Strings,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        single::check_gap(gap)?;
+        let matrix = single::substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        let chars_a: Vec<char> = a.chars().collect();
+        vectorize_with(
+            pre.wrap_second(|_, b| sequence::needleman_wunsch(&chars_a, &b.chars().collect::<Vec<_>>(), &scoring).score),
+            n_threads,
+            chunk_size,
+            &a,
+            bs,
+        )
+    }
+
+    /// Calculates the score of the best local alignment of two sequences with
+    /// the Smith-Waterman algorithm. `matrix` and `gap` are interpreted as by
+    /// `single.needleman_wunsch`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First sequence to align
+    /// * `bs` - Secondary sequences to align to `a`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Local alignment scores of `a` and each `b` in `bs`
+    #[pyfunction(matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn smith_waterman(
+        a: Text,
+        bs: Strings,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        single::check_gap(gap)?;
+        let matrix = single::substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        let chars_a: Vec<char> = a.chars().collect();
+        vectorize_with(
+            pre.wrap_second(|_, b| sequence::smith_waterman(&chars_a, &b.chars().collect::<Vec<_>>(), &scoring).score),
+            n_threads,
+            chunk_size,
+            &a,
+            bs,
+        )
+    }
+
+    /// Calculates the fraction of identical positions in an optimal alignment
+    /// of two sequences. Arguments are interpreted as by
+    /// `single.percent_identity`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First sequence to align
+    /// * `bs` - Secondary sequences to align to `a`
+    /// * `mode` - Either `'global'` or `'local'`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Identities of `a` and each `b` in `bs`
+    #[pyfunction(mode = "\"global\"", matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, mode='global', matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn percent_identity(
+        a: Text,
+        bs: Strings,
+        mode: &str,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        let align = single::aligner(mode)?;
+        single::check_gap(gap)?;
+        let matrix = single::substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        let chars_a: Vec<char> = a.chars().collect();
+        vectorize_with(
+            pre.wrap_second(|_, b| align(&chars_a, &b.chars().collect::<Vec<_>>(), &scoring).identity()),
+            n_threads,
+            chunk_size,
+            &a,
+            bs,
+        )
+    }
+
+    /// Like optimal string alignment, but substrings can be edited an unlimited
+    /// number of times, and the triangle inequality holds.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between each `a` in `as_` and the `b` at the same
+    ///   position in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn damerau_levenshtein_pairwise(
+        as_: Strings,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<usize>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<usize>(crate::units::damerau_levenshtein(unit), &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates the Jaro similarity between two strings. The returned value
+    /// is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jaro_pairwise(
+        as_: Strings,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<f64>(crate::units::jaro(unit), &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Like Jaro but gives a boost to strings that have a common prefix. With
+    /// `long_strings` Winkler's long string adjustment is also applied.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `long_strings` - Whether to apply the long string adjustment, which
+    ///   requires `unit='char'`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(long_strings = "false", unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, long_strings=False, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jaro_winkler_pairwise(
+        as_: Strings,
+        bs: Strings,
+        long_strings: bool,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        if long_strings {
+            crate::units::check_chars(unit, "long_strings")?;
+            pairwise::<f64>(crate::jaro::jaro_winkler_long, &pre, n_threads, chunk_size, as_, bs)
+        } else {
+            pairwise::<f64>(crate::units::jaro_winkler(unit), &pre, n_threads, chunk_size, as_, bs)
+        }
+    }
+
+    /// Calculates the minimum number of insertions, deletions, and substitutions
+    /// required to change one string into the other.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between each `a` in `as_` and the `b` at the same
+    ///   position in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn levenshtein_pairwise(
+        as_: Strings,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<usize>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<usize>(crate::units::levenshtein(unit), &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates a normalized score of the Damerau–Levenshtein algorithm between
+    /// 0.0 and 1.0 (inclusive), where 1.0 means the strings are the same.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn normalized_damerau_levenshtein_pairwise(
+        as_: Strings,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<f64>(crate::units::normalized_damerau_levenshtein(unit), &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates a normalized score of the Levenshtein algorithm between 0.0 and
+    /// 1.0 (inclusive), where 1.0 means the strings are the same.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn normalized_levenshtein_pairwise(
+        as_: Strings,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<f64>(crate::units::normalized_levenshtein(unit), &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Like Levenshtein but allows for adjacent transpositions. Each substring can
+    /// only be edited once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `unit` - What counts as one symbol: `"char"` for Unicode scalars,
+    ///   `"grapheme"` for the characters a reader sees, or `"byte"` for
+    ///   bytes of the UTF-8 encoding
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between each `a` in `as_` and the `b` at the same
+    ///   position in `bs`
+    #[pyfunction(unit = "Unit::Char", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, unit='char', case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn osa_distance_pairwise(
+        as_: Strings,
+        bs: Strings,
+        unit: Unit,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<usize>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<usize>(crate::units::osa_distance(unit), &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates a Sørensen-Dice similarity distance using n-grams, bigrams by
+    /// default. With `tokens` the n-grams are built from words instead of
+    /// characters.
+    /// See http://en.wikipedia.org/wiki/S%C3%B8rensen%E2%80%93Dice_coefficient.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `n` - Number of characters (or words) per n-gram
+    /// * `tokens` - Whether to compare word n-grams instead of character n-grams
+    /// * `tokenizer` - How to split the strings into tokens in `tokens` mode:
+    ///   `None` for runs of letters and digits, a delimiter string, a compiled
+    ///   regular expression matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing in `tokens` mode,
+    ///   such as "inc" and "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(n = "2", tokens = "false", tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, n=2, tokens=False, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sorensen_dice_pairwise(
+        as_: Strings,
+        bs: Strings,
+        n: usize,
+        tokens: bool,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        single::check_ngram(n)?;
+        single::check_tokenizer(tokens, &tokenizer, &stopwords)?;
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = pairwise_with(pre.wrap(|a, b| dice::sorensen_dice(a, b, n, tokens.then_some(&tokenizer))), n_threads, chunk_size, as_, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Compares the sorted intersection of the token sets of two strings
+    /// against the intersection joined with each remainder. The returned value
+    /// is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn token_set_ratio_pairwise(
+        as_: Strings,
+        bs: Strings,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = pairwise_with(pre.wrap(|a, b| fuzz::token_set_ratio_with(a, b, &tokenizer)), n_threads, chunk_size, as_, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Calculates the best normalized similarity of the shorter string against
+    /// every equal-length window of the longer string. The returned value is
+    /// between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn partial_ratio_pairwise(
+        as_: Strings,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<f64>(fuzz::partial_ratio, &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates the normalized Indel similarity, `2 * LCS / (|a| + |b|)`. The
+    /// returned value is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ratio_pairwise(
+        as_: Strings,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<f64>(fuzz::ratio, &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Like `ratio` but sorts the tokens of each string first, so that word order
+    /// does not affect the score.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn token_sort_ratio_pairwise(
+        as_: Strings,
+        bs: Strings,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = pairwise_with(pre.wrap(|a, b| fuzz::token_sort_ratio_with(a, b, &tokenizer)), n_threads, chunk_size, as_, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Weighted combination of the full, partial, token-sort, and token-set ratios
+    /// using the fuzzywuzzy heuristics. The returned value is between 0.0 and 1.0
+    /// (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `tokenizer` - How to split the strings into tokens: `None` for runs of
+    ///   letters and digits, a delimiter string, a compiled regular expression
+    ///   matching the tokens, or a callable returning them
+    /// * `stopwords` - Tokens to remove before comparing, such as "inc" and
+    ///   "ltd", or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(tokenizer = "None", stopwords = "None", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, tokenizer=None, stopwords=None, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn wratio_pairwise(
+        as_: Strings,
+        bs: Strings,
+        tokenizer: Option<Tokenizer>,
+        stopwords: Option<Stopwords>,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let tokenizer = tokenizer.unwrap_or(Tokenizer::Words).without(stopwords);
+        let scores = pairwise_with(pre.wrap(|a, b| fuzz::wratio_with(a, b, &tokenizer)), n_threads, chunk_size, as_, bs)?;
+        tokenizer.check()?;
+        Ok(scores)
+    }
+
+    /// Calculates the U.S. Census Bureau strcmp95 similarity, a Jaro-Winkler
+    /// variant that gives partial credit to similar characters and optionally
+    /// boosts long strings. The returned value is between 0.0 and 1.0 (higher
+    /// value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `long_strings` - Whether to apply the long string adjustment
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(long_strings = "false", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, long_strings=False, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn strcmp95_pairwise(
+        as_: Strings,
+        bs: Strings,
+        long_strings: bool,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise_with(pre.wrap(|a, b| crate::jaro::strcmp95(a, b, long_strings)), n_threads, chunk_size, as_, bs)
+    }
+
+    /// Like Levenshtein but substitutions commonly made by optical character
+    /// recognition, such as `0`/`O`, `1`/`l`/`I`, and `rn`/`m`, cost less than 1.0.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between each `a` in `as_` and the `b` at the same
+    ///   position in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ocr_levenshtein_pairwise(
+        as_: Strings,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<f64>(weighted::ocr_levenshtein, &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Blends the normalized Levenshtein similarity of the Metaphone codes of
+    /// two strings with the Jaro-Winkler similarity of the raw strings. A
+    /// `weight` of 1.0 uses only the phonetic codes and 0.0 only the raw
+    /// strings.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `weight` - Share of the phonetic similarity in the result
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(weight = "0.5", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, weight=0.5, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn phonetic_hybrid_pairwise(
+        as_: Strings,
+        bs: Strings,
+        weight: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        single::check_weight(weight)?;
+        pairwise_with(pre.wrap(|a, b| phonetic::phonetic_hybrid(a, b, weight)), n_threads, chunk_size, as_, bs)
+    }
+
+    /// Like Levenshtein but every edit costs `decay ** k`, where `k` is the
+    /// earlier of the positions of the edit in the two strings, so that errors
+    /// near the start cost more than errors near the end.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `decay` - Factor applied to the cost of each later position
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distances between each `a` in `as_` and the `b` at the same
+    ///   position in `bs`
+    #[pyfunction(decay = "0.9", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, decay=0.9, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn position_weighted_levenshtein_pairwise(
+        as_: Strings,
+        bs: Strings,
+        decay: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        single::check_decay(decay)?;
+        pairwise_with(pre.wrap(|a, b| weighted::position_weighted_levenshtein(a, b, decay)), n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates the Jensen-Shannon divergence of the character n-gram
+    /// frequency profiles of two strings. The returned value is between 0.0 and
+    /// 1.0 (lower value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `ngram` - Number of characters per n-gram
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Divergences between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(ngram = "3", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, ngram=3, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn jensen_shannon_pairwise(
+        as_: Strings,
+        bs: Strings,
+        ngram: usize,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        single::check_ngram(ngram)?;
+        pairwise_with(pre.wrap(|a, b| divergence::jensen_shannon(a, b, ngram)), n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates the LIG3 similarity, `2I / (2I + D)`, where `D` is the
+    /// Levenshtein distance and `I` the number of identical characters in an
+    /// optimal alignment. The returned value is between 0.0 and 1.0 (higher value
+    /// means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn lig3_pairwise(
+        as_: Strings,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        pairwise::<f64>(align::lig3, &pre, n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates the score of an optimal global alignment of two sequences
+    /// with the Needleman-Wunsch algorithm. `matrix` and `gap` are interpreted
+    /// as by `single.needleman_wunsch`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Alignment scores of each `a` in `as_` and the `b` at the
+    ///   same position in `bs`
+    #[pyfunction(matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn needleman_wunsch_pairwise(
+        as_: Strings,
+        bs: Strings,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        single::check_gap(gap)?;
+        let matrix = single::substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        pairwise_with(
+            pre.wrap(|a, b| {
+                let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+                sequence::needleman_wunsch(&a, &b, &scoring).score
+            }),
+            n_threads,
+            chunk_size,
+            as_,
+            bs,
+        )
+    }
+
+    /// Calculates the score of the best local alignment of two sequences with
+    /// the Smith-Waterman algorithm. `matrix` and `gap` are interpreted as by
+    /// `single.needleman_wunsch`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Local alignment scores of each `a` in `as_` and the `b` at
+    ///   the same position in `bs`
+    #[pyfunction(matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn smith_waterman_pairwise(
+        as_: Strings,
+        bs: Strings,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        single::check_gap(gap)?;
+        let matrix = single::substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        pairwise_with(
+            pre.wrap(|a, b| {
+                let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+                sequence::smith_waterman(&a, &b, &scoring).score
+            }),
+            n_threads,
+            chunk_size,
+            as_,
+            bs,
+        )
+    }
+
+    /// Calculates the fraction of identical positions in an optimal alignment
+    /// of two sequences. Arguments are interpreted as by
+    /// `single.percent_identity`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `mode` - Either `'global'` or `'local'`
+    /// * `matrix` - Substitution matrix name or user matrix
+    /// * `gap` - Score of a single gap position, at most 0.0
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Identities of each `a` in `as_` and the `b` at the same
+    ///   position in `bs`
+    #[pyfunction(mode = "\"global\"", matrix = "None", gap = "-1.0", case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(as_, bs, /, mode='global', matrix=None, gap=-1.0, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn percent_identity_pairwise(
+        as_: Strings,
+        bs: Strings,
+        mode: &str,
+        matrix: Option<&PyAny>,
+        gap: f64,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let align = single::aligner(mode)?;
+        single::check_gap(gap)?;
+        let matrix = single::substitution_matrix(matrix)?;
+        let scoring = sequence::Scoring { matrix: &matrix, gap };
+        pairwise_with(
+            pre.wrap(|a, b| {
+                let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+                align(&a, &b, &scoring).identity()
+            }),
+            n_threads,
+            chunk_size,
+            as_,
+            bs,
+        )
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric`. Rows are computed in parallel.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `symmetric` - Whether `as_` and `bs` hold the same strings, so that
+    ///   only the upper triangle is scored and then mirrored. Raises
+    ///   `InvalidParameterError` for `jaro` and `jaro_winkler`, which score
+    ///   `(a, b)` and `(b, a)` differently
+    /// * `mask` - Boolean matrix of shape `(len(as_), len(bs))` whose unset
+    ///   cells are skipped, or `None` to score every pair
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Matrix whose row `i` holds the scores between `as_[i]` and
+    ///   each `b` in `bs`, with `nan` for skipped pairs
+    #[pyfunction(symmetric = "false", mask = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, symmetric=False, mask=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist(
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        symmetric: bool,
+        mask: Option<input::Mask>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<Vec<Vec<f64>>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        check_symmetric(metric, symmetric)?;
+        let metric = metric::Metric::from_name(metric)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let (as_, bs) = (as_.as_strs()?, bs.as_strs()?);
+        let row = batch::packed(&metric);
+        let matrix = score_matrix(metric.score, row, n_threads, chunk_size, progress, &as_, &bs, symmetric, mask.as_ref(), f64::NAN)?;
+        if bs.is_empty() {
+            return Ok(vec![Vec::new(); as_.len()]);
+        }
+        Ok(matrix.chunks(bs.len()).map(<[f64]>::to_vec).collect())
+    }
+
+    /// Scores every string in `as_` against every string in `bs` into a
+    /// row-major matrix, in tasks of at least `chunk_size` strings and one row
+    /// by default. With `symmetric`, only the
+    /// upper triangle is scored and then mirrored into the lower one. Cells
+    /// unset in `mask` are not scored and hold `skipped` instead. Without a
+    /// mask, `row` scores a whole row at a time where it handles its string.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn score_matrix<T: Copy + Default + Send + Sync, F: Fn(&str, &str) -> T + Sync>(
+        score: F,
+        row: Option<&batch::RowFn<T>>,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        progress: Option<&PyAny>,
+        as_: &[&str],
+        bs: &[&str],
+        symmetric: bool,
+        mask: Option<&input::Mask>,
+        skipped: T,
+    ) -> PyResult<Vec<T>> {
+        if symmetric && as_ != bs {
+            return Err(InvalidParameterError::new_err("symmetric=True requires as_ and bs to hold the same strings"));
+        }
+        let min_len = min_len(chunk_size)?;
+        let (rows, columns) = (as_.len(), bs.len());
+        if let Some(mask) = mask {
+            mask.check_shape(rows, columns)?;
+        }
+        let mask = mask.map(|mask| move |i: usize, j: usize| mask.get(i, j));
+        let total = if symmetric { rows * (rows + 1) / 2 } else { rows * columns };
+        let reporter = progress::Reporter::new(progress, Some(total));
+        thread_pool(n_threads)?.install_reporting(&reporter, |counter| {
+            batch::score_matrix(counter, min_len, &score, row, as_, bs, symmetric, mask.as_ref(), skipped)
+        })
+    }
+
+    /// Calculates the scores of every pair of distinct strings in `xs` under
+    /// the metric named `metric`, in the condensed order of
+    /// `scipy.spatial.distance.pdist`: `(0, 1), (0, 2), ..., (1, 2), ...`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `xs` - Strings to compare with each other
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Scores of the `len(xs) * (len(xs) - 1) / 2` pairs
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, xs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    pub fn pdist(metric: &str, xs: Strings, case_insensitive: bool, preprocessor: Option<Preprocessor>, chunk_size: Option<usize>, n_threads: NThreads, progress: Option<&PyAny>) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        condensed(metric::Metric::from_name(metric)?.score, n_threads, chunk_size, progress, &xs.preprocessed(&pre).as_strs()?)
+    }
+
+    pub(crate) fn condensed<T: Copy + Send, F: Fn(&str, &str) -> T + Sync>(
+        score: F,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        progress: Option<&PyAny>,
+        xs: &[&str],
+    ) -> PyResult<Vec<T>> {
+        let min_len = min_len(chunk_size)?;
+        let reporter = progress::Reporter::new(progress, Some(xs.len() * xs.len().saturating_sub(1) / 2));
+        thread_pool(n_threads)?.install_reporting(&reporter, |counter| batch::score_condensed(counter, min_len, &score, xs))
+    }
+
+    /// Converts between the condensed scores of `pdist` and the square matrix
+    /// of `cdist`, like `scipy.spatial.distance.squareform`: a list of scores
+    /// becomes a list of rows and a list of rows becomes a list of scores.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `x` - Condensed scores, or a square matrix as a list of rows
+    /// * `diagonal` - Score of each string with itself, `0.0` for distances
+    ///   and `1.0` for similarities
+    /// * `checks` - Whether to check that a square matrix is symmetric and holds
+    ///   `diagonal` on its diagonal before condensing it
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The square matrix of condensed scores, or the condensed
+    ///   scores of a square matrix
+    #[pyfunction(diagonal = "0.0", checks = "true")]
+    #[pyo3(text_signature = "(x, /, diagonal=0.0, checks=True)")]
+    pub fn squareform(py: Python<'_>, x: &PyAny, diagonal: f64, checks: bool) -> PyResult<PyObject> {
+        if let Ok(condensed) = x.extract::<Vec<f64>>() {
+            let (side, matrix) = batch::square_from_condensed(&condensed, diagonal)?;
+            let rows: Vec<Vec<f64>> = matrix.chunks(side).map(<[f64]>::to_vec).collect();
+            return Ok(rows.into_py(py));
+        }
+        let rows: Vec<Vec<f64>> = x.extract()?;
+        let side = rows.len();
+        if let Some(row) = rows.iter().find(|row| row.len() != side) {
+            return Err(InvalidParameterError::new_err(format!("expected a square matrix, got a row of {} scores among {} rows", row.len(), side)));
+        }
+        Ok(batch::condensed_from_square((side, side), &rows.concat(), diagonal, checks)?.into_py(py))
+    }
+
+    /// Finds the `k` best matches for `a` among `bs` under the metric named
+    /// `metric`, keeping only a bounded heap of candidates per thread.
+    /// Similarities rank higher scores first and distances lower scores first.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `k` - Number of matches to return
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Up to `k` pairs of an index into `bs` and its score, best
+    ///   match first
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, a, bs, k, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn top_k(
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        k: usize,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<Vec<(usize, f64)>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let (a, bs) = (a.preprocessed(&pre), bs.preprocessed(&pre));
+        let min_len = min_len(chunk_size)?;
+        let pool = thread_pool(n_threads)?;
+        let reporter = progress::Reporter::new(progress, bs.len_hint());
+        let mut best = rank::TopK::new(k);
+        bs.for_each_batch(|start, bs| {
+            let batch = pool.install_reporting(&reporter, |counter| batch::best_k(counter, min_len, metric, &a, bs, start, k))?;
+            best = std::mem::replace(&mut best, rank::TopK::new(0)).merge(batch);
+            Ok(())
+        })?;
+        Ok(best.into_sorted_vec().into_iter().map(|r| (r.index, r.score)).collect())
+    }
+
+    /// Finds the best match for `a` among `bs` under the metric named
+    /// `metric` with a parallel reduction: the highest score for similarities
+    /// and the lowest for distances. Ties go to the lowest index.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Index into `bs` of the best match and its score, or `None`
+    ///   if `bs` is empty
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn best_match(
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<Option<(usize, f64)>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let (a, bs) = (a.preprocessed(&pre), bs.preprocessed(&pre));
+        let min_len = min_len(chunk_size)?;
+        let pool = thread_pool(n_threads)?;
+        let reporter = progress::Reporter::new(progress, bs.len_hint());
+        let mut best = None;
+        bs.for_each_batch(|start, bs| {
+            let batch = pool.install_reporting(&reporter, |counter| batch::best_one(counter, min_len, metric, &a, bs, start))?;
+            best = best.max(batch);
+            Ok(())
+        })?;
+        Ok(best.map(|r| (r.index, r.score)))
+    }
+
+    /// Compares `a` to each string in `bs` under the metric named `metric` and
+    /// keeps only the scores passing `cutoff`, filtering inside the parallel
+    /// loop. Similarities pass when at least `cutoff` and distances when at
+    /// most `cutoff`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `cutoff` - Worst score to keep
+    /// * `sort` - Whether to sort the results best match first
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of an index into `bs` and its score, in the order of
+    ///   `bs` or best match first
+    #[pyfunction(sort = "false", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, a, bs, cutoff, /, sort=False, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn threshold(
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        cutoff: f64,
+        sort: bool,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<Vec<(usize, f64)>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let (a, bs) = (a.preprocessed(&pre), bs.preprocessed(&pre));
+        let min_len = min_len(chunk_size)?;
+        let pool = thread_pool(n_threads)?;
+        let reporter = progress::Reporter::new(progress, bs.len_hint());
+        let mut output = Vec::new();
+        bs.for_each_batch(|start, bs| {
+            pool.install_reporting(&reporter, |counter| {
+                batch::extend_passing(counter, min_len, metric, &a, bs, start, cutoff, &mut output)
+            })
+        })?;
+        if sort {
+            sort_best_first(metric.kind, &pool, &mut output);
+        }
+        Ok(output)
+    }
+
+    /// Calculates the scores between `a` and each string in `bs` under the
+    /// metric named `metric` and sorts them best match first with a parallel
+    /// sort: highest first for similarities and lowest first for distances.
+    /// Ties go to the lowest index.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of an index into `bs` and its score, best match first
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    pub fn ranked(metric: &str, a: Text, bs: Strings, case_insensitive: bool, preprocessor: Option<Preprocessor>, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<(usize, f64)>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        ranked_with(metric::Metric::from_name(metric)?, n_threads, chunk_size, &a.preprocessed(&pre), bs.preprocessed(&pre))
+    }
+
+    pub(crate) fn ranked_with(
+        metric: metric::Metric,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        a: &str,
+        bs: Strings,
+    ) -> PyResult<Vec<(usize, f64)>> {
+        let mut output: Vec<(usize, f64)> = vectorize_with(metric.score, n_threads, chunk_size, a, bs)?
+            .into_iter()
+            .enumerate()
+            .collect();
+        sort_best_first(metric.kind, &thread_pool(n_threads)?, &mut output);
+        Ok(output)
+    }
+
+    /// Scores `a` against the strings of `bs` under the metric named
+    /// `metric`, one chunk of `chunk_size` candidates at a time, so that only
+    /// one chunk of results is held in memory at once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`, a sequence or an iterator
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Number of candidates scored per step
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Iterator yielding a list of scores per chunk, in the order
+    ///   of `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "65536", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=65536, n_threads=None)")]
+    pub fn iter_scores(
+        metric: &str,
+        a: Text,
+        bs: &PyAny,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: usize,
+        n_threads: NThreads,
+    ) -> PyResult<stream::ScoreChunks> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        stream::ScoreChunks::new(metric::Metric::from_name(metric)?, &a, bs, pre, chunk_size, n_threads, false)
+    }
+
+    /// Finds the best match among `choices` for each string in `queries`
+    /// under the metric named `metric`, in one parallel pass over the
+    /// queries. Each query keeps its best score so far as a cutoff, so that
+    /// candidates which cannot beat it are pruned early. Ties go to the lowest
+    /// index.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `queries` - Strings to find matches for
+    /// * `choices` - Strings to match each query against
+    /// * `threshold` - Worst score a match may have, or `None` to keep every
+    ///   best match
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each query, the index into `choices` of its best match
+    ///   and its score, or `None` if no choice passes `threshold`
+    #[pyfunction(threshold = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, queries, choices, /, threshold=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_lists(
+        metric: &str,
+        queries: Strings,
+        choices: Strings,
+        threshold: Option<f64>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<Vec<Option<(usize, f64)>>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let (queries, choices) = (queries.preprocessed(&pre), choices.preprocessed(&pre));
+        let (queries, choices) = (queries.as_strs()?, choices.as_strs()?);
+        let lengths = metric.char_lengths(&choices);
+        let min_len = min_len(chunk_size)?;
+        let reporter = progress::Reporter::new(progress, Some(queries.len() * choices.len()));
+        thread_pool(n_threads)?
+            .install_reporting(&reporter, |counter| {
+                queries
+                    .par_iter()
+                    .with_min_len(batch::rows_per_task(min_len, choices.len()))
+                    .map(|&a| {
+                        if counter.is_cancelled() {
+                            return None;
+                        }
+                        let len_a = metric.char_length(a);
+                        let mut best: Option<rank::Ranked> = None;
+                        for (i, &b) in choices.iter().enumerate() {
+                            let len_b = lengths.as_ref().map(|lengths| lengths[i]);
+                            let score = match best.map(|r| r.score).or(threshold) {
+                                Some(cutoff) => metric.score_cutoff_sized(a, len_a, b, len_b, cutoff),
+                                None => Some((metric.score)(a, b)),
+                            };
+                            if let Some(score) = score {
+                                best = best.max(Some(rank::Ranked::new(metric.kind, i, score)));
+                            }
+                        }
+                        counter.advance(choices.len());
+                        best.map(|r| (r.index, r.score))
+                    })
+                    .collect()
+            })
+    }
+
+    /// Joins two lists of keys on the pairs whose score under the metric
+    /// named `key_metric` passes `threshold`. The keys of `right` are blocked
+    /// by length, so that each key of `left` is only compared to the keys
+    /// whose length lets them pass `threshold` at all, and those are verified
+    /// in parallel, one key of `left` per task.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `left` - Keys of the first table
+    /// * `right` - Keys of the second table
+    /// * `key_metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `threshold` - Worst score of two matching keys
+    /// * `how` - `"inner"` for the matches only, `"left"` or `"right"` to
+    ///   also keep the unmatched keys of that table, or `"outer"` for both
+    /// * `top_k` - Largest number of matches kept per key of `left`, or
+    ///   `None` to keep all of them
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Triples of an index into `left`, an index into `right`
+    ///   and their score, in the order of `left` and best match first, with
+    ///   `None` for the side and score of an unmatched key. Unmatched keys of
+    ///   `right` come last.
+    #[pyfunction(how = "\"left\"", top_k = "1", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(left, right, key_metric, threshold, /, how='left', top_k=1, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn fuzzy_join(
+        left: Strings,
+        right: Strings,
+        key_metric: &str,
+        threshold: f64,
+        how: &str,
+        top_k: Option<usize>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<Vec<(Option<usize>, Option<usize>, Option<f64>)>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(key_metric)?;
+        let how = batch::Join::from_name(how)?;
+        batch::check_top_k(top_k)?;
+        let min_len = min_len(chunk_size)?;
+        let (left, right) = (left.preprocessed(&pre), right.preprocessed(&pre));
+        let (left, right) = (left.as_strs()?, right.as_strs()?);
+        let reporter = progress::Reporter::new(progress, Some(left.len() * right.len()));
+        let matches = thread_pool(n_threads)?.install_reporting(&reporter, |counter| {
+            batch::join_matches(counter, min_len, metric, &left, &right, threshold, top_k)
+        })?;
+        Ok(how.rows(matches, right.len()))
+    }
+
+    /// Groups the near-duplicates in `strings` under the metric named
+    /// `metric`. Every pair is compared in parallel, and pairs whose score
+    /// passes `threshold` are linked, so that a group holds every string
+    /// reachable through such links.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `strings` - Strings to deduplicate
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `threshold` - Worst score of two strings that are near-duplicates
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each string, the index of the first string of its
+    ///   group, which is its own index for the representative of each group
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(strings, metric, threshold, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn dedupe(
+        strings: Strings,
+        metric: &str,
+        threshold: f64,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<Vec<usize>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let strings = strings.preprocessed(&pre);
+        let xs = strings.as_strs()?;
+        let lengths = metric.char_lengths(&xs);
+        let min_len = min_len(chunk_size)?;
+        let length = |i: usize| lengths.as_ref().map(|lengths| lengths[i]);
+        let reporter = progress::Reporter::new(progress, Some(xs.len() * xs.len().saturating_sub(1) / 2));
+        let links: Vec<Vec<usize>> = thread_pool(n_threads)?
+            .install_reporting(&reporter, |counter| {
+                (0..xs.len())
+                    .into_par_iter()
+                    .with_min_len(batch::rows_per_task(min_len, xs.len()))
+                    .map(|i| {
+                        if counter.is_cancelled() {
+                            return Vec::new();
+                        }
+                        let passes =
+                            |j: usize| metric.score_cutoff_sized(xs[i], length(i), xs[j], length(j), threshold).is_some();
+                        let row = (i + 1..xs.len())
+                            .filter(|&j| passes(j))
+                            .collect();
+                        counter.advance(xs.len() - i - 1);
+                        row
+                    })
+                    .collect()
+            })?;
+
+        let mut groups = cluster::DisjointSet::new(xs.len());
+        for (i, row) in links.into_iter().enumerate() {
+            for j in row {
+                groups.union(i, j);
+            }
+        }
+        Ok(groups.into_labels())
+    }
+
+    /// Calculates the scores between one string and many others under each of
+    /// several metrics, in one pass over `bs`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metrics` - Names of the metrics, e.g. `["levenshtein", "jaro"]`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Dictionary from each metric name to the scores between `a`
+    ///   and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metrics, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn multi_scores<'py>(
+        py: Python<'py>,
+        metrics: Vec<&str>,
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<&'py PyDict> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let columns = multi_columns(&metrics, n_threads, chunk_size, &a.preprocessed(&pre), bs.preprocessed(&pre))?;
+        let output = PyDict::new(py);
+        for (metric, column) in metrics.into_iter().zip(columns) {
+            output.set_item(metric, column)?;
+        }
+        Ok(output)
+    }
+
+    /// Scores of `a` against each string of `bs` under each of `metrics`,
+    /// one column per metric. Every candidate is scored under all metrics
+    /// by the same task.
+    pub(crate) fn multi_columns(
+        metrics: &[&str],
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        a: &str,
+        bs: Strings,
+    ) -> PyResult<Vec<Vec<f64>>> {
+        for (i, metric) in metrics.iter().enumerate() {
+            if metrics[..i].contains(metric) {
+                return Err(InvalidParameterError::new_err(format!("metric '{}' is given more than once", metric)));
+            }
+        }
+        let scorers = metrics
+            .iter()
+            .map(|metric| metric::Metric::from_name(metric).map(|metric| metric.score))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let rows = vectorize_with(
+            |a, b| scorers.iter().map(|score| score(a, b)).collect::<Vec<f64>>(),
+            n_threads,
+            chunk_size,
+            a,
+            bs,
+        )?;
+
+        let mut columns = vec![Vec::with_capacity(rows.len()); scorers.len()];
+        for row in rows {
+            for (column, score) in columns.iter_mut().zip(row) {
+                column.push(score);
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Weighted average of several similarity metrics, e.g.
+    /// `[("jaro_winkler", 2.0), ("sorensen_dice", 1.0)]`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metrics` - Pairs of a similarity name and its non-negative weight
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Combined similarities between `a` and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metrics, a, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ensemble(
+        metrics: Vec<(&str, f64)>,
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let a = pre.apply(&a);
+        let ensemble = metric::Ensemble::new(&metrics)?;
+        vectorize_with(pre.wrap_second(|a, b| ensemble.score(a, b)), n_threads, chunk_size, &a, bs)
+    }
+
+    /// Weighted average of several similarity metrics, for the strings at the
+    /// same positions of two equal-length lists.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metrics` - Pairs of a similarity name and its non-negative weight
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the matching `a` in `as_`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `casefold` - Unicode case folding to apply instead of lowercasing,
+    ///   `"full"` or `"turkic"` for the Turkic dotted and dotless I, or `None`
+    /// * `collation` - Locale tag such as `"sv"` or `"de-u-co-phonebk"` whose
+    ///   collation rules decide which strings are equivalent, ignoring case and
+    ///   accents unless the tag sets a strength such as `"sv-u-ks-level2"`, or
+    ///   `None`
+    /// * `normalize` - Unicode normalization form to bring both strings to,
+    ///   `"nfc"`, `"nfkc"`, `"nfd"` or `"nfkd"`, or `None` to leave them as is
+    /// * `normalize_width` - Whether to replace full-width letters, digits and
+    ///   symbols with the usual ones and half-width katakana with full-width ones
+    /// * `transliterate` - Whether to spell Cyrillic and Greek letters in Latin
+    ///   ones, so that "Москва" matches "Moskva"
+    /// * `strip_accents` - Whether to remove accents and other combining marks
+    ///   from both strings
+    /// * `strip_punctuation` - Whether to remove punctuation and symbols, every
+    ///   character but letters, digits, whitespace and combining marks
+    /// * `collapse_whitespace` - Whether to replace every run of whitespace with
+    ///   a single space
+    /// * `trim` - Whether to remove leading and trailing whitespace
+    /// * `preprocessor` - `Preprocessor` whose steps run before the other
+    ///   preprocessing options, or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Combined similarities between each pair
+    #[pyfunction(case_insensitive = "false", casefold = "None", collation = "None", normalize = "None", normalize_width = "false", transliterate = "false", strip_accents = "false", strip_punctuation = "false", collapse_whitespace = "false", trim = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metrics, as_, bs, /, case_insensitive=False, casefold=None, collation=None, normalize=None, normalize_width=False, transliterate=False, strip_accents=False, strip_punctuation=False, collapse_whitespace=False, trim=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ensemble_pairwise(
+        metrics: Vec<(&str, f64)>,
+        as_: Strings,
+        bs: Strings,
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::new(case_insensitive, casefold, collation, normalize, normalize_width, transliterate, strip_accents, strip_punctuation, collapse_whitespace, trim).after(preprocessor);
+        let ensemble = metric::Ensemble::new(&metrics)?;
+        pairwise_with(pre.wrap(|a, b| ensemble.score(a, b)), n_threads, chunk_size, as_, bs)
+    }
+
+    /// Calculates the score of each explicit pair of strings under the metric
+    /// named `metric`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `pairs` - Pairs of strings `(a, b)` to compare
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Score of each pair, in the order of `pairs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, pairs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    pub fn score_pairs(metric: &str, pairs: Vec<(&str, &str)>, case_insensitive: bool, preprocessor: Option<Preprocessor>, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let preprocessed: Vec<_> = pairs.iter().map(|&(a, b)| (pre.apply(a), pre.apply(b))).collect();
+        let pairs: Vec<(&str, &str)> = preprocessed.iter().map(|(a, b)| (&**a, &**b)).collect();
+        score_pairs_with(metric::Metric::from_name(metric)?.score, n_threads, chunk_size, &pairs)
+    }
+
+    pub(crate) fn score_pairs_with<T: Send, F: Fn(&str, &str) -> T + Sync>(
+        score: F,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        pairs: &[(&str, &str)],
+    ) -> PyResult<Vec<T>> {
+        let min_len = min_len(chunk_size)?;
+        let reporter = progress::Reporter::new(None, Some(pairs.len()));
+        let output = thread_pool(n_threads)?
+            .install_sized(&reporter, pairs.len(), min_len, |counter, min_len| {
+                pairs
+                    .par_iter()
+                    .with_min_len(min_len)
+                    .map(|&(a, b)| (!counter.is_cancelled()).then(|| score(a, b)))
+                    .collect::<Option<Vec<T>>>()
+            })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    fn vectorize_bytes<T: Send>(
+        f: fn(&[u8], &[u8]) -> T,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        a: &[u8],
+        bs: &ByteStrings,
+    ) -> PyResult<Vec<T>> {
+        let bs = bs.as_slices();
+        let min_len = min_len(chunk_size)?;
+        let reporter = progress::Reporter::new(None, Some(bs.len()));
+        let output = thread_pool(n_threads)?
+            .install_sized(&reporter, bs.len(), min_len, |counter, min_len| {
+                bs
+                    .par_iter()
+                    .with_min_len(min_len)
+                    .map(|&b| (!counter.is_cancelled()).then(|| f(a, b)))
+                    .collect::<Option<Vec<T>>>()
+            })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Calculates the number of positions at which two equal-length byte
+    /// strings differ, without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `bs` - Secondary byte strings to compare to `a`, or a 2D
+    ///   array holding one per row
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and each `b` in `bs`, or `None` where the lengths differ
+    #[pyfunction(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, chunk_size=None, n_threads=None)")]
+    pub fn hamming_bytes(a: Bytes, bs: ByteStrings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<Option<usize>>> {
+        vectorize_bytes(bytes::hamming, n_threads, chunk_size, &a, &bs)
+    }
+
+    /// Calculates the Levenshtein distance between two byte strings, byte by
+    /// byte and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `bs` - Secondary byte strings to compare to `a`, or a 2D
+    ///   array holding one per row
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and each `b` in `bs`
+    #[pyfunction(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, chunk_size=None, n_threads=None)")]
+    pub fn levenshtein_bytes(a: Bytes, bs: ByteStrings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<usize>> {
+        vectorize_bytes(bytes::levenshtein, n_threads, chunk_size, &a, &bs)
+    }
+
+    /// Calculates the normalized Levenshtein similarity between two byte
+    /// strings, between 0.0 and 1.0, without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `bs` - Secondary byte strings to compare to `a`, or a 2D
+    ///   array holding one per row
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and each `b` in `bs`
+    #[pyfunction(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, chunk_size=None, n_threads=None)")]
+    pub fn normalized_levenshtein_bytes(a: Bytes, bs: ByteStrings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        vectorize_bytes(bytes::normalized_levenshtein, n_threads, chunk_size, &a, &bs)
+    }
+
+    /// Calculates the Damerau-Levenshtein distance between two byte strings,
+    /// byte by byte and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `bs` - Secondary byte strings to compare to `a`, or a 2D
+    ///   array holding one per row
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Distance between `a` and each `b` in `bs`
+    #[pyfunction(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, chunk_size=None, n_threads=None)")]
+    pub fn damerau_levenshtein_bytes(a: Bytes, bs: ByteStrings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<usize>> {
+        vectorize_bytes(bytes::damerau_levenshtein, n_threads, chunk_size, &a, &bs)
+    }
+
+    /// Calculates the Jaro similarity between two byte strings, byte by byte
+    /// and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `bs` - Secondary byte strings to compare to `a`, or a 2D
+    ///   array holding one per row
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and each `b` in `bs`
+    #[pyfunction(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, chunk_size=None, n_threads=None)")]
+    pub fn jaro_bytes(a: Bytes, bs: ByteStrings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        vectorize_bytes(bytes::jaro, n_threads, chunk_size, &a, &bs)
+    }
+
+    /// Calculates the Jaro-Winkler similarity between two byte strings, byte
+    /// by byte and without decoding them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First byte string to compare
+    /// * `bs` - Secondary byte strings to compare to `a`, or a 2D
+    ///   array holding one per row
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and each `b` in `bs`
+    #[pyfunction(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(a, bs, /, chunk_size=None, n_threads=None)")]
+    pub fn jaro_winkler_bytes(a: Bytes, bs: ByteStrings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        vectorize_bytes(bytes::jaro_winkler, n_threads, chunk_size, &a, &bs)
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric`, keeping only the entries that
+    /// pass `cutoff` as a sparse matrix in coordinate format. Similarities
+    /// pass when at least `cutoff` and distances when at most `cutoff`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `cutoff` - Worst score to keep
+    /// * `mask` - Boolean matrix of shape `(len(as_), len(bs))` whose unset
+    ///   cells are skipped, or `None` to score every pair
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Row indices into `as_`, column indices into `bs`, and
+    ///   scores of the kept entries, in row-major order
+    #[pyfunction(mask = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, cutoff, /, mask=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_sparse(
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        cutoff: f64,
+        mask: Option<input::Mask>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<(Vec<usize>, Vec<usize>, Vec<f64>)> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        sparse_matrix(metric, n_threads, chunk_size, progress, &as_.as_strs()?, &bs.as_strs()?, cutoff, mask.as_ref())
+    }
+
+    /// Scores every string in `as_` against every string in `bs`, one row per
+    /// parallel task, and keeps the coordinates and scores of the entries
+    /// passing `cutoff`. Cells unset in `mask` are not scored.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn sparse_matrix(
+        metric: metric::Metric,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        progress: Option<&PyAny>,
+        as_: &[&str],
+        bs: &[&str],
+        cutoff: f64,
+        mask: Option<&input::Mask>,
+    ) -> PyResult<(Vec<usize>, Vec<usize>, Vec<f64>)> {
+        let min_len = min_len(chunk_size)?;
+        if let Some(mask) = mask {
+            mask.check_shape(as_.len(), bs.len())?;
+        }
+        let lengths = metric.char_lengths(bs);
+        let reporter = progress::Reporter::new(progress, Some(as_.len() * bs.len()));
+        let rows: Vec<Vec<(usize, f64)>> = thread_pool(n_threads)?
+            .install_reporting(&reporter, |counter| {
+                as_
+                    .par_iter()
+                    .with_min_len(batch::rows_per_task(min_len, bs.len()))
+                    .enumerate()
+                    .map(|(i, &a)| {
+                        if counter.is_cancelled() {
+                            return Vec::new();
+                        }
+                        let len_a = metric.char_length(a);
+                        let row = bs
+                            .iter()
+                            .enumerate()
+                            .filter(|&(j, _)| mask.is_none_or(|mask| mask.get(i, j)))
+                            .filter_map(|(j, &b)| {
+                                let len_b = lengths.as_ref().map(|lengths| lengths[j]);
+                                metric.score_cutoff_sized(a, len_a, b, len_b, cutoff).map(|score| (j, score))
+                            })
+                            .collect();
+                        counter.advance(bs.len());
+                        row
+                    })
+                    .collect()
+            })?;
+
+        let size = rows.iter().map(Vec::len).sum();
+        let (mut row_indices, mut column_indices, mut scores) =
+            (Vec::with_capacity(size), Vec::with_capacity(size), Vec::with_capacity(size));
+        for (i, row) in rows.into_iter().enumerate() {
+            for (j, score) in row {
+                row_indices.push(i);
+                column_indices.push(j);
+                scores.push(score);
+            }
+        }
+        Ok((row_indices, column_indices, scores))
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric`, one tile at a time, so that no
+    /// more than `max_memory` bytes of scores are held at once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `max_memory` - Largest size of one tile of scores, in bytes
+    /// * `checkpoint` - Path of a file to save finished tiles to and resume
+    ///   from, or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Iterator yielding, for each tile, the row into `as_` and
+    ///   column into `bs` of its first score and the tile as a list of rows
+    #[pyfunction(max_memory = "268435456", checkpoint = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, max_memory=268435456, checkpoint=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn iter_cdist(
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        max_memory: usize,
+        checkpoint: Option<std::path::PathBuf>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<stream::ScoreTiles> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        stream::ScoreTiles::new(metric, as_, bs, max_memory, checkpoint, chunk_size, n_threads, false)
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric` and writes them to the file at
+    /// `path` one tile at a time, so that no more than `max_memory` bytes of
+    /// scores are held at once and no Python object is made for them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `path` - Path of the file to write
+    /// * `format` - `"npy"` for a 2D array, `"npz"` for an archive holding it
+    ///   as `scores`, `"parquet"` for a table of `row`, `column` and `score`,
+    ///   or `None` to tell from the extension of `path`
+    /// * `max_memory` - Largest size of one tile of scores, in bytes
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Number of scores written
+    #[pyfunction(format = "None", max_memory = "268435456", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, path, /, format=None, max_memory=268435456, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_to_file(
+        py: Python<'_>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        path: std::path::PathBuf,
+        format: Option<&str>,
+        max_memory: usize,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<usize> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let score = metric::Metric::from_name(metric)?.score;
+        let format = writer::Format::of(&path, format)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let (as_, bs) = (as_.as_strs()?, bs.as_strs()?);
+        let (tile_rows, tile_columns) = stream::tile_shape(bs.len(), max_memory)?;
+        let min_len = min_len(chunk_size)?;
+        let pool = thread_pool(n_threads)?;
+        let reporter = progress::Reporter::new(progress, Some(as_.len() * bs.len()));
+        let mut output = writer::DenseWriter::create(py, &path, format, as_.len(), bs.len())?;
+        let written = (|| {
+            for row in (0..as_.len()).step_by(tile_rows) {
+                let rows = &as_[row..(row + tile_rows).min(as_.len())];
+                for column in (0..bs.len()).step_by(tile_columns) {
+                    let columns = &bs[column..(column + tile_columns).min(bs.len())];
+                    let mut tile = vec![0.0; rows.len() * columns.len()];
+                    pool.install_reporting(&reporter, |counter| {
+                        tile.par_chunks_mut(columns.len())
+                            .zip(rows.par_iter())
+                            .with_min_len(batch::rows_per_task(min_len, columns.len()))
+                            .for_each(|(cells, &a)| {
+                                if counter.is_cancelled() {
+                                    return;
+                                }
+                                for (cell, &b) in cells.iter_mut().zip(columns) {
+                                    *cell = score(a, b);
+                                }
+                                counter.advance(columns.len());
+                            })
+                    })?;
+                    output.write(py, row, column, columns.len(), &tile)?;
+                }
+            }
+            Ok(())
+        })();
+        match written.and_then(|()| output.finish(py)) {
+            Ok(()) => Ok(as_.len() * bs.len()),
+            Err(err) => {
+                let _ = std::fs::remove_file(&path);
+                Err(err)
+            }
+        }
+    }
+
+    /// Finds the pairs of a string in `as_` and a string in `bs` whose score
+    /// under the metric named `metric` passes `cutoff`, like `cdist_sparse`,
+    /// and writes them to the file at `path` as they are found, a band of
+    /// rows at a time, so that no Python object is made for them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `cutoff` - Worst score of a pair that is written
+    /// * `path` - Path of the file to write
+    /// * `format` - `"npz"` for an archive of the arrays `row`, `column` and
+    ///   `score`, `"parquet"` for a table of those columns, or `None` to tell
+    ///   from the extension of `path`
+    /// * `max_memory` - Largest size of the scores of one band of rows, were
+    ///   they all kept, in bytes
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Number of pairs written
+    #[pyfunction(format = "None", max_memory = "268435456", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, cutoff, path, /, format=None, max_memory=268435456, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_sparse_to_file(
+        py: Python<'_>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        cutoff: f64,
+        path: std::path::PathBuf,
+        format: Option<&str>,
+        max_memory: usize,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<usize> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let format = writer::Format::of(&path, format)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let (as_, bs) = (as_.as_strs()?, bs.as_strs()?);
+        let (band, _) = stream::tile_shape(bs.len(), max_memory)?;
+        let min_len = min_len(chunk_size)?;
+        let pool = thread_pool(n_threads)?;
+        let reporter = progress::Reporter::new(progress, Some(as_.len() * bs.len()));
+        let lengths = metric.char_lengths(&bs);
+        let mut output = writer::SparseWriter::create(py, &path, format)?;
+        let mut count = 0;
+        let written = (|| {
+            for row in (0..as_.len()).step_by(band) {
+                let rows = &as_[row..(row + band).min(as_.len())];
+                let matches: Vec<Vec<(usize, f64)>> = pool.install_reporting(&reporter, |counter| {
+                    rows.par_iter()
+                        .with_min_len(batch::rows_per_task(min_len, bs.len()))
+                        .map(|&a| {
+                            if counter.is_cancelled() {
+                                return Vec::new();
+                            }
+                            let len_a = metric.char_length(a);
+                            let matches = bs
+                                .iter()
+                                .enumerate()
+                                .filter_map(|(j, &b)| {
+                                    let len_b = lengths.as_ref().map(|lengths| lengths[j]);
+                                    metric.score_cutoff_sized(a, len_a, b, len_b, cutoff).map(|score| (j, score))
+                                })
+                                .collect();
+                            counter.advance(bs.len());
+                            matches
+                        })
+                        .collect()
+                })?;
+                let (mut row_indices, mut column_indices, mut scores) = (Vec::new(), Vec::new(), Vec::new());
+                for (i, matches) in matches.into_iter().enumerate() {
+                    for (j, score) in matches {
+                        row_indices.push((row + i) as u64);
+                        column_indices.push(j as u64);
+                        scores.push(score);
+                    }
+                }
+                if !scores.is_empty() {
+                    count += scores.len();
+                    output.write(py, &row_indices, &column_indices, &scores)?;
+                }
+            }
+            Ok(())
+        })();
+        let finished = match written {
+            Ok(()) => output.finish(py),
+            Err(err) => {
+                output.discard();
+                Err(err)
+            }
+        };
+        match finished {
+            Ok(()) => Ok(count),
+            Err(err) => {
+                let _ = std::fs::remove_file(&path);
+                Err(err)
+            }
+        }
+    }
+
+    /// Preprocesses `choices` once for the metric named `metric`, so that many
+    /// queries can then be scored against them. Choices are split into the
+    /// characters or bigram counts the metric works on, so this pays off for
+    /// `levenshtein`, `normalized_levenshtein`, `damerau_levenshtein`,
+    /// `normalized_damerau_levenshtein`, `jaro`, `jaro_winkler`, and
+    /// `sorensen_dice`; other metrics score the stored strings.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"sorensen_dice"`
+    /// * `choices` - Strings that queries will be scored against
+    /// * `case_insensitive` - Whether to lowercase the choices and queries
+    /// * `preprocessor` - `Preprocessor` applied to the choices and queries
+    ///   before they are lowercased, or `None`
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Prepared choices, with `scores(query)` and
+    ///   `top_k(query, k)` methods
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, choices, /, case_insensitive=False, preprocessor=None, n_threads=None)")]
+    pub fn prepare(
+        metric: &str,
+        choices: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        n_threads: NThreads,
+    ) -> PyResult<prepared::PreparedChoices> {
+        prepared::PreparedChoices::new(metric, choices, case_insensitive, preprocessor, n_threads)
+    }
+
+    /// Aggregate of each row of a score matrix.
+    #[derive(Clone, Copy, Debug)]
+    pub(crate) enum Reduction {
+        Min,
+        Max,
+        Mean,
+        /// Number of scores passing the cutoff.
+        Count(f64),
+    }
+
+    impl Reduction {
+        pub(crate) fn new(name: &str, cutoff: Option<f64>) -> PyResult<Self> {
+            let reduction = match name {
+                "min" => Self::Min,
+                "max" => Self::Max,
+                "mean" => Self::Mean,
+                "count" => {
+                    let cutoff = cutoff.ok_or_else(|| InvalidParameterError::new_err("reduction 'count' requires a cutoff"))?;
+                    return Ok(Self::Count(cutoff));
+                }
+                _ => {
+                    return Err(InvalidParameterError::new_err(format!(
+                        "unknown reduction '{}', expected one of: min, max, mean, count",
+                        name
+                    )))
+                }
+            };
+            match cutoff {
+                Some(_) => Err(InvalidParameterError::new_err(format!(
+                    "cutoff only applies to reduction 'count', not '{}'",
+                    name
+                ))),
+                None => Ok(reduction),
+            }
+        }
+    }
+
+    /// Reduces each row of the matrix of scores of `as_` against `bs` as it
+    /// is computed, in tasks of rows like `score_matrix`, so that no row is
+    /// ever stored.
+    /// The minimum of a distance and the maximum of a similarity use the
+    /// best score so far as a cutoff, pruning the remaining comparisons where
+    /// the metric allows it. Empty rows reduce to NaN, or 0 for `Count`.
+    pub(crate) fn reduce_rows(
+        metric: metric::Metric,
+        n_threads: NThreads,
+        chunk_size: Option<usize>,
+        progress: Option<&PyAny>,
+        as_: &[&str],
+        bs: &[&str],
+        reduction: Reduction,
+    ) -> PyResult<Vec<f64>> {
+        let min_len = min_len(chunk_size)?;
+        let pruned = matches!(
+            (reduction, metric.kind),
+            (Reduction::Min, metric::Kind::Distance) | (Reduction::Max, metric::Kind::Similarity)
+        );
+        let lengths = match reduction {
+            Reduction::Count(_) => metric.char_lengths(bs),
+            _ => pruned.then(|| metric.char_lengths(bs)).flatten(),
+        };
+        let reporter = progress::Reporter::new(progress, Some(as_.len() * bs.len()));
+        let output = thread_pool(n_threads)?
+            .install_reporting(&reporter, |counter| {
+                as_
+                    .par_iter()
+                    .with_min_len(batch::rows_per_task(min_len, bs.len()))
+                    .map(|&a| {
+                        if counter.is_cancelled() {
+                            return None;
+                        }
+                        let len_a = lengths.as_ref().and(metric.char_length(a));
+                        let len_b = |j: usize| lengths.as_ref().map(|lengths| lengths[j]);
+                        let value = match reduction {
+                            Reduction::Count(cutoff) => (0..bs.len())
+                                .filter(|&j| metric.score_cutoff_sized(a, len_a, bs[j], len_b(j), cutoff).is_some())
+                                .count() as f64,
+                            Reduction::Mean if bs.is_empty() => f64::NAN,
+                            Reduction::Mean => bs.iter().map(|&b| (metric.score)(a, b)).sum::<f64>() / bs.len() as f64,
+                            Reduction::Min | Reduction::Max => {
+                                let better = |x: f64, y: f64| match reduction {
+                                    Reduction::Min => x < y,
+                                    _ => x > y,
+                                };
+                                let mut best: Option<f64> = None;
+                                for (j, &b) in bs.iter().enumerate() {
+                                    let score = match best {
+                                        Some(best) if pruned => metric.score_cutoff_sized(a, len_a, b, len_b(j), best),
+                                        _ => Some((metric.score)(a, b)),
+                                    };
+                                    if let Some(score) = score {
+                                        if best.is_none_or(|best| better(score, best)) {
+                                            best = Some(score);
+                                        }
+                                    }
+                                }
+                                best.unwrap_or(f64::NAN)
+                            }
+                        };
+                        counter.advance(bs.len());
+                        Some(value)
+                    })
+                    .collect::<Option<Vec<f64>>>()
+            })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Calculates one aggregate per row of the matrix of scores of every
+    /// string in `as_` against every string in `bs` under the metric named
+    /// `metric`, without holding any full row in memory.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `reduction` - `"min"`, `"max"`, `"mean"`, or `"count"` of the scores
+    ///   passing `cutoff`
+    /// * `cutoff` - Worst score counted by `"count"`, and `None` otherwise
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Aggregate of the scores between each `a` in `as_` and all
+    ///   of `bs`, as floats, or as integers for `"count"`
+    #[pyfunction(cutoff = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, reduction, /, cutoff=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_reduce(
+        py: Python<'_>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        reduction: &str,
+        cutoff: Option<f64>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let reduction = Reduction::new(reduction, cutoff)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let values = reduce_rows(metric, n_threads, chunk_size, progress, &as_.as_strs()?, &bs.as_strs()?, reduction)?;
+        Ok(match reduction {
+            Reduction::Count(_) => values.into_iter().map(|count| count as usize).collect::<Vec<_>>().into_py(py),
+            _ => values.into_py(py),
+        })
+    }
+
+    /// Parallel version of `single.simhash` for many texts.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `texts` - Texts to fingerprint
+    /// * `n` - Number of characters per n-gram
+    /// * `chunk_size` - Smallest number of texts each parallel task
+    ///   fingerprints, or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Fingerprint of each text in `texts`
+    #[pyfunction(n = "3", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(texts, /, n=3, chunk_size=None, n_threads=None)")]
+    pub fn simhash(texts: Strings, n: usize, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<u64>> {
+        single::check_ngram(n)?;
+        vectorize_with(|_, text| crate::simhash::simhash(text, n), n_threads, chunk_size, "", texts)
+    }
+
+    /// Finds the fingerprints within a Hamming distance of `fingerprint`, by
+    /// a linear scan that compares 64-bit words only.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `fingerprint` - Fingerprint to look up
+    /// * `fingerprints` - Fingerprints to search
+    /// * `max_distance` - Largest number of differing bits of a match
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of an index into `fingerprints` and its distance,
+    ///   closest first and then in the order of `fingerprints`
+    #[pyfunction(n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(fingerprint, fingerprints, max_distance, /, n_threads=None)")]
+    pub fn simhash_search(
+        fingerprint: u64,
+        fingerprints: Vec<u64>,
+        max_distance: u32,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<(usize, u32)>> {
+        let reporter = progress::Reporter::new(None, Some(fingerprints.len()));
+        let mut found = thread_pool(n_threads)?.install_sized(&reporter, fingerprints.len(), 1, |_, min_len| {
+            fingerprints
+                .par_iter()
+                .with_min_len(min_len)
+                .enumerate()
+                .map(|(i, &other)| (i, crate::simhash::hamming(fingerprint, other)))
+                .filter(|&(_, distance)| distance <= max_distance)
+                .collect::<Vec<_>>()
+        })?;
+        found.sort_unstable_by_key(|&(i, distance)| (distance, i));
+        Ok(found)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  NumPy Output
+// ------------------------------------------------------------------------
+
+#[cfg(feature = "python")]
+pub mod vectorized_np {
+    use super::*;
+    use numpy::ndarray::Array2;
+    use numpy::{IntoPyArray, PyArray1};
+    use pyo3::types::PyDict;
+
+    /// Raises `ImportError` up front when NumPy is not installed, instead of
+    /// letting the array constructors panic after the work is done.
+    pub(crate) fn require_numpy(py: Python<'_>) -> PyResult<()> {
+        py.import("numpy").map(|_| ())
+    }
+
+    /// Floating point type of the returned scores.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) enum Dtype {
+        Float32,
+        Float64,
+    }
+
+    impl Dtype {
+        /// Reads anything `numpy.dtype` accepts, such as `"float32"` or
+        /// `numpy.float32`, defaulting to `float64` for `None`.
+        pub(crate) fn from_py(py: Python<'_>, dtype: Option<&PyAny>) -> PyResult<Self> {
+            let dtype = match dtype {
+                Some(dtype) => dtype,
+                None => return Ok(Self::Float64),
+            };
+            let name: String = py.import("numpy")?.getattr("dtype")?.call1((dtype,))?.getattr("name")?.extract()?;
+            match name.as_str() {
+                "float32" => Ok(Self::Float32),
+                "float64" => Ok(Self::Float64),
+                _ => Err(InvalidParameterError::new_err(format!("dtype must be float32 or float64, got {}", name))),
+            }
+        }
+    }
+
+    /// Wraps a row-major buffer of scores as a 2D array.
+    pub(crate) fn matrix_array<T: numpy::Element>(py: Python<'_>, shape: (usize, usize), matrix: Vec<T>) -> PyObject {
+        Array2::from_shape_vec(shape, matrix)
+            .expect("matrix buffer matches its shape")
+            .into_pyarray(py)
+            .into()
+    }
+
+    /// Calculates the scores between one string and many others under the
+    /// metric named `metric`, like the functions of `vectorized`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `dtype` - NumPy dtype of the scores, `float64` (the default) or
+    ///   `float32`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Array of the scores between `a` and each `b` in `bs`
+    #[pyfunction(dtype = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, /, dtype=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn scores<'py>(
+        py: Python<'py>,
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        dtype: Option<&PyAny>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let score = metric::Metric::from_name(metric)?.score;
+        let (a, bs) = (a.preprocessed(&pre), bs.preprocessed(&pre));
+        Ok(match Dtype::from_py(py, dtype)? {
+            Dtype::Float64 => vectorized::vectorize_with(score, n_threads, chunk_size, &a, bs)?.into_pyarray(py).into(),
+            Dtype::Float32 => vectorized::vectorize_with(|a, b| score(a, b) as f32, n_threads, chunk_size, &a, bs)?.into_pyarray(py).into(),
+        })
+    }
+
+    /// Calculates the scores between the strings at the same positions of two
+    /// equal-length lists under the metric named `metric`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `dtype` - NumPy dtype of the scores, `float64` (the default) or
+    ///   `float32`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Array of the scores between each `a` in `as_` and the `b`
+    ///   at the same position in `bs`
+    #[pyfunction(dtype = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, dtype=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn pairwise<'py>(
+        py: Python<'py>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        dtype: Option<&PyAny>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let score = metric::Metric::from_name(metric)?.score;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        Ok(match Dtype::from_py(py, dtype)? {
+            Dtype::Float64 => vectorized::pairwise_with(score, n_threads, chunk_size, as_, bs)?.into_pyarray(py).into(),
+            Dtype::Float32 => vectorized::pairwise_with(|a, b| score(a, b) as f32, n_threads, chunk_size, as_, bs)?.into_pyarray(py).into(),
+        })
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric`. Rows are computed in parallel
+    /// directly into the buffer of the returned array.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `symmetric` - Whether `as_` and `bs` hold the same strings, so that
+    ///   only the upper triangle is scored and then mirrored. Raises
+    ///   `InvalidParameterError` for `jaro` and `jaro_winkler`, which score
+    ///   `(a, b)` and `(b, a)` differently
+    /// * `mask` - Boolean matrix of shape `(len(as_), len(bs))` whose unset
+    ///   cells are skipped, or `None` to score every pair
+    /// * `dtype` - NumPy dtype of the scores, `float64` (the default) or
+    ///   `float32`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Array of shape `(len(as_), len(bs))` whose row `i` holds
+    ///   the scores between `as_[i]` and each `b` in `bs`, with `nan` for
+    ///   skipped pairs
+    #[pyfunction(symmetric = "false", mask = "None", dtype = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, symmetric=False, mask=None, dtype=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist<'py>(
+        py: Python<'py>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        symmetric: bool,
+        mask: Option<input::Mask>,
+        dtype: Option<&PyAny>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        vectorized::check_symmetric(metric, symmetric)?;
+        let metric = metric::Metric::from_name(metric)?;
+        let score = metric.score;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let (as_, bs) = (as_.as_strs()?, bs.as_strs()?);
+        let shape = (as_.len(), bs.len());
+        Ok(match Dtype::from_py(py, dtype)? {
+            Dtype::Float64 => {
+                let row = batch::packed(&metric);
+                let matrix = vectorized::score_matrix(score, row, n_threads, chunk_size, progress, &as_, &bs, symmetric, mask.as_ref(), f64::NAN)?;
+                matrix_array(py, shape, matrix)
+            }
+            Dtype::Float32 => {
+                let row = batch::packed_with(metric, |score| score as f32);
+                let matrix = vectorized::score_matrix(
+                    |a, b| score(a, b) as f32,
+                    row.as_ref().map(|row| row as &batch::RowFn<f32>),
+                    n_threads,
+                    chunk_size,
+                    progress,
+                    &as_,
+                    &bs,
+                    symmetric,
+                    mask.as_ref(),
+                    f32::NAN,
+                )?;
+                matrix_array(py, shape, matrix)
+            }
+        })
+    }
+
+    /// Calculates the scores of every pair of distinct strings in `xs` under
+    /// the metric named `metric`, in the condensed order of
+    /// `scipy.spatial.distance.pdist`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `xs` - Strings to compare with each other
+    /// * `dtype` - NumPy dtype of the scores, `float64` (the default) or
+    ///   `float32`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Array of the scores of the `len(xs) * (len(xs) - 1) / 2`
+    ///   pairs
+    #[pyfunction(dtype = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, xs, /, dtype=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn pdist<'py>(
+        py: Python<'py>,
+        metric: &str,
+        xs: Strings,
+        dtype: Option<&PyAny>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let score = metric::Metric::from_name(metric)?.score;
+        let xs = xs.preprocessed(&pre);
+        let xs = xs.as_strs()?;
+        Ok(match Dtype::from_py(py, dtype)? {
+            Dtype::Float64 => vectorized::condensed(score, n_threads, chunk_size, progress, &xs)?.into_pyarray(py).into(),
+            Dtype::Float32 => vectorized::condensed(|a, b| score(a, b) as f32, n_threads, chunk_size, progress, &xs)?.into_pyarray(py).into(),
+        })
+    }
+
+    /// Converts between the condensed scores of `pdist` and the square matrix
+    /// of `cdist`, like `scipy.spatial.distance.squareform`: a 1D array
+    /// becomes a square matrix and a square matrix becomes a 1D array. The
+    /// result is `float32` for a `float32` array and `float64` otherwise.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `x` - Condensed scores, or a square matrix
+    /// * `diagonal` - Score of each string with itself, `0.0` for distances
+    ///   and `1.0` for similarities
+    /// * `checks` - Whether to check that a square matrix is symmetric and holds
+    ///   `diagonal` on its diagonal before condensing it
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Array of the square matrix of condensed scores, or of the
+    ///   condensed scores of a square matrix
+    #[pyfunction(diagonal = "0.0", checks = "true")]
+    #[pyo3(text_signature = "(x, /, diagonal=0.0, checks=True)")]
+    pub fn squareform(py: Python<'_>, x: &PyAny, diagonal: f64, checks: bool) -> PyResult<PyObject> {
+        require_numpy(py)?;
+        let dtype: &str = py.import("numpy")?.call_method1("asarray", (x,))?.getattr("dtype")?.getattr("name")?.extract()?;
+        match dtype {
+            "float32" => convert_squareform(py, x, diagonal as f32, checks),
+            _ => convert_squareform(py, x, diagonal, checks),
+        }
+    }
+
+    fn convert_squareform<T: numpy::Element + Copy + PartialEq + std::fmt::Display>(
+        py: Python<'_>,
+        x: &PyAny,
+        diagonal: T,
+        checks: bool,
+    ) -> PyResult<PyObject> {
+        let array: &numpy::PyArrayDyn<T> = py
+            .import("numpy")?
+            .call_method1("ascontiguousarray", (x, T::get_dtype(py)))?
+            .downcast()?;
+        let values = array.readonly();
+        let values = values.as_slice()?;
+        match *array.shape() {
+            [_] => {
+                let (side, matrix) = batch::square_from_condensed(values, diagonal)?;
+                Ok(matrix_array(py, (side, side), matrix))
+            }
+            [rows, columns] => Ok(batch::condensed_from_square((rows, columns), values, diagonal, checks)?
+                .into_pyarray(py)
+                .into()),
+            ref shape => Err(InvalidParameterError::new_err(format!("expected a 1D or 2D array, got {} dimensions", shape.len()))),
+        }
+    }
+
+    /// Scores `a` against the strings of `bs` under the metric named
+    /// `metric`, one chunk of `chunk_size` candidates at a time, so that only
+    /// one chunk of results is held in memory at once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`, a sequence or an iterator
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Number of candidates scored per step
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Iterator yielding an array of scores per chunk, in the
+    ///   order of `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "65536", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=65536, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn iter_scores(
+        py: Python<'_>,
+        metric: &str,
+        a: Text,
+        bs: &PyAny,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: usize,
+        n_threads: NThreads,
+    ) -> PyResult<stream::ScoreChunks> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        stream::ScoreChunks::new(metric::Metric::from_name(metric)?, &a, bs, pre, chunk_size, n_threads, true)
+    }
+
+    /// Calculates the scores between one string and many others under each of
+    /// several metrics, in one pass over `bs`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metrics` - Names of the metrics, e.g. `["levenshtein", "jaro"]`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Dictionary from each metric name to the array of scores
+    ///   between `a` and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metrics, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn multi_scores<'py>(
+        py: Python<'py>,
+        metrics: Vec<&str>,
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<&'py PyDict> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let columns = vectorized::multi_columns(&metrics, n_threads, chunk_size, &a.preprocessed(&pre), bs.preprocessed(&pre))?;
+        let output = PyDict::new(py);
+        for (metric, column) in metrics.into_iter().zip(columns) {
+            output.set_item(metric, column.into_pyarray(py))?;
+        }
+        Ok(output)
+    }
+
+    /// Calculates the score of each explicit pair of strings under the metric
+    /// named `metric`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `pairs` - Pairs of strings `(a, b)` to compare
+    /// * `dtype` - NumPy dtype of the scores, `float64` (the default) or
+    ///   `float32`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Array of the score of each pair, in the order of `pairs`
+    #[pyfunction(dtype = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, pairs, /, dtype=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn score_pairs<'py>(
+        py: Python<'py>,
+        metric: &str,
+        pairs: Vec<(&str, &str)>,
+        dtype: Option<&PyAny>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let score = metric::Metric::from_name(metric)?.score;
+        let preprocessed: Vec<_> = pairs.iter().map(|&(a, b)| (pre.apply(a), pre.apply(b))).collect();
+        let pairs: Vec<(&str, &str)> = preprocessed.iter().map(|(a, b)| (&**a, &**b)).collect();
+        Ok(match Dtype::from_py(py, dtype)? {
+            Dtype::Float64 => vectorized::score_pairs_with(score, n_threads, chunk_size, &pairs)?.into_pyarray(py).into(),
+            Dtype::Float32 => vectorized::score_pairs_with(|a, b| score(a, b) as f32, n_threads, chunk_size, &pairs)?.into_pyarray(py).into(),
+        })
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric`, keeping only the entries that
+    /// pass `cutoff` as a sparse matrix in coordinate format, e.g. for
+    /// `scipy.sparse.coo_matrix((scores, (rows, columns)))`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `cutoff` - Worst score to keep
+    /// * `mask` - Boolean matrix of shape `(len(as_), len(bs))` whose unset
+    ///   cells are skipped, or `None` to score every pair
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Arrays of the row indices into `as_`, column indices into
+    ///   `bs`, and scores of the kept entries, in row-major order
+    #[pyfunction(mask = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, cutoff, /, mask=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_sparse<'py>(
+        py: Python<'py>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        cutoff: f64,
+        mask: Option<input::Mask>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<(&'py PyArray1<usize>, &'py PyArray1<usize>, &'py PyArray1<f64>)> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let metric = metric::Metric::from_name(metric)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let (as_, bs) = (as_.as_strs()?, bs.as_strs()?);
+        let (rows, columns, scores) =
+            vectorized::sparse_matrix(metric, n_threads, chunk_size, progress, &as_, &bs, cutoff, mask.as_ref())?;
+        Ok((rows.into_pyarray(py), columns.into_pyarray(py), scores.into_pyarray(py)))
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric`, one tile at a time, so that no
+    /// more than `max_memory` bytes of scores are held at once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `max_memory` - Largest size of one tile of scores, in bytes
+    /// * `checkpoint` - Path of a file to save finished tiles to and resume
+    ///   from, or `None`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Iterator yielding, for each tile, the row into `as_` and
+    ///   column into `bs` of its first score and the tile as a 2D array
+    #[pyfunction(max_memory = "268435456", checkpoint = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, max_memory=268435456, checkpoint=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn iter_cdist(
+        py: Python<'_>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        max_memory: usize,
+        checkpoint: Option<std::path::PathBuf>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<stream::ScoreTiles> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        stream::ScoreTiles::new(metric, as_, bs, max_memory, checkpoint, chunk_size, n_threads, true)
+    }
+
+    /// Calculates the scores between `a` and each string in `bs` under the
+    /// metric named `metric` and sorts them best match first with a parallel
+    /// sort, like `vectorized.ranked`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Arrays of the indices into `bs` and their scores, best
+    ///   match first
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ranked<'py>(
+        py: Python<'py>,
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<(&'py PyArray1<usize>, &'py PyArray1<f64>)> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let output = vectorized::ranked_with(metric::Metric::from_name(metric)?, n_threads, chunk_size, &a.preprocessed(&pre), bs.preprocessed(&pre))?;
+        let (indices, scores): (Vec<usize>, Vec<f64>) = output.into_iter().unzip();
+        Ok((indices.into_pyarray(py), scores.into_pyarray(py)))
+    }
+
+    /// Calculates one aggregate per row of the matrix of scores of every
+    /// string in `as_` against every string in `bs` under the metric named
+    /// `metric`, like `vectorized.cdist_reduce`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `reduction` - `"min"`, `"max"`, `"mean"`, or `"count"` of the scores
+    ///   passing `cutoff`
+    /// * `cutoff` - Worst score counted by `"count"`, and `None` otherwise
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Array of shape `(len(as_),)` of the aggregates, of floats,
+    ///   or of integers for `"count"`
+    #[pyfunction(cutoff = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, reduction, /, cutoff=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_reduce(
+        py: Python<'_>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        reduction: &str,
+        cutoff: Option<f64>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_numpy(py)?;
+        let metric = metric::Metric::from_name(metric)?;
+        let reduction = vectorized::Reduction::new(reduction, cutoff)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let values = vectorized::reduce_rows(metric, n_threads, chunk_size, progress, &as_.as_strs()?, &bs.as_strs()?, reduction)?;
+        Ok(match reduction {
+            vectorized::Reduction::Count(_) => {
+                values.into_iter().map(|count| count as usize).collect::<Vec<_>>().into_pyarray(py).into()
+            }
+            _ => values.into_pyarray(py).into(),
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+pub mod vectorized_arrow {
+    use super::*;
+    use pyo3::types::PyBytes;
+
+    /// Raises `ImportError` up front when `pyarrow` is not installed.
+    fn require_pyarrow(py: Python<'_>) -> PyResult<()> {
+        py.import("pyarrow").map(|_| ())
+    }
+
+    /// Wraps `len` little-endian 8 byte values as a `pyarrow` array of the
+    /// type named `type_name`. The values are written once into the buffer
+    /// Arrow reads, and no Python object is made per value.
+    fn arrow_array(py: Python<'_>, type_name: &str, len: usize, values: impl Iterator<Item = [u8; 8]>) -> PyResult<PyObject> {
+        let pyarrow = py.import("pyarrow")?;
+        let data = PyBytes::new_with(py, len * 8, |buffer| {
+            for (slot, value) in buffer.chunks_exact_mut(8).zip(values) {
+                slot.copy_from_slice(&value);
+            }
+            Ok(())
+        })?;
+        let buffers = vec![py.None(), pyarrow.call_method1("py_buffer", (data,))?.into()];
+        let array = pyarrow
+            .getattr("Array")?
+            .call_method1("from_buffers", (pyarrow.call_method0(type_name)?, len, buffers))?;
+        Ok(array.into())
+    }
+
+    pub(crate) fn float64_array(py: Python<'_>, values: &[f64]) -> PyResult<PyObject> {
+        arrow_array(py, "float64", values.len(), values.iter().map(|value| value.to_le_bytes()))
+    }
+
+    pub(crate) fn uint64_array(py: Python<'_>, values: &[u64]) -> PyResult<PyObject> {
+        arrow_array(py, "uint64", values.len(), values.iter().map(|value| value.to_le_bytes()))
+    }
+
+    /// Calculates the scores between one string and many others under the
+    /// metric named `metric`, like `vectorized_np.scores`, as an Arrow array.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`, such as a `pyarrow`
+    ///   string array or chunked array
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - `pyarrow` float64 array of the scores between `a` and
+    ///   each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn scores(
+        py: Python<'_>,
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_pyarrow(py)?;
+        let score = metric::Metric::from_name(metric)?.score;
+        let (a, bs) = (a.preprocessed(&pre), bs.preprocessed(&pre));
+        float64_array(py, &vectorized::vectorize_with(score, n_threads, chunk_size, &a, bs)?)
+    }
+
+    /// Calculates the scores between the strings at the same positions of two
+    /// equal-length lists under the metric named `metric`, like
+    /// `vectorized_np.pairwise`, as an Arrow array.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare, such as a `pyarrow` string array
+    ///   or chunked array
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - `pyarrow` float64 array of the scores between each `a` in
+    ///   `as_` and the `b` at the same position in `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn pairwise(
+        py: Python<'_>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_pyarrow(py)?;
+        let score = metric::Metric::from_name(metric)?.score;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        float64_array(py, &vectorized::pairwise_with(score, n_threads, chunk_size, as_, bs)?)
+    }
+
+    /// Calculates one aggregate per row of the matrix of scores of every
+    /// string in `as_` against every string in `bs` under the metric named
+    /// `metric`, like `vectorized.cdist_reduce`, as an Arrow array.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare, such as a `pyarrow` string array
+    ///   or chunked array
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `reduction` - `"min"`, `"max"`, `"mean"`, or `"count"` of the scores
+    ///   passing `cutoff`
+    /// * `cutoff` - Worst score counted by `"count"`, and `None` otherwise
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - `pyarrow` array of the `len(as_)` aggregates, of float64,
+    ///   or of uint64 for `"count"`
+    #[pyfunction(cutoff = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, as_, bs, reduction, /, cutoff=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_reduce(
+        py: Python<'_>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        reduction: &str,
+        cutoff: Option<f64>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        require_pyarrow(py)?;
+        let metric = metric::Metric::from_name(metric)?;
+        let reduction = vectorized::Reduction::new(reduction, cutoff)?;
+        let (as_, bs) = (as_.preprocessed(&pre), bs.preprocessed(&pre));
+        let values = vectorized::reduce_rows(metric, n_threads, chunk_size, progress, &as_.as_strs()?, &bs.as_strs()?, reduction)?;
+        match reduction {
+            vectorized::Reduction::Count(_) => {
+                uint64_array(py, &values.into_iter().map(|count| count as u64).collect::<Vec<_>>())
+            }
+            _ => float64_array(py, &values),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+pub mod pandas {
+    use super::*;
+    use input::Series;
+    use numpy::IntoPyArray;
+    use pyo3::types::{PyDict, PyList};
+
+    /// Wraps `values` as a pandas Series with the index `index`.
+    fn to_series(py: Python<'_>, values: PyObject, index: &PyAny) -> PyResult<PyObject> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("index", index)?;
+        Ok(py.import("pandas")?.getattr("Series")?.call((values,), Some(kwargs))?.into())
+    }
+
+    /// `values` with a `missing` value inserted wherever `present` is unset,
+    /// `width` values per row.
+    fn scatter<T: Copy>(present: &[bool], values: Vec<T>, width: usize, missing: T) -> Vec<T> {
+        let mut values = values.into_iter();
+        let mut output = Vec::with_capacity(present.len() * width);
+        for &present in present {
+            if present {
+                output.extend(values.by_ref().take(width));
+            } else {
+                output.extend(std::iter::repeat_n(missing, width));
+            }
+        }
+        output
+    }
+
+    /// Calculates the scores between the strings at the same positions of two
+    /// pandas Series under the metric named `metric`. The Series are matched
+    /// by position, not aligned on their indexes, and a missing value on
+    /// either side scores NaN.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `s1` - First strings to compare
+    /// * `s2` - Secondary strings to compare to the string at the same
+    ///   position in `s1`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Series of the scores, with the index of `s1`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, s1, s2, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn compare_series(
+        py: Python<'_>,
+        metric: &str,
+        s1: Series,
+        s2: Series,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        vectorized_np::require_numpy(py)?;
+        let score = metric::Metric::from_name(metric)?.score;
+        let min_len = vectorized::min_len(chunk_size)?;
+        let (as_, bs) = (s1.values()?, s2.values()?);
+        let (as_, bs): (Vec<_>, Vec<_>) = (
+            as_.into_iter().map(|a| a.map(|a| pre.apply(a))).collect(),
+            bs.into_iter().map(|b| b.map(|b| pre.apply(b))).collect(),
+        );
+        if as_.len() != bs.len() {
+            return Err(LengthMismatchError::new_err(format!(
+                "s1 and s2 must have the same length, got {} and {}",
+                as_.len(),
+                bs.len()
+            )));
+        }
+        let reporter = progress::Reporter::new(None, Some(as_.len()));
+        let scores = vectorized::thread_pool(n_threads)?
+            .install_sized(&reporter, as_.len(), min_len, |counter, min_len| {
+                as_.par_iter()
+                    .zip(bs.par_iter())
+                    .with_min_len(min_len)
+                    .map(|pair| {
+                        (!counter.is_cancelled()).then(|| match pair {
+                            (Some(a), Some(b)) => score(a, b),
+                            _ => f64::NAN,
+                        })
+                    })
+                    .collect::<Option<Vec<f64>>>()
+            })?
+            .unwrap_or_default();
+        to_series(py, scores.into_pyarray(py).into(), s1.index())
+    }
+
+    /// Calculates the scores of every string of a pandas Series against every
+    /// string in `choices` under the metric named `metric`, or one aggregate
+    /// of them per string, like `vectorized.cdist_reduce`. Missing values of
+    /// the Series score NaN, and count no scores passing a cutoff.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `series` - First strings to compare
+    /// * `choices` - Secondary strings to compare to each string of `series`
+    /// * `reduction` - `"min"`, `"max"`, `"mean"`, or `"count"` of the scores
+    ///   passing `cutoff`, or `None` for all the scores
+    /// * `cutoff` - Worst score counted by `"count"`, and `None` otherwise
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// * `progress` - Callable invoked periodically with the number of completed
+    ///   comparisons and the total, or `None`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - DataFrame of the scores, with the index of `series` and
+    ///   one column per choice, or Series of the aggregates, with the index
+    ///   of `series`
+    #[pyfunction(reduction = "None", cutoff = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO", progress = "None")]
+    #[pyo3(text_signature = "(metric, series, choices, /, reduction=None, cutoff=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None, progress=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn series_vs_list(
+        py: Python<'_>,
+        metric: &str,
+        series: Series,
+        choices: Strings,
+        reduction: Option<&str>,
+        cutoff: Option<f64>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        progress: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        vectorized_np::require_numpy(py)?;
+        let metric = metric::Metric::from_name(metric)?;
+        let values = series.values()?;
+        let present: Vec<bool> = values.iter().map(Option::is_some).collect();
+        let strings: Vec<_> = values.iter().flatten().map(|s| pre.apply(s)).collect();
+        let strings: Vec<&str> = strings.iter().map(|s| &**s).collect();
+        let choices = choices.as_strs()?;
+        let preprocessed: Vec<_> = choices.iter().map(|s| pre.apply(s)).collect();
+        let preprocessed: Vec<&str> = preprocessed.iter().map(|s| &**s).collect();
+
+        let Some(reduction) = reduction else {
+            if cutoff.is_some() {
+                return Err(InvalidParameterError::new_err("cutoff only applies to reduction 'count'"));
+            }
+            let matrix =
+                vectorized::score_matrix(metric.score, batch::packed(&metric), n_threads, chunk_size, progress, &strings, &preprocessed, false, None, f64::NAN)?;
+            let matrix = scatter(&present, matrix, choices.len(), f64::NAN);
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("index", series.index())?;
+            kwargs.set_item("columns", PyList::new(py, &choices))?;
+            let matrix = vectorized_np::matrix_array(py, (values.len(), choices.len()), matrix);
+            return Ok(py.import("pandas")?.getattr("DataFrame")?.call((matrix,), Some(kwargs))?.into());
+        };
+        let reduction = vectorized::Reduction::new(reduction, cutoff)?;
+        let reduced = vectorized::reduce_rows(metric, n_threads, chunk_size, progress, &strings, &preprocessed, reduction)?;
+        let reduced = match reduction {
+            vectorized::Reduction::Count(_) => {
+                let counts = reduced.into_iter().map(|count| count as usize).collect();
+                scatter(&present, counts, 1, 0).into_pyarray(py).into()
+            }
+            _ => scatter(&present, reduced, 1, f64::NAN).into_pyarray(py).into(),
+        };
+        to_series(py, reduced, series.index())
+    }
+}
+
+#[cfg(feature = "python")]
+pub mod aio {
+    use super::*;
+    use error::ThreadPoolError;
+
+    /// Settles an asyncio future with the outcome of a background job. It is
+    /// scheduled on the event loop that owns the future, since futures may
+    /// only be settled from their loop's thread.
+    #[pyclass(module = "strsim")]
+    struct Settle {
+        future: PyObject,
+        outcome: Option<PyResult<PyObject>>,
+    }
+
+    #[pymethods]
+    impl Settle {
+        fn __call__(&mut self, py: Python<'_>) -> PyResult<()> {
+            let future = self.future.as_ref(py);
+            // A cancelled future is already done and drops the outcome.
+            if future.call_method0("done")?.is_true()? {
+                return Ok(());
+            }
+            match self.outcome.take() {
+                Some(Ok(value)) => future.call_method1("set_result", (value,))?,
+                Some(Err(err)) => future.call_method1("set_exception", (err,))?,
+                None => return Ok(()),
+            };
+            Ok(())
+        }
+    }
+
+    /// Copies `strings` out of their Python objects, so that the job holds no
+    /// reference to them.
+    fn owned(strings: Strings) -> PyResult<Vec<String>> {
+        Ok(strings.as_strs()?.into_iter().map(str::to_owned).collect())
+    }
+
+    /// Runs `job` on a thread of its own and returns a future of its result
+    /// on the running event loop. The job takes the GIL only to hand its
+    /// result over, and the thread pool releases it while scoring.
+    fn spawn<T, F>(py: Python<'_>, job: F) -> PyResult<&PyAny>
+    where
+        T: IntoPy<PyObject>,
+        F: FnOnce() -> PyResult<T> + Send + 'static,
+    {
+        let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+        let future = event_loop.call_method0("create_future")?;
+        let (event_loop_ref, future_ref): (PyObject, PyObject) = (event_loop.into(), future.into());
+        std::thread::Builder::new()
+            .name("strsim-aio".to_string())
+            .spawn(move || {
+                let outcome = job();
+                Python::with_gil(|py| {
+                    let settle = Settle { future: future_ref, outcome: Some(outcome.map(|value| value.into_py(py))) };
+                    // A loop closed while the job ran has no one left to await it.
+                    let _ = Py::new(py, settle)
+                        .and_then(|settle| event_loop_ref.call_method1(py, "call_soon_threadsafe", (settle,)));
+                })
+            })
+            .map_err(|err| ThreadPoolError::new_err(format!("cannot start a background thread: {}", err)))?;
+        Ok(future)
+    }
+
+    /// Calculates the scores between one string and many others under the
+    /// metric named `metric` on a background thread, like
+    /// `vectorized.scores`, without blocking the running event loop.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Awaitable of the scores between `a` and each `b` in `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn scores<'py>(
+        py: Python<'py>,
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<&'py PyAny> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let score = metric::Metric::from_name(metric)?.score;
+        let (a, bs) = (a.preprocessed(&pre).into_owned(), owned(bs.preprocessed(&pre))?);
+        spawn(py, move || vectorized::vectorize_with(score, n_threads, chunk_size, &a, Strings::from(bs)))
+    }
+
+    /// Calculates the scores between the strings at the same positions of two
+    /// equal-length lists under the metric named `metric` on a background
+    /// thread, like `vectorized.pairwise`, without blocking the running event
+    /// loop.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to the string at the same
+    ///   position in `as_`
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Awaitable of the scores between each `a` in `as_` and the
+    ///   `b` at the same position in `bs`
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn pairwise<'py>(
+        py: Python<'py>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<&'py PyAny> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let score = metric::Metric::from_name(metric)?.score;
+        let (as_, bs) = (owned(as_.preprocessed(&pre))?, owned(bs.preprocessed(&pre))?);
+        spawn(py, move || {
+            vectorized::pairwise_with(score, n_threads, chunk_size, Strings::from(as_), Strings::from(bs))
+        })
+    }
+
+    /// Calculates the scores of every string in `as_` against every string in
+    /// `bs` under the metric named `metric` on a background thread, like
+    /// `vectorized.cdist`, without blocking the running event loop.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `symmetric` - Whether `as_` and `bs` hold the same strings, so that
+    ///   only the upper triangle is scored and then mirrored. Raises
+    ///   `InvalidParameterError` for `jaro` and `jaro_winkler`, which score
+    ///   `(a, b)` and `(b, a)` differently
+    /// * `mask` - Boolean matrix of shape `(len(as_), len(bs))` whose unset
+    ///   cells are skipped, or `None` to score every pair
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Awaitable of the matrix whose row `i` holds the scores
+    ///   between `as_[i]` and each `b` in `bs`, with `nan` for skipped pairs
+    #[pyfunction(symmetric = "false", mask = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, as_, bs, /, symmetric=False, mask=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist<'py>(
+        py: Python<'py>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        symmetric: bool,
+        mask: Option<input::Mask>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<&'py PyAny> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        vectorized::check_symmetric(metric, symmetric)?;
+        let metric = metric::Metric::from_name(metric)?;
+        let (as_, bs) = (owned(as_.preprocessed(&pre))?, owned(bs.preprocessed(&pre))?);
+        spawn(py, move || {
+            let as_: Vec<&str> = as_.iter().map(String::as_str).collect();
+            let bs: Vec<&str> = bs.iter().map(String::as_str).collect();
+            let row = batch::packed(&metric);
+            let matrix =
+                vectorized::score_matrix(metric.score, row, n_threads, chunk_size, None, &as_, &bs, symmetric, mask.as_ref(), f64::NAN)?;
+            if bs.is_empty() {
+                return Ok(vec![Vec::new(); as_.len()]);
+            }
+            Ok(matrix.chunks(bs.len()).map(<[f64]>::to_vec).collect::<Vec<_>>())
+        })
+    }
+
+    /// Finds the `k` best matches for `a` among `bs` under the metric named
+    /// `metric` on a background thread, like `vectorized.top_k`, without
+    /// blocking the running event loop.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `k` - Number of matches to return
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Awaitable of up to `k` pairs of an index into `bs` and its
+    ///   score, best match first
+    #[pyfunction(case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, a, bs, k, /, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn top_k<'py>(
+        py: Python<'py>,
+        metric: &str,
+        a: Text,
+        bs: Strings,
+        k: usize,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<&'py PyAny> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        metric::Metric::from_name(metric)?;
+        let (metric, a, bs) = (metric.to_owned(), a.preprocessed(&pre).into_owned(), owned(bs.preprocessed(&pre))?);
+        spawn(py, move || vectorized::top_k(&metric, Text::from(a), Strings::from(bs), k, false, None, chunk_size, n_threads, None))
+    }
+
+    /// Calculates one aggregate per row of the matrix of scores of every
+    /// string in `as_` against every string in `bs` under the metric named
+    /// `metric` on a background thread, like `vectorized.cdist_reduce`,
+    /// without blocking the running event loop.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `metric` - Name of the metric, e.g. `"levenshtein"`
+    /// * `as_` - First strings to compare
+    /// * `bs` - Secondary strings to compare to each `a` in `as_`
+    /// * `reduction` - `"min"`, `"max"`, `"mean"`, or `"count"` of the scores
+    ///   passing `cutoff`
+    /// * `cutoff` - Worst score counted by `"count"`, and `None` otherwise
+    /// * `case_insensitive` - Whether to lowercase both strings before comparing
+    ///   them
+    /// * `preprocessor` - `Preprocessor` whose steps run before lowercasing,
+    ///   or `None`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Awaitable of the aggregate of the scores between each `a`
+    ///   in `as_` and all of `bs`, as floats, or as integers for `"count"`
+    #[pyfunction(cutoff = "None", case_insensitive = "false", preprocessor = "None", chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "(metric, as_, bs, reduction, /, cutoff=None, case_insensitive=False, preprocessor=None, chunk_size=None, n_threads=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cdist_reduce<'py>(
+        py: Python<'py>,
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        reduction: &str,
+        cutoff: Option<f64>,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+    ) -> PyResult<&'py PyAny> {
+        let pre = Preprocess::lowercasing(case_insensitive).after(preprocessor);
+        let metric = metric::Metric::from_name(metric)?;
+        let reduction = vectorized::Reduction::new(reduction, cutoff)?;
+        let (as_, bs) = (owned(as_.preprocessed(&pre))?, owned(bs.preprocessed(&pre))?);
+        spawn(py, move || {
+            let as_: Vec<&str> = as_.iter().map(String::as_str).collect();
+            let bs: Vec<&str> = bs.iter().map(String::as_str).collect();
+            let values = vectorized::reduce_rows(metric, n_threads, chunk_size, None, &as_, &bs, reduction)?;
+            Ok(Python::with_gil(|py| match reduction {
+                vectorized::Reduction::Count(_) => {
+                    values.into_iter().map(|count| count as usize).collect::<Vec<_>>().into_py(py)
+                }
+                _ => values.into_py(py),
+            }))
+        })
+    }
+}
+
+// ------------------------------------------------------------------------
+//  Module Declarations
+// ------------------------------------------------------------------------
+
+#[cfg(feature = "python")]
+#[pymodule]
+#[pyo3(name = "_py_strsim")]
+fn py_strsim(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    register_child_modules(py, m)?;
+    register_classes(m)?;
+    error::register_exceptions(py, m)?;
+    m.add_function(wrap_pyfunction!(sklearn::sklearn_metric, m)?)?;
+    Ok(())
+}
+
+#[cfg(feature = "python")]
+fn register_child_modules(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let single_module = PyModule::new(py, "single")?;
+    single_module.add_function(wrap_pyfunction!(single::damerau_levenshtein, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::jaro, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::jaro_winkler, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::levenshtein, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::normalized_levenshtein, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::normalized_damerau_levenshtein, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::osa_distance, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::sorensen_dice, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::token_set_ratio, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::partial_ratio, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::ratio, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::token_sort_ratio, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::wratio, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::jaro_details, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::levenshtein_editops, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::levenshtein_opcodes, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::alignment, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::strcmp95, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::ocr_levenshtein, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::metaphone, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::phonetic_hybrid, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::position_weighted_levenshtein, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::jensen_shannon, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::lig3, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::levenshtein_alignment_long, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::levenshtein_long, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::normalized_levenshtein_long, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::needleman_wunsch, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::smith_waterman, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::percent_identity, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::ensemble, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::hamming_bytes, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::levenshtein_bytes, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::normalized_levenshtein_bytes, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::damerau_levenshtein_bytes, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::jaro_bytes, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::jaro_winkler_bytes, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::simhash, single_module)?)?;
+    single_module.add_function(wrap_pyfunction!(single::simhash_distance, single_module)?)?;
+
+    let vectorized_module = PyModule::new(py, "vectorized")?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::damerau_levenshtein, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro_winkler, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::levenshtein, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::normalized_levenshtein, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::normalized_damerau_levenshtein, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::osa_distance, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::sorensen_dice, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::token_set_ratio, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::partial_ratio, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::ratio, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::token_sort_ratio, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::wratio, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::strcmp95, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::ocr_levenshtein, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::phonetic_hybrid, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::position_weighted_levenshtein, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jensen_shannon, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::lig3, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::needleman_wunsch, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::smith_waterman, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::percent_identity, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::damerau_levenshtein_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro_winkler_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::levenshtein_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::normalized_damerau_levenshtein_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::normalized_levenshtein_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::osa_distance_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::sorensen_dice_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::token_set_ratio_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::partial_ratio_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::ratio_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::token_sort_ratio_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::wratio_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::strcmp95_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::ocr_levenshtein_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::phonetic_hybrid_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::position_weighted_levenshtein_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jensen_shannon_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::lig3_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::needleman_wunsch_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::smith_waterman_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::percent_identity_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::cdist, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::pdist, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::squareform, vectorized_module)?)?;
+
+    let vectorized_np_module = PyModule::new(py, "vectorized_np")?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::scores, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::pairwise, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::cdist, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::pdist, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::squareform, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::iter_scores, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::multi_scores, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::score_pairs, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::cdist_sparse, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::iter_cdist, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::ranked, vectorized_np_module)?)?;
+    vectorized_np_module.add_function(wrap_pyfunction!(vectorized_np::cdist_reduce, vectorized_np_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::top_k, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::best_match, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::threshold, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::ranked, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::iter_scores, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::match_lists, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::fuzzy_join, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::dedupe, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::multi_scores, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::ensemble, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::ensemble_pairwise, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::score_pairs, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::hamming_bytes, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::levenshtein_bytes, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::normalized_levenshtein_bytes, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::damerau_levenshtein_bytes, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro_bytes, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::jaro_winkler_bytes, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::cdist_sparse, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::iter_cdist, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::cdist_to_file, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::cdist_sparse_to_file, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::prepare, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::cdist_reduce, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::simhash, vectorized_module)?)?;
+    vectorized_module.add_function(wrap_pyfunction!(vectorized::simhash_search, vectorized_module)?)?;
+
+    let vectorized_arrow_module = PyModule::new(py, "vectorized_arrow")?;
+    vectorized_arrow_module.add_function(wrap_pyfunction!(vectorized_arrow::scores, vectorized_arrow_module)?)?;
+    vectorized_arrow_module.add_function(wrap_pyfunction!(vectorized_arrow::pairwise, vectorized_arrow_module)?)?;
+    vectorized_arrow_module.add_function(wrap_pyfunction!(vectorized_arrow::cdist_reduce, vectorized_arrow_module)?)?;
+
+    let pandas_module = PyModule::new(py, "pandas")?;
+    pandas_module.add_function(wrap_pyfunction!(pandas::compare_series, pandas_module)?)?;
+    pandas_module.add_function(wrap_pyfunction!(pandas::series_vs_list, pandas_module)?)?;
+
+    let aio_module = PyModule::new(py, "aio")?;
+    aio_module.add_function(wrap_pyfunction!(aio::scores, aio_module)?)?;
+    aio_module.add_function(wrap_pyfunction!(aio::pairwise, aio_module)?)?;
+    aio_module.add_function(wrap_pyfunction!(aio::cdist, aio_module)?)?;
+    aio_module.add_function(wrap_pyfunction!(aio::top_k, aio_module)?)?;
+    aio_module.add_function(wrap_pyfunction!(aio::cdist_reduce, aio_module)?)?;
+
+    let benchmark_module = PyModule::new(py, "benchmark")?;
+    benchmark_module.add_function(wrap_pyfunction!(benchmark::py_run, benchmark_module)?)?;
+    benchmark_module.add_function(wrap_pyfunction!(benchmark::py_workload, benchmark_module)?)?;
+
+    parent.add_submodule(single_module)?;
+    parent.add_submodule(vectorized_module)?;
+    parent.add_submodule(vectorized_np_module)?;
+    parent.add_submodule(vectorized_arrow_module)?;
+    parent.add_submodule(pandas_module)?;
+    parent.add_submodule(aio_module)?;
+    parent.add_submodule(benchmark_module)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "python")]
+fn register_classes(parent: &PyModule) -> PyResult<()> {
+    parent.add_class::<jaro::JaroDetails>()?;
+    parent.add_class::<tfidf::SoftTfidf>()?;
+    parent.add_class::<tfidf::TfidfMatcher>()?;
+    parent.add_class::<stream::ScoreChunks>()?;
+    parent.add_class::<stream::ScoreTiles>()?;
+    parent.add_class::<prepared::PreparedChoices>()?;
+    parent.add_class::<bktree::BKTree>()?;
+    parent.add_class::<cached::CachedLevenshtein>()?;
+    parent.add_class::<cached::CachedJaroWinkler>()?;
+    parent.add_class::<cached::CachedDice>()?;
+    parent.add_class::<memo::ScoreCache>()?;
+    parent.add_class::<frequency::FrequencyDictionary>()?;
+    parent.add_class::<symspell::SymSpell>()?;
+    parent.add_class::<symspell::SpellCorrector>()?;
+    parent.add_class::<automaton::LevenshteinAutomaton>()?;
+    parent.add_class::<vptree::VPTree>()?;
+    parent.add_class::<ngram::NGramIndex>()?;
+    parent.add_class::<diceindex::DiceIndex>()?;
+    parent.add_class::<minhash::MinHashLSH>()?;
+    parent.add_class::<hnsw::HNSWIndex>()?;
+    parent.add_class::<fuzzy::FuzzySet>()?;
+    parent.add_class::<fuzzy::FuzzyDict>()?;
+    parent.add_class::<preprocess::Preprocessor>()?;
+    parent.add_class::<mapped::CandidateFile>()?;
+    parent.add_class::<sklearn::SklearnMetric>()?;
+
+    Ok(())
+}
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_functions_preprocess_every_string() {
+        let clean = testing::preprocessor(&["casefold", "strip_accents", "collapse_whitespace"]);
+        let raw: Vec<String> = ["Café  Noir", "CAFE NOIR", "tea", "Thé", "ÉCLAIR", "eclairs", ""]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let cleaned: Vec<String> = raw.iter().map(|s| clean.apply(s).into_owned()).collect();
+        let (a, cleaned_a) = ("CAFÉ noir".to_string(), "cafe noir".to_string());
+        let strings = |xs: &Vec<String>| Strings::from(xs.clone());
+        let pre = || Some(clean.clone());
+        let auto = NThreads::AUTO;
+
+        for metric in ["levenshtein", "jaro_winkler"] {
+            assert_eq!(
+                vectorized::cdist(metric, strings(&raw), strings(&raw), false, None, true, pre(), None, auto, None).unwrap(),
+                vectorized::cdist(metric, strings(&cleaned), strings(&cleaned), false, None, false, None, None, auto, None).unwrap()
+            );
+            assert_eq!(
+                vectorized::pdist(metric, strings(&raw), false, pre(), None, auto, None).unwrap(),
+                vectorized::pdist(metric, strings(&cleaned), false, None, None, auto, None).unwrap()
+            );
+            assert_eq!(
+                vectorized::top_k(metric, a.clone().into(), strings(&raw), 3, false, pre(), None, auto, None).unwrap(),
+                vectorized::top_k(metric, cleaned_a.clone().into(), strings(&cleaned), 3, false, None, None, auto, None).unwrap()
+            );
+            assert_eq!(
+                vectorized::ranked(metric, a.clone().into(), strings(&raw), false, pre(), None, auto).unwrap(),
+                vectorized::ranked(metric, cleaned_a.clone().into(), strings(&cleaned), false, None, None, auto).unwrap()
+            );
+            assert_eq!(
+                vectorized::match_lists(metric, strings(&raw), strings(&raw[2..].to_vec()), None, false, pre(), None, auto, None).unwrap(),
+                vectorized::match_lists(metric, strings(&cleaned), strings(&cleaned[2..].to_vec()), None, false, None, None, auto, None).unwrap()
+            );
+            let pairs: Vec<(&str, &str)> = raw.iter().zip(raw.iter().rev()).map(|(a, b)| (&**a, &**b)).collect();
+            let cleaned_pairs: Vec<(&str, &str)> = cleaned.iter().zip(cleaned.iter().rev()).map(|(a, b)| (&**a, &**b)).collect();
+            assert_eq!(
+                vectorized::score_pairs(metric, pairs, false, pre(), None, auto).unwrap(),
+                vectorized::score_pairs(metric, cleaned_pairs, false, None, None, auto).unwrap()
+            );
+        }
+        assert_eq!(vectorized::dedupe(strings(&raw), "levenshtein", 1.0, false, pre(), None, auto, None).unwrap(), [0, 0, 2, 3, 4, 4, 6]);
+        assert_eq!(
+            vectorized::fuzzy_join(strings(&raw), strings(&cleaned), "levenshtein", 0.0, "inner", None, false, pre(), None, auto, None).unwrap(),
+            vectorized::fuzzy_join(strings(&cleaned), strings(&cleaned), "levenshtein", 0.0, "inner", None, false, None, None, auto, None).unwrap()
+        );
+        assert_eq!(
+            vectorized::cdist_sparse("levenshtein", strings(&raw), strings(&raw), 1.0, None, false, pre(), None, auto, None).unwrap(),
+            vectorized::cdist_sparse("levenshtein", strings(&cleaned), strings(&cleaned), 1.0, None, false, None, None, auto, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn matrix_functions_normalize_through_the_preprocessor() {
+        let steps = ["nfc", "transliterate", "casefold", "strip_accents"];
+        let preprocessor = testing::preprocessor(&steps);
+        let xs: Vec<String> = ["Cafe\u{301}", "Café", "Москва", "Moskva", "JOSÉ", "Jose"].into_iter().map(String::from).collect();
+        let matrix = vectorized::cdist("levenshtein", xs.clone().into(), xs.clone().into(), false, None, false, Some(preprocessor.clone()), None, NThreads::AUTO, None).unwrap();
+        // The same steps as keyword options of the metric function.
+        let (nfc, full, auto) = (Some(Normalization::Nfc), Some(CaseFold::Full), NThreads::AUTO);
+        for (x, row) in xs.iter().zip(&matrix) {
+            let (x, bs) = (x.clone().into(), xs.clone().into());
+            let distances = vectorized::levenshtein(x, bs, Unit::Char, false, full, None, nfc, false, true, true, false, false, false, None, None, auto);
+            assert_eq!(row, &distances.unwrap().into_iter().map(|d| d as f64).collect::<Vec<_>>());
+        }
+        assert_eq!((matrix[0][1], matrix[2][3], matrix[4][5]), (0.0, 0.0, 0.0));
+        assert_eq!(
+            vectorized::top_k("levenshtein", "MOSKVA".to_string().into(), xs.clone().into(), 2, false, Some(preprocessor), None, NThreads::AUTO, None).unwrap(),
+            [(2, 0.0), (3, 0.0)]
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use pyo3::prelude::*;
+
+use crate::error::InvalidParameterError;
+use crate::input::BATCH_SIZE;
+
+// ------------------------------------------------------------------------
+//  Memory Maps
+// ------------------------------------------------------------------------
+
+/// A read-only view of a whole file. On Unix the file is mapped into memory,
+/// so that the operating system pages it in as it is read and drops the
+/// pages again under memory pressure; elsewhere it is read into memory.
+#[cfg(unix)]
+struct Map {
+    ptr: *const u8,
+    len: usize,
+}
+
+// SAFETY: the mapping is read-only and lives until `drop`, so it can be read
+// from any thread.
+#[cfg(unix)]
+unsafe impl Send for Map {}
+#[cfg(unix)]
+unsafe impl Sync for Map {}
+
+#[cfg(unix)]
+impl Map {
+    fn new(file: &File) -> std::io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "file too large to map"))?;
+        if len == 0 {
+            return Ok(Self { ptr: std::ptr::NonNull::dangling().as_ptr(), len });
+        }
+        // SAFETY: a fresh private read-only mapping of `len` bytes of an open
+        // file, checked for failure before use.
+        unsafe {
+            let ptr = libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0);
+            if ptr == libc::MAP_FAILED {
+                return Err(std::io::Error::last_os_error());
+            }
+            // Candidates are read from start to end, so read-ahead pays off.
+            libc::madvise(ptr, len, libc::MADV_SEQUENTIAL);
+            Ok(Self { ptr: ptr as *const u8, len })
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: `ptr` is valid for `len` bytes until `drop`, or dangling
+        // and never read when `len` is zero.
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Map {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: the mapping was made in `new` with this length.
+            unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Map(Vec<u8>);
+
+#[cfg(not(unix))]
+impl Map {
+    fn new(mut file: &File) -> std::io::Result<Self> {
+        use std::io::Read;
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Candidate Files
+// ------------------------------------------------------------------------
+
+/// How the strings of a candidate file are delimited, passed from Python by
+/// its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One string per line, ended by `\n` or `\r\n`.
+    Lines,
+    /// Each string preceded by its length in bytes, as a 32-bit
+    /// little-endian integer.
+    LengthPrefixed,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "lines" => Ok(Self::Lines),
+            "length_prefixed" => Ok(Self::LengthPrefixed),
+            _ => Err(InvalidParameterError::new_err(format!(
+                "unknown format '{}', expected 'lines' or 'length_prefixed'",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lines => "lines",
+            Self::LengthPrefixed => "length_prefixed",
+        }
+    }
+}
+
+/// The mapped file behind a `CandidateFile`, shared with the inputs read
+/// from it.
+pub struct Mapped {
+    path: PathBuf,
+    format: Format,
+    map: Map,
+    len: OnceLock<usize>,
+}
+
+impl Mapped {
+    /// Number of strings, once a full pass over the file has counted them.
+    pub fn len_hint(&self) -> Option<usize> {
+        self.len.get().copied()
+    }
+
+    /// Number of strings, counting them on the first call.
+    pub fn count(&self) -> PyResult<usize> {
+        if let Some(&len) = self.len.get() {
+            return Ok(len);
+        }
+        let mut len = 0;
+        let mut records = self.records();
+        while records.next().transpose()?.is_some() {
+            len += 1;
+        }
+        Ok(*self.len.get_or_init(|| len))
+    }
+
+    fn records(&self) -> Records<'_> {
+        Records { mapped: self, bytes: self.map.bytes(), position: 0, index: 0 }
+    }
+
+    /// Slices of all the strings in the file.
+    pub fn strs(&self) -> PyResult<Vec<&str>> {
+        let strs = self.records().collect::<PyResult<Vec<&str>>>()?;
+        let _ = self.len.set(strs.len());
+        Ok(strs)
+    }
+
+    /// Calls `f` with the index of the first string of each batch of up to
+    /// `BATCH_SIZE` strings and the batch itself, so that only the slices of
+    /// one batch are held at once.
+    pub fn for_each_batch(&self, mut f: impl FnMut(usize, &[&str]) -> PyResult<()>) -> PyResult<()> {
+        let mut records = self.records();
+        let mut start = 0;
+        loop {
+            let batch = records.by_ref().take(BATCH_SIZE).collect::<PyResult<Vec<&str>>>()?;
+            if batch.is_empty() {
+                let _ = self.len.set(start);
+                return Ok(());
+            }
+            f(start, &batch)?;
+            start += batch.len();
+        }
+    }
+}
+
+/// Iterator over the strings of a mapped file, in order.
+struct Records<'a> {
+    mapped: &'a Mapped,
+    bytes: &'a [u8],
+    position: usize,
+    index: usize,
+}
+
+impl<'a> Records<'a> {
+    fn error(&self, reason: &str) -> PyErr {
+        InvalidParameterError::new_err(format!(
+            "string {} of '{}' {}",
+            self.index,
+            self.mapped.path.display(),
+            reason
+        ))
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = PyResult<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.bytes[self.position..];
+        if rest.is_empty() {
+            return None;
+        }
+        let record = match self.mapped.format {
+            Format::Lines => {
+                let end = rest.iter().position(|&byte| byte == b'\n');
+                self.position += end.map_or(rest.len(), |end| end + 1);
+                let line = &rest[..end.unwrap_or(rest.len())];
+                line.strip_suffix(b"\r").unwrap_or(line)
+            }
+            Format::LengthPrefixed => {
+                let Some(prefix) = rest.get(..4) else {
+                    return Some(Err(self.error("is cut off in its length")));
+                };
+                let len = u32::from_le_bytes(prefix.try_into().expect("4 byte length")) as usize;
+                let Some(record) = rest.get(4..4 + len) else {
+                    return Some(Err(self.error("is cut off by the end of the file")));
+                };
+                self.position += 4 + len;
+                record
+            }
+        };
+        let record = std::str::from_utf8(record).map_err(|_| self.error("is not valid UTF-8"));
+        self.index += 1;
+        Some(record)
+    }
+}
+
+/// Strings stored in a file, one per line or each after its length, that
+/// any batch function takes in place of a list. The file is memory-mapped
+/// and its strings are read from the mapping as they are compared, so that
+/// no Python string is made for them. Functions that go through their
+/// candidates once, such as `top_k`, `best_match` and `threshold`, and the
+/// index constructors, read them in batches and hold only one batch of
+/// slices at a time. The file must not change while it is open.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of the file
+/// * `format` - `"lines"` for one UTF-8 string per line, or
+///   `"length_prefixed"` for each string preceded by its length in bytes as
+///   a 32-bit little-endian integer
+#[pyclass(module = "strsim", text_signature = "(path, /, format='lines')")]
+#[derive(Clone)]
+pub struct CandidateFile {
+    mapped: Arc<Mapped>,
+}
+
+impl CandidateFile {
+    pub fn mapped(&self) -> Arc<Mapped> {
+        Arc::clone(&self.mapped)
+    }
+}
+
+#[pymethods]
+impl CandidateFile {
+    #[new]
+    #[args(format = "\"lines\"")]
+    fn new(path: PathBuf, format: &str) -> PyResult<Self> {
+        let format = Format::from_name(format)?;
+        let map = Map::new(&File::open(&path)?)?;
+        Ok(Self { mapped: Arc::new(Mapped { path, format, map, len: OnceLock::new() }) })
+    }
+
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.mapped.path.clone()
+    }
+
+    #[getter]
+    fn format(&self) -> &'static str {
+        self.mapped.format.name()
+    }
+
+    fn __len__(&self, py: Python<'_>) -> PyResult<usize> {
+        py.allow_threads(|| self.mapped.count())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CandidateFile('{}', format='{}')", self.mapped.path.display(), self.mapped.format.name())
+    }
+}
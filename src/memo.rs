@@ -0,0 +1,466 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::metric::Metric;
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::input::{Strings, Text};
+
+// ------------------------------------------------------------------------
+//  Least Recently Used Pairs
+// ------------------------------------------------------------------------
+
+const NONE: usize = usize::MAX;
+
+/// A pair of strings and its score, linked into the recency list.
+struct Entry {
+    hash: u64,
+    a: String,
+    b: String,
+    score: f64,
+    newer: usize,
+    older: usize,
+}
+
+/// Scores of up to `capacity` pairs, dropping the least recently used pair
+/// to make room for a new one. Entries live in a slab, linked from the most
+/// to the least recently used, and are found by the hash of their pair; the
+/// strings themselves are only compared to rule out a collision.
+struct Lru {
+    capacity: usize,
+    by_hash: HashMap<u64, usize>,
+    entries: Vec<Entry>,
+    newest: usize,
+    oldest: usize,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, by_hash: HashMap::new(), entries: Vec::new(), newest: NONE, oldest: NONE }
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let (newer, older) = (self.entries[i].newer, self.entries[i].older);
+        match newer {
+            NONE => self.newest = older,
+            newer => self.entries[newer].older = older,
+        }
+        match older {
+            NONE => self.oldest = newer,
+            older => self.entries[older].newer = newer,
+        }
+    }
+
+    fn push_newest(&mut self, i: usize) {
+        self.entries[i].newer = NONE;
+        self.entries[i].older = self.newest;
+        match self.newest {
+            NONE => self.oldest = i,
+            newest => self.entries[newest].newer = i,
+        }
+        self.newest = i;
+    }
+
+    /// Score of a pair, which becomes the most recently used.
+    fn get(&mut self, hash: u64, a: &str, b: &str) -> Option<f64> {
+        let i = *self.by_hash.get(&hash)?;
+        if self.entries[i].a != a || self.entries[i].b != b {
+            return None;
+        }
+        self.unlink(i);
+        self.push_newest(i);
+        Some(self.entries[i].score)
+    }
+
+    /// Stores the score of a pair as the most recently used, in place of
+    /// the least recently used pair if full, or of a pair of the same hash.
+    fn insert(&mut self, hash: u64, a: &str, b: &str, score: f64) {
+        let slot = match self.by_hash.get(&hash) {
+            Some(&i) => Some(i),
+            None if self.entries.len() < self.capacity => None,
+            None => Some(self.oldest),
+        };
+        let entry = Entry { hash, a: a.to_owned(), b: b.to_owned(), score, newer: NONE, older: NONE };
+        let i = match slot {
+            Some(i) => {
+                self.unlink(i);
+                self.by_hash.remove(&self.entries[i].hash);
+                self.entries[i] = entry;
+                i
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        self.by_hash.insert(hash, i);
+        self.push_newest(i);
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new(self.capacity);
+    }
+}
+
+// ------------------------------------------------------------------------
+//  Score Caches
+// ------------------------------------------------------------------------
+
+/// Hit and miss counts of a score cache and its size, as
+/// `functools.lru_cache` reports them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheInfo {
+    pub hits: u64,
+    pub misses: u64,
+    pub maxsize: usize,
+    pub currsize: usize,
+}
+
+struct State {
+    lru: Lru,
+    hits: u64,
+    misses: u64,
+}
+
+/// A metric that remembers the scores of the last `maxsize` pairs it
+/// scored, so that pairs repeated across calls are scored once. Pairs are
+/// ordered: `(a, b)` and `(b, a)` are cached apart, since not every metric
+/// is symmetric. A batch looks all its pairs up at once, scores the pairs
+/// it misses in parallel, each distinct pair once, and then stores them.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Name of the metric, e.g. `"levenshtein"`
+/// * `maxsize` - Largest number of pairs kept, at least 1
+#[cfg_attr(feature = "python", pyclass(module = "strsim", text_signature = "(metric, /, maxsize=65536)"))]
+pub struct ScoreCache {
+    name: String,
+    metric: Metric,
+    hasher: RandomState,
+    state: Mutex<State>,
+}
+
+impl ScoreCache {
+    /// Makes an empty cache of the metric named `metric`, keeping up to
+    /// `maxsize` pairs.
+    pub fn new(metric: &str, maxsize: usize) -> Result<Self> {
+        if maxsize == 0 {
+            return Err(Error::value("maxsize must be at least 1"));
+        }
+        let state = State { lru: Lru::new(maxsize), hits: 0, misses: 0 };
+        Ok(Self {
+            name: metric.to_owned(),
+            metric: Metric::from_name(metric)?,
+            hasher: RandomState::new(),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Score of two strings, from the cache if the pair is in it.
+    pub fn score(&self, a: &str, b: &str) -> f64 {
+        let hash = self.hasher.hash_one((a, b));
+        let cached = {
+            let mut state = self.lock();
+            let cached = state.lru.get(hash, a, b);
+            match cached {
+                Some(_) => state.hits += 1,
+                None => state.misses += 1,
+            }
+            cached
+        };
+        cached.unwrap_or_else(|| {
+            let score = (self.metric.score)(a, b);
+            self.lock().lru.insert(hash, a, b, score);
+            score
+        })
+    }
+
+    /// Scores between `a` and each string in `bs`, on `n_threads` threads,
+    /// or on one per available core when `None`.
+    pub fn scores<S: AsRef<str> + Sync>(&self, a: &str, bs: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+        let pairs: Vec<(&str, &str)> = bs.iter().map(|b| (a, b.as_ref())).collect();
+        self.score_pairs(&pairs, n_threads)
+    }
+
+    /// Scores of each pair of strings, on `n_threads` threads, or on one per
+    /// available core when `None`.
+    pub fn score_pairs(&self, pairs: &[(&str, &str)], n_threads: Option<usize>) -> Result<Vec<f64>> {
+        let pool = thread_pool(n_threads)?;
+        let output = self.batch(pairs, |missed| {
+            Ok::<_, Error>(pool.run_sized(missed.len(), 1, |counter, min_len| self.score_each(counter, min_len, missed)))
+        })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Hit and miss counts since the cache was made or cleared.
+    pub fn info(&self) -> CacheInfo {
+        let state = self.lock();
+        CacheInfo {
+            hits: state.hits,
+            misses: state.misses,
+            maxsize: state.lru.capacity,
+            currsize: state.lru.entries.len(),
+        }
+    }
+
+    /// Drops every cached pair and resets the counts.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.lru.clear();
+        (state.hits, state.misses) = (0, 0);
+    }
+
+    pub fn metric(&self) -> &str {
+        &self.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().lru.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn score_each(&self, counter: &Counter, min_len: usize, pairs: &[(&str, &str)]) -> Option<Vec<f64>> {
+        pairs
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|&(a, b)| (!counter.is_cancelled()).then(|| (self.metric.score)(a, b)))
+            .collect()
+    }
+
+    /// Scores `pairs`, looking each one up and passing the distinct pairs
+    /// missed to `score_missed`, whose scores are then cached. Returns
+    /// `None` if `score_missed` does, for a cancelled job.
+    fn batch<E>(
+        &self,
+        pairs: &[(&str, &str)],
+        score_missed: impl FnOnce(&[(&str, &str)]) -> Result<Option<Vec<f64>>, E>,
+    ) -> Result<Option<Vec<f64>>, E> {
+        let hashes: Vec<u64> = pairs.iter().map(|pair| self.hasher.hash_one(pair)).collect();
+        let mut output: Vec<f64> = vec![0.0; pairs.len()];
+        let mut missed: Vec<(&str, &str)> = Vec::new();
+        let mut missed_at: Vec<(usize, usize)> = Vec::new();
+        {
+            let mut state = self.lock();
+            let mut distinct: HashMap<(&str, &str), usize> = HashMap::new();
+            for (i, (&(a, b), &hash)) in pairs.iter().zip(&hashes).enumerate() {
+                if let Some(score) = state.lru.get(hash, a, b) {
+                    output[i] = score;
+                    continue;
+                }
+                let k = *distinct.entry((a, b)).or_insert_with(|| {
+                    missed.push((a, b));
+                    missed.len() - 1
+                });
+                missed_at.push((i, k));
+            }
+            state.misses += missed.len() as u64;
+            state.hits += (pairs.len() - missed.len()) as u64;
+        }
+        if missed.is_empty() {
+            return Ok(Some(output));
+        }
+
+        let Some(scores) = score_missed(&missed)? else {
+            return Ok(None);
+        };
+        let mut state = self.lock();
+        for &(i, k) in &missed_at {
+            output[i] = scores[k];
+        }
+        for (&(a, b), &score) in missed.iter().zip(&scores) {
+            state.lru.insert(self.hasher.hash_one((a, b)), a, b, score);
+        }
+        Ok(Some(output))
+    }
+}
+
+#[cfg(feature = "python")]
+impl ScoreCache {
+    /// Like `score_pairs`, with progress reporting and interrupts as in the
+    /// `vectorized` functions.
+    fn py_batch(&self, pairs: &[(&str, &str)], chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        let min_len = crate::vectorized::min_len(chunk_size)?;
+        let pool = thread_pool(n_threads)?;
+        let output = self.batch(pairs, |missed| {
+            let reporter = crate::progress::Reporter::new(None, Some(missed.len()));
+            pool.install_sized(&reporter, missed.len(), min_len, |counter, min_len| {
+                self.score_each(counter, min_len, missed)
+            })
+        })?;
+        Ok(output.unwrap_or_default())
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ScoreCache {
+    #[new]
+    #[args(maxsize = "65536")]
+    fn py_new(metric: &str, maxsize: usize) -> PyResult<Self> {
+        Ok(Self::new(metric, maxsize)?)
+    }
+
+    /// Calculates the score of two strings, or returns it from the cache.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Score between `a` and `b`
+    #[pyo3(name = "score", text_signature = "($self, a, b, /)")]
+    fn py_score(&self, a: Text, b: Text) -> f64 {
+        self.score(&a, &b)
+    }
+
+    /// Calculates the scores between `a` and each string in `bs`, scoring
+    /// only the pairs missing from the cache.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `chunk_size` - Smallest number of strings each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Score between `a` and each `b` in `bs`
+    #[args(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "scores", text_signature = "($self, a, bs, /, chunk_size=None, n_threads=None)")]
+    fn py_scores(&self, a: Text, bs: Strings, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        let bs = bs.as_strs()?;
+        let pairs: Vec<(&str, &str)> = bs.iter().map(|&b| (&*a, b)).collect();
+        self.py_batch(&pairs, chunk_size, n_threads)
+    }
+
+    /// Calculates the score of each pair of strings, scoring only the pairs
+    /// missing from the cache.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `pairs` - Pairs of strings `(a, b)` to compare
+    /// * `chunk_size` - Smallest number of pairs each parallel task scores,
+    ///   or `None` to let the scheduler split the work
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Score of each pair, in the order of `pairs`
+    #[args(chunk_size = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "score_pairs", text_signature = "($self, pairs, /, chunk_size=None, n_threads=None)")]
+    fn py_score_pairs(&self, pairs: Vec<(&str, &str)>, chunk_size: Option<usize>, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        self.py_batch(&pairs, chunk_size, n_threads)
+    }
+
+    /// Reports the hits and misses of the cache since it was made or
+    /// cleared, as `functools.lru_cache` does.
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Tuple of the hits, the misses, the largest number of
+    ///   pairs kept and the number kept
+    #[pyo3(text_signature = "($self, /)")]
+    fn cache_info(slf: PyRef<'_, Self>) -> (u64, u64, usize, usize) {
+        let info = slf.info();
+        (info.hits, info.misses, info.maxsize, info.currsize)
+    }
+
+    /// Drops every cached pair and resets the counts.
+    #[pyo3(name = "clear", text_signature = "($self, /)")]
+    fn py_clear(slf: PyRef<'_, Self>) {
+        slf.clear();
+    }
+
+    #[getter(metric)]
+    fn py_metric(&self) -> &str {
+        self.metric()
+    }
+
+    /// Number of cached pairs.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let info = self.info();
+        format!("ScoreCache('{}', maxsize={}) with {} pairs", self.name, info.maxsize, info.currsize)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(cache: &ScoreCache) -> (u64, u64, usize) {
+        let info = cache.info();
+        (info.hits, info.misses, info.currsize)
+    }
+
+    #[test]
+    fn least_recently_used_pairs_are_evicted_at_maxsize() {
+        let cache = ScoreCache::new("levenshtein", 2).unwrap();
+        assert_eq!(cache.score("kitten", "sitting"), 3.0);
+        assert_eq!(cache.score("flaw", "lawn"), 2.0);
+        assert_eq!(counts(&cache), (0, 2, 2));
+        // A hit makes ("kitten", "sitting") the most recently used pair.
+        assert_eq!(cache.score("kitten", "sitting"), 3.0);
+        assert_eq!(cache.score("a", "b"), 1.0);
+        assert_eq!(counts(&cache), (1, 3, 2));
+        cache.score("kitten", "sitting");
+        assert_eq!(counts(&cache), (2, 3, 2));
+        cache.score("flaw", "lawn");
+        assert_eq!(counts(&cache), (2, 4, 2));
+        // Pairs are ordered.
+        cache.score("lawn", "flaw");
+        assert_eq!(counts(&cache), (2, 5, 2));
+        assert_eq!(cache.info().maxsize, 2);
+    }
+
+    #[test]
+    fn batches_count_repeated_pairs_as_hits_and_keep_the_last_pairs() {
+        let cache = ScoreCache::new("levenshtein", 3).unwrap();
+        let pairs = [("a", "b"), ("a", "b"), ("c", "dd"), ("e", "e"), ("f", ""), ("g", "gg")];
+        assert_eq!(cache.score_pairs(&pairs, Some(2)).unwrap(), [1.0, 1.0, 2.0, 0.0, 1.0, 1.0]);
+        assert_eq!(counts(&cache), (1, 5, 3));
+        assert_eq!(cache.scores("e", &["e", "x"], None).unwrap(), [0.0, 1.0]);
+        assert_eq!(counts(&cache), (2, 6, 3));
+        cache.score("a", "b");
+        assert_eq!(counts(&cache), (2, 7, 3));
+        cache.clear();
+        assert_eq!(counts(&cache), (0, 0, 0));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn maxsize_must_be_positive() {
+        assert!(matches!(ScoreCache::new("levenshtein", 0), Err(Error::Value(_))));
+        assert!(matches!(ScoreCache::new("no_such_metric", 1), Err(Error::Value(_))));
+        let cache = ScoreCache::new("jaro", 1).unwrap();
+        cache.score("a", "b");
+        cache.score("c", "d");
+        cache.score("c", "d");
+        assert_eq!(counts(&cache), (1, 2, 1));
+    }
+}
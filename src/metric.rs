@@ -0,0 +1,308 @@
+use crate::error::{Error, Result};
+
+// ------------------------------------------------------------------------
+//  Metric Lookup
+// ------------------------------------------------------------------------
+
+/// Names accepted wherever a similarity metric is selected by name.
+pub const SIMILARITY_NAMES: [&str; 10] = [
+    "jaro",
+    "jaro_winkler",
+    "normalized_damerau_levenshtein",
+    "normalized_levenshtein",
+    "partial_ratio",
+    "ratio",
+    "sorensen_dice",
+    "token_set_ratio",
+    "token_sort_ratio",
+    "wratio",
+];
+
+/// Looks up a similarity function (scores between 0.0 and 1.0) by name.
+/// 
+/// # Arguments
+/// 
+/// * `name` - Name of the similarity function, e.g. `"jaro_winkler"`
+/// 
+/// # Returns
+/// 
+/// * `output` - The matching similarity function
+pub fn similarity(name: &str) -> Result<fn(&str, &str) -> f64> {
+    match name {
+        "jaro" => Ok(crate::scratch::jaro),
+        "jaro_winkler" => Ok(crate::scratch::jaro_winkler),
+        "normalized_damerau_levenshtein" => Ok(crate::scratch::normalized_damerau_levenshtein),
+        "normalized_levenshtein" => Ok(crate::scratch::normalized_levenshtein),
+        "partial_ratio" => Ok(crate::fuzz::partial_ratio),
+        "ratio" => Ok(crate::fuzz::ratio),
+        "sorensen_dice" => Ok(crate::scratch::sorensen_dice),
+        "token_set_ratio" => Ok(crate::fuzz::token_set_ratio),
+        "token_sort_ratio" => Ok(crate::fuzz::token_sort_ratio),
+        "wratio" => Ok(crate::fuzz::wratio),
+        _ => Err(Error::Value(format!(
+            "unknown similarity metric '{}', expected one of: {}",
+            name,
+            SIMILARITY_NAMES.join(", "),
+        ))),
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Metrics
+// ------------------------------------------------------------------------
+
+/// Names accepted wherever a distance or similarity metric is selected by
+/// name.
+pub const METRIC_NAMES: [&str; 16] = [
+    "damerau_levenshtein",
+    "jaro",
+    "jaro_winkler",
+    "levenshtein",
+    "lig3",
+    "normalized_damerau_levenshtein",
+    "normalized_levenshtein",
+    "ocr_levenshtein",
+    "osa_distance",
+    "partial_ratio",
+    "ratio",
+    "sorensen_dice",
+    "strcmp95",
+    "token_set_ratio",
+    "token_sort_ratio",
+    "wratio",
+];
+
+/// Names of the metrics scoring `(a, b)` and `(b, a)` alike, whose matrix
+/// of a list against itself can be mirrored from its upper triangle. The
+/// Jaro similarities of `strsim` look for the matches of `a` in `b`, so they
+/// are left out.
+pub const SYMMETRIC_NAMES: [&str; 14] = [
+    "damerau_levenshtein",
+    "levenshtein",
+    "lig3",
+    "normalized_damerau_levenshtein",
+    "normalized_levenshtein",
+    "ocr_levenshtein",
+    "osa_distance",
+    "partial_ratio",
+    "ratio",
+    "sorensen_dice",
+    "strcmp95",
+    "token_set_ratio",
+    "token_sort_ratio",
+    "wratio",
+];
+
+/// Whether lower or higher scores of a metric mean more similar strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Distance,
+    Similarity,
+}
+
+/// A distance or similarity function.
+pub type Scorer = fn(&str, &str) -> f64;
+
+/// A scorer that returns `None` as soon as the score is known to fail a
+/// cutoff.
+pub type BoundedScorer = fn(&str, &str, f64) -> Option<f64>;
+
+/// A scorer of one string against a batch of others into a row of scores,
+/// which returns false, leaving the row untouched, for strings it does not
+/// handle.
+pub type RowScorer = fn(&str, &[&str], &mut [f64]) -> bool;
+
+/// A distance or similarity function together with its kind. Integer
+/// distances are reported as floats so all metrics share one signature.
+/// `bounded`, where available, computes the score only as far as needed to
+/// tell whether it passes a cutoff, and `length_bound` is the best score
+/// strings of two lengths in characters can reach. `row`, where available,
+/// scores packed batches of strings with SIMD instructions.
+#[derive(Clone, Copy, Debug)]
+pub struct Metric {
+    pub kind: Kind,
+    pub score: fn(&str, &str) -> f64,
+    pub bounded: Option<BoundedScorer>,
+    pub length_bound: Option<fn(usize, usize) -> f64>,
+    pub row: Option<RowScorer>,
+}
+
+impl Metric {
+    /// Looks up a distance or similarity function by name.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `name` - Name of the metric, e.g. `"levenshtein"`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The matching metric
+    pub fn from_name(name: &str) -> Result<Self> {
+        let distance = |score| Ok(Self { kind: Kind::Distance, score, bounded: None, length_bound: None, row: None });
+        let similarity_of = |score| Ok(Self { kind: Kind::Similarity, score, bounded: None, length_bound: None, row: None });
+        let metric = match name {
+            "damerau_levenshtein" => distance(|a, b| crate::scratch::damerau_levenshtein(a, b) as f64),
+            "levenshtein" => distance(|a, b| crate::scratch::levenshtein(a, b) as f64),
+            "ocr_levenshtein" => distance(crate::weighted::ocr_levenshtein),
+            "osa_distance" => distance(|a, b| crate::scratch::osa_distance(a, b) as f64),
+            "lig3" => similarity_of(crate::align::lig3),
+            "strcmp95" => similarity_of(|a, b| crate::jaro::strcmp95(a, b, false)),
+            _ => match similarity(name) {
+                Ok(score) => similarity_of(score),
+                Err(_) => Err(Error::Value(format!(
+                    "unknown metric '{}', expected one of: {}",
+                    name,
+                    METRIC_NAMES.join(", "),
+                ))),
+            },
+        };
+        metric.map(|metric: Self| Self {
+            bounded: bounded(name),
+            length_bound: crate::cutoff::length_bound(name),
+            row: crate::simd::row(name),
+            ..metric
+        })
+    }
+
+    /// Calculates the score of two strings if it passes `cutoff`, pruning
+    /// the computation early where the metric allows it.
+    pub fn score_cutoff(&self, a: &str, b: &str, cutoff: f64) -> Option<f64> {
+        match self.bounded {
+            Some(bounded) => bounded(a, b, cutoff),
+            None => {
+                let score = (self.score)(a, b);
+                self.passes(score, cutoff).then_some(score)
+            }
+        }
+    }
+
+    /// Scores `a` against each of `bs` into `output`, with the packed kernel
+    /// of the metric where it has one that handles `a`.
+    pub fn score_row(&self, a: &str, bs: &[&str], output: &mut [f64]) {
+        if self.row.is_some_and(|row| row(a, bs, output)) {
+            return;
+        }
+        for (cell, b) in output.iter_mut().zip(bs) {
+            *cell = (self.score)(a, b);
+        }
+    }
+
+    /// Like `score_cutoff`, but first rejects strings of `len_a` and `len_b`
+    /// characters whose lengths alone fail `cutoff`, before any other work.
+    /// Lengths of `None`, from `char_length` for metrics the lengths do not
+    /// bound, skip the check.
+    pub fn score_cutoff_sized(
+        &self,
+        a: &str,
+        len_a: Option<usize>,
+        b: &str,
+        len_b: Option<usize>,
+        cutoff: f64,
+    ) -> Option<f64> {
+        if let (Some(len_a), Some(len_b)) = (len_a, len_b) {
+            if !self.lengths_pass(len_a, len_b, cutoff) {
+                return None;
+            }
+        }
+        self.score_cutoff(a, b, cutoff)
+    }
+
+    /// Number of characters of `s` for `score_cutoff_sized`, or `None` for
+    /// metrics whose scores the lengths do not bound.
+    pub fn char_length(&self, s: &str) -> Option<usize> {
+        self.length_bound.map(|_| s.chars().count())
+    }
+
+    /// `char_length` of each string, counted once for a whole batch.
+    pub fn char_lengths<S: AsRef<str>>(&self, xs: &[S]) -> Option<Vec<usize>> {
+        self.length_bound.map(|_| xs.iter().map(|x| x.as_ref().chars().count()).collect())
+    }
+
+    /// Whether strings of `len_a` and `len_b` characters may pass `cutoff`,
+    /// judging from their lengths alone. Always true for metrics whose scores
+    /// the lengths do not bound.
+    pub fn lengths_pass(&self, len_a: usize, len_b: usize, cutoff: f64) -> bool {
+        let slack = match self.kind {
+            Kind::Distance => -crate::cutoff::EPSILON,
+            Kind::Similarity => crate::cutoff::EPSILON,
+        };
+        self.length_bound.is_none_or(|bound| self.passes(bound(len_a, len_b) + slack, cutoff))
+    }
+
+    /// Whether `score` is at least as good as `cutoff`: no greater for
+    /// distances and no smaller for similarities.
+    pub fn passes(&self, score: f64, cutoff: f64) -> bool {
+        match self.kind {
+            Kind::Distance => score <= cutoff,
+            Kind::Similarity => score >= cutoff,
+        }
+    }
+}
+
+/// Cutoff-aware version of the metric named `name`, if there is one.
+fn bounded(name: &str) -> Option<BoundedScorer> {
+    match name {
+        "damerau_levenshtein" => Some(crate::cutoff::damerau_levenshtein),
+        "jaro" => Some(crate::cutoff::jaro),
+        "jaro_winkler" => Some(crate::cutoff::jaro_winkler),
+        "levenshtein" => Some(crate::cutoff::levenshtein),
+        "normalized_levenshtein" => Some(crate::cutoff::normalized_levenshtein),
+        "osa_distance" => Some(crate::cutoff::osa_distance),
+        "ratio" => Some(crate::cutoff::ratio),
+        _ => None,
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Ensembles
+// ------------------------------------------------------------------------
+
+/// A weighted average of similarity metrics, so that the combined score
+/// stays between 0.0 and 1.0.
+#[derive(Clone, Debug)]
+pub struct Ensemble {
+    parts: Vec<(Scorer, f64)>,
+    total_weight: f64,
+}
+
+impl Ensemble {
+    /// Looks up each similarity metric by name and pairs it with its weight.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `weights` - Pairs of a similarity name, e.g. `"jaro_winkler"`, and
+    ///   its non-negative weight
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The ensemble
+    pub fn new(weights: &[(&str, f64)]) -> Result<Self> {
+        let mut parts = Vec::with_capacity(weights.len());
+        for &(name, weight) in weights {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(Error::Value(format!(
+                    "weight of '{}' must be finite and non-negative, got {}",
+                    name, weight
+                )));
+            }
+            parts.push((similarity(name)?, weight));
+        }
+        let total_weight: f64 = parts.iter().map(|&(_, weight)| weight).sum();
+        if total_weight <= 0.0 {
+            return Err(Error::value("ensemble weights must not all be zero"));
+        }
+        Ok(Self { parts, total_weight })
+    }
+
+    /// Weighted average of the similarities of `a` and `b`.
+    pub fn score(&self, a: &str, b: &str) -> f64 {
+        self.parts
+            .iter()
+            .filter(|&&(_, weight)| weight > 0.0)
+            .map(|&(similarity, weight)| weight * similarity(a, b))
+            .sum::<f64>()
+            / self.total_weight
+    }
+}
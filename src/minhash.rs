@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use rayon::prelude::*;
+
+use crate::checkpoint::Fingerprint;
+use crate::error::{Error, Result};
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+use crate::tokens::check_ngram;
+use crate::tokens;
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+
+// ------------------------------------------------------------------------
+//  MinHash Signatures
+// ------------------------------------------------------------------------
+
+/// Mersenne prime modulus of the hash permutations.
+const PRIME: u64 = (1 << 61) - 1;
+
+/// MinHash over the shingles of a string: for each of `num_perm` random
+/// permutations `(a * x + b) mod p` of the shingle hashes, the smallest
+/// permuted hash. Two signatures agree in any one position with probability
+/// equal to the Jaccard similarity of the shingle sets.
+#[derive(Clone, Debug)]
+struct MinHasher {
+    ngram: usize,
+    tokens: bool,
+    seed: u64,
+    permutations: Vec<(u64, u64)>,
+}
+
+impl MinHasher {
+    fn new(num_perm: usize, ngram: usize, tokens: bool, seed: u64) -> Self {
+        let mut state = seed;
+        let mut next = || {
+            // SplitMix64, so that a seed always yields the same permutations.
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            (z ^ (z >> 31)) % PRIME
+        };
+        let permutations = (0..num_perm).map(|_| (next().max(1), next())).collect();
+        Self { ngram, tokens, seed, permutations }
+    }
+
+    /// Shingles of `s`: its lowercased tokens, or its character n-grams.
+    fn shingles(&self, s: &str) -> Vec<String> {
+        if self.tokens {
+            tokens::tokenize(s)
+        } else {
+            tokens::char_ngrams(s, self.ngram)
+        }
+    }
+
+    /// Signature of `s`, or `None` if it has no shingles.
+    fn signature(&self, s: &str) -> Option<Vec<u64>> {
+        let hashes: Vec<u64> = self
+            .shingles(s)
+            .iter()
+            .map(|shingle| {
+                let mut hash = Fingerprint::new();
+                hash.write(shingle.as_bytes());
+                hash.finish() % PRIME
+            })
+            .collect();
+        if hashes.is_empty() {
+            return None;
+        }
+        let signature = self
+            .permutations
+            .iter()
+            .map(|&(a, b)| {
+                hashes
+                    .iter()
+                    .map(|&x| ((u128::from(a) * u128::from(x) + u128::from(b)) % u128::from(PRIME)) as u64)
+                    .min()
+                    .expect("there is at least one shingle")
+            })
+            .collect();
+        Some(signature)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Locality-Sensitive Hashing
+// ------------------------------------------------------------------------
+
+/// Locality-sensitive hashing index over MinHash signatures, for
+/// approximate Jaccard similarity search. Signatures of `bands * rows`
+/// values are cut into `bands` bands of `rows` values, and two documents
+/// become candidates when all rows of any one band agree. Documents with
+/// Jaccard similarity `s` are therefore found with probability
+/// `1 - (1 - s^rows)^bands`, an S-curve rising around `(1 / bands)^(1 / rows)`.
+/// Only the band hashes are kept, not the documents or their signatures.
+/// 
+/// # Arguments
+/// 
+/// * `bands` - Number of bands
+/// * `rows` - Number of signature values per band
+/// * `ngram` - Number of characters per shingle
+/// * `tokens` - Whether to use the lowercased words as shingles, instead of
+///   character n-grams
+/// * `seed` - Seed of the hash permutations. Only signatures made with the
+///   same seed and number of permutations are comparable
+/// * `preprocessor` - `Preprocessor` applied to the documents and queries
+///   before they are shingled, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(bands=16, rows=8, /, ngram=3, tokens=False, seed=1, preprocessor=None)")
+)]
+pub struct MinHashLSH {
+    hasher: MinHasher,
+    preprocessor: Preprocessor,
+    rows: usize,
+    /// Documents in each bucket of each band, in increasing order.
+    buckets: Vec<HashMap<u64, Vec<usize>>>,
+    n_documents: usize,
+}
+
+impl MinHashLSH {
+    /// Makes an empty index of `bands` bands of `rows` signature values
+    /// each, shingling the documents into character n-grams of `ngram`
+    /// characters, or into their lowercased words with `tokens`. Only
+    /// signatures made with the same `seed` and number of permutations are
+    /// comparable.
+    pub fn new(bands: usize, rows: usize, ngram: usize, tokens: bool, seed: u64) -> Result<Self> {
+        if bands == 0 || rows == 0 {
+            return Err(Error::value("bands and rows must be at least 1"));
+        }
+        check_ngram(ngram)?;
+        let num_perm = bands.checked_mul(rows).ok_or_else(|| Error::value("bands * rows is too large"))?;
+        Ok(Self {
+            hasher: MinHasher::new(num_perm, ngram, tokens, seed),
+            preprocessor: Preprocessor::default(),
+            rows,
+            buckets: vec![HashMap::new(); bands],
+            n_documents: 0,
+        })
+    }
+
+    /// MinHash signature of `s`, `bands * rows` permuted hashes, or `None`
+    /// if `s` has no shingles.
+    pub fn signature(&self, s: &str) -> Option<Vec<u64>> {
+        self.hasher.signature(&self.preprocessor.apply(s))
+    }
+
+    /// Adds documents to the index, hashing them on `n_threads` threads, or
+    /// on all available cores when `None`. They are numbered in the order
+    /// they are added, and documents without shingles are numbered but never
+    /// matched.
+    pub fn extend<S: AsRef<str> + Sync>(&mut self, documents: &[S], n_threads: Option<usize>) -> Result<()> {
+        let keys = thread_pool(n_threads)?.run_sized(documents.len(), 1, |counter, min_len| {
+            self.keys_of(counter, documents, min_len)
+        });
+        self.insert(keys.unwrap_or_default());
+        Ok(())
+    }
+
+    /// Indices of the documents sharing a band with `s`, in increasing order.
+    pub fn query(&self, s: &str) -> Vec<usize> {
+        match self.signature(s) {
+            Some(signature) => self.candidates(&self.band_keys(&signature)),
+            None => Vec::new(),
+        }
+    }
+
+    /// Parallel version of `query` for many query strings, on `n_threads`
+    /// threads, or on all available cores when `None`.
+    pub fn query_many<S: AsRef<str> + Sync>(&self, strings: &[S], n_threads: Option<usize>) -> Result<Vec<Vec<usize>>> {
+        let keys = thread_pool(n_threads)?.run_sized(strings.len(), 1, |counter, min_len| {
+            self.keys_of(counter, strings, min_len)
+        });
+        Ok(self.matches(keys.unwrap_or_default()))
+    }
+
+    /// Pairs `(i, j)` of indexed documents with `i < j` sharing at least one
+    /// band, the candidate near-duplicates of the whole collection, sorted.
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = self
+            .buckets
+            .iter()
+            .flat_map(HashMap::values)
+            .flat_map(|bucket| {
+                bucket
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(k, &i)| bucket[k + 1..].iter().map(move |&j| (i, j)))
+            })
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Number of indexed documents.
+    pub fn len(&self) -> usize {
+        self.n_documents
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n_documents == 0
+    }
+
+    fn band_keys(&self, signature: &[u64]) -> Vec<u64> {
+        signature
+            .chunks(self.rows)
+            .map(|band| {
+                let mut hash = Fingerprint::new();
+                for value in band {
+                    hash.write(&value.to_le_bytes());
+                }
+                hash.finish()
+            })
+            .collect()
+    }
+
+    /// Band keys of each of `strings`, computed in parallel, or `None` if
+    /// the job was cancelled.
+    fn keys_of<S: AsRef<str> + Sync>(&self, counter: &Counter, strings: &[S], min_len: usize) -> Option<Vec<Option<Vec<u64>>>> {
+        strings
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|s| (!counter.is_cancelled()).then(|| self.signature(s.as_ref()).map(|sig| self.band_keys(&sig))))
+            .collect()
+    }
+
+    /// Numbers documents with the band keys `keys` after those indexed.
+    fn insert(&mut self, keys: Vec<Option<Vec<u64>>>) {
+        let n_added = keys.len();
+        for (i, keys) in keys.into_iter().enumerate() {
+            for (key, buckets) in keys.iter().flatten().zip(&mut self.buckets) {
+                buckets.entry(*key).or_default().push(self.n_documents + i);
+            }
+        }
+        self.n_documents += n_added;
+    }
+
+    /// Documents sharing a bucket with the band keys `keys`, in increasing
+    /// order.
+    fn candidates(&self, keys: &[u64]) -> Vec<usize> {
+        let mut found: Vec<usize> = keys
+            .iter()
+            .zip(&self.buckets)
+            .filter_map(|(key, buckets)| buckets.get(key))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    fn matches(&self, keys: Vec<Option<Vec<u64>>>) -> Vec<Vec<usize>> {
+        keys.into_iter().map(|keys| keys.map(|keys| self.candidates(&keys)).unwrap_or_default()).collect()
+    }
+}
+
+impl Persist for MinHashLSH {
+    const KIND: &'static str = "MinHashLSH";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.usize(self.buckets.len());
+        encoder.usize(self.rows);
+        encoder.usize(self.hasher.ngram);
+        encoder.bool(self.hasher.tokens);
+        encoder.varint(self.hasher.seed);
+        self.preprocessor.encode(encoder);
+        encoder.usize(self.n_documents);
+        for buckets in &self.buckets {
+            let mut buckets: Vec<(&u64, &Vec<usize>)> = buckets.iter().collect();
+            buckets.sort_unstable();
+            encoder.usize(buckets.len());
+            for (&key, documents) in buckets {
+                encoder.u64(key);
+                encoder.ascending(documents);
+            }
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let (bands, rows, ngram) = (decoder.usize()?, decoder.usize()?, decoder.usize()?);
+        let (tokens, seed) = (decoder.bool()?, decoder.varint()?);
+        let mut lsh = Self { preprocessor: Preprocessor::decode(decoder)?, ..Self::new(bands, rows, ngram, tokens, seed)? };
+        lsh.n_documents = decoder.usize()?;
+        for buckets in &mut lsh.buckets {
+            for _ in 0..decoder.length()? {
+                let key = decoder.u64()?;
+                buckets.insert(key, decoder.ascending(lsh.n_documents)?);
+            }
+        }
+        Ok(lsh)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MinHashLSH {
+    #[new]
+    #[args(bands = "16", rows = "8", ngram = "3", tokens = "false", seed = "1", preprocessor = "None")]
+    fn py_new(
+        bands: usize,
+        rows: usize,
+        ngram: usize,
+        tokens: bool,
+        seed: u64,
+        preprocessor: Option<Preprocessor>,
+    ) -> PyResult<Self> {
+        Ok(Self { preprocessor: preprocessor.unwrap_or_default(), ..Self::new(bands, rows, ngram, tokens, seed)? })
+    }
+
+    /// Calculates the MinHash signature of a string.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `s` - String to sign
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - `bands * rows` permuted hashes, or `None` if `s` has no
+    ///   shingles
+    #[pyo3(name = "signature", text_signature = "($self, s, /)")]
+    fn py_signature(&self, s: &str) -> Option<Vec<u64>> {
+        self.signature(s)
+    }
+
+    /// Adds documents to the index. They are numbered in the order they are
+    /// added, and documents without shingles are numbered but never matched.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `documents` - Strings to add, a sequence or an iterable
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "extend", text_signature = "($self, documents, /, n_threads=None)")]
+    fn py_extend(&mut self, documents: Strings, n_threads: NThreads) -> PyResult<()> {
+        documents.for_each_batch(|_, documents| {
+            let keys = self.py_keys_of(documents, n_threads)?;
+            self.insert(keys);
+            Ok(())
+        })
+    }
+
+    /// Finds the indexed documents likely to be similar to `s`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `s` - Query string
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Indices of the documents sharing a band with `s`, in
+    ///   increasing order
+    #[pyo3(name = "query", text_signature = "($self, s, /)")]
+    fn py_query(&self, s: &str) -> Vec<usize> {
+        self.query(s)
+    }
+
+    /// Parallel version of `query` for many query strings.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `strings` - Query strings
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each query, indices of the documents sharing a band
+    ///   with it, in increasing order
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "query_many", text_signature = "($self, strings, /, n_threads=None)")]
+    fn py_query_many(&self, strings: Strings, n_threads: NThreads) -> PyResult<Vec<Vec<usize>>> {
+        Ok(self.matches(self.py_keys_of(&strings.as_strs()?, n_threads)?))
+    }
+
+    /// Finds the pairs of indexed documents sharing at least one band, the
+    /// candidate near-duplicates of the whole collection.
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs `(i, j)` with `i < j`, sorted
+    #[pyo3(name = "candidate_pairs", text_signature = "($self, /)")]
+    fn py_candidate_pairs(&self) -> Vec<(usize, usize)> {
+        self.candidate_pairs()
+    }
+
+    /// Saves the index to a file in a compact binary format, to be restored
+    /// by `MinHashLSH.load` without hashing the documents again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads an index written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved index
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of indexed documents.
+    fn __len__(&self) -> usize {
+        self.n_documents
+    }
+}
+
+#[cfg(feature = "python")]
+impl MinHashLSH {
+    /// `keys_of` on `n_threads` threads, releasing the GIL.
+    fn py_keys_of(&self, strings: &[&str], n_threads: NThreads) -> PyResult<Vec<Option<Vec<u64>>>> {
+        let reporter = Reporter::new(None, Some(strings.len()));
+        let keys = thread_pool(n_threads)?.install_sized(&reporter, strings.len(), 1, |counter, min_len| {
+            self.keys_of(counter, strings, min_len)
+        })?;
+        Ok(keys.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_duplicates_share_a_band() {
+        let mut lsh = MinHashLSH::new(16, 4, 3, false, 1).unwrap();
+        let documents = ["the quick brown fox jumps", "the quick brown fox jumped", "lorem ipsum dolor sit amet", ""];
+        lsh.extend(&documents, Some(1)).unwrap();
+        assert_eq!(lsh.len(), 4);
+        assert_eq!(lsh.query("the quick brown fox jumps"), [0, 1]);
+        assert_eq!(lsh.query(""), Vec::<usize>::new());
+        assert_eq!(lsh.candidate_pairs(), [(0, 1)]);
+        assert_eq!(lsh.signature(""), None);
+        assert_eq!(lsh.signature("abc").map(|signature| signature.len()), Some(64));
+    }
+
+    #[test]
+    fn collision_rate_follows_the_s_curve_around_the_threshold() {
+        // With 8 bands of 4 rows the curve rises around a Jaccard similarity
+        // of (1 / 8)^(1 / 4), about 0.59.
+        let (bands, rows) = (8, 4);
+        let pairs = 400;
+        // Word sets of `size` words sharing `shared` of them, whose Jaccard
+        // similarity is `shared / (2 * size - shared)`.
+        for (size, shared) in [(18, 6), (18, 12), (17, 14), (18, 16)] {
+            let jaccard = shared as f64 / (2 * size - shared) as f64;
+            let document = |pair: usize, words: std::ops::Range<usize>| {
+                words.map(|word| format!("p{}w{}", pair, word)).collect::<Vec<_>>().join(" ")
+            };
+            let a: Vec<String> = (0..pairs).map(|pair| document(pair, 0..size)).collect();
+            let b: Vec<String> = (0..pairs).map(|pair| document(pair, size - shared..2 * size - shared)).collect();
+            let mut lsh = MinHashLSH::new(bands, rows, 3, true, 7).unwrap();
+            lsh.extend(&a, None).unwrap();
+            let found = lsh.query_many(&b, None).unwrap();
+            let collisions = found.iter().enumerate().filter(|(pair, found)| found.contains(pair)).count();
+            let rate = collisions as f64 / pairs as f64;
+            let expected = 1.0 - (1.0 - jaccard.powi(rows as i32)).powi(bands as i32);
+            assert!((rate - expected).abs() < 0.08, "jaccard {}: rate {} != {}", jaccard, rate, expected);
+            assert_eq!(MinHashLSH::from_bytes(&lsh.to_bytes()).unwrap().query_many(&b, Some(1)).unwrap(), found);
+        }
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        assert!(matches!(MinHashLSH::new(0, 8, 3, false, 1), Err(Error::Value(_))));
+        assert!(matches!(MinHashLSH::new(16, 0, 3, false, 1), Err(Error::Value(_))));
+        assert!(matches!(MinHashLSH::new(usize::MAX, 2, 3, false, 1), Err(Error::Value(_))));
+        assert!(MinHashLSH::new(16, 8, 0, false, 1).is_err());
+    }
+}
@@ -0,0 +1,464 @@
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use rayon::prelude::*;
+
+use crate::error::Result;
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::metric::Metric;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+use crate::rank::Ranked;
+use crate::tokens::check_ngram;
+use crate::tokens;
+
+// ------------------------------------------------------------------------
+//  N-Gram Index
+// ------------------------------------------------------------------------
+
+/// Inverted index from character n-grams to the choices containing them.
+/// Queries first generate as candidates the choices sharing at least
+/// `min_shared` distinct n-grams with the query, counted from the postings
+/// of the query's own n-grams, and then verify only those candidates with a
+/// full metric. N-grams are taken from the lowercased strings, and strings
+/// shorter than `ngram` are a single n-gram.
+/// 
+/// # Arguments
+/// 
+/// * `choices` - Strings to index, or `None` to start empty
+/// * `ngram` - Number of characters per n-gram
+/// * `preprocessor` - `Preprocessor` applied to the choices and queries before
+///   they are split into n-grams and scored, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(choices=None, /, ngram=3, preprocessor=None)")
+)]
+pub struct NGramIndex {
+    ngram: usize,
+    preprocessor: Preprocessor,
+    /// The choices after preprocessing.
+    choices: Vec<String>,
+    vocabulary: HashMap<String, usize>,
+    /// Choices containing each n-gram, in increasing order.
+    postings: Vec<Vec<usize>>,
+}
+
+impl NGramIndex {
+    /// Makes an empty index of n-grams of `ngram` characters.
+    pub fn new(ngram: usize) -> Result<Self> {
+        check_ngram(ngram)?;
+        Ok(Self {
+            ngram,
+            preprocessor: Preprocessor::default(),
+            choices: Vec::new(),
+            vocabulary: HashMap::new(),
+            postings: Vec::new(),
+        })
+    }
+
+    /// Makes an empty index that preprocesses the choices and queries.
+    pub(crate) fn preprocessed(ngram: usize, preprocessor: Preprocessor) -> Result<Self> {
+        Ok(Self { preprocessor, ..Self::new(ngram)? })
+    }
+
+    /// Adds choices to the index. They are numbered after the existing ones.
+    pub fn extend<I>(&mut self, choices: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for choice in choices {
+            self.insert(choice.as_ref());
+        }
+    }
+
+    /// Indices of the choices sharing at least `min_shared` distinct n-grams
+    /// with `s`, in increasing order, without verifying them.
+    pub fn candidates(&self, s: &str, min_shared: usize) -> Vec<usize> {
+        self.generate(&self.preprocessor.apply(s), min_shared)
+    }
+
+    /// Finds the choices matching `s`: the candidates sharing at least
+    /// `min_shared` n-grams with it, scored by `metric` and kept if the score
+    /// passes `threshold`. Returns pairs of a choice index and its score,
+    /// best match first.
+    pub fn query(&self, s: &str, min_shared: usize, metric: Metric, threshold: Option<f64>) -> Vec<(usize, f64)> {
+        self.matches(s, min_shared, &metric, threshold)
+    }
+
+    /// Parallel version of `query` for many query strings, on `n_threads`
+    /// threads, or on all available cores when `None`.
+    pub fn query_many<S: AsRef<str> + Sync>(
+        &self,
+        strings: &[S],
+        min_shared: usize,
+        metric: Metric,
+        threshold: Option<f64>,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<Vec<(usize, f64)>>> {
+        let output = thread_pool(n_threads)?.run_sized(strings.len(), 1, |counter, min_len| {
+            self.matches_many(counter, strings, min_shared, &metric, threshold, min_len)
+        });
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Number of indexed choices.
+    pub fn len(&self) -> usize {
+        self.choices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.choices.is_empty()
+    }
+
+    /// The choice numbered `index`, after preprocessing.
+    pub fn choice(&self, index: usize) -> &str {
+        &self.choices[index]
+    }
+
+    fn distinct_ngrams(&self, s: &str) -> Vec<String> {
+        let mut grams = tokens::char_ngrams(s, self.ngram);
+        grams.sort_unstable();
+        grams.dedup();
+        grams
+    }
+
+    pub(crate) fn insert(&mut self, choice: &str) {
+        let index = self.choices.len();
+        let choice = self.preprocessor.apply(choice).into_owned();
+        for gram in self.distinct_ngrams(&choice) {
+            let next = self.vocabulary.len();
+            let term = *self.vocabulary.entry(gram).or_insert(next);
+            if term == self.postings.len() {
+                self.postings.push(Vec::new());
+            }
+            self.postings[term].push(index);
+        }
+        self.choices.push(choice);
+    }
+
+    /// Indices of the choices sharing at least `min_shared` distinct n-grams
+    /// with `s`, already preprocessed, in increasing order.
+    fn generate(&self, s: &str, min_shared: usize) -> Vec<usize> {
+        if min_shared == 0 {
+            return (0..self.choices.len()).collect();
+        }
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for gram in self.distinct_ngrams(s) {
+            if let Some(&term) = self.vocabulary.get(&gram) {
+                for &choice in &self.postings[term] {
+                    *shared.entry(choice).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut candidates: Vec<usize> = shared
+            .into_iter()
+            .filter(|&(_, count)| count >= min_shared)
+            .map(|(choice, _)| choice)
+            .collect();
+        candidates.sort_unstable();
+        candidates
+    }
+
+    /// Candidates for `s` scored by `metric`, keeping those that pass
+    /// `threshold`, best match first.
+    pub(crate) fn matches(&self, s: &str, min_shared: usize, metric: &Metric, threshold: Option<f64>) -> Vec<(usize, f64)> {
+        let s = self.preprocessor.apply(s);
+        let mut matches: Vec<Ranked> = self
+            .generate(&s, min_shared)
+            .into_iter()
+            .filter_map(|choice| {
+                let candidate = &self.choices[choice];
+                let score = match threshold {
+                    Some(threshold) => metric.score_cutoff(&s, candidate, threshold)?,
+                    None => (metric.score)(&s, candidate),
+                };
+                Some(Ranked::new(metric.kind, choice, score))
+            })
+            .collect();
+        matches.sort_unstable_by(|a, b| b.cmp(a));
+        matches.into_iter().map(|r| (r.index, r.score)).collect()
+    }
+
+    /// `matches` for each of `strings` in parallel, or `None` if the job was
+    /// cancelled.
+    fn matches_many<S: AsRef<str> + Sync>(
+        &self,
+        counter: &Counter,
+        strings: &[S],
+        min_shared: usize,
+        metric: &Metric,
+        threshold: Option<f64>,
+        min_len: usize,
+    ) -> Option<Vec<Vec<(usize, f64)>>> {
+        strings
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|s| (!counter.is_cancelled()).then(|| self.matches(s.as_ref(), min_shared, metric, threshold)))
+            .collect()
+    }
+}
+
+impl Persist for NGramIndex {
+    const KIND: &'static str = "NGramIndex";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.usize(self.ngram);
+        self.preprocessor.encode(encoder);
+        encoder.strs(&self.choices);
+        let mut grams = vec![""; self.vocabulary.len()];
+        for (gram, &term) in &self.vocabulary {
+            grams[term] = gram;
+        }
+        encoder.strs(&grams);
+        for postings in &self.postings {
+            encoder.ascending(postings);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let mut index = Self::new(decoder.usize()?)?;
+        index.preprocessor = Preprocessor::decode(decoder)?;
+        index.choices = decoder.strs()?;
+        let grams = decoder.strs()?;
+        let n_grams = grams.len();
+        index.vocabulary = grams.into_iter().enumerate().map(|(term, gram)| (gram, term)).collect();
+        decoder.check(index.vocabulary.len() == n_grams)?;
+        index.postings = (0..n_grams)
+            .map(|_| decoder.ascending(index.choices.len()))
+            .collect::<crate::Result<_>>()?;
+        Ok(index)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NGramIndex {
+    #[new]
+    #[args(choices = "None", ngram = "3", preprocessor = "None")]
+    fn py_new(choices: Option<Strings>, ngram: usize, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        let mut index = Self::preprocessed(ngram, preprocessor.unwrap_or_default())?;
+        if let Some(choices) = choices {
+            index.py_extend(choices)?;
+        }
+        Ok(index)
+    }
+
+    /// Adds choices to the index. They are numbered after the existing ones.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `choices` - Strings to add
+    #[pyo3(name = "extend", text_signature = "($self, choices, /)")]
+    fn py_extend(&mut self, choices: Strings) -> PyResult<()> {
+        choices.for_each_batch(|_, choices| {
+            self.extend(choices);
+            Ok(())
+        })
+    }
+
+    /// Generates the candidates for `s` without verifying them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `s` - Query string
+    /// * `min_shared` - Minimum number of distinct n-grams a choice shares
+    ///   with `s` to be a candidate
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Indices of the candidate choices, in increasing order
+    #[args(min_shared = "1")]
+    #[pyo3(name = "candidates", text_signature = "($self, s, /, min_shared=1)")]
+    fn py_candidates(&self, s: &str, min_shared: usize) -> Vec<usize> {
+        self.candidates(s, min_shared)
+    }
+
+    /// Finds the choices matching `s`: the candidates sharing at least
+    /// `min_shared` n-grams with it, scored by `refine_metric` and kept if
+    /// the score passes `threshold`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `s` - Query string
+    /// * `min_shared` - Minimum number of distinct n-grams a choice shares
+    ///   with `s` to be a candidate
+    /// * `refine_metric` - Name of the metric verifying the candidates
+    /// * `threshold` - Largest distance or smallest similarity of a match, or
+    ///   `None` to keep every candidate
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of a choice index and its score, best match first
+    #[args(min_shared = "1", refine_metric = "\"normalized_levenshtein\"", threshold = "None")]
+    #[pyo3(
+        name = "query",
+        text_signature = "($self, s, /, min_shared=1, refine_metric='normalized_levenshtein', threshold=None)"
+    )]
+    fn py_query(
+        &self,
+        s: &str,
+        min_shared: usize,
+        refine_metric: &str,
+        threshold: Option<f64>,
+    ) -> PyResult<Vec<(usize, f64)>> {
+        let metric = Metric::from_name(refine_metric)?;
+        Ok(self.query(s, min_shared, metric, threshold))
+    }
+
+    /// Parallel version of `query` for many query strings.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `strings` - Query strings
+    /// * `min_shared` - Minimum number of distinct n-grams a choice shares
+    ///   with a query to be a candidate
+    /// * `refine_metric` - Name of the metric verifying the candidates
+    /// * `threshold` - Largest distance or smallest similarity of a match, or
+    ///   `None` to keep every candidate
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each query, pairs of a choice index and its score,
+    ///   best match first
+    #[args(min_shared = "1", refine_metric = "\"normalized_levenshtein\"", threshold = "None", n_threads = "NThreads::AUTO")]
+    #[pyo3(
+        name = "query_many",
+        text_signature = "($self, strings, /, min_shared=1, refine_metric='normalized_levenshtein', threshold=None, n_threads=None)"
+    )]
+    fn py_query_many(
+        &self,
+        strings: Strings,
+        min_shared: usize,
+        refine_metric: &str,
+        threshold: Option<f64>,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<Vec<(usize, f64)>>> {
+        let metric = Metric::from_name(refine_metric)?;
+        let strings = strings.as_strs()?;
+        let reporter = Reporter::new(None, Some(strings.len().saturating_mul(self.choices.len())));
+        let output = thread_pool(n_threads)?.install_sized(&reporter, strings.len(), 1, |counter, min_len| {
+            self.matches_many(counter, &strings, min_shared, &metric, threshold, min_len)
+        })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Saves the index to a file in a compact binary format, to be restored
+    /// by `NGramIndex.load` without indexing the choices again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads an index written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved index
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of indexed choices.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    /// Largest `min_shared` that keeps every choice within Levenshtein
+    /// distance `k` of `s`: each edit changes at most `ngram` of the n-grams
+    /// of `s`, so at most `k * ngram` of its distinct n-grams go missing.
+    fn safe_min_shared(index: &NGramIndex, s: &str, k: usize) -> usize {
+        index.distinct_ngrams(s).len().saturating_sub(k * index.ngram)
+    }
+
+    #[test]
+    fn candidates_keep_every_match_of_brute_force() {
+        let mut rng = Rng::new(593);
+        let choices = rng.strings(300, 10);
+        let queries = rng.strings(40, 10);
+        for ngram in 1..=3 {
+            let mut index = NGramIndex::new(ngram).unwrap();
+            index.extend(&choices);
+            for query in queries.iter().chain(choices.iter().take(10)).chain([&String::new()]) {
+                for k in 0..=2 {
+                    let expected: Vec<(usize, f64)> = choices
+                        .iter()
+                        .map(|choice| strsim::levenshtein(query, choice))
+                        .enumerate()
+                        .filter(|&(_, d)| d <= k)
+                        .map(|(i, d)| (i, d as f64))
+                        .collect();
+                    let min_shared = safe_min_shared(&index, query, k);
+                    let candidates = index.candidates(query, min_shared);
+                    assert!(expected.iter().all(|(i, _)| candidates.contains(i)), "{} {:?} {}", ngram, query, k);
+                    let mut found = index.query(query, min_shared, Metric::from_name("levenshtein").unwrap(), Some(k as f64));
+                    found.sort_unstable_by_key(|&(i, _)| i);
+                    assert_eq!(found, expected, "{} {:?} {}", ngram, query, k);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn query_matches_brute_force_similarity_above_the_threshold() {
+        let mut rng = Rng::new(5930);
+        let choices = rng.strings(300, 10);
+        let metric = || Metric::from_name("normalized_levenshtein").unwrap();
+        let mut index = NGramIndex::new(2).unwrap();
+        index.extend(&choices);
+        for query in rng.strings(40, 10) {
+            // Choices have at most 10 characters, so every match passing the
+            // threshold is at most 2 edits away.
+            let longest = query.chars().count().max(10) as f64;
+            let threshold = 1.0 - 2.0 / longest;
+            let expected: Vec<(usize, f64)> = choices
+                .iter()
+                .map(|choice| strsim::normalized_levenshtein(&query, choice))
+                .enumerate()
+                .filter(|&(_, score)| score >= threshold)
+                .collect();
+            let mut found = index.query(&query, safe_min_shared(&index, &query, 2), metric(), Some(threshold));
+            found.sort_unstable_by_key(|&(i, _)| i);
+            assert_eq!(found, expected, "{:?}", query);
+        }
+    }
+}
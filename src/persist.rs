@@ -0,0 +1,252 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+// ------------------------------------------------------------------------
+//  Binary Encoding
+// ------------------------------------------------------------------------
+
+const MAGIC: &[u8; 8] = b"STRSIMIX";
+const VERSION: usize = 2;
+
+/// Writer of the compact binary format of saved indexes. Every file starts
+/// with magic bytes, a format version and the name of the class it holds.
+/// Unsigned integers are written as LEB128 varints, and ascending lists of
+/// indices as the varint gaps between them.
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn new(kind: &str) -> Self {
+        let mut encoder = Self { bytes: MAGIC.to_vec() };
+        encoder.usize(VERSION);
+        encoder.str(kind);
+        encoder
+    }
+
+    pub fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    pub fn usize(&mut self, value: usize) {
+        self.varint(value as u64);
+    }
+
+    /// Writes a full-width value, such as a hash.
+    pub fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn f64(&mut self, value: f64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.bytes.push(u8::from(value));
+    }
+
+    pub fn str(&mut self, value: &str) {
+        self.usize(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn strs<S: AsRef<str>>(&mut self, values: &[S]) {
+        self.usize(values.len());
+        for value in values {
+            self.str(value.as_ref());
+        }
+    }
+
+    /// Writes an optional index, `None` as zero and others shifted by one.
+    pub fn index(&mut self, value: Option<usize>) {
+        self.usize(value.map_or(0, |value| value + 1));
+    }
+
+    /// Writes an ascending list of indices as the gaps between them.
+    pub fn ascending(&mut self, values: &[usize]) {
+        self.usize(values.len());
+        let mut previous = 0;
+        for &value in values {
+            self.usize(value - previous);
+            previous = value;
+        }
+    }
+}
+
+/// Reader of the format written by `Encoder`. Lengths and indices are
+/// checked as they are read, so a truncated or corrupted file is reported as
+/// an `Error::Value` rather than building a broken index.
+pub struct Decoder<'a> {
+    kind: &'a str,
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn new(bytes: &'a [u8], kind: &'a str) -> Result<Self> {
+        let foreign = || Error::Value(format!("data is not a saved {}", kind));
+        let bytes = bytes.strip_prefix(MAGIC).ok_or_else(foreign)?;
+        let mut decoder = Self { kind, bytes };
+        let version = decoder.usize().map_err(|_| foreign())?;
+        if version != VERSION {
+            return Err(Error::Value(format!(
+                "saved {} has format version {}, expected {}",
+                kind, version, VERSION
+            )));
+        }
+        if decoder.str().map_err(|_| foreign())? != kind {
+            return Err(foreign());
+        }
+        Ok(decoder)
+    }
+
+    pub fn corrupt(&self) -> Error {
+        Error::Value(format!("saved {} is truncated or corrupted", self.kind))
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if n > self.bytes.len() {
+            return Err(self.corrupt());
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn varint(&mut self) -> Result<u64> {
+        let mut value: u64 = 0;
+        for shift in (0..u64::BITS).step_by(7) {
+            let byte = self.take(1)?[0];
+            let bits = u64::from(byte & 0x7f);
+            if bits.checked_shl(shift).is_none_or(|shifted| shifted >> shift != bits) {
+                return Err(self.corrupt());
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.corrupt())
+    }
+
+    pub fn usize(&mut self) -> Result<usize> {
+        let value = self.varint()?;
+        usize::try_from(value).map_err(|_| self.corrupt())
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("slice of 8 bytes")))
+    }
+
+    pub fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().expect("slice of 8 bytes")))
+    }
+
+    pub fn bool(&mut self) -> Result<bool> {
+        match self.take(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(self.corrupt()),
+        }
+    }
+
+    pub fn str(&mut self) -> Result<String> {
+        let len = self.usize()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| self.corrupt())
+    }
+
+    pub fn strs(&mut self) -> Result<Vec<String>> {
+        let len = self.length()?;
+        (0..len).map(|_| self.str()).collect()
+    }
+
+    /// Reads a length, which cannot exceed the bytes left since every item
+    /// takes at least one byte.
+    pub fn length(&mut self) -> Result<usize> {
+        let len = self.usize()?;
+        if len > self.bytes.len() {
+            return Err(self.corrupt());
+        }
+        Ok(len)
+    }
+
+    /// Reads an optional index, which must be below `bound`.
+    pub fn index(&mut self, bound: usize) -> Result<Option<usize>> {
+        match self.usize()? {
+            0 => Ok(None),
+            value if value <= bound => Ok(Some(value - 1)),
+            _ => Err(self.corrupt()),
+        }
+    }
+
+    /// Reads an ascending list of indices, which must be below `bound`.
+    pub fn ascending(&mut self, bound: usize) -> Result<Vec<usize>> {
+        let len = self.length()?;
+        let mut values = Vec::with_capacity(len);
+        let mut previous: usize = 0;
+        for _ in 0..len {
+            let value = previous.checked_add(self.usize()?).filter(|&value| value < bound);
+            previous = value.ok_or_else(|| self.corrupt())?;
+            values.push(previous);
+        }
+        Ok(values)
+    }
+
+    /// Fails unless `valid` holds, for checks of decoded values that the
+    /// reader cannot make itself.
+    pub fn check(&self, valid: bool) -> Result<()> {
+        if valid {
+            Ok(())
+        } else {
+            Err(self.corrupt())
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        self.check(self.bytes.is_empty())
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Persistent Indexes
+// ------------------------------------------------------------------------
+
+/// An index that can be saved to bytes or a file and loaded back without
+/// being rebuilt. Loading restores the index exactly, so it answers queries
+/// as the saved one did.
+pub trait Persist: Sized {
+    /// Class name recorded in the header of saved data.
+    const KIND: &'static str;
+
+    fn encode(&self, encoder: &mut Encoder);
+
+    fn decode(decoder: &mut Decoder) -> Result<Self>;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new(Self::KIND);
+        self.encode(&mut encoder);
+        encoder.bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = Decoder::new(bytes, Self::KIND)?;
+        let index = Self::decode(&mut decoder)?;
+        decoder.finish()?;
+        Ok(index)
+    }
+
+    fn write_file(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, self.to_bytes())?)
+    }
+
+    fn read_file(path: &Path) -> Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
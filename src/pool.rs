@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyOverflowError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use crate::error::{Error, Result};
+#[cfg(feature = "python")]
+use crate::error::InvalidParameterError;
+use crate::progress::Counter;
+
+// ------------------------------------------------------------------------
+//  Thread Pools
+// ------------------------------------------------------------------------
+
+/// Jobs of fewer items than this run on the calling thread, where handing
+/// them to the pool would take longer than the comparisons themselves.
+pub const SERIAL_LIMIT: usize = 128;
+
+/// Most threads a pool may have. Larger counts are almost certainly a
+/// mistake, such as a byte count read from the wrong setting, and would
+/// exhaust memory on thread stacks before doing any work.
+pub const MAX_THREADS: usize = 1024;
+
+/// A shared rayon pool. With the `python` feature its `install` releases the
+/// GIL while the work runs, so other Python threads keep running meanwhile.
+#[derive(Clone)]
+pub struct Pool(Arc<rayon::ThreadPool>);
+
+impl Pool {
+    #[cfg(feature = "python")]
+    pub fn install<T: Send, F: FnOnce() -> T + Send>(&self, op: F) -> T {
+        Python::with_gil(|py| py.allow_threads(|| self.0.install(op)))
+    }
+
+    #[cfg(not(feature = "python"))]
+    pub fn install<T: Send, F: FnOnce() -> T + Send>(&self, op: F) -> T {
+        self.0.install(op)
+    }
+
+    /// Runs a job over `len` items split into tasks of at least `min_len`
+    /// items, except that jobs below `SERIAL_LIMIT` run on the calling thread
+    /// as a single task. `op` is given an untracked counter and the smallest
+    /// number of items per task to use.
+    pub fn run_sized<T: Send, F: FnOnce(&Counter, usize) -> T + Send>(&self, len: usize, min_len: usize, op: F) -> T {
+        let counter = Counter::new(false);
+        if len < SERIAL_LIMIT {
+            return op(&counter, len.max(1));
+        }
+        self.install(|| op(&counter, min_len))
+    }
+}
+
+#[cfg(feature = "python")]
+impl Pool {
+    /// Like `install`, but while the work runs the calling thread wakes up
+    /// every `REPORT_INTERVAL` to check for signals and to pass its progress
+    /// to the callback of `reporter`. If a signal handler or the callback
+    /// raises, the job is cancelled and the error returned.
+    pub(crate) fn install_reporting<T: Send, F: FnOnce(&Counter) -> T + Send>(
+        &self,
+        reporter: &crate::progress::Reporter,
+        op: F,
+    ) -> PyResult<T> {
+        let counter = reporter.counter();
+        if !reporter.is_monitored() {
+            return Ok(self.install(|| op(counter)));
+        }
+        Python::with_gil(|py| {
+            std::thread::scope(|scope| {
+                let caller = std::thread::current();
+                let job = scope.spawn(move || {
+                    let output = self.0.install(|| op(counter));
+                    caller.unpark();
+                    output
+                });
+                while !job.is_finished() {
+                    py.allow_threads(|| std::thread::park_timeout(crate::progress::REPORT_INTERVAL));
+                    if let Err(err) = reporter.poll(py) {
+                        counter.cancel();
+                        py.allow_threads(|| job.join().ok());
+                        return Err(err);
+                    }
+                }
+                let output = job.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                reporter.report()?;
+                Ok(output)
+            })
+        })
+    }
+
+    /// Like `install_reporting` for a job over `len` items split into tasks
+    /// of at least `min_len` items, except that jobs below `SERIAL_LIMIT`
+    /// run on the calling thread as a single task. `op` is given the
+    /// counter and the smallest number of items per task to use.
+    pub(crate) fn install_sized<T: Send, F: FnOnce(&Counter, usize) -> T + Send>(
+        &self,
+        reporter: &crate::progress::Reporter,
+        len: usize,
+        min_len: usize,
+        op: F,
+    ) -> PyResult<T> {
+        if len < SERIAL_LIMIT {
+            let output = op(reporter.counter(), len.max(1));
+            reporter.report()?;
+            return Ok(output);
+        }
+        self.install_reporting(reporter, |counter| op(counter, min_len))
+    }
+}
+
+/// Pools built by the process `pid`. A child forked from it inherits the
+/// pools but none of their worker threads, so they are not used there.
+#[derive(Default)]
+struct Pools {
+    pid: u32,
+    by_size: HashMap<usize, Pool>,
+}
+
+/// Returns the shared pool of `n_threads` threads, or of one thread per
+/// available core when `n_threads` is `None` or 0. More than `MAX_THREADS`
+/// is an error. Pools are built on first use and kept for the lifetime of
+/// the process, one per distinct size. A process forked after using them
+/// builds its own on first use.
+pub fn thread_pool(n_threads: impl Into<Option<usize>>) -> Result<Pool> {
+    static POOLS: OnceLock<Mutex<Pools>> = OnceLock::new();
+
+    // `available_parallelism` reads the cgroup limits from disk on every
+    // call, which costs more than a small job itself.
+    static AVAILABLE: OnceLock<usize> = OnceLock::new();
+
+    let n_threads = match n_threads.into() {
+        Some(n_threads) if n_threads > MAX_THREADS => {
+            return Err(Error::Value(format!("n_threads must be at most {}, got {}", MAX_THREADS, n_threads)));
+        }
+        Some(n_threads) if n_threads > 0 => n_threads,
+        _ => *AVAILABLE.get_or_init(|| std::thread::available_parallelism().map_or(1, |n| n.get())),
+    };
+    let lock = || POOLS.get_or_init(Default::default).lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let pid = std::process::id();
+    {
+        let mut pools = lock();
+        if pools.pid != pid {
+            // Dropping the inherited pools would wake workers that only
+            // exist in the parent, and could wait on locks they held.
+            std::mem::take(&mut pools.by_size).into_values().for_each(std::mem::forget);
+            pools.pid = pid;
+        }
+        if let Some(pool) = pools.by_size.get(&n_threads) {
+            return Ok(pool.clone());
+        }
+    }
+    // The lock is not held while the threads start, so that a fork from
+    // another thread meanwhile leaves it free in the child.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(n_threads)
+        .build()
+        .map_err(|error| Error::ThreadPool(format!("failed to start {} threads: {}", n_threads, error)))?;
+    let pool = Pool(Arc::new(pool));
+    Ok(lock().by_size.entry(n_threads).or_insert(pool).clone())
+}
+
+// ------------------------------------------------------------------------
+//  Python
+// ------------------------------------------------------------------------
+
+/// Number of threads passed from Python, where `None` and 0 both mean one
+/// thread per available core, as they do for `thread_pool`. Negative counts
+/// and counts above `MAX_THREADS` are rejected with `InvalidParameterError`
+/// as the argument is read, instead of the `OverflowError` a plain unsigned
+/// integer would raise.
+#[cfg(feature = "python")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NThreads(Option<usize>);
+
+#[cfg(feature = "python")]
+impl NThreads {
+    /// One thread per available core, the default of every `n_threads`
+    /// argument.
+    pub const AUTO: Self = Self(None);
+
+    pub fn get(self) -> Option<usize> {
+        self.0
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<NThreads> for Option<usize> {
+    fn from(n_threads: NThreads) -> Self {
+        n_threads.0
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'source> FromPyObject<'source> for NThreads {
+    fn extract(obj: &'source PyAny) -> PyResult<Self> {
+        if obj.is_none() {
+            return Ok(Self::AUTO);
+        }
+        let invalid = || {
+            InvalidParameterError::new_err(format!(
+                "n_threads must be between 0 and {}, or None for all available cores, got {}",
+                MAX_THREADS, obj
+            ))
+        };
+        let n_threads: i128 = match obj.extract() {
+            Ok(n_threads) => n_threads,
+            Err(error) if error.is_instance_of::<PyOverflowError>(obj.py()) => return Err(invalid()),
+            Err(error) => return Err(error),
+        };
+        match usize::try_from(n_threads) {
+            Ok(n_threads) if n_threads <= MAX_THREADS => Ok(Self((n_threads > 0).then_some(n_threads))),
+            _ => Err(invalid()),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::bytes::Seq;
+use crate::cached::fold;
+use crate::input::Strings;
+use crate::metric::{Metric, Scorer};
+use crate::preprocess::Preprocessor;
+use crate::progress::Reporter;
+use crate::rank::{Ranked, TopK};
+use crate::pool::{thread_pool, NThreads};
+
+// ------------------------------------------------------------------------
+//  Prepared Forms
+// ------------------------------------------------------------------------
+
+/// A string reduced to what one metric needs to score it.
+enum Form {
+    /// Characters, for the edit distances and Jaro similarities.
+    Chars(Vec<char>),
+    /// Text without whitespace and its bigram counts sorted by bigram, for
+    /// the Sørensen–Dice similarity.
+    Bigrams(String, Vec<((char, char), usize)>),
+    /// The string itself, for metrics without a prepared form.
+    Text(String),
+}
+
+/// How a metric prepares and scores strings.
+#[derive(Clone, Copy)]
+enum Method {
+    Levenshtein,
+    NormalizedLevenshtein,
+    DamerauLevenshtein,
+    NormalizedDamerauLevenshtein,
+    Jaro,
+    JaroWinkler,
+    SorensenDice,
+    Other(Scorer),
+}
+
+impl Method {
+    fn new(name: &str, metric: Metric) -> Self {
+        match name {
+            "levenshtein" => Self::Levenshtein,
+            "normalized_levenshtein" => Self::NormalizedLevenshtein,
+            "damerau_levenshtein" => Self::DamerauLevenshtein,
+            "normalized_damerau_levenshtein" => Self::NormalizedDamerauLevenshtein,
+            "jaro" => Self::Jaro,
+            "jaro_winkler" => Self::JaroWinkler,
+            "sorensen_dice" => Self::SorensenDice,
+            _ => Self::Other(metric.score),
+        }
+    }
+
+    fn prepare(self, s: String) -> Form {
+        match self {
+            Self::SorensenDice => {
+                let (text, counts) = crate::dice::bigram_counts(&s);
+                Form::Bigrams(text, counts)
+            }
+            Self::Other(_) => Form::Text(s),
+            _ => Form::Chars(s.chars().collect()),
+        }
+    }
+
+    /// Scores two strings prepared by this method, exactly as the metric
+    /// scores the original strings.
+    fn score(self, a: &Form, b: &Form) -> f64 {
+        let normalized = |distance: usize, a: &[char], b: &[char]| {
+            if a.is_empty() && b.is_empty() {
+                1.0
+            } else {
+                1.0 - distance as f64 / a.len().max(b.len()) as f64
+            }
+        };
+        match (self, a, b) {
+            (Self::Levenshtein, Form::Chars(a), Form::Chars(b)) => levenshtein(a, b) as f64,
+            (Self::NormalizedLevenshtein, Form::Chars(a), Form::Chars(b)) => normalized(levenshtein(a, b), a, b),
+            (Self::DamerauLevenshtein, Form::Chars(a), Form::Chars(b)) => {
+                strsim::generic_damerau_levenshtein(a, b) as f64
+            }
+            (Self::NormalizedDamerauLevenshtein, Form::Chars(a), Form::Chars(b)) => {
+                normalized(strsim::generic_damerau_levenshtein(a, b), a, b)
+            }
+            (Self::Jaro, Form::Chars(a), Form::Chars(b)) => strsim::generic_jaro(&Seq(a), &Seq(b)),
+            (Self::JaroWinkler, Form::Chars(a), Form::Chars(b)) => strsim::generic_jaro_winkler(&Seq(a), &Seq(b)),
+            (Self::SorensenDice, Form::Bigrams(text_a, a), Form::Bigrams(text_b, b)) => {
+                crate::dice::bigram_dice(text_a, a, text_b, b)
+            }
+            (Self::Other(score), Form::Text(a), Form::Text(b)) => score(a, b),
+            _ => unreachable!("both strings are prepared by the same method"),
+        }
+    }
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    crate::cutoff::levenshtein_within(a, b, usize::MAX).expect("no distance exceeds usize::MAX")
+}
+
+
+// ------------------------------------------------------------------------
+//  Prepared Choices
+// ------------------------------------------------------------------------
+
+/// A list of choices preprocessed once for one metric, so that many queries
+/// can be scored against it without preparing the choices again for each
+/// one. Choices are optionally preprocessed and lowercased, and split into
+/// characters or bigram counts where the metric works on those. Created by
+/// `vectorized.prepare`.
+#[pyclass(module = "strsim")]
+pub struct PreparedChoices {
+    metric: Metric,
+    method: Method,
+    case_insensitive: bool,
+    preprocessor: Preprocessor,
+    forms: Vec<Form>,
+}
+
+impl PreparedChoices {
+    pub(crate) fn new(
+        name: &str,
+        choices: Strings,
+        case_insensitive: bool,
+        preprocessor: Option<Preprocessor>,
+        n_threads: NThreads,
+    ) -> PyResult<Self> {
+        let metric = Metric::from_name(name)?;
+        let mut prepared = Self {
+            metric,
+            method: Method::new(name, metric),
+            case_insensitive,
+            preprocessor: preprocessor.unwrap_or_default(),
+            forms: Vec::new(),
+        };
+        let choices = choices.as_strs()?;
+        let reporter = Reporter::new(None, Some(choices.len()));
+        prepared.forms = thread_pool(n_threads)?.install_sized(&reporter, choices.len(), 1, |_, min_len| {
+            choices.par_iter().with_min_len(min_len).map(|&choice| prepared.form(choice)).collect()
+        })?;
+        Ok(prepared)
+    }
+
+    /// `s` preprocessed, folded and prepared for the metric.
+    fn form(&self, s: &str) -> Form {
+        self.method.prepare(fold(&self.preprocessor.apply(s), self.case_insensitive).into_owned())
+    }
+}
+
+
+#[pymethods]
+impl PreparedChoices {
+    /// Calculates the scores between `query` and each prepared choice.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - String to compare to the choices
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Scores between `query` and each choice, in the order of
+    ///   the choices
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "($self, query, /, n_threads=None)")]
+    fn scores(&self, query: &str, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        let query = self.form(query);
+        let reporter = Reporter::new(None, Some(self.forms.len()));
+        let output = thread_pool(n_threads)?.install_sized(&reporter, self.forms.len(), 1, |counter, min_len| {
+            self.forms
+                .par_iter()
+                .with_min_len(min_len)
+                .map(|choice| (!counter.is_cancelled()).then(|| self.method.score(&query, choice)))
+                .collect::<Option<Vec<f64>>>()
+        })?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Finds the `k` best matches for `query` among the prepared choices.
+    /// Similarities rank higher scores first and distances lower scores first.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - String to compare to the choices
+    /// * `k` - Number of matches to return
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Up to `k` pairs of an index into the choices and its
+    ///   score, best match first
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(text_signature = "($self, query, k, /, n_threads=None)")]
+    fn top_k(&self, query: &str, k: usize, n_threads: NThreads) -> PyResult<Vec<(usize, f64)>> {
+        let query = self.form(query);
+        let reporter = Reporter::new(None, Some(self.forms.len()));
+        let best = thread_pool(n_threads)?.install_sized(&reporter, self.forms.len(), 1, |counter, min_len| {
+            self.forms
+                .par_iter()
+                .with_min_len(min_len)
+                .enumerate()
+                .fold(
+                    || TopK::new(k),
+                    |mut best, (i, choice)| {
+                        if !counter.is_cancelled() {
+                            best.push(Ranked::new(self.metric.kind, i, self.method.score(&query, choice)));
+                        }
+                        best
+                    },
+                )
+                .reduce(|| TopK::new(k), TopK::merge)
+        })?;
+        Ok(best.into_sorted_vec().into_iter().map(|r| (r.index, r.score)).collect())
+    }
+
+    /// Number of prepared choices.
+    fn __len__(&self) -> usize {
+        self.forms.len()
+    }
+}
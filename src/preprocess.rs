@@ -0,0 +1,832 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+use regex::{NoExpand, Regex, RegexBuilder};
+use unicode_normalization::char::{compose, is_combining_mark};
+use unicode_normalization::{is_nfc_quick, is_nfd_quick, is_nfkc_quick, is_nfkd_quick, IsNormalized, UnicodeNormalization};
+
+use crate::collation::Collation;
+use crate::error::InvalidParameterError;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::transliterate::transliterate;
+
+// ------------------------------------------------------------------------
+//  Preprocessing
+// ------------------------------------------------------------------------
+
+/// A Unicode normalization form, passed from Python by its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    Nfc,
+    Nfkc,
+    Nfd,
+    Nfkd,
+}
+
+impl Normalization {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nfc" => Ok(Self::Nfc),
+            "nfkc" => Ok(Self::Nfkc),
+            "nfd" => Ok(Self::Nfd),
+            "nfkd" => Ok(Self::Nfkd),
+            _ => Err(InvalidParameterError::new_err(format!(
+                "unknown normalization form '{}', expected 'nfc', 'nfkc', 'nfd' or 'nfkd'",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Nfc => "nfc",
+            Self::Nfkc => "nfkc",
+            Self::Nfd => "nfd",
+            Self::Nfkd => "nfkd",
+        }
+    }
+
+    /// `s` in this form. The quick check settles most strings, which are
+    /// usually normalized already, without copying them.
+    pub fn apply<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let quick = match self {
+            Self::Nfc => is_nfc_quick(s.chars()),
+            Self::Nfkc => is_nfkc_quick(s.chars()),
+            Self::Nfd => is_nfd_quick(s.chars()),
+            Self::Nfkd => is_nfkd_quick(s.chars()),
+        };
+        if quick == IsNormalized::Yes {
+            return Cow::Borrowed(s);
+        }
+        Cow::Owned(match self {
+            Self::Nfc => s.nfc().collect(),
+            Self::Nfkc => s.nfkc().collect(),
+            Self::Nfd => s.nfd().collect(),
+            Self::Nfkd => s.nfkd().collect(),
+        })
+    }
+}
+
+impl<'py> FromPyObject<'py> for Normalization {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        Self::from_name(obj.extract()?)
+    }
+}
+
+/// A variant of Unicode case folding, passed from Python by its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseFold {
+    /// Full case folding, which also expands characters such as "ß" to "ss".
+    Full,
+    /// Full case folding with the Turkic dotted and dotless I, where "I"
+    /// folds to "ı" and "İ" to "i".
+    Turkic,
+}
+
+impl CaseFold {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "full" => Ok(Self::Full),
+            "turkic" => Ok(Self::Turkic),
+            _ => Err(InvalidParameterError::new_err(format!(
+                "unknown case folding '{}', expected 'full' or 'turkic'",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Turkic => "turkic",
+        }
+    }
+}
+
+impl<'py> FromPyObject<'py> for CaseFold {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        Self::from_name(obj.extract()?)
+    }
+}
+
+/// Normalization applied in Rust to the strings of a metric call before they
+/// are compared, so that callers need not rewrite every string in Python
+/// first. Strings the options leave unchanged are borrowed, not copied.
+#[derive(Clone, Debug, Default)]
+pub struct Preprocess {
+    /// Steps to run before the options, in their own order.
+    pub preprocessor: Preprocessor,
+    /// Whether to lowercase the strings.
+    pub case_insensitive: bool,
+    /// Case folding to apply instead of lowercasing.
+    pub casefold: Option<CaseFold>,
+    /// Collation under which to compare the strings.
+    pub collation: Option<Collation>,
+    /// Unicode normalization form to bring the strings to.
+    pub normalize: Option<Normalization>,
+    /// Whether to replace full-width and half-width forms with the usual ones.
+    pub normalize_width: bool,
+    /// Whether to spell Cyrillic and Greek letters in Latin ones.
+    pub transliterate: bool,
+    /// Whether to remove accents and other combining marks.
+    pub strip_accents: bool,
+    /// Whether to remove punctuation and symbols.
+    pub strip_punctuation: bool,
+    /// Whether to replace runs of whitespace with a single space.
+    pub collapse_whitespace: bool,
+    /// Whether to remove leading and trailing whitespace.
+    pub trim: bool,
+}
+
+impl Preprocess {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        case_insensitive: bool,
+        casefold: Option<CaseFold>,
+        collation: Option<Collation>,
+        normalize: Option<Normalization>,
+        normalize_width: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        strip_punctuation: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+    ) -> Self {
+        Self {
+            preprocessor: Preprocessor::default(),
+            case_insensitive,
+            casefold,
+            collation,
+            normalize,
+            normalize_width,
+            transliterate,
+            strip_accents,
+            strip_punctuation,
+            collapse_whitespace,
+            trim,
+        }
+    }
+
+    /// Preprocessing that only lowercases, if `case_insensitive`.
+    pub fn lowercasing(case_insensitive: bool) -> Self {
+        Self { case_insensitive, ..Self::default() }
+    }
+
+    /// These options run after the steps of `preprocessor`, if any.
+    pub fn after(self, preprocessor: Option<Preprocessor>) -> Self {
+        Self { preprocessor: preprocessor.unwrap_or_default(), ..self }
+    }
+
+    /// Whether this leaves every string as it is.
+    pub fn is_empty(&self) -> bool {
+        self.preprocessor.steps.is_empty()
+            && !self.case_insensitive
+            && self.casefold.is_none()
+            && self.collation.is_none()
+            && self.normalize.is_none()
+            && !self.normalize_width
+            && !self.transliterate
+            && !self.strip_accents
+            && !self.strip_punctuation
+            && !self.collapse_whitespace
+            && !self.trim
+    }
+
+    /// `s` with the options applied. Normalization comes first, so that
+    /// compatibility forms such as full-width punctuation are already folded
+    /// when the later steps look at the characters, and case before accents,
+    /// so that the dot of a Turkic "İ" is still there to fold. The steps of
+    /// a preprocessor come before all of them.
+    pub fn apply<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let (mut s, mut decomposed) = self.preprocessor.run(s);
+        if let Some(form) = self.normalize {
+            s = changed(s, |s| form.apply(s));
+            decomposed = matches!(form, Normalization::Nfd | Normalization::Nfkd);
+        }
+        if self.normalize_width {
+            s = changed(s, |s| normalize_width(s, decomposed));
+        }
+        if self.transliterate {
+            s = changed(s, transliterate);
+        }
+        if let Some(collation) = &self.collation {
+            s = changed(s, |s| collation.key(s));
+            decomposed = false;
+        }
+        if let Some(fold) = self.casefold {
+            s = changed(s, |s| casefold(s, fold));
+        } else if self.case_insensitive {
+            s = changed(s, lowercase);
+        }
+        if self.strip_accents {
+            s = changed(s, |s| strip_accents(s, decomposed));
+        }
+        if self.strip_punctuation {
+            s = changed(s, strip_punctuation);
+        }
+        if self.collapse_whitespace {
+            s = changed(s, collapse_whitespace);
+        }
+        if self.trim {
+            s = changed(s, |s| Cow::Borrowed(s.trim()));
+        }
+        s
+    }
+
+    /// `f` applied to both strings after preprocessing them.
+    pub fn wrap<'a, T>(&'a self, f: impl Fn(&str, &str) -> T + Sync + 'a) -> impl Fn(&str, &str) -> T + Sync + 'a {
+        move |a, b| f(&self.apply(a), &self.apply(b))
+    }
+
+    /// `f` applied after preprocessing only its second string, for calls
+    /// that preprocess the first string once up front.
+    pub fn wrap_second<'a, T>(&'a self, f: impl Fn(&str, &str) -> T + Sync + 'a) -> impl Fn(&str, &str) -> T + Sync + 'a {
+        move |a, b| f(a, &self.apply(b))
+    }
+}
+
+/// `s` after `step`, keeping it borrowed when the step changes nothing. A
+/// step returns a borrowed slice of its input, of the same length when it
+/// leaves the string as is.
+fn changed<'a>(s: Cow<'a, str>, step: impl Fn(&str) -> Cow<'_, str>) -> Cow<'a, str> {
+    match s {
+        Cow::Borrowed(s) => step(s),
+        Cow::Owned(s) => match step(&s) {
+            Cow::Borrowed(stepped) if stepped.len() == s.len() => Cow::Owned(s),
+            stepped => Cow::Owned(stepped.into_owned()),
+        },
+    }
+}
+
+/// `chars` collected into a string, or `s` itself if that is the same.
+fn rebuilt(s: &str, chars: impl Iterator<Item = char>) -> Cow<'_, str> {
+    let rebuilt: String = chars.collect();
+    if rebuilt == s {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(rebuilt)
+    }
+}
+
+fn lowercase(s: &str) -> Cow<'_, str> {
+    if s.chars().all(|c| c.to_lowercase().eq([c])) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.to_lowercase())
+    }
+}
+
+/// Full-width katakana and punctuation for the half-width forms U+FF61 to
+/// U+FF9F, with the half-width voiced sound marks as combining ones.
+const HALFWIDTH_KATAKANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン\u{3099}\u{309a}";
+
+/// The usual form of a character of the Halfwidth and Fullwidth Forms block
+/// or of the ideographic space, if it has one. This is the single step of
+/// their compatibility decompositions, which would go on to split "￣" into a
+/// space and a combining macron and Hangul letters into conjoining jamo.
+fn width_variant(c: char) -> Option<char> {
+    let offset = |from: u32| char::from_u32(c as u32 - from);
+    match c {
+        '\u{3000}' => Some(' '),
+        '\u{ff01}'..='\u{ff5e}' => offset(0xfee0),
+        '\u{ff5f}' => Some('\u{2985}'),
+        '\u{ff60}' => Some('\u{2986}'),
+        '\u{ff61}'..='\u{ff9f}' => HALFWIDTH_KATAKANA.chars().nth(c as usize - 0xff61),
+        '\u{ffa0}' => Some('\u{3164}'),
+        '\u{ffa1}'..='\u{ffbe}' => offset(0xce70),
+        '\u{ffc2}'..='\u{ffc7}' => offset(0xce73),
+        '\u{ffca}'..='\u{ffcf}' => offset(0xce75),
+        '\u{ffd2}'..='\u{ffd7}' => offset(0xce77),
+        '\u{ffda}'..='\u{ffdc}' => offset(0xce79),
+        '\u{ffe0}' => Some('¢'),
+        '\u{ffe1}' => Some('£'),
+        '\u{ffe2}' => Some('¬'),
+        '\u{ffe3}' => Some('¯'),
+        '\u{ffe4}' => Some('¦'),
+        '\u{ffe5}' => Some('¥'),
+        '\u{ffe6}' => Some('₩'),
+        '\u{ffe8}' => Some('│'),
+        '\u{ffe9}' => Some('←'),
+        '\u{ffea}' => Some('↑'),
+        '\u{ffeb}' => Some('→'),
+        '\u{ffec}' => Some('↓'),
+        '\u{ffed}' => Some('■'),
+        '\u{ffee}' => Some('○'),
+        _ => None,
+    }
+}
+
+/// `s` with full-width letters, digits and symbols in their usual width and
+/// half-width katakana and Hangul in full width, so that "ＡＢＣ" becomes
+/// "ABC" and "ｶﾞ" becomes "ガ", or "カ" and a combining voiced sound mark if
+/// `decomposed`. Other characters, such as the compatibility ideographs
+/// `normalize="nfkc"` would also replace, are kept.
+fn normalize_width(s: &str, decomposed: bool) -> Cow<'_, str> {
+    if s.is_ascii() || !s.chars().any(|c| width_variant(c).is_some()) {
+        return Cow::Borrowed(s);
+    }
+    let mut normalized = String::with_capacity(s.len());
+    for c in s.chars() {
+        let c = width_variant(c).unwrap_or(c);
+        let voiced = match c {
+            '\u{3099}' | '\u{309a}' if !decomposed => normalized.chars().last().and_then(|kana| compose(kana, c)),
+            _ => None,
+        };
+        if let Some(voiced) = voiced {
+            normalized.pop();
+            normalized.push(voiced);
+        } else {
+            normalized.push(c);
+        }
+    }
+    Cow::Owned(normalized)
+}
+
+/// `s` with full Unicode case folding, under which strings that differ only
+/// in case compare equal even where lowercasing leaves them apart, as "ß" and
+/// "SS" or "ς" and "Σ". The folding of a character is its lowercase
+/// uppercased and lowercased again, which the Unicode case tables make agree
+/// with `CaseFolding.txt` except for the dotless "ı", folded to itself.
+pub(crate) fn casefold(s: &str, fold: CaseFold) -> Cow<'_, str> {
+    if s.is_ascii() && fold == CaseFold::Full {
+        return lowercase(s);
+    }
+    let mut folded = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // A decomposed "İ" is "I" followed by a combining dot above.
+            'I' if fold == CaseFold::Turkic => {
+                folded.push(if chars.next_if_eq(&'\u{307}').is_some() { 'i' } else { 'ı' });
+            }
+            'İ' if fold == CaseFold::Turkic => folded.push('i'),
+            'ı' => folded.push(c),
+            _ => {
+                for lower in c.to_lowercase() {
+                    for upper in lower.to_uppercase() {
+                        folded.extend(upper.to_lowercase());
+                    }
+                }
+            }
+        }
+    }
+    if folded == s {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(folded)
+    }
+}
+
+/// `s` without combining marks, so that "José" becomes "Jose". Characters are
+/// decomposed first to separate their accents, and the rest recomposed
+/// unless `decomposed`. Letters such as "ø" that have no decomposition are
+/// kept.
+fn strip_accents(s: &str, decomposed: bool) -> Cow<'_, str> {
+    if s.is_ascii() {
+        return Cow::Borrowed(s);
+    }
+    let stripped = s.nfd().filter(|&c| !is_combining_mark(c));
+    if decomposed {
+        rebuilt(s, stripped)
+    } else {
+        rebuilt(s, stripped.nfc())
+    }
+}
+
+/// Whether `c` is kept by `strip_punctuation`: letters, digits, whitespace,
+/// and the combining marks that belong to them.
+fn is_word_or_space(c: char) -> bool {
+    c.is_alphanumeric() || c.is_whitespace() || is_combining_mark(c)
+}
+
+/// `s` without punctuation and symbols.
+fn strip_punctuation(s: &str) -> Cow<'_, str> {
+    if s.chars().all(is_word_or_space) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.chars().filter(|&c| is_word_or_space(c)).collect())
+    }
+}
+
+/// `s` with every run of whitespace replaced by a single space.
+fn collapse_whitespace(s: &str) -> Cow<'_, str> {
+    if !s.contains("  ") && !s.contains(|c: char| c.is_whitespace() && c != ' ') {
+        return Cow::Borrowed(s);
+    }
+    let mut collapsed = String::with_capacity(s.len());
+    let mut in_space = false;
+    for c in s.chars() {
+        if !c.is_whitespace() {
+            collapsed.push(c);
+            in_space = false;
+        } else if !in_space {
+            collapsed.push(' ');
+            in_space = true;
+        }
+    }
+    Cow::Owned(collapsed)
+}
+
+
+// ------------------------------------------------------------------------
+//  Preprocessing Pipelines
+// ------------------------------------------------------------------------
+
+/// Flags of Python's `re` that a substitution pattern may carry.
+const IGNORECASE: u64 = 2;
+const MULTILINE: u64 = 8;
+const DOTALL: u64 = 16;
+const UNICODE: u64 = 32;
+const VERBOSE: u64 = 64;
+
+/// Replacement of the matches of a regular expression, compiled once by the
+/// `regex` crate, so that it runs without the GIL.
+#[derive(Debug)]
+struct Substitution {
+    pattern: Regex,
+    /// Source and `re` flags of the pattern, to save and show it.
+    source: String,
+    flags: u64,
+    replacement: String,
+}
+
+impl Substitution {
+    /// Compiles `source` with the `re` flags `flags`, of which
+    /// `re.IGNORECASE`, `re.MULTILINE`, `re.DOTALL` and `re.VERBOSE` are
+    /// supported. `re.UNICODE` is the default for strings anyway.
+    fn new(source: String, flags: u64, replacement: String) -> crate::Result<Self> {
+        if flags & !(IGNORECASE | MULTILINE | DOTALL | UNICODE | VERBOSE) != 0 {
+            return Err(crate::Error::value(format!(
+                "unsupported flags {:#x} of substitution pattern '{}', expected re.IGNORECASE, re.MULTILINE, re.DOTALL or re.VERBOSE",
+                flags, source
+            )));
+        }
+        let pattern = RegexBuilder::new(&source)
+            .case_insensitive(flags & IGNORECASE != 0)
+            .multi_line(flags & MULTILINE != 0)
+            .dot_matches_new_line(flags & DOTALL != 0)
+            .ignore_whitespace(flags & VERBOSE != 0)
+            .build()
+            .map_err(|err| crate::Error::value(format!("invalid substitution pattern '{}': {}", source, err)))?;
+        Ok(Self { pattern, source, flags, replacement })
+    }
+
+    /// The substitution of a step, whose pattern is a string or a compiled
+    /// `re.Pattern` of strings.
+    fn extract(pattern: &PyAny, replacement: String) -> PyResult<Self> {
+        let (source, flags) = match pattern.extract::<String>() {
+            Ok(source) => (source, 0),
+            Err(_) => (
+                pattern.getattr("pattern").and_then(|source| source.extract::<String>()).map_err(|_| {
+                    PyTypeError::new_err("substitution pattern must be a string or a compiled regular expression of strings")
+                })?,
+                pattern.getattr("flags")?.extract()?,
+            ),
+        };
+        Ok(Self::new(source, flags, replacement)?)
+    }
+
+    /// `s` with the matches replaced by `replacement`, taken literally.
+    fn apply<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        self.pattern.replace_all(s, NoExpand(&self.replacement))
+    }
+
+    /// The pattern as it is given to `Preprocessor`: its source, or a
+    /// compiled `re.Pattern` if it has flags.
+    fn pattern_object(&self, py: Python<'_>) -> PyResult<PyObject> {
+        if self.flags & !UNICODE == 0 {
+            return Ok(self.source.clone().into_py(py));
+        }
+        Ok(py.import("re")?.call_method1("compile", (&self.source, self.flags))?.into())
+    }
+}
+
+/// One step of a `Preprocessor`.
+#[derive(Clone, Debug)]
+enum Step {
+    Lowercase,
+    CaseFold(CaseFold),
+    Collate(Collation),
+    Normalize(Normalization),
+    NormalizeWidth,
+    Transliterate,
+    StripAccents,
+    StripPunctuation,
+    CollapseWhitespace,
+    Trim,
+    Substitute(Arc<Substitution>),
+}
+
+impl Step {
+    fn from_name(name: &str) -> PyResult<Self> {
+        Ok(match name {
+            "lowercase" => Self::Lowercase,
+            "casefold" => Self::CaseFold(CaseFold::Full),
+            "normalize_width" => Self::NormalizeWidth,
+            "transliterate" => Self::Transliterate,
+            "strip_accents" => Self::StripAccents,
+            "strip_punctuation" => Self::StripPunctuation,
+            "collapse_whitespace" => Self::CollapseWhitespace,
+            "trim" => Self::Trim,
+            _ => match Normalization::from_name(name) {
+                Ok(form) => Self::Normalize(form),
+                Err(_) => {
+                    return Err(InvalidParameterError::new_err(format!("unknown preprocessing step '{}'", name)));
+                }
+            },
+        })
+    }
+
+    /// `s` after this step, given whether it is decomposed.
+    fn apply<'a>(&self, s: &'a str, decomposed: bool) -> Cow<'a, str> {
+        match self {
+            Self::Lowercase => lowercase(s),
+            Self::CaseFold(fold) => casefold(s, *fold),
+            Self::Collate(collation) => collation.key(s),
+            Self::Normalize(form) => form.apply(s),
+            Self::NormalizeWidth => normalize_width(s, decomposed),
+            Self::Transliterate => transliterate(s),
+            Self::StripAccents => strip_accents(s, decomposed),
+            Self::StripPunctuation => strip_punctuation(s),
+            Self::CollapseWhitespace => collapse_whitespace(s),
+            Self::Trim => Cow::Borrowed(s.trim()),
+            Self::Substitute(substitution) => substitution.apply(s),
+        }
+    }
+
+    /// Whether a string is decomposed after this step, given whether it was.
+    fn decomposes(&self, decomposed: bool) -> bool {
+        match self {
+            Self::Normalize(form) => matches!(form, Normalization::Nfd | Normalization::Nfkd),
+            Self::Collate(_) => false,
+            _ => decomposed,
+        }
+    }
+
+    /// The step as it is given to `Preprocessor`.
+    fn to_object(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(match self {
+            Self::Lowercase => "lowercase".into_py(py),
+            Self::CaseFold(CaseFold::Full) => "casefold".into_py(py),
+            Self::CaseFold(fold) => ("casefold", fold.name()).into_py(py),
+            Self::Collate(collation) => ("collation", collation.tag()).into_py(py),
+            Self::Normalize(form) => form.name().into_py(py),
+            Self::NormalizeWidth => "normalize_width".into_py(py),
+            Self::Transliterate => "transliterate".into_py(py),
+            Self::StripAccents => "strip_accents".into_py(py),
+            Self::StripPunctuation => "strip_punctuation".into_py(py),
+            Self::CollapseWhitespace => "collapse_whitespace".into_py(py),
+            Self::Trim => "trim".into_py(py),
+            Self::Substitute(substitution) => ("sub", substitution.pattern_object(py)?, &substitution.replacement).into_py(py),
+        })
+    }
+}
+
+impl<'py> FromPyObject<'py> for Step {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if let Ok(name) = obj.extract::<&str>() {
+            return Self::from_name(name);
+        }
+        let Ok(step) = obj.downcast::<PyTuple>() else {
+            return Err(PyTypeError::new_err(format!(
+                "preprocessing step must be a name or a tuple, got '{}'",
+                obj.get_type().name()?
+            )));
+        };
+        let name: &str = step.get_item(0)?.extract()?;
+        match (name, step.len()) {
+            ("casefold", 2) => Ok(Self::CaseFold(step.get_item(1)?.extract()?)),
+            ("collation", 2) => Ok(Self::Collate(step.get_item(1)?.extract()?)),
+            ("normalize", 2) => Ok(Self::Normalize(step.get_item(1)?.extract()?)),
+            ("sub", 3) => {
+                let substitution = Substitution::extract(step.get_item(1)?, step.get_item(2)?.extract()?)?;
+                Ok(Self::Substitute(Arc::new(substitution)))
+            }
+            _ => Err(InvalidParameterError::new_err(format!("unknown preprocessing step {}", obj.repr()?))),
+        }
+    }
+}
+
+/// Ordered list of preprocessing steps, checked once when it is built and
+/// then run in Rust on every string it is given. It can be passed as the
+/// `preprocessor` of any metric, whose other preprocessing options then run
+/// after its steps, or of any index, which applies it to the indexed and the
+/// query strings and saves it with them, and it can be called on a string
+/// directly.
+/// 
+/// Each step is a name or a tuple:
+/// 
+/// * `"lowercase"` - Lowercases the string
+/// * `"casefold"` or `("casefold", "turkic")` - Applies full Unicode case
+///   folding, or its Turkic variant
+/// * `("collation", locale)` - Replaces the string with its collation key
+///   under a locale, as the `collation` option of the metrics does
+/// * `"nfc"`, `"nfkc"`, `"nfd"`, `"nfkd"` or `("normalize", form)` - Brings
+///   the string to a Unicode normalization form
+/// * `"normalize_width"` - Replaces full-width and half-width forms with the
+///   usual ones
+/// * `"transliterate"` - Spells Cyrillic and Greek letters in Latin ones
+/// * `"strip_accents"` - Removes accents and other combining marks
+/// * `"strip_punctuation"` - Removes punctuation and symbols
+/// * `"collapse_whitespace"` - Replaces every run of whitespace with a single
+///   space
+/// * `"trim"` - Removes leading and trailing whitespace
+/// * `("sub", pattern, replacement)` - Replaces the matches of a regular
+///   expression, a string or a compiled `re.Pattern`, with `replacement`
+///   taken literally. The pattern is compiled once by Rust's `regex` crate,
+///   which has no lookaround or backreferences, and runs in parallel like
+///   the other steps
+/// 
+/// # Arguments
+/// 
+/// * `steps` - Steps to run, in order
+#[pyclass(module = "strsim", text_signature = "(steps=(), /)")]
+#[derive(Clone, Debug, Default)]
+pub struct Preprocessor {
+    steps: Vec<Step>,
+}
+
+impl Preprocessor {
+    /// `s` after the steps, and whether it is left decomposed.
+    fn run<'a>(&self, s: &'a str) -> (Cow<'a, str>, bool) {
+        let mut s = Cow::Borrowed(s);
+        let mut decomposed = false;
+        for step in &self.steps {
+            s = changed(s, |s| step.apply(s, decomposed));
+            decomposed = step.decomposes(decomposed);
+        }
+        (s, decomposed)
+    }
+
+    /// `s` after the steps.
+    pub fn apply<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        self.run(s).0
+    }
+}
+
+impl Persist for Preprocessor {
+    const KIND: &'static str = "Preprocessor";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.usize(self.steps.len());
+        for step in &self.steps {
+            match step {
+                Step::Lowercase => encoder.usize(0),
+                Step::CaseFold(fold) => {
+                    encoder.usize(1);
+                    encoder.str(fold.name());
+                }
+                Step::Normalize(form) => {
+                    encoder.usize(2);
+                    encoder.str(form.name());
+                }
+                Step::NormalizeWidth => encoder.usize(3),
+                Step::Transliterate => encoder.usize(4),
+                Step::StripAccents => encoder.usize(5),
+                Step::StripPunctuation => encoder.usize(6),
+                Step::CollapseWhitespace => encoder.usize(7),
+                Step::Trim => encoder.usize(8),
+                Step::Collate(collation) => {
+                    encoder.usize(10);
+                    encoder.str(collation.tag());
+                }
+                Step::Substitute(substitution) => {
+                    encoder.usize(9);
+                    encoder.str(&substitution.source);
+                    encoder.varint(substitution.flags);
+                    encoder.str(&substitution.replacement);
+                }
+            }
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let steps = (0..decoder.length()?)
+            .map(|_| {
+                Ok(match decoder.usize()? {
+                    0 => Step::Lowercase,
+                    1 => Step::CaseFold(CaseFold::from_name(&decoder.str()?)?),
+                    2 => Step::Normalize(Normalization::from_name(&decoder.str()?)?),
+                    3 => Step::NormalizeWidth,
+                    4 => Step::Transliterate,
+                    5 => Step::StripAccents,
+                    6 => Step::StripPunctuation,
+                    7 => Step::CollapseWhitespace,
+                    8 => Step::Trim,
+                    9 => {
+                        let (source, flags, replacement) = (decoder.str()?, decoder.varint()?, decoder.str()?);
+                        Step::Substitute(Arc::new(Substitution::new(source, flags, replacement)?))
+                    }
+                    10 => Step::Collate(Collation::from_tag(&decoder.str()?)?),
+                    _ => return Err(decoder.corrupt()),
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(Self { steps })
+    }
+}
+
+#[pymethods]
+impl Preprocessor {
+    #[new]
+    #[args(steps = "None")]
+    pub(crate) fn new(steps: Option<&PyAny>) -> PyResult<Self> {
+        let Some(steps) = steps else {
+            return Ok(Self::default());
+        };
+        if steps.extract::<&str>().is_ok() {
+            return Err(PyTypeError::new_err("steps must be an iterable of steps, not a string"));
+        }
+        let steps = steps.iter()?.map(|step| step?.extract()).collect::<PyResult<_>>()?;
+        Ok(Self { steps })
+    }
+
+    /// Steps of the preprocessor, in order, as they are given to it.
+    #[getter]
+    fn steps(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.steps.iter().map(|step| step.to_object(py)).collect()
+    }
+
+    /// Preprocesses `s`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `s` - String to preprocess
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - `s` after the steps
+    fn __call__(&self, s: &str) -> String {
+        self.apply(s).into_owned()
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("Preprocessor({})", self.steps(py)?.into_py(py).as_ref(py).repr()?))
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of steps.
+    fn __len__(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `Preprocessor` of the steps the Python expression `steps` lists.
+    fn preprocessor(steps: &str) -> PyResult<Preprocessor> {
+        Python::with_gil(|py| Preprocessor::new(Some(py.eval(steps, None, None)?)))
+    }
+
+    #[test]
+    fn substitutions_replace_literally_without_the_gil() {
+        let clean = preprocessor(r"['casefold', ('sub', r'\d+', r'\1#'), ('sub', __import__('re').compile('^the ', 2), '')]").unwrap();
+        // Worker threads that never take the GIL run every step.
+        let cleaned: Vec<String> = std::thread::scope(|scope| {
+            let workers: Vec<_> = ["The 12 Apes", "café 7", "theme"]
+                .into_iter()
+                .map(|s| scope.spawn(|| clean.apply(s).into_owned()))
+                .collect();
+            workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+        });
+        assert_eq!(cleaned, [r"\1# apes", r"café \1#", "theme"]);
+        assert!(matches!(clean.apply("no digits"), Cow::Borrowed("no digits")));
+
+        let loaded = Preprocessor::from_bytes(&clean.to_bytes()).unwrap();
+        assert_eq!(loaded.apply("THE 3 Kings"), r"\1# kings");
+        Python::with_gil(|py| {
+            let steps = loaded.steps(py).unwrap().into_py(py);
+            let steps = steps.as_ref(py).repr().unwrap().to_string();
+            assert_eq!(steps, r"['casefold', ('sub', '\\d+', '\\1#'), ('sub', re.compile('^the ', re.IGNORECASE), '')]");
+        });
+    }
+
+    #[test]
+    fn unsupported_substitutions_are_rejected() {
+        Python::with_gil(|py| {
+            for steps in [
+                "[('sub', '(?<=a)b', '')]",
+                r"[('sub', r'(a)\1', '')]",
+                "[('sub', __import__('re').compile('a', __import__('re').ASCII), '')]",
+            ] {
+                assert!(matches!(preprocessor(steps), Err(err) if err.is_instance_of::<pyo3::exceptions::PyValueError>(py)));
+            }
+            for steps in ["[('sub', b'a', '')]", "[('sub', 1, '')]"] {
+                assert!(matches!(preprocessor(steps), Err(err) if err.is_instance_of::<PyTypeError>(py)));
+            }
+        });
+    }
+}
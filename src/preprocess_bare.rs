@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+
+use crate::persist::{Decoder, Encoder, Persist};
+
+// ------------------------------------------------------------------------
+//  Preprocessing Pipelines
+// ------------------------------------------------------------------------
+
+/// Preprocessor of a build without Python, which has no steps. Indexes hold
+/// one in place of the Python `Preprocessor`, and files saved by Python with
+/// a non-empty one fail to load.
+#[derive(Clone, Debug, Default)]
+pub struct Preprocessor {}
+
+impl Preprocessor {
+    /// `s` as it is.
+    pub fn apply<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(s)
+    }
+}
+
+impl Persist for Preprocessor {
+    const KIND: &'static str = "Preprocessor";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.usize(0);
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        if decoder.length()? != 0 {
+            return Err(crate::Error::value("the saved preprocessor needs the python feature"));
+        }
+        Ok(Self {})
+    }
+}
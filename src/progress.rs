@@ -0,0 +1,113 @@
+#[cfg(feature = "python")]
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+// ------------------------------------------------------------------------
+//  Progress Reporting
+// ------------------------------------------------------------------------
+
+/// How long the calling thread waits between progress reports and signal
+/// checks while a batch job runs.
+pub const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Jobs of fewer comparisons than this, without a callback, run without being
+/// watched. They finish before an interrupt would be noticed anyway.
+pub const UNMONITORED_LIMIT: usize = 10_000;
+
+/// Comparisons completed by the workers of a batch job, and whether the job
+/// should stop early. Workers advance it as they go and check
+/// `is_cancelled` before starting more work. Untracked counters ignore
+/// `advance`, so that workers do not contend over a count nobody reads.
+#[derive(Debug, Default)]
+pub struct Counter {
+    tracked: bool,
+    completed: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Counter {
+    pub fn new(tracked: bool) -> Self {
+        Self { tracked, ..Self::default() }
+    }
+
+    /// Records `n` more completed comparisons.
+    pub fn advance(&self, n: usize) {
+        if self.tracked {
+            self.completed.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of comparisons completed so far.
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// Asks the workers to skip their remaining work.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the job has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Watches a batch job from the calling thread: checks for signals such as
+/// `KeyboardInterrupt`, and reports progress to an optional Python callback,
+/// called with the number of completed comparisons and the total number of
+/// comparisons, or `None` when the total is not known up front.
+#[cfg(feature = "python")]
+pub struct Reporter<'py> {
+    callback: Option<&'py PyAny>,
+    total: Option<usize>,
+    counter: Counter,
+    reported: Cell<Option<usize>>,
+}
+
+#[cfg(feature = "python")]
+impl<'py> Reporter<'py> {
+    pub fn new(callback: Option<&'py PyAny>, total: Option<usize>) -> Self {
+        Self {
+            callback,
+            total,
+            counter: Counter::new(callback.is_some()),
+            reported: Cell::new(None),
+        }
+    }
+
+    /// Whether the job should be watched while it runs: always with a
+    /// callback, and otherwise unless it is known to be small.
+    pub fn is_monitored(&self) -> bool {
+        self.callback.is_some() || self.total.is_none_or(|total| total >= UNMONITORED_LIMIT)
+    }
+
+    /// The counter shared with the workers.
+    pub fn counter(&self) -> &Counter {
+        &self.counter
+    }
+
+    /// Raises any pending signal, such as `KeyboardInterrupt`, then reports
+    /// progress.
+    pub fn poll(&self, py: Python<'_>) -> PyResult<()> {
+        py.check_signals()?;
+        self.report()
+    }
+
+    /// Calls the callback if any comparisons completed since the last call,
+    /// or if it has not been called yet.
+    pub fn report(&self) -> PyResult<()> {
+        if let Some(callback) = self.callback {
+            let completed = self.counter.completed();
+            if self.reported.get() != Some(completed) {
+                callback.call1((completed, self.total))?;
+                self.reported.set(Some(completed));
+            }
+        }
+        Ok(())
+    }
+}
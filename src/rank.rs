@@ -0,0 +1,109 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::metric::Kind;
+
+// ------------------------------------------------------------------------
+//  Ranked Candidates
+// ------------------------------------------------------------------------
+
+/// The score of a candidate together with its index, ordered so that better
+/// matches compare greater. Equal scores rank the lower index higher.
+#[derive(Clone, Copy, Debug)]
+pub struct Ranked {
+    pub index: usize,
+    pub score: f64,
+    key: f64,
+}
+
+impl Ranked {
+    pub fn new(kind: Kind, index: usize, score: f64) -> Self {
+        let key = match kind {
+            Kind::Distance => -score,
+            Kind::Similarity => score,
+        };
+        Self { index, score, key }
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .total_cmp(&other.key)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+
+// ------------------------------------------------------------------------
+//  Bounded Selection
+// ------------------------------------------------------------------------
+
+/// Keeps the `k` best candidates seen so far in a bounded heap whose top is
+/// the worst of them.
+#[derive(Clone, Debug)]
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<Ranked>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k.saturating_add(1).min(1024)),
+        }
+    }
+
+    /// Offers a candidate, dropping the worst one if more than `k` are kept.
+    pub fn push(&mut self, candidate: Ranked) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(candidate));
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if candidate > worst.0 {
+                *worst = Reverse(candidate);
+            }
+        }
+    }
+
+    /// Score of the worst kept candidate once `k` are kept. Only candidates
+    /// at least this good can still enter the selection.
+    pub fn cutoff(&self) -> Option<f64> {
+        if self.heap.len() < self.k {
+            None
+        } else {
+            self.heap.peek().map(|worst| worst.0.score)
+        }
+    }
+
+    /// Combines the candidates kept by two selections.
+    pub fn merge(mut self, other: Self) -> Self {
+        for Reverse(candidate) in other.heap {
+            self.push(candidate);
+        }
+        self
+    }
+
+    /// The kept candidates, best first.
+    pub fn into_sorted_vec(self) -> Vec<Ranked> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(candidate)| candidate)
+            .collect()
+    }
+}
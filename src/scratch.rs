@@ -0,0 +1,275 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------
+//  Per-Thread Scratch Buffers
+// ------------------------------------------------------------------------
+
+/// Buffers lent to one comparison at a time on each thread, so that scoring
+/// millions of short strings does not allocate per pair. Buffers grown past
+/// `KEEP` elements by a long comparison are shrunk back afterwards, so one
+/// long string does not pin its memory to the thread.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    pub(crate) a: Vec<char>,
+    pub(crate) b: Vec<char>,
+    pub(crate) rows: [Vec<usize>; 3],
+    flags: Vec<bool>,
+    last_row: HashMap<char, usize>,
+    bigrams: HashMap<(char, char), usize>,
+}
+
+const KEEP: usize = 1 << 16;
+
+impl Scratch {
+    fn trim(&mut self) {
+        for buffer in [&mut self.a, &mut self.b] {
+            buffer.shrink_to(KEEP);
+        }
+        for row in &mut self.rows {
+            row.shrink_to(KEEP);
+        }
+        self.flags.shrink_to(KEEP);
+        self.last_row.shrink_to(KEEP);
+        self.bigrams.shrink_to(KEEP);
+    }
+}
+
+/// Runs `f` with the scratch buffers of the calling thread, or with fresh
+/// ones if a comparison on this thread already holds them.
+pub(crate) fn with_scratch<T>(f: impl FnOnce(&mut Scratch) -> T) -> T {
+    thread_local! {
+        static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::default());
+    }
+    SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut scratch) => {
+            let output = f(&mut scratch);
+            scratch.trim();
+            output
+        }
+        Err(_) => f(&mut Scratch::default()),
+    })
+}
+
+/// Replaces the contents of `buffer` with `chars`.
+pub(crate) fn fill(buffer: &mut Vec<char>, chars: impl Iterator<Item = char>) {
+    buffer.clear();
+    buffer.extend(chars);
+}
+
+// ------------------------------------------------------------------------
+//  Metrics
+// ------------------------------------------------------------------------
+//  The metrics of `strsim` 0.10 over characters, step for step, with their
+//  per-call vectors and maps taken from the scratch buffers instead.
+
+/// `strsim::levenshtein` without allocating.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    with_scratch(|scratch| {
+        fill(&mut scratch.b, b.chars());
+        let b = &scratch.b;
+        if a.is_empty() {
+            return b.len();
+        }
+        let cache = &mut scratch.rows[0];
+        cache.clear();
+        cache.extend(1..=b.len());
+        let mut result = 0;
+        for (i, x) in a.chars().enumerate() {
+            result = i + 1;
+            let mut distance_b = i;
+            for (cell, &y) in cache.iter_mut().zip(b) {
+                let distance_a = distance_b + usize::from(x != y);
+                distance_b = *cell;
+                result = (result + 1).min(distance_a.min(distance_b + 1));
+                *cell = result;
+            }
+        }
+        result
+    })
+}
+
+/// `strsim::normalized_levenshtein` without allocating.
+pub fn normalized_levenshtein(a: &str, b: &str) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / a.chars().count().max(b.chars().count()) as f64
+}
+
+/// `strsim::osa_distance` without allocating.
+pub fn osa_distance(a: &str, b: &str) -> usize {
+    if a == b {
+        return 0;
+    }
+    with_scratch(|scratch| {
+        fill(&mut scratch.a, a.chars());
+        fill(&mut scratch.b, b.chars());
+        let (a, b) = (&scratch.a, &scratch.b);
+        if a.is_empty() || b.is_empty() {
+            return a.len().max(b.len());
+        }
+        let [before, previous, current] = &mut scratch.rows;
+        for row in [&mut *before, &mut *previous] {
+            row.clear();
+            row.extend(0..=b.len());
+        }
+        current.clear();
+        current.resize(b.len() + 1, 0);
+        for (i, &x) in a.iter().enumerate() {
+            current[0] = i + 1;
+            for (j, &y) in b.iter().enumerate() {
+                let cost = usize::from(x != y);
+                current[j + 1] = (current[j] + 1).min((previous[j + 1] + 1).min(previous[j] + cost));
+                if i > 0 && j > 0 && x != y && x == b[j - 1] && y == a[i - 1] {
+                    current[j + 1] = current[j + 1].min(before[j - 1] + 1);
+                }
+            }
+            // The rows move down one: what was `previous` becomes `before`.
+            std::mem::swap(before, previous);
+            previous.copy_from_slice(current);
+        }
+        current[b.len()]
+    })
+}
+
+/// `strsim::damerau_levenshtein` without allocating.
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    with_scratch(|scratch| {
+        fill(&mut scratch.a, a.chars());
+        fill(&mut scratch.b, b.chars());
+        let (a, b) = (&scratch.a, &scratch.b);
+        if a.is_empty() || b.is_empty() {
+            return a.len().max(b.len());
+        }
+        let width = a.len() + 2;
+        let at = |i: usize, j: usize| j * width + i;
+        let distances = &mut scratch.rows[0];
+        distances.clear();
+        distances.resize(width * (b.len() + 2), 0);
+        let max_distance = a.len() + b.len();
+        distances[0] = max_distance;
+        for i in 0..=a.len() {
+            distances[at(i + 1, 0)] = max_distance;
+            distances[at(i + 1, 1)] = i;
+        }
+        for j in 0..=b.len() {
+            distances[at(0, j + 1)] = max_distance;
+            distances[at(1, j + 1)] = j;
+        }
+        let last_row = &mut scratch.last_row;
+        last_row.clear();
+        for i in 1..=a.len() {
+            let mut db = 0;
+            for j in 1..=b.len() {
+                let k = last_row.get(&b[j - 1]).copied().unwrap_or(0);
+                let insertion = distances[at(i, j + 1)] + 1;
+                let deletion = distances[at(i + 1, j)] + 1;
+                let transposition = distances[at(k, db)] + (i - k - 1) + 1 + (j - db - 1);
+                let mut substitution = distances[at(i, j)] + 1;
+                if a[i - 1] == b[j - 1] {
+                    db = j;
+                    substitution -= 1;
+                }
+                distances[at(i + 1, j + 1)] = substitution.min(insertion.min(deletion.min(transposition)));
+            }
+            last_row.insert(a[i - 1], i);
+        }
+        distances[at(a.len() + 1, b.len() + 1)]
+    })
+}
+
+/// `strsim::normalized_damerau_levenshtein` without allocating.
+pub fn normalized_damerau_levenshtein(a: &str, b: &str) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    1.0 - damerau_levenshtein(a, b) as f64 / a.chars().count().max(b.chars().count()) as f64
+}
+
+/// `strsim::jaro` without allocating.
+pub fn jaro(a: &str, b: &str) -> f64 {
+    with_scratch(|scratch| {
+        fill(&mut scratch.a, a.chars());
+        fill(&mut scratch.b, b.chars());
+        let (a, b) = (&scratch.a, &scratch.b);
+        if a.is_empty() && b.is_empty() {
+            return 1.0;
+        }
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+        if a.len() == 1 && b.len() == 1 {
+            return if a == b { 1.0 } else { 0.0 };
+        }
+        let search_range = a.len().max(b.len()) / 2 - 1;
+        let consumed = &mut scratch.flags;
+        consumed.clear();
+        consumed.resize(b.len(), false);
+        let (mut matches, mut transpositions, mut last_match) = (0.0, 0.0, 0);
+        for (i, &x) in a.iter().enumerate() {
+            let (low, high) = (i.saturating_sub(search_range), (b.len() - 1).min(i + search_range));
+            if low > high {
+                continue;
+            }
+            if let Some(j) = (low..=high).find(|&j| x == b[j] && !consumed[j]) {
+                consumed[j] = true;
+                matches += 1.0;
+                if j < last_match {
+                    transpositions += 1.0;
+                }
+                last_match = j;
+            }
+        }
+        if matches == 0.0 {
+            return 0.0;
+        }
+        (1.0 / 3.0) * ((matches / a.len() as f64) + (matches / b.len() as f64) + ((matches - transpositions) / matches))
+    })
+}
+
+/// `strsim::jaro_winkler` without allocating.
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let jaro = jaro(a, b);
+    let prefix = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+    (jaro + 0.1 * prefix as f64 * (1.0 - jaro)).min(1.0)
+}
+
+/// `strsim::sorensen_dice` without allocating. Like the original, it
+/// measures the texts without whitespace in bytes.
+pub fn sorensen_dice(a: &str, b: &str) -> f64 {
+    with_scratch(|scratch| {
+        fill(&mut scratch.a, a.chars().filter(|c| !c.is_whitespace()));
+        fill(&mut scratch.b, b.chars().filter(|c| !c.is_whitespace()));
+        let (a, b) = (&scratch.a, &scratch.b);
+        let bytes = |chars: &[char]| chars.iter().map(|c| c.len_utf8()).sum::<usize>();
+        let (len_a, len_b) = (bytes(a), bytes(b));
+        if len_a == 0 && len_b == 0 {
+            return 1.0;
+        }
+        if len_a == 0 || len_b == 0 {
+            return 0.0;
+        }
+        if a == b {
+            return 1.0;
+        }
+        if len_a < 2 || len_b < 2 {
+            return 0.0;
+        }
+        let bigrams = &mut scratch.bigrams;
+        bigrams.clear();
+        for gram in a.windows(2) {
+            *bigrams.entry((gram[0], gram[1])).or_insert(0) += 1;
+        }
+        let mut intersection = 0;
+        for gram in b.windows(2) {
+            if let Some(count) = bigrams.get_mut(&(gram[0], gram[1])) {
+                if *count > 0 {
+                    *count -= 1;
+                    intersection += 1;
+                }
+            }
+        }
+        (2 * intersection) as f64 / (len_a + len_b - 2) as f64
+    })
+}
@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::align::{Step, Tag};
+
+// ------------------------------------------------------------------------
+//  Substitution Matrices
+// ------------------------------------------------------------------------
+
+const AMINO_ACIDS: &str = "ARNDCQEGHILKMFPSTWYVBZX*";
+
+const BLOSUM62: &str = "
+ 4 -1 -2 -2  0 -1 -1  0 -2 -1 -1 -1 -1 -2 -1  1  0 -3 -2  0 -2 -1  0 -4
+-1  5  0 -2 -3  1  0 -2  0 -3 -2  2 -1 -3 -2 -1 -1 -3 -2 -3 -1  0 -1 -4
+-2  0  6  1 -3  0  0  0  1 -3 -3  0 -2 -3 -2  1  0 -4 -2 -3  3  0 -1 -4
+-2 -2  1  6 -3  0  2 -1 -1 -3 -4 -1 -3 -3 -1  0 -1 -4 -3 -3  4  1 -1 -4
+ 0 -3 -3 -3  9 -3 -4 -3 -3 -1 -1 -3 -1 -2 -3 -1 -1 -2 -2 -1 -3 -3 -2 -4
+-1  1  0  0 -3  5  2 -2  0 -3 -2  1  0 -3 -1  0 -1 -2 -1 -2  0  3 -1 -4
+-1  0  0  2 -4  2  5 -2  0 -3 -3  1 -2 -3 -1  0 -1 -3 -2 -2  1  4 -1 -4
+ 0 -2  0 -1 -3 -2 -2  6 -2 -4 -4 -2 -3 -3 -2  0 -2 -2 -3 -3 -1 -2 -1 -4
+-2  0  1 -1 -3  0  0 -2  8 -3 -3 -1 -2 -1 -2 -1 -2 -2  2 -3  0  0 -1 -4
+-1 -3 -3 -3 -1 -3 -3 -4 -3  4  2 -3  1  0 -3 -2 -1 -3 -1  3 -3 -3 -1 -4
+-1 -2 -3 -4 -1 -2 -3 -4 -3  2  4 -2  2  0 -3 -2 -1 -2 -1  1 -4 -3 -1 -4
+-1  2  0 -1 -3  1  1 -2 -1 -3 -2  5 -1 -3 -1  0 -1 -3 -2 -2  0  1 -1 -4
+-1 -1 -2 -3 -1  0 -2 -3 -2  1  2 -1  5  0 -2 -1 -1 -1 -1  1 -3 -1 -1 -4
+-2 -3 -3 -3 -2 -3 -3 -3 -1  0  0 -3  0  6 -4 -2 -2  1  3 -1 -3 -3 -1 -4
+-1 -2 -2 -1 -3 -1 -1 -2 -2 -3 -3 -1 -2 -4  7 -1 -1 -4 -3 -2 -2 -1 -2 -4
+ 1 -1  1  0 -1  0  0  0 -1 -2 -2  0 -1 -2 -1  4  1 -3 -2 -2  0  0  0 -4
+ 0 -1  0 -1 -1 -1 -1 -2 -2 -1 -1 -1 -1 -2 -1  1  5 -2 -2  0 -1 -1  0 -4
+-3 -3 -4 -4 -2 -2 -3 -2 -2 -3 -2 -3 -1  1 -4 -3 -2 11  2 -3 -4 -3 -2 -4
+-2 -2 -2 -3 -2 -1 -2 -3  2 -1 -1 -2 -1  3 -3 -2 -2  2  7 -1 -3 -2 -1 -4
+ 0 -3 -3 -3 -1 -2 -2 -3 -3  3  1 -2  1 -1 -2 -2  0 -3 -1  4 -3 -2 -1 -4
+-2 -1  3  4 -3  0  1 -1  0 -3 -4  0 -3 -3 -2  0 -1 -4 -3 -3  4  1 -1 -4
+-1  0  0  1 -3  3  4 -2  0 -3 -3  1 -1 -3 -1  0 -1 -3 -2 -2  1  4 -1 -4
+ 0 -1 -1 -1 -2 -1 -1 -1 -1 -1 -1 -1 -1 -1 -2  0  0 -2 -1 -1 -1 -1 -1 -4
+-4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4  1
+";
+
+const PAM250: &str = "
+ 2 -2  0  0 -2  0  0  1 -1 -1 -2 -1 -1 -3  1  1  1 -6 -3  0  0  0  0 -8
+-2  6  0 -1 -4  1 -1 -3  2 -2 -3  3  0 -4  0  0 -1  2 -4 -2 -1  0 -1 -8
+ 0  0  2  2 -4  1  1  0  2 -2 -3  1 -2 -3  0  1  0 -4 -2 -2  2  1  0 -8
+ 0 -1  2  4 -5  2  3  1  1 -2 -4  0 -3 -6 -1  0  0 -7 -4 -2  3  3 -1 -8
+-2 -4 -4 -5 12 -5 -5 -3 -3 -2 -6 -5 -5 -4 -3  0 -2 -8  0 -2 -4 -5 -3 -8
+ 0  1  1  2 -5  4  2 -1  3 -2 -2  1 -1 -5  0 -1 -1 -5 -4 -2  1  3 -1 -8
+ 0 -1  1  3 -5  2  4  0  1 -2 -3  0 -2 -5 -1  0  0 -7 -4 -2  3  3 -1 -8
+ 1 -3  0  1 -3 -1  0  5 -2 -3 -4 -2 -3 -5  0  1  0 -7 -5 -1  0  0 -1 -8
+-1  2  2  1 -3  3  1 -2  6 -2 -2  0 -2 -2  0 -1 -1 -3  0 -2  1  2 -1 -8
+-1 -2 -2 -2 -2 -2 -2 -3 -2  5  2 -2  2  1 -2 -1  0 -5 -1  4 -2 -2 -1 -8
+-2 -3 -3 -4 -6 -2 -3 -4 -2  2  6 -3  4  2 -3 -3 -2 -2 -1  2 -3 -3 -1 -8
+-1  3  1  0 -5  1  0 -2  0 -2 -3  5  0 -5 -1  0  0 -3 -4 -2  1  0 -1 -8
+-1  0 -2 -3 -5 -1 -2 -3 -2  2  4  0  6  0 -2 -2 -1 -4 -2  2 -2 -2 -1 -8
+-3 -4 -3 -6 -4 -5 -5 -5 -2  1  2 -5  0  9 -5 -3 -3  0  7 -1 -4 -5 -2 -8
+ 1  0  0 -1 -3  0 -1  0  0 -2 -3 -1 -2 -5  6  1  0 -6 -5 -1 -1  0 -1 -8
+ 1  0  1  0  0 -1  0  1 -1 -1 -3  0 -2 -3  1  2  1 -2 -3 -1  0  0  0 -8
+ 1 -1  0  0 -2 -1  0  0 -1  0 -2  0 -1 -3  0  1  3 -5 -3  0  0 -1  0 -8
+-6  2 -4 -7 -8 -5 -7 -7 -3 -5 -2 -3 -4  0 -6 -2 -5 17  0 -6 -5 -6 -4 -8
+-3 -4 -2 -4  0 -4 -4 -5  0 -1 -1 -4 -2  7 -5 -3 -3  0 10 -2 -3 -4 -2 -8
+ 0 -2 -2 -2 -2 -2 -2 -1 -2  4  2 -2  2 -1 -1 -1  0 -6 -2  4 -2 -2 -1 -8
+ 0 -1  2  3 -4  1  3  0  1 -2 -3  1 -2 -4 -1  0  0 -5 -3 -2  3  2 -1 -8
+ 0  0  1  3 -5  3  3  0  2 -2 -3  0 -2 -5  0  0 -1 -6 -4 -2  2  3 -1 -8
+ 0 -1  0 -1 -3 -1 -1 -1 -1 -1 -1 -1 -1 -2 -1  0  0 -4 -2 -1 -1 -1 -1 -8
+-8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8 -8  1
+";
+
+/// Scores for aligning one character against another. Pairs missing from the
+/// matrix score `match_score` when equal and `mismatch_score` otherwise.
+#[derive(Clone, Debug)]
+pub struct SubstitutionMatrix {
+    scores: HashMap<(char, char), f64>,
+    fallback: Option<char>,
+    match_score: f64,
+    mismatch_score: f64,
+}
+
+impl Default for SubstitutionMatrix {
+    fn default() -> Self {
+        Self::identity(1.0, -1.0)
+    }
+}
+
+impl SubstitutionMatrix {
+    /// Matrix scoring every equal pair `match_score` and every other pair
+    /// `mismatch_score`.
+    pub fn identity(match_score: f64, mismatch_score: f64) -> Self {
+        Self {
+            scores: HashMap::new(),
+            fallback: None,
+            match_score,
+            mismatch_score,
+        }
+    }
+
+    /// Matrix built from explicit `(x, y, score)` entries. Entries apply in
+    /// both directions unless the reverse pair is given as well.
+    pub fn from_entries<I: IntoIterator<Item = (char, char, f64)>>(entries: I, match_score: f64, mismatch_score: f64) -> Self {
+        let mut matrix = Self::identity(match_score, mismatch_score);
+        for (x, y, score) in entries {
+            matrix.scores.insert((x, y), score);
+            matrix.scores.entry((y, x)).or_insert(score);
+        }
+        matrix
+    }
+
+    fn from_table(table: &str) -> Self {
+        let symbols: Vec<char> = AMINO_ACIDS.chars().collect();
+        let mut scores = HashMap::new();
+        for (row, line) in table.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+            for (column, value) in line.split_whitespace().enumerate() {
+                let score: f64 = value.parse().expect("substitution tables are numeric");
+                scores.insert((symbols[row], symbols[column]), score);
+            }
+        }
+        Self {
+            scores,
+            fallback: Some('X'),
+            match_score: 1.0,
+            mismatch_score: -1.0,
+        }
+    }
+
+    /// Looks up a built-in matrix by name, `"blosum62"` or `"pam250"`.
+    /// Residues are compared case-insensitively and unknown residues score
+    /// like `X`.
+    pub fn named(name: &str) -> Option<&'static Self> {
+        static BLOSUM62_MATRIX: OnceLock<SubstitutionMatrix> = OnceLock::new();
+        static PAM250_MATRIX: OnceLock<SubstitutionMatrix> = OnceLock::new();
+        match name.to_ascii_lowercase().as_str() {
+            "blosum62" => Some(BLOSUM62_MATRIX.get_or_init(|| Self::from_table(BLOSUM62))),
+            "pam250" => Some(PAM250_MATRIX.get_or_init(|| Self::from_table(PAM250))),
+            _ => None,
+        }
+    }
+
+    /// Score of aligning `x` against `y`.
+    pub fn score(&self, x: char, y: char) -> f64 {
+        if let Some(fallback) = self.fallback {
+            let known = |c: char| {
+                let c = c.to_ascii_uppercase();
+                if AMINO_ACIDS.contains(c) { c } else { fallback }
+            };
+            return self.scores[&(known(x), known(y))];
+        }
+        match self.scores.get(&(x, y)) {
+            Some(&score) => score,
+            None if x == y => self.match_score,
+            None => self.mismatch_score,
+        }
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Global and Local Alignment
+// ------------------------------------------------------------------------
+
+/// Substitution matrix and linear gap penalty used to score an alignment.
+#[derive(Clone, Debug)]
+pub struct Scoring<'m> {
+    pub matrix: &'m SubstitutionMatrix,
+    pub gap: f64,
+}
+
+/// Result of a global or local alignment. `path` covers only the aligned
+/// region, which is the whole of both sequences for global alignments.
+#[derive(Clone, Debug)]
+pub struct Alignment {
+    pub score: f64,
+    pub path: Vec<Step>,
+}
+
+/// Signature shared by `needleman_wunsch` and `smith_waterman`.
+pub type Aligner = fn(&[char], &[char], &Scoring) -> Alignment;
+
+impl Alignment {
+    /// Fraction of the alignment columns, gaps included, that pair identical
+    /// characters. An empty alignment has an identity of 0.0.
+    pub fn identity(&self) -> f64 {
+        if self.path.is_empty() {
+            return 0.0;
+        }
+        let identical = self.path.iter().filter(|(tag, _, _)| *tag == Tag::Equal).count();
+        identical as f64 / self.path.len() as f64
+    }
+}
+
+fn align(a: &[char], b: &[char], scoring: &Scoring, local: bool) -> Alignment {
+    let width = b.len() + 1;
+    let floor = if local { 0.0 } else { f64::NEG_INFINITY };
+    let mut matrix = vec![0.0f64; (a.len() + 1) * width];
+    if !local {
+        for i in 0..=a.len() {
+            matrix[i * width] = i as f64 * scoring.gap;
+        }
+        for (j, cell) in matrix.iter_mut().take(width).enumerate() {
+            *cell = j as f64 * scoring.gap;
+        }
+    }
+
+    let mut best = (0.0, 0, 0);
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let score = (matrix[(i - 1) * width + j - 1] + scoring.matrix.score(a[i - 1], b[j - 1]))
+                .max(matrix[(i - 1) * width + j] + scoring.gap)
+                .max(matrix[i * width + j - 1] + scoring.gap)
+                .max(floor);
+            matrix[i * width + j] = score;
+            if score > best.0 {
+                best = (score, i, j);
+            }
+        }
+    }
+
+    let (score, mut i, mut j) = if local {
+        best
+    } else {
+        (matrix[a.len() * width + b.len()], a.len(), b.len())
+    };
+
+    let mut path = Vec::new();
+    while i > 0 || j > 0 {
+        let current = matrix[i * width + j];
+        if local && current <= 0.0 {
+            break;
+        }
+        if i > 0 && j > 0 {
+            let diagonal = matrix[(i - 1) * width + j - 1] + scoring.matrix.score(a[i - 1], b[j - 1]);
+            if current == diagonal {
+                i -= 1;
+                j -= 1;
+                path.push((if a[i] == b[j] { Tag::Equal } else { Tag::Replace }, i, j));
+                continue;
+            }
+        }
+        if i > 0 && (j == 0 || current == matrix[(i - 1) * width + j] + scoring.gap) {
+            i -= 1;
+            path.push((Tag::Delete, i, j));
+        } else {
+            j -= 1;
+            path.push((Tag::Insert, i, j));
+        }
+    }
+    path.reverse();
+
+    Alignment { score, path }
+}
+
+/// Aligns two sequences end to end with the Needleman-Wunsch algorithm.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First sequence to align
+/// * `b` - Secondary sequence to align to `a`
+/// * `scoring` - Substitution matrix and gap penalty
+/// 
+/// # Returns
+/// 
+/// * `output` - Optimal global alignment of `a` and `b`
+pub fn needleman_wunsch(a: &[char], b: &[char], scoring: &Scoring) -> Alignment {
+    align(a, b, scoring, false)
+}
+
+/// Finds the best scoring aligned pair of substrings of two sequences with
+/// the Smith-Waterman algorithm.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First sequence to align
+/// * `b` - Secondary sequence to align to `a`
+/// * `scoring` - Substitution matrix and gap penalty
+/// 
+/// # Returns
+/// 
+/// * `output` - Optimal local alignment of `a` and `b`
+pub fn smith_waterman(a: &[char], b: &[char], scoring: &Scoring) -> Alignment {
+    align(a, b, scoring, true)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    /// Renders the aligned region as two gapped rows.
+    fn rows(a: &[char], b: &[char], alignment: &Alignment) -> (String, String) {
+        let mut rows = (String::new(), String::new());
+        for &(tag, i, j) in &alignment.path {
+            rows.0.push(if tag == Tag::Insert { '-' } else { a[i] });
+            rows.1.push(if tag == Tag::Delete { '-' } else { b[j] });
+        }
+        rows
+    }
+
+    #[test]
+    fn named_matrices_match_the_published_tables() {
+        let blosum62 = SubstitutionMatrix::named("BLOSUM62").unwrap();
+        let cases = [('A', 'A', 4.0), ('W', 'W', 11.0), ('C', 'C', 9.0), ('A', 'R', -1.0), ('E', 'Q', 2.0), ('W', 'C', -2.0), ('I', 'V', 3.0), ('*', '*', 1.0), ('D', '*', -4.0)];
+        for (x, y, score) in cases {
+            assert_eq!(blosum62.score(x, y), score, "BLOSUM62 {}{}", x, y);
+        }
+        let pam250 = SubstitutionMatrix::named("pam250").unwrap();
+        let cases = [('A', 'A', 2.0), ('W', 'W', 17.0), ('C', 'C', 12.0), ('W', 'R', 2.0), ('Y', 'F', 7.0), ('W', 'C', -8.0), ('I', 'V', 4.0), ('*', '*', 1.0), ('D', '*', -8.0)];
+        for (x, y, score) in cases {
+            assert_eq!(pam250.score(x, y), score, "PAM250 {}{}", x, y);
+        }
+        assert_eq!(blosum62.score('w', 'c'), -2.0);
+        assert_eq!(blosum62.score('J', 'A'), blosum62.score('X', 'A'));
+        assert!(SubstitutionMatrix::named("blosum45").is_none());
+    }
+
+    #[test]
+    fn named_matrices_are_symmetric() {
+        for name in ["blosum62", "pam250"] {
+            let matrix = SubstitutionMatrix::named(name).unwrap();
+            for x in AMINO_ACIDS.chars() {
+                for y in AMINO_ACIDS.chars() {
+                    assert_eq!(matrix.score(x, y), matrix.score(y, x), "{} {}{}", name, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn needleman_wunsch_aligns_the_textbook_example() {
+        // Match 1, mismatch -1 and gap -1, as in the Wikipedia article.
+        let matrix = SubstitutionMatrix::identity(1.0, -1.0);
+        let scoring = Scoring { matrix: &matrix, gap: -1.0 };
+        let (a, b) = (chars("GCATGCG"), chars("GATTACA"));
+        let alignment = needleman_wunsch(&a, &b, &scoring);
+        assert_eq!(alignment.score, 0.0);
+        // One of the article's three optimal alignments; ties prefer a
+        // diagonal step, then a gap in `b`, when tracing back.
+        assert_eq!(rows(&a, &b, &alignment), ("GCA-TGCG".into(), "G-ATTACA".into()));
+        assert_eq!(alignment.identity(), 4.0 / 8.0);
+    }
+
+    #[test]
+    fn smith_waterman_aligns_the_textbook_example() {
+        // Match 3, mismatch -3 and gap -2, as in the Wikipedia article.
+        let matrix = SubstitutionMatrix::identity(3.0, -3.0);
+        let scoring = Scoring { matrix: &matrix, gap: -2.0 };
+        let (a, b) = (chars("TGTTACGG"), chars("GGTTGACTA"));
+        let alignment = smith_waterman(&a, &b, &scoring);
+        assert_eq!(alignment.score, 13.0);
+        assert_eq!(rows(&a, &b, &alignment), ("GTT-AC".into(), "GTTGAC".into()));
+        assert_eq!(alignment.path.first(), Some(&(Tag::Equal, 1, 1)));
+    }
+
+    #[test]
+    fn alignments_of_empty_sequences_are_all_gaps() {
+        let matrix = SubstitutionMatrix::default();
+        let scoring = Scoring { matrix: &matrix, gap: -2.0 };
+        let alignment = needleman_wunsch(&chars("AC"), &[], &scoring);
+        assert_eq!(alignment.score, -4.0);
+        assert_eq!(alignment.path, [(Tag::Delete, 0, 0), (Tag::Delete, 1, 0)]);
+        let alignment = smith_waterman(&chars("AC"), &[], &scoring);
+        assert_eq!((alignment.score, alignment.path.len()), (0.0, 0));
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::str::Chars;
+
+use crate::metric::RowScorer;
+
+// ------------------------------------------------------------------------
+//  Packed Levenshtein
+// ------------------------------------------------------------------------
+
+/// Candidates compared side by side, one per 64-bit lane of a 256-bit
+/// vector.
+pub const LANES: usize = 4;
+
+/// A query of at most 64 characters prepared for the packed kernel. Each of
+/// its characters maps to the bit-vector of its positions in the query, with
+/// which the bit-parallel algorithm of Myers and Hyyrö computes a whole
+/// column of the distance matrix per character of a candidate. `LANES`
+/// candidates share each step, every lane holding the column of one of them.
+struct PackedQuery {
+    ascii: [u64; 128],
+    other: HashMap<char, u64>,
+    len: usize,
+}
+
+impl PackedQuery {
+    /// Prepares `query`, or returns `None` if it is longer than a word.
+    fn new(query: &str) -> Option<Self> {
+        let mut packed = Self { ascii: [0; 128], other: HashMap::new(), len: 0 };
+        for (i, c) in query.chars().enumerate() {
+            if i == 64 {
+                return None;
+            }
+            if c.is_ascii() {
+                packed.ascii[c as usize] |= 1 << i;
+            } else {
+                *packed.other.entry(c).or_insert(0) |= 1 << i;
+            }
+            packed.len = i + 1;
+        }
+        Some(packed)
+    }
+
+    fn mask(&self, c: char) -> u64 {
+        if c.is_ascii() {
+            self.ascii[c as usize]
+        } else {
+            self.other.get(&c).copied().unwrap_or(0)
+        }
+    }
+
+    /// Calls `each` with the index, distance to the query and length in
+    /// characters of each candidate, in order.
+    fn distances(&self, candidates: &[&str], mut each: impl FnMut(usize, usize, usize)) {
+        #[cfg(target_arch = "x86_64")]
+        let avx2 = is_x86_feature_detected!("avx2");
+        for (first, batch) in candidates.chunks(LANES).enumerate() {
+            let mut chars: [Chars<'_>; LANES] = std::array::from_fn(|lane| batch.get(lane).map_or("", |b| b).chars());
+            let (distances, lengths) = if self.len == 0 {
+                (std::array::from_fn(|lane| chars[lane].by_ref().count()), [0; LANES])
+            } else {
+                #[cfg(target_arch = "x86_64")]
+                if avx2 {
+                    // SAFETY: the CPU was just checked to support AVX2.
+                    unsafe { self.run_avx2(&mut chars) }
+                } else {
+                    self.run(&mut chars)
+                }
+                #[cfg(not(target_arch = "x86_64"))]
+                self.run(&mut chars)
+            };
+            for lane in 0..batch.len() {
+                // An empty query has no column, so each distance is the length.
+                let length = if self.len == 0 { distances[lane] } else { lengths[lane] };
+                each(first * LANES + lane, distances[lane], length);
+            }
+        }
+    }
+
+    /// Loads the next character of each lane into `x`, setting `active` to
+    /// all ones for the lanes that have one and counting it in `lengths`.
+    /// Returns whether any lane had one.
+    #[inline(always)]
+    fn next_masks(
+        &self,
+        chars: &mut [Chars<'_>; LANES],
+        x: &mut [u64; LANES],
+        active: &mut [u64; LANES],
+        lengths: &mut [usize; LANES],
+    ) -> bool {
+        let mut any = false;
+        for lane in 0..LANES {
+            match chars[lane].next() {
+                Some(c) => {
+                    x[lane] = self.mask(c);
+                    active[lane] = !0;
+                    lengths[lane] += 1;
+                    any = true;
+                }
+                None => (x[lane], active[lane]) = (0, 0),
+            }
+        }
+        any
+    }
+
+    /// The kernel one lane at a time, for CPUs without AVX2.
+    fn run(&self, chars: &mut [Chars<'_>; LANES]) -> ([usize; LANES], [usize; LANES]) {
+        let last = 1 << (self.len - 1);
+        let (mut vp, mut vn, mut distances) = ([!0u64; LANES], [0u64; LANES], [self.len; LANES]);
+        let (mut x, mut active, mut lengths) = ([0; LANES], [0; LANES], [0; LANES]);
+        while self.next_masks(chars, &mut x, &mut active, &mut lengths) {
+            for lane in 0..LANES {
+                if active[lane] == 0 {
+                    continue;
+                }
+                let d0 = ((x[lane] & vp[lane]).wrapping_add(vp[lane]) ^ vp[lane]) | x[lane] | vn[lane];
+                let hp = vn[lane] | !(d0 | vp[lane]);
+                let hn = d0 & vp[lane];
+                distances[lane] += usize::from(hp & last != 0);
+                distances[lane] -= usize::from(hn & last != 0);
+                let hp = (hp << 1) | 1;
+                let hn = hn << 1;
+                vp[lane] = hn | !(d0 | hp);
+                vn[lane] = hp & d0;
+            }
+        }
+        (distances, lengths)
+    }
+
+    /// The kernel on all lanes at once, the same steps as `run` in AVX2
+    /// registers. Lanes whose candidate has ended keep their state.
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    fn run_avx2(&self, chars: &mut [Chars<'_>; LANES]) -> ([usize; LANES], [usize; LANES]) {
+        use std::arch::x86_64::*;
+
+        let load = |words: &[u64; LANES]| {
+            _mm256_set_epi64x(words[3] as i64, words[2] as i64, words[1] as i64, words[0] as i64)
+        };
+        let ones = _mm256_set1_epi64x(-1);
+        let one = _mm256_set1_epi64x(1);
+        let last = _mm256_set1_epi64x((1u64 << (self.len - 1)) as i64);
+        let (mut vp, mut vn) = (ones, _mm256_setzero_si256());
+        let mut distances = _mm256_set1_epi64x(self.len as i64);
+        let (mut x, mut active, mut lengths) = ([0; LANES], [0; LANES], [0; LANES]);
+        while self.next_masks(chars, &mut x, &mut active, &mut lengths) {
+            let (x, active) = (load(&x), load(&active));
+            let d0 = _mm256_or_si256(
+                _mm256_or_si256(_mm256_xor_si256(_mm256_add_epi64(_mm256_and_si256(x, vp), vp), vp), x),
+                vn,
+            );
+            let hp = _mm256_or_si256(vn, _mm256_andnot_si256(_mm256_or_si256(d0, vp), ones));
+            let hn = _mm256_and_si256(d0, vp);
+            // Comparisons yield -1 in the lanes where the last bit is set.
+            let up = _mm256_cmpeq_epi64(_mm256_and_si256(hp, last), last);
+            let down = _mm256_cmpeq_epi64(_mm256_and_si256(hn, last), last);
+            distances = _mm256_sub_epi64(distances, _mm256_and_si256(up, active));
+            distances = _mm256_add_epi64(distances, _mm256_and_si256(down, active));
+            let hp = _mm256_or_si256(_mm256_slli_epi64::<1>(hp), one);
+            let hn = _mm256_slli_epi64::<1>(hn);
+            let next_vp = _mm256_or_si256(hn, _mm256_andnot_si256(_mm256_or_si256(d0, hp), ones));
+            let next_vn = _mm256_and_si256(hp, d0);
+            vp = _mm256_or_si256(_mm256_and_si256(next_vp, active), _mm256_andnot_si256(active, vp));
+            vn = _mm256_or_si256(_mm256_and_si256(next_vn, active), _mm256_andnot_si256(active, vn));
+        }
+        let distances = [
+            _mm256_extract_epi64::<0>(distances) as usize,
+            _mm256_extract_epi64::<1>(distances) as usize,
+            _mm256_extract_epi64::<2>(distances) as usize,
+            _mm256_extract_epi64::<3>(distances) as usize,
+        ];
+        (distances, lengths)
+    }
+}
+
+/// Levenshtein distances between `query` and each of `candidates`, with
+/// the packed kernel. Returns false, leaving `output` untouched, for queries
+/// longer than 64 characters.
+pub fn levenshtein(query: &str, candidates: &[&str], output: &mut [f64]) -> bool {
+    let Some(query) = PackedQuery::new(query) else {
+        return false;
+    };
+    query.distances(candidates, |i, distance, _| output[i] = distance as f64);
+    true
+}
+
+/// Normalized Levenshtein similarities between `query` and each of
+/// `candidates`, like `levenshtein`.
+pub fn normalized_levenshtein(query: &str, candidates: &[&str], output: &mut [f64]) -> bool {
+    let Some(packed) = PackedQuery::new(query) else {
+        return false;
+    };
+    packed.distances(candidates, |i, distance, len| {
+        let max_len = packed.len.max(len);
+        output[i] = if max_len == 0 { 1.0 } else { 1.0 - distance as f64 / max_len as f64 };
+    });
+    true
+}
+
+/// Packed kernel of the metric named `name`, if there is one.
+pub(crate) fn row(name: &str) -> Option<RowScorer> {
+    match name {
+        "levenshtein" => Some(levenshtein),
+        "normalized_levenshtein" => Some(normalized_levenshtein),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    const ALPHABET: [char; 5] = ['a', 'b', 'c', 'é', '字'];
+
+    fn query(rng: &mut Rng, len: usize) -> String {
+        (0..len).map(|_| ALPHABET[rng.below(ALPHABET.len())]).collect()
+    }
+
+    fn candidates(rng: &mut Rng, count: usize) -> Vec<String> {
+        (0..count).map(|_| rng.string(&ALPHABET, 80)).collect()
+    }
+
+    #[test]
+    fn kernels_match_strsim() {
+        let mut rng = Rng::new(639);
+        // The kernels need a non-empty query, which `distances` ensures.
+        for len in [1, 2, 31, 62, 63, 64].repeat(10) {
+            let query = query(&mut rng, len);
+            let packed = PackedQuery::new(&query).unwrap();
+            for count in 1..=LANES {
+                let batch = candidates(&mut rng, count);
+                let expected: Vec<usize> = batch.iter().map(|b| strsim::levenshtein(&query, b)).collect();
+                let lengths: Vec<usize> = batch.iter().map(|b| b.chars().count()).collect();
+                let chars = || std::array::from_fn(|lane| batch.get(lane).map_or("", |b| b).chars());
+
+                let (distances, counted) = packed.run(&mut chars());
+                assert_eq!(distances[..count], expected[..], "scalar, query of {} characters", len);
+                assert_eq!(counted[..count], lengths[..]);
+
+                #[cfg(target_arch = "x86_64")]
+                if is_x86_feature_detected!("avx2") {
+                    // SAFETY: the CPU was just checked to support AVX2.
+                    let (distances, counted) = unsafe { packed.run_avx2(&mut chars()) };
+                    assert_eq!(distances[..count], expected[..], "AVX2, query of {} characters", len);
+                    assert_eq!(counted[..count], lengths[..]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rows_match_strsim() {
+        let mut rng = Rng::new(6390);
+        for len in [0, 1, 5, 40, 63, 64, 65].repeat(5) {
+            let query = query(&mut rng, len);
+            for count in [0, 1, 3, 4, 5, 7, 9, 13] {
+                let batch = candidates(&mut rng, count);
+                let strs: Vec<&str> = batch.iter().map(String::as_str).collect();
+
+                let mut distances = vec![-1.0; count];
+                let mut similarities = vec![-1.0; count];
+                let handled = levenshtein(&query, &strs, &mut distances);
+                assert_eq!(handled, len <= 64);
+                assert_eq!(normalized_levenshtein(&query, &strs, &mut similarities), handled);
+                if !handled {
+                    assert!(distances.iter().chain(&similarities).all(|&x| x == -1.0));
+                    continue;
+                }
+                for (i, b) in strs.iter().enumerate() {
+                    assert_eq!(distances[i], strsim::levenshtein(&query, b) as f64, "{:?} {:?}", query, b);
+                    assert_eq!(similarities[i], strsim::normalized_levenshtein(&query, b), "{:?} {:?}", query, b);
+                }
+            }
+        }
+    }
+}
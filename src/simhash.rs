@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use crate::checkpoint::Fingerprint;
+use crate::tokens;
+
+// ------------------------------------------------------------------------
+//  SimHash Fingerprints
+// ------------------------------------------------------------------------
+
+/// Scrambles the bits of an FNV-1a hash with the SplitMix64 finalizer, so
+/// that every bit of the result depends on every byte hashed.
+pub(crate) fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Charikar's SimHash of the lowercased character `n`-grams of `text`.
+/// Every distinct gram votes with its count on each of the 64 bits, for the
+/// bit if its hash has it set and against otherwise, and the fingerprint
+/// keeps the bits with a positive total. Similar texts share most grams and
+/// so get fingerprints a small Hamming distance apart. Texts without grams
+/// get the fingerprint 0.
+/// 
+/// # Arguments
+/// 
+/// * `text` - Text to fingerprint
+/// * `n` - Number of characters per gram
+/// 
+/// # Returns
+/// 
+/// * `output` - 64-bit fingerprint of `text`
+pub fn simhash(text: &str, n: usize) -> u64 {
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for gram in tokens::char_ngrams(text, n) {
+        *counts.entry(gram).or_insert(0) += 1;
+    }
+
+    let mut votes = [0i64; 64];
+    for (gram, count) in counts {
+        let mut hash = Fingerprint::new();
+        hash.write(gram.as_bytes());
+        let hash = mix(hash.finish());
+        for (bit, vote) in votes.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *vote += count;
+            } else {
+                *vote -= count;
+            }
+        }
+    }
+
+    votes
+        .iter()
+        .enumerate()
+        .filter(|&(_, &vote)| vote > 0)
+        .fold(0, |fingerprint, (bit, _)| fingerprint | 1 << bit)
+}
+
+/// Number of bits in which two fingerprints differ.
+pub fn hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
@@ -0,0 +1,204 @@
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+
+use crate::error::InvalidParameterError;
+use crate::metric::{Kind, Metric};
+use crate::pool::NThreads;
+use crate::vectorized::score_matrix;
+use crate::vectorized_np::{matrix_array, require_numpy};
+
+// ------------------------------------------------------------------------
+//  scikit-learn Metrics
+// ------------------------------------------------------------------------
+
+/// A metric as scikit-learn expects one: a callable taking two samples and
+/// returning their distance. Similarities are turned into distances as
+/// `1.0 - similarity`, so that identical strings are always at distance 0.
+/// 
+/// scikit-learn only hands numeric arrays to callable metrics, so samples are
+/// usually one-column arrays of indices into `strings`, as in its FAQ on
+/// string data. A sample may also be a string, or a row holding one.
+/// 
+/// Called with two 2D arrays of samples instead of two samples, or through
+/// `pairwise`, the whole distance matrix is computed in parallel without a
+/// Python call per pair, ready for estimators built with
+/// `metric="precomputed"`.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Name of the metric, e.g. `"levenshtein"`
+/// * `strings` - Strings that numeric samples index into, or `None` if samples
+///   are strings
+/// * `n_threads` - Number of threads used for whole matrices, or `None` or 0
+///   for all available cores
+#[pyclass(module = "strsim", text_signature = "(metric, /, strings=None, n_threads=None)")]
+#[derive(Clone)]
+pub struct SklearnMetric {
+    name: String,
+    metric: Metric,
+    strings: Option<Vec<String>>,
+    n_threads: NThreads,
+}
+
+impl SklearnMetric {
+    fn distance(metric: Metric, a: &str, b: &str) -> f64 {
+        let score = (metric.score)(a, b);
+        match metric.kind {
+            Kind::Distance => score,
+            Kind::Similarity => 1.0 - score,
+        }
+    }
+
+    /// The string a sample stands for: the sample itself, or the entry of
+    /// `strings` it holds the index of.
+    fn resolve<'a>(&'a self, sample: &'a PyAny) -> PyResult<&'a str> {
+        if let Ok(s) = sample.extract::<&str>() {
+            return Ok(s);
+        }
+        let index: f64 = sample.extract()?;
+        let strings = self.strings.as_deref().ok_or_else(|| {
+            InvalidParameterError::new_err("numeric samples are indices into strings, which this metric was made without")
+        })?;
+        if index.fract() != 0.0 || index < 0.0 || index >= strings.len() as f64 {
+            return Err(PyIndexError::new_err(format!(
+                "sample {} is not an index into {} strings",
+                index,
+                strings.len()
+            )));
+        }
+        Ok(&strings[index as usize])
+    }
+
+    /// The string of a sample passed to `__call__`, which scikit-learn passes
+    /// as a row of one value.
+    fn resolve_row<'a>(&'a self, row: &'a PyAny) -> PyResult<&'a str> {
+        if row.extract::<&str>().is_ok() {
+            return self.resolve(row);
+        }
+        match row.len()? {
+            1 => self.resolve(row.get_item(0)?),
+            n => Err(InvalidParameterError::new_err(format!("expected a sample of one string or index, got {} values", n))),
+        }
+    }
+
+    /// The strings of a whole array of samples, one per row.
+    fn resolve_all<'a>(&'a self, py: Python<'a>, samples: &'a PyAny) -> PyResult<Vec<&'a str>> {
+        let numpy = py.import("numpy")?;
+        let array = numpy.call_method1("asarray", (samples,))?;
+        let shape: Vec<usize> = array.getattr("shape")?.extract()?;
+        if shape.len() == 2 && shape[1] != 1 {
+            return Err(InvalidParameterError::new_err(format!(
+                "expected samples of one string or index, got {} values per row",
+                shape[1]
+            )));
+        }
+        let values: &PyAny = numpy.call_method1("ravel", (array,))?.call_method0("tolist")?;
+        values.iter()?.map(|value| self.resolve(value?)).collect()
+    }
+
+    fn matrix(&self, py: Python<'_>, xs: &PyAny, ys: Option<&PyAny>) -> PyResult<PyObject> {
+        require_numpy(py)?;
+        let as_ = self.resolve_all(py, xs)?;
+        let bs = match ys {
+            Some(ys) => self.resolve_all(py, ys)?,
+            None => as_.clone(),
+        };
+        let metric = self.metric;
+        let distances = score_matrix(
+            |a, b| Self::distance(metric, a, b),
+            None,
+            self.n_threads,
+            None,
+            None,
+            &as_,
+            &bs,
+            false,
+            None,
+            f64::NAN,
+        )?;
+        Ok(matrix_array(py, (as_.len(), bs.len()), distances))
+    }
+}
+
+#[pymethods]
+impl SklearnMetric {
+    #[new]
+    #[args(strings = "None", n_threads = "NThreads::AUTO")]
+    fn new(metric: &str, strings: Option<Vec<String>>, n_threads: NThreads) -> PyResult<Self> {
+        Ok(Self { name: metric.to_owned(), metric: Metric::from_name(metric)?, strings, n_threads })
+    }
+
+    /// Calculates the distance between two samples, or the matrix of
+    /// distances between two 2D arrays of samples.
+    fn __call__(&self, py: Python<'_>, x: &PyAny, y: &PyAny) -> PyResult<PyObject> {
+        let is_matrix = |samples: &PyAny| -> PyResult<bool> {
+            Ok(samples.hasattr("ndim")? && samples.getattr("ndim")?.extract::<usize>()? == 2)
+        };
+        if is_matrix(x)? && is_matrix(y)? {
+            return self.matrix(py, x, Some(y));
+        }
+        let (a, b) = (self.resolve_row(x)?, self.resolve_row(y)?);
+        Ok(Self::distance(self.metric, a, b).into_py(py))
+    }
+
+    /// Calculates the distances between every sample in `xs` and every sample
+    /// in `ys`, for estimators built with `metric="precomputed"`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `xs` - Samples: strings, indices into `strings`, or one-column arrays
+    ///   of either
+    /// * `ys` - Secondary samples, or `None` to compare `xs` with themselves
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - NumPy array of shape `(len(xs), len(ys))` holding the
+    ///   distances
+    #[args(ys = "None")]
+    #[pyo3(text_signature = "($self, xs, /, ys=None)")]
+    fn pairwise(&self, py: Python<'_>, xs: &PyAny, ys: Option<&PyAny>) -> PyResult<PyObject> {
+        self.matrix(py, xs, ys)
+    }
+
+    #[getter]
+    fn metric(&self) -> &str {
+        &self.name
+    }
+
+    #[getter]
+    fn strings(&self) -> Option<Vec<String>> {
+        self.strings.clone()
+    }
+
+    fn __reduce__(slf: PyRef<'_, Self>) -> (PyObject, PyObject) {
+        let py = slf.py();
+        let args = (slf.name.clone(), slf.strings.clone(), slf.n_threads.get());
+        (py.get_type::<Self>().into(), args.into_py(py))
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.strings {
+            Some(strings) => format!("SklearnMetric('{}', strings=<{} strings>)", self.name, strings.len()),
+            None => format!("SklearnMetric('{}')", self.name),
+        }
+    }
+}
+
+/// Wraps the metric named `metric` for scikit-learn, as a `SklearnMetric`.
+/// 
+/// # Arguments
+/// 
+/// * `metric` - Name of the metric, e.g. `"levenshtein"`
+/// * `strings` - Strings that numeric samples index into, or `None` if samples
+///   are strings
+/// * `n_threads` - Number of threads used for whole matrices, or `None` or 0
+///   for all available cores
+/// 
+/// # Returns
+/// 
+/// * `output` - Callable metric for scikit-learn
+#[pyfunction(strings = "None", n_threads = "NThreads::AUTO")]
+#[pyo3(text_signature = "(metric, /, strings=None, n_threads=None)")]
+pub fn sklearn_metric(metric: &str, strings: Option<Vec<String>>, n_threads: NThreads) -> PyResult<SklearnMetric> {
+    SklearnMetric::new(metric, strings, n_threads)
+}
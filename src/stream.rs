@@ -0,0 +1,273 @@
+use std::path::PathBuf;
+
+use numpy::IntoPyArray;
+use pyo3::prelude::*;
+use pyo3::types::{PyIterator, PySlice};
+use rayon::prelude::*;
+
+use crate::checkpoint::{Checkpoint, Fingerprint, Layout};
+use crate::error::InvalidParameterError;
+use crate::input::{self, Strings};
+use crate::metric::Metric;
+use crate::preprocess::Preprocess;
+use crate::progress::Reporter;
+use crate::pool::{thread_pool, NThreads};
+
+// ------------------------------------------------------------------------
+//  Chunked Scores
+// ------------------------------------------------------------------------
+
+pub(crate) fn check_chunk_size(chunk_size: usize) -> PyResult<()> {
+    if chunk_size == 0 {
+        return Err(InvalidParameterError::new_err("chunk_size must be at least 1"));
+    }
+    Ok(())
+}
+
+/// Iterator over the scores between one string and many others, computed one
+/// chunk of candidates at a time. Each step slices the next `chunk_size`
+/// candidates out of the original sequence, or pulls them from the original
+/// iterator, scores them in parallel, and yields the scores as a list, or as
+/// a NumPy array when created through `vectorized_np`. `a` is preprocessed
+/// once and each chunk as it is read.
+#[pyclass(module = "strsim")]
+pub struct ScoreChunks {
+    metric: Metric,
+    a: String,
+    pre: Preprocess,
+    candidates: PyObject,
+    len: Option<usize>,
+    position: usize,
+    chunk_size: usize,
+    n_threads: NThreads,
+    numpy: bool,
+}
+
+impl ScoreChunks {
+    pub(crate) fn new(
+        metric: Metric,
+        a: &str,
+        candidates: &PyAny,
+        pre: Preprocess,
+        chunk_size: usize,
+        n_threads: NThreads,
+        numpy: bool,
+    ) -> PyResult<Self> {
+        check_chunk_size(chunk_size)?;
+        let (candidates, len) = if input::is_sequence(candidates) {
+            (candidates.into(), Some(candidates.len()?))
+        } else {
+            (candidates.iter()?.into(), None)
+        };
+        Ok(Self {
+            metric,
+            a: pre.apply(a).into_owned(),
+            pre,
+            candidates,
+            len,
+            position: 0,
+            chunk_size,
+            n_threads,
+            numpy,
+        })
+    }
+}
+
+#[pymethods]
+impl ScoreChunks {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let candidates = slf.candidates.clone_ref(py).into_ref(py);
+        let (chunk, batch): (Strings, Vec<String>);
+        let bs = match slf.len {
+            Some(len) => {
+                let end = (slf.position + slf.chunk_size).min(len);
+                let slice = PySlice::new(py, slf.position as isize, end as isize, 1);
+                chunk = candidates.get_item(slice)?.extract::<Strings>()?.preprocessed(&slf.pre);
+                chunk.as_strs()?
+            }
+            None => {
+                batch = input::next_batch(candidates.downcast::<PyIterator>()?, slf.chunk_size)?
+                    .into_iter()
+                    .map(|b| slf.pre.apply(&b).into_owned())
+                    .collect();
+                batch.iter().map(String::as_str).collect()
+            }
+        };
+        if bs.is_empty() {
+            return Ok(None);
+        }
+
+        let (score, a) = (slf.metric.score, slf.a.as_str());
+        let reporter = Reporter::new(None, Some(bs.len()));
+        let scores = thread_pool(slf.n_threads)?
+            .install_reporting(&reporter, |counter| {
+                bs
+                    .par_iter()
+                    .map(|&b| (!counter.is_cancelled()).then(|| score(a, b)))
+                    .collect::<Option<Vec<f64>>>()
+            })?
+            .unwrap_or_default();
+        slf.position += bs.len();
+
+        if slf.numpy {
+            crate::vectorized_np::require_numpy(py)?;
+            Ok(Some(scores.into_pyarray(py).into()))
+        } else {
+            Ok(Some(scores.into_py(py)))
+        }
+    }
+
+    /// Number of candidates not yet scored, or `None` when reading from an
+    /// iterator of unknown length.
+    #[getter]
+    fn remaining(&self) -> Option<usize> {
+        self.len.map(|len| len - self.position)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Tiled Matrices
+// ------------------------------------------------------------------------
+
+/// Rows and columns of the largest tiles of a matrix with `columns` columns
+/// whose scores fit in `max_memory` bytes. Tiles span whole rows when at
+/// least one row fits.
+pub(crate) fn tile_shape(columns: usize, max_memory: usize) -> PyResult<(usize, usize)> {
+    let cells = max_memory / std::mem::size_of::<f64>();
+    if cells == 0 {
+        return Err(InvalidParameterError::new_err(format!(
+            "max_memory must be at least {} bytes",
+            std::mem::size_of::<f64>()
+        )));
+    }
+    if columns <= cells {
+        Ok(((cells / columns.max(1)).max(1), columns.max(1)))
+    } else {
+        Ok((1, cells))
+    }
+}
+
+/// Iterator over the tiles of the matrix of scores of every string in one
+/// list against every string in another, computed one tile at a time so
+/// that no more than one tile of results is held in memory. Each step yields
+/// the row and column of the tile's top-left cell and the tile itself, as a
+/// list of rows, or as a 2D NumPy array when created through
+/// `vectorized_np`. With a checkpoint, every finished tile is also saved to
+/// disk, and the tiles a previous run saved are read back instead of being
+/// scored again.
+#[pyclass(module = "strsim")]
+pub struct ScoreTiles {
+    metric: Metric,
+    as_: Vec<String>,
+    bs: Vec<String>,
+    tile_rows: usize,
+    tile_columns: usize,
+    row: usize,
+    column: usize,
+    checkpoint: Option<Checkpoint>,
+    chunk_size: Option<usize>,
+    n_threads: NThreads,
+    numpy: bool,
+}
+
+impl ScoreTiles {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        metric: &str,
+        as_: Strings,
+        bs: Strings,
+        max_memory: usize,
+        checkpoint: Option<PathBuf>,
+        chunk_size: Option<usize>,
+        n_threads: NThreads,
+        numpy: bool,
+    ) -> PyResult<Self> {
+        let scorer = Metric::from_name(metric)?;
+        crate::vectorized::min_len(chunk_size)?;
+        let as_: Vec<String> = as_.as_strs()?.into_iter().map(str::to_owned).collect();
+        let bs: Vec<String> = bs.as_strs()?.into_iter().map(str::to_owned).collect();
+        let (tile_rows, tile_columns) = tile_shape(bs.len(), max_memory)?;
+        let checkpoint = match checkpoint {
+            Some(path) => {
+                let mut fingerprint = Fingerprint::new();
+                fingerprint.write_str(metric);
+                for s in as_.iter().chain(&bs) {
+                    fingerprint.write_str(s);
+                }
+                let layout = Layout {
+                    rows: as_.len(),
+                    columns: bs.len(),
+                    tile_rows,
+                    tile_columns,
+                };
+                Some(Checkpoint::open(&path, fingerprint.finish(), layout)?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            metric: scorer,
+            as_,
+            bs,
+            tile_rows,
+            tile_columns,
+            row: 0,
+            column: 0,
+            checkpoint,
+            chunk_size,
+            n_threads,
+            numpy,
+        })
+    }
+}
+
+#[pymethods]
+impl ScoreTiles {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        if slf.row >= slf.as_.len() || slf.bs.is_empty() {
+            return Ok(None);
+        }
+        let (row, column) = (slf.row, slf.column);
+        let row_end = (row + slf.tile_rows).min(slf.as_.len());
+        let column_end = (column + slf.tile_columns).min(slf.bs.len());
+        let shape = (row_end - row, column_end - column);
+        let tile = match &mut slf.checkpoint {
+            Some(checkpoint) if checkpoint.restored() > 0 => checkpoint.read_tile(row, column, shape.0 * shape.1)?,
+            _ => {
+                let as_: Vec<&str> = slf.as_[row..row_end].iter().map(String::as_str).collect();
+                let bs: Vec<&str> = slf.bs[column..column_end].iter().map(String::as_str).collect();
+                let tile = crate::vectorized::score_matrix(slf.metric.score, crate::batch::packed(&slf.metric), slf.n_threads, slf.chunk_size, None, &as_, &bs, false, None, f64::NAN)?;
+                if let Some(checkpoint) = &mut slf.checkpoint {
+                    checkpoint.write_tile(row, column, &tile)?;
+                }
+                tile
+            }
+        };
+
+        if column_end < slf.bs.len() {
+            slf.column = column_end;
+        } else {
+            slf.column = 0;
+            slf.row = row_end;
+        }
+
+        let tile: PyObject = if slf.numpy {
+            crate::vectorized_np::require_numpy(py)?;
+            numpy::ndarray::Array2::from_shape_vec(shape, tile)
+                .expect("tile buffer matches its shape")
+                .into_pyarray(py)
+                .into()
+        } else {
+            tile.chunks(shape.1).map(<[f64]>::to_vec).collect::<Vec<_>>().into_py(py)
+        };
+        Ok(Some((row, column, tile).into_py(py)))
+    }
+}
@@ -0,0 +1,905 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+
+use crate::error::{Error, Result};
+use crate::frequency::{self, FrequencyDictionary};
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::preprocess::Preprocessor;
+use crate::tokens;
+
+// ------------------------------------------------------------------------
+//  Suggestions
+// ------------------------------------------------------------------------
+
+/// How many suggestions a lookup returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verbosity {
+    /// The closest suggestion with the highest count.
+    Top,
+    /// Every suggestion at the smallest distance found.
+    Closest,
+    /// Every suggestion within the maximum edit distance.
+    All,
+}
+
+impl Verbosity {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "top" => Ok(Self::Top),
+            "closest" => Ok(Self::Closest),
+            "all" => Ok(Self::All),
+            _ => Err(Error::value(format!(
+                "unknown verbosity '{}', expected one of: top, closest, all",
+                name
+            ))),
+        }
+    }
+}
+
+/// A suggested correction: the term, its distance from the input, and its
+/// count in the dictionary.
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    pub term: String,
+    pub distance: usize,
+    pub count: u64,
+}
+
+impl Suggestion {
+    pub fn into_tuple(self) -> (String, usize, u64) {
+        (self.term, self.distance, self.count)
+    }
+}
+
+/// Optimal string alignment distance, the restricted Damerau-Levenshtein
+/// distance SymSpell ranks suggestions by, if it is at most `max`.
+fn distance_within(a: &str, b: &str, max: usize) -> Option<usize> {
+    if a.chars().count().abs_diff(b.chars().count()) > max {
+        return None;
+    }
+    let distance = strsim::osa_distance(a, b);
+    (distance <= max).then_some(distance)
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+fn prefix(s: &str, length: usize) -> String {
+    s.chars().take(length).collect()
+}
+
+
+// ------------------------------------------------------------------------
+//  SymSpell
+// ------------------------------------------------------------------------
+
+/// Spelling correction by symmetric deletion. Every dictionary term is
+/// indexed under all the strings reachable from the first `prefix_length`
+/// characters of it by deleting up to `max_edit_distance` characters. A
+/// lookup generates the deletions of its input and only compares the input
+/// with the terms sharing one, instead of with the whole dictionary.
+/// 
+/// # Arguments
+/// 
+/// * `max_edit_distance` - Largest edit distance a lookup may use
+/// * `prefix_length` - Number of leading characters of each term that are
+///   indexed, greater than `max_edit_distance`
+/// * `preprocessor` - `Preprocessor` applied to the terms and lookups, or
+///   `None`. Suggestions are the terms after preprocessing
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(max_edit_distance=2, prefix_length=7, preprocessor=None)")
+)]
+pub struct SymSpell {
+    max_edit_distance: usize,
+    prefix_length: usize,
+    preprocessor: Preprocessor,
+    /// The terms after preprocessing.
+    terms: Vec<String>,
+    counts: Vec<u64>,
+    index: HashMap<String, usize>,
+    deletes: HashMap<String, Vec<usize>>,
+    max_length: usize,
+    total_count: u64,
+}
+
+impl SymSpell {
+    /// Makes an empty dictionary for lookups within `max_edit_distance`,
+    /// indexing the first `prefix_length` characters of each term.
+    pub fn new(max_edit_distance: usize, prefix_length: usize) -> Result<Self> {
+        if prefix_length <= max_edit_distance {
+            return Err(Error::value(format!(
+                "prefix_length must be greater than max_edit_distance, got {} and {}",
+                prefix_length, max_edit_distance
+            )));
+        }
+        Ok(Self {
+            max_edit_distance,
+            prefix_length,
+            preprocessor: Preprocessor::default(),
+            terms: Vec::new(),
+            counts: Vec::new(),
+            index: HashMap::new(),
+            deletes: HashMap::new(),
+            max_length: 0,
+            total_count: 0,
+        })
+    }
+
+    /// Adds a term to the dictionary, or adds to its count if present.
+    /// Returns whether the term is new.
+    pub fn create_dictionary_entry(&mut self, term: &str, count: u64) -> bool {
+        self.add_entry(term, count)
+    }
+
+    /// Loads terms and their counts from a text file with one entry per
+    /// line, the term in column `term_index` and the count in column
+    /// `count_index`, split by `separator` or by any whitespace when `None`.
+    /// Gzipped files are decompressed. Lines without a term or a valid count
+    /// are skipped. Returns the number of entries loaded.
+    pub fn load_dictionary(
+        &mut self,
+        path: &Path,
+        term_index: usize,
+        count_index: usize,
+        separator: Option<&str>,
+    ) -> Result<usize> {
+        frequency::read_entries(path, term_index, count_index, separator, |term, count| {
+            self.add_entry(term, count);
+        })
+    }
+
+    /// Adds every term of `dictionary` with its count, as
+    /// `create_dictionary_entry` does. Returns the number of new terms.
+    pub fn add_dictionary(&mut self, dictionary: &FrequencyDictionary) -> usize {
+        dictionary.iter().filter(|&(term, count)| self.add_entry(term, count)).count()
+    }
+
+    /// Finds dictionary terms close to a single word, by optimal string
+    /// alignment distance, sorted by distance and then by descending count.
+    /// With `include_unknown`, `phrase` itself is returned when nothing is
+    /// found.
+    pub fn lookup(
+        &self,
+        phrase: &str,
+        verbosity: Verbosity,
+        max_edit_distance: Option<usize>,
+        include_unknown: bool,
+    ) -> Result<Vec<Suggestion>> {
+        let max_edit_distance = self.check_distance(max_edit_distance)?;
+        let phrase = self.preprocessor.apply(phrase);
+        let mut suggestions = self.suggest(&phrase, verbosity, max_edit_distance);
+        if suggestions.is_empty() && include_unknown {
+            suggestions.push(Suggestion { term: phrase.to_string(), distance: max_edit_distance + 1, count: 0 });
+        }
+        Ok(suggestions)
+    }
+
+    /// Corrects a phrase of several words, splitting and joining words where
+    /// that gives a closer or more frequent correction.
+    pub fn lookup_compound(&self, phrase: &str, max_edit_distance: Option<usize>) -> Result<Suggestion> {
+        let max_edit_distance = self.check_distance(max_edit_distance)?;
+        Ok(self.correct_phrase(&self.preprocessor.apply(phrase), max_edit_distance))
+    }
+
+    /// Number of terms in the dictionary.
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The strings reachable from the prefix of `term` by up to
+    /// `max_edit_distance` deletions, including the prefix itself.
+    fn deletes_of(&self, term: &str) -> HashSet<String> {
+        let mut deletes = HashSet::new();
+        if char_len(term) <= self.max_edit_distance {
+            deletes.insert(String::new());
+        }
+        let key = prefix(term, self.prefix_length);
+        deletes.insert(key.clone());
+        self.add_deletes(&key, 0, &mut deletes);
+        deletes
+    }
+
+    fn add_deletes(&self, word: &str, distance: usize, deletes: &mut HashSet<String>) {
+        let distance = distance + 1;
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() <= 1 {
+            return;
+        }
+        for i in 0..chars.len() {
+            let delete: String = chars[..i].iter().chain(&chars[i + 1..]).collect();
+            if deletes.insert(delete.clone()) && distance < self.max_edit_distance {
+                self.add_deletes(&delete, distance, deletes);
+            }
+        }
+    }
+
+    fn add_entry(&mut self, term: &str, count: u64) -> bool {
+        let term = self.preprocessor.apply(term);
+        let term = term.as_ref();
+        self.total_count = self.total_count.saturating_add(count);
+        if let Some(&i) = self.index.get(term) {
+            self.counts[i] = self.counts[i].saturating_add(count);
+            return false;
+        }
+
+        let i = self.terms.len();
+        self.terms.push(term.to_string());
+        self.counts.push(count);
+        self.index.insert(term.to_string(), i);
+        self.max_length = self.max_length.max(char_len(term));
+        for delete in self.deletes_of(term) {
+            self.deletes.entry(delete).or_default().push(i);
+        }
+        true
+    }
+
+    fn check_distance(&self, max_edit_distance: Option<usize>) -> Result<usize> {
+        match max_edit_distance {
+            Some(distance) if distance > self.max_edit_distance => Err(Error::value(format!(
+                "max_edit_distance must be at most {}, the distance the index was built for",
+                self.max_edit_distance
+            ))),
+            Some(distance) => Ok(distance),
+            None => Ok(self.max_edit_distance),
+        }
+    }
+
+    /// Suggestions for a single term, best first.
+    fn suggest(&self, input: &str, verbosity: Verbosity, max_edit_distance: usize) -> Vec<Suggestion> {
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        let input_len = char_len(input);
+        if input_len.saturating_sub(max_edit_distance) > self.max_length {
+            return suggestions;
+        }
+
+        if let Some(&i) = self.index.get(input) {
+            suggestions.push(Suggestion { term: input.to_string(), distance: 0, count: self.counts[i] });
+            if verbosity != Verbosity::All {
+                return suggestions;
+            }
+        }
+        if max_edit_distance == 0 {
+            return suggestions;
+        }
+
+        let mut considered_terms: HashSet<usize> = HashSet::new();
+        if let Some(&i) = self.index.get(input) {
+            considered_terms.insert(i);
+        }
+        let mut considered_deletes: HashSet<String> = HashSet::new();
+        let mut max_distance = max_edit_distance;
+        let input_prefix = prefix(input, self.prefix_length);
+        let input_prefix_len = char_len(&input_prefix);
+        let mut candidates = VecDeque::from([input_prefix]);
+
+        while let Some(candidate) = candidates.pop_front() {
+            let candidate_len = char_len(&candidate);
+            let length_diff = input_prefix_len - candidate_len;
+            if length_diff > max_distance {
+                if verbosity == Verbosity::All {
+                    continue;
+                }
+                break;
+            }
+
+            for &i in self.deletes.get(&candidate).into_iter().flatten() {
+                if !considered_terms.insert(i) {
+                    continue;
+                }
+                let term = &self.terms[i];
+                let term_len = char_len(term);
+                if term_len.abs_diff(input_len) > max_distance
+                    || term_len < candidate_len
+                    || (term_len == candidate_len && *term != candidate)
+                {
+                    considered_terms.remove(&i);
+                    continue;
+                }
+                let distance = match distance_within(input, term, max_distance) {
+                    Some(distance) => distance,
+                    None => continue,
+                };
+
+                let suggestion = Suggestion { term: term.clone(), distance, count: self.counts[i] };
+                if let Some(first) = suggestions.first() {
+                    match verbosity {
+                        Verbosity::Closest if distance < max_distance => suggestions.clear(),
+                        Verbosity::Top => {
+                            if distance < max_distance || suggestion.count > first.count {
+                                max_distance = distance;
+                                suggestions[0] = suggestion;
+                            }
+                            continue;
+                        }
+                        _ => {}
+                    }
+                }
+                if verbosity != Verbosity::All {
+                    max_distance = distance;
+                }
+                suggestions.push(suggestion);
+            }
+
+            if length_diff < max_edit_distance && candidate_len <= self.prefix_length {
+                if verbosity != Verbosity::All && length_diff >= max_distance {
+                    continue;
+                }
+                let chars: Vec<char> = candidate.chars().collect();
+                for j in 0..chars.len() {
+                    let delete: String = chars[..j].iter().chain(&chars[j + 1..]).collect();
+                    if considered_deletes.insert(delete.clone()) {
+                        candidates.push_back(delete);
+                    }
+                }
+            }
+        }
+
+        suggestions.sort_by(|a, b| a.distance.cmp(&b.distance).then(b.count.cmp(&a.count)));
+        suggestions
+    }
+
+    /// Count assumed for a term missing from the dictionary, smaller the
+    /// longer the term.
+    fn unknown_count(term: &str) -> f64 {
+        10.0 / 10f64.powi(char_len(term).min(300) as i32)
+    }
+
+    /// Corrects a whole phrase, fixing misspelt words, words run together,
+    /// and words split apart.
+    fn correct_phrase(&self, input: &str, max_edit_distance: usize) -> Suggestion {
+        let terms = tokens::tokenize(input);
+        let total = self.total_count.max(1) as f64;
+        let mut parts: Vec<(String, usize, f64)> = Vec::new();
+        let mut last_combined = false;
+
+        for (i, term) in terms.iter().enumerate() {
+            let suggestions = self.suggest(term, Verbosity::Top, max_edit_distance);
+
+            if i > 0 && !last_combined {
+                let combined = format!("{}{}", terms[i - 1], term);
+                if let Some(best) = self.suggest(&combined, Verbosity::Top, max_edit_distance).into_iter().next() {
+                    let previous = parts.last().expect("a part per earlier term").clone();
+                    let current = match suggestions.first() {
+                        Some(s) => (s.term.clone(), s.distance, s.count as f64),
+                        None => (term.clone(), max_edit_distance + 1, Self::unknown_count(term)),
+                    };
+                    let separate = previous.1 + current.1;
+                    if best.distance + 1 < separate
+                        || (best.distance + 1 == separate && best.count as f64 > previous.2 / total * current.2)
+                    {
+                        *parts.last_mut().expect("a part per earlier term") =
+                            (best.term, best.distance + 1, best.count as f64);
+                        last_combined = true;
+                        continue;
+                    }
+                }
+            }
+            last_combined = false;
+
+            if let Some(best) = suggestions.first() {
+                if best.distance == 0 || char_len(term) == 1 {
+                    parts.push((best.term.clone(), best.distance, best.count as f64));
+                    continue;
+                }
+            }
+
+            let mut best_split: Option<(String, usize, f64)> =
+                suggestions.first().map(|s| (s.term.clone(), s.distance, s.count as f64));
+            let chars: Vec<char> = term.chars().collect();
+            for j in 1..chars.len() {
+                let left: String = chars[..j].iter().collect();
+                let right: String = chars[j..].iter().collect();
+                let (Some(left), Some(right)) = (
+                    self.suggest(&left, Verbosity::Top, max_edit_distance).into_iter().next(),
+                    self.suggest(&right, Verbosity::Top, max_edit_distance).into_iter().next(),
+                ) else {
+                    continue;
+                };
+                let split = format!("{} {}", left.term, right.term);
+                let distance = distance_within(term, &split, max_edit_distance).unwrap_or(max_edit_distance + 1);
+                let count = left.count as f64 / total * right.count as f64;
+                let better = match &best_split {
+                    Some((_, best_distance, best_count)) => {
+                        distance < *best_distance || (distance == *best_distance && count > *best_count)
+                    }
+                    None => true,
+                };
+                if better {
+                    best_split = Some((split, distance, count));
+                }
+            }
+            parts.push(
+                best_split.unwrap_or_else(|| (term.clone(), max_edit_distance + 1, Self::unknown_count(term))),
+            );
+        }
+
+        let term = parts.iter().map(|(term, _, _)| term.as_str()).collect::<Vec<_>>().join(" ");
+        let count = parts.iter().fold(total, |count, &(_, _, part)| count * part / total);
+        Suggestion {
+            distance: strsim::osa_distance(input, &term),
+            term,
+            count: count.min(u64::MAX as f64) as u64,
+        }
+    }
+}
+
+impl Persist for SymSpell {
+    const KIND: &'static str = "SymSpell";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.usize(self.max_edit_distance);
+        encoder.usize(self.prefix_length);
+        self.preprocessor.encode(encoder);
+        encoder.strs(&self.terms);
+        for &count in &self.counts {
+            encoder.varint(count);
+        }
+        encoder.varint(self.total_count);
+        let mut deletes: Vec<(&String, &Vec<usize>)> = self.deletes.iter().collect();
+        deletes.sort_unstable();
+        encoder.usize(deletes.len());
+        for (delete, terms) in deletes {
+            encoder.str(delete);
+            encoder.ascending(terms);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let mut speller = Self::new(decoder.usize()?, decoder.usize()?)?;
+        speller.preprocessor = Preprocessor::decode(decoder)?;
+        speller.terms = decoder.strs()?;
+        speller.counts = (0..speller.terms.len()).map(|_| decoder.varint()).collect::<crate::Result<_>>()?;
+        speller.total_count = decoder.varint()?;
+        for (i, term) in speller.terms.iter().enumerate() {
+            speller.index.insert(term.clone(), i);
+            speller.max_length = speller.max_length.max(char_len(term));
+        }
+        decoder.check(speller.index.len() == speller.terms.len())?;
+        for _ in 0..decoder.length()? {
+            let delete = decoder.str()?;
+            let terms = decoder.ascending(speller.terms.len())?;
+            speller.deletes.insert(delete, terms);
+        }
+        Ok(speller)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SymSpell {
+    #[new]
+    #[args(max_edit_distance = "2", prefix_length = "7", preprocessor = "None")]
+    fn py_new(max_edit_distance: usize, prefix_length: usize, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        Ok(Self { preprocessor: preprocessor.unwrap_or_default(), ..Self::new(max_edit_distance, prefix_length)? })
+    }
+
+    /// Adds a term to the dictionary, or adds to its count if present.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `term` - Correctly spelt term
+    /// * `count` - Frequency of the term, used to rank suggestions
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Whether the term is new
+    #[pyo3(name = "create_dictionary_entry", text_signature = "($self, term, count, /)")]
+    fn py_create_dictionary_entry(&mut self, term: &str, count: u64) -> bool {
+        self.create_dictionary_entry(term, count)
+    }
+
+    /// Loads terms and their counts from a text file with one entry per
+    /// line, gzipped or not. Lines without a term or a valid count are
+    /// skipped.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - Path of the dictionary file
+    /// * `term_index` - Column of the term
+    /// * `count_index` - Column of the count
+    /// * `separator` - String between columns, or `None` for any whitespace
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Number of entries loaded
+    #[args(term_index = "0", count_index = "1", separator = "None")]
+    #[pyo3(name = "load_dictionary", text_signature = "($self, path, /, term_index=0, count_index=1, separator=None)")]
+    fn py_load_dictionary(
+        &mut self,
+        path: PathBuf,
+        term_index: usize,
+        count_index: usize,
+        separator: Option<&str>,
+    ) -> PyResult<usize> {
+        Ok(self.load_dictionary(&path, term_index, count_index, separator)?)
+    }
+
+    /// Adds every term of a frequency dictionary with its count, so that a
+    /// list read once can fill several dictionaries.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `dictionary` - `FrequencyDictionary` of the terms to add
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Number of new terms
+    #[pyo3(name = "add_dictionary", text_signature = "($self, dictionary, /)")]
+    fn py_add_dictionary(&mut self, dictionary: PyRef<'_, FrequencyDictionary>) -> usize {
+        self.add_dictionary(&dictionary)
+    }
+
+    /// Finds dictionary terms close to a single word, by optimal string
+    /// alignment distance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `phrase` - Word to correct
+    /// * `verbosity` - `"top"` for the closest, most frequent term,
+    ///   `"closest"` for all terms at the smallest distance, or `"all"` for
+    ///   every term within `max_edit_distance`
+    /// * `max_edit_distance` - Largest distance of a suggestion, or `None`
+    ///   for the distance the index was built for
+    /// * `include_unknown` - Whether to return `phrase` itself when nothing
+    ///   is found
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Triples of a term, its distance, and its count, sorted by
+    ///   distance and then by descending count
+    #[args(verbosity = "\"top\"", max_edit_distance = "None", include_unknown = "false")]
+    #[pyo3(
+        name = "lookup",
+        text_signature = "($self, phrase, /, verbosity='top', max_edit_distance=None, include_unknown=False)"
+    )]
+    fn py_lookup(
+        &self,
+        phrase: &str,
+        verbosity: &str,
+        max_edit_distance: Option<usize>,
+        include_unknown: bool,
+    ) -> PyResult<Vec<(String, usize, u64)>> {
+        let verbosity = Verbosity::from_name(verbosity)?;
+        let suggestions = self.lookup(phrase, verbosity, max_edit_distance, include_unknown)?;
+        Ok(suggestions.into_iter().map(Suggestion::into_tuple).collect())
+    }
+
+    /// Corrects a phrase of several words. Words are lowercased and
+    /// corrected one at a time, and words wrongly run together or split
+    /// apart are split or joined where that gives a closer or more frequent
+    /// correction.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `phrase` - Phrase to correct
+    /// * `max_edit_distance` - Largest distance of each word's correction, or
+    ///   `None` for the distance the index was built for
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The corrected phrase, its distance from `phrase`, and its
+    ///   estimated count
+    #[args(max_edit_distance = "None")]
+    #[pyo3(name = "lookup_compound", text_signature = "($self, phrase, /, max_edit_distance=None)")]
+    fn py_lookup_compound(&self, phrase: &str, max_edit_distance: Option<usize>) -> PyResult<(String, usize, u64)> {
+        Ok(self.lookup_compound(phrase, max_edit_distance)?.into_tuple())
+    }
+
+    /// Saves the dictionary to a file in a compact binary format, to be
+    /// restored by `SymSpell.load` without generating the deletes again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads a dictionary written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved dictionary
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of terms in the dictionary.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Spell Correction
+// ------------------------------------------------------------------------
+
+/// Spelling corrector ranking the dictionary words near a misspelling by
+/// both their distance and their frequency, in the spirit of a noisy
+/// channel model: each correction scores `ln(count) - distance *
+/// ln(edit_penalty)`, so a word `edit_penalty` times more frequent makes up
+/// for one more edit. A word found in the dictionary is always its own best
+/// correction. Candidates come from a SymSpell index and distances are
+/// optimal string alignment distances.
+/// 
+/// # Arguments
+/// 
+/// * `max_edits` - Largest edit distance a correction may have
+/// * `edit_penalty` - Factor by which each edit lowers the weight of a
+///   correction, at least 1
+/// * `preprocessor` - `Preprocessor` applied to the words and the words to
+///   correct, or `None`. Corrections are the words after preprocessing
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(max_edits=2, /, edit_penalty=1000.0, preprocessor=None)")
+)]
+pub struct SpellCorrector {
+    speller: SymSpell,
+    edit_penalty: f64,
+}
+
+impl SpellCorrector {
+    /// Makes an empty corrector for corrections within `max_edits`, each
+    /// edit lowering the weight of a correction by `edit_penalty`.
+    pub fn new(max_edits: usize, edit_penalty: f64) -> Result<Self> {
+        if edit_penalty.is_nan() || edit_penalty < 1.0 {
+            return Err(Error::value(format!("edit_penalty must be at least 1, got {}", edit_penalty)));
+        }
+        Ok(Self { speller: SymSpell::new(max_edits, max_edits.max(6) + 1)?, edit_penalty })
+    }
+
+    /// Adds a word to the dictionary, or adds to its count if present.
+    /// Returns whether the word is new.
+    pub fn add(&mut self, word: &str, count: u64) -> bool {
+        self.speller.add_entry(word, count)
+    }
+
+    /// Loads words and their counts from a text file with one entry per
+    /// line, as `SymSpell::load_dictionary` does.
+    pub fn load_dictionary(
+        &mut self,
+        path: &Path,
+        term_index: usize,
+        count_index: usize,
+        separator: Option<&str>,
+    ) -> Result<usize> {
+        self.speller.load_dictionary(path, term_index, count_index, separator)
+    }
+
+    /// Adds every word of `dictionary` with its count. Returns the number of
+    /// new words.
+    pub fn add_dictionary(&mut self, dictionary: &FrequencyDictionary) -> usize {
+        self.speller.add_dictionary(dictionary)
+    }
+
+    /// Suggests up to `top_k` corrections for a word, best first, within
+    /// `max_edits`, or within the corrector's own when `None`.
+    pub fn correct(&self, word: &str, max_edits: Option<usize>, top_k: usize) -> Result<Vec<Suggestion>> {
+        let max_edits = match max_edits {
+            Some(max_edits) if max_edits > self.speller.max_edit_distance => {
+                return Err(Error::value(format!(
+                    "max_edits must be at most {}, the distance the corrector was built for",
+                    self.speller.max_edit_distance
+                )));
+            }
+            max_edits => max_edits.unwrap_or(self.speller.max_edit_distance),
+        };
+        Ok(self.corrections(word, max_edits, top_k))
+    }
+
+    /// Number of words in the dictionary.
+    pub fn len(&self) -> usize {
+        self.speller.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.speller.is_empty()
+    }
+
+    fn corrections(&self, word: &str, max_edits: usize, top_k: usize) -> Vec<Suggestion> {
+        let word = self.speller.preprocessor.apply(word);
+        let mut suggestions = self.speller.suggest(&word, Verbosity::All, max_edits);
+        let weight = self.edit_penalty.ln();
+        let score = |suggestion: &Suggestion| (suggestion.count as f64).ln() - suggestion.distance as f64 * weight;
+        suggestions.sort_by(|a, b| {
+            (a.distance > 0)
+                .cmp(&(b.distance > 0))
+                .then(score(b).total_cmp(&score(a)))
+                .then(a.distance.cmp(&b.distance))
+                .then_with(|| a.term.cmp(&b.term))
+        });
+        suggestions.truncate(top_k);
+        suggestions
+    }
+}
+
+impl Persist for SpellCorrector {
+    const KIND: &'static str = "SpellCorrector";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.f64(self.edit_penalty);
+        self.speller.encode(encoder);
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let edit_penalty = decoder.f64()?;
+        decoder.check(edit_penalty >= 1.0)?;
+        Ok(Self { edit_penalty, speller: SymSpell::decode(decoder)? })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SpellCorrector {
+    #[new]
+    #[args(max_edits = "2", edit_penalty = "1000.0", preprocessor = "None")]
+    fn py_new(max_edits: usize, edit_penalty: f64, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        let mut corrector = Self::new(max_edits, edit_penalty)?;
+        corrector.speller.preprocessor = preprocessor.unwrap_or_default();
+        Ok(corrector)
+    }
+
+    /// Adds a word to the dictionary, or adds to its count if present.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `word` - Correctly spelt word
+    /// * `count` - Frequency of the word
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Whether the word is new
+    #[args(count = "1")]
+    #[pyo3(name = "add", text_signature = "($self, word, /, count=1)")]
+    fn py_add(&mut self, word: &str, count: u64) -> bool {
+        self.add(word, count)
+    }
+
+    /// Loads words and their counts from a text file with one entry per
+    /// line, as `SymSpell.load_dictionary` does.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - Path of the dictionary file
+    /// * `term_index` - Column of the word
+    /// * `count_index` - Column of the count
+    /// * `separator` - String between columns, or `None` for any whitespace
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Number of entries loaded
+    #[args(term_index = "0", count_index = "1", separator = "None")]
+    #[pyo3(name = "load_dictionary", text_signature = "($self, path, /, term_index=0, count_index=1, separator=None)")]
+    fn py_load_dictionary(
+        &mut self,
+        path: PathBuf,
+        term_index: usize,
+        count_index: usize,
+        separator: Option<&str>,
+    ) -> PyResult<usize> {
+        Ok(self.load_dictionary(&path, term_index, count_index, separator)?)
+    }
+
+    /// Adds every word of a frequency dictionary with its count.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `dictionary` - `FrequencyDictionary` of the words to add
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Number of new words
+    #[pyo3(name = "add_dictionary", text_signature = "($self, dictionary, /)")]
+    fn py_add_dictionary(&mut self, dictionary: PyRef<'_, FrequencyDictionary>) -> usize {
+        self.add_dictionary(&dictionary)
+    }
+
+    /// Suggests corrections for a word, best first.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `word` - Word to correct
+    /// * `max_edits` - Largest edit distance of a correction, or `None` for
+    ///   the corrector's
+    /// * `top_k` - Number of corrections to return
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Up to `top_k` triples of a word, its distance, and its
+    ///   count
+    #[args(max_edits = "None", top_k = "1")]
+    #[pyo3(name = "correct", text_signature = "($self, word, /, max_edits=None, top_k=1)")]
+    fn py_correct(&self, word: &str, max_edits: Option<usize>, top_k: usize) -> PyResult<Vec<(String, usize, u64)>> {
+        let corrections = self.correct(word, max_edits, top_k)?;
+        Ok(corrections.into_iter().map(Suggestion::into_tuple).collect())
+    }
+
+    /// Saves the corrector to a file in a compact binary format, to be
+    /// restored by `SpellCorrector.load` without loading the words again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads a corrector written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved corrector
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of words in the dictionary.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preprocessor_applies_to_terms_and_lookups() {
+        let preprocessor = crate::testing::preprocessor(&["lowercase"]);
+        let mut speller = SymSpell { preprocessor, ..SymSpell::new(2, 7).unwrap() };
+        speller.create_dictionary_entry("Hello", 3);
+        assert!(!speller.create_dictionary_entry("HELLO", 2));
+        let lookup = |speller: &SymSpell| -> Vec<(String, usize, u64)> {
+            let suggestions = speller.lookup("HELO", Verbosity::Top, None, false).unwrap();
+            suggestions.into_iter().map(Suggestion::into_tuple).collect()
+        };
+        assert_eq!(lookup(&speller), [("hello".to_string(), 1, 5)]);
+        assert_eq!(lookup(&SymSpell::from_bytes(&speller.to_bytes()).unwrap()), [("hello".to_string(), 1, 5)]);
+    }
+}
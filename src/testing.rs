@@ -0,0 +1,75 @@
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyList;
+
+#[cfg(feature = "python")]
+use crate::preprocess::Preprocessor;
+
+// ------------------------------------------------------------------------
+//  Test Inputs
+// ------------------------------------------------------------------------
+
+/// Deterministic xorshift generator of test inputs, so that failures
+/// reproduce without a dependency on `rand`.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform integer in `0..n`.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// String of up to `max_len` characters drawn from `alphabet`.
+    pub(crate) fn string(&mut self, alphabet: &[char], max_len: usize) -> String {
+        let len = self.below(max_len + 1);
+        (0..len).map(|_| alphabet[self.below(alphabet.len())]).collect()
+    }
+
+    /// Strings over a small alphabet with a non-ASCII character, half of
+    /// them edits or extensions of earlier ones so that close matches and
+    /// long common prefixes are frequent.
+    pub(crate) fn strings(&mut self, count: usize, max_len: usize) -> Vec<String> {
+        const ALPHABET: [char; 4] = ['a', 'b', 'c', 'é'];
+        let mut strings: Vec<String> = Vec::with_capacity(count);
+        for _ in 0..count {
+            if strings.is_empty() || self.below(2) == 0 {
+                strings.push(self.string(&ALPHABET, max_len));
+                continue;
+            }
+            let mut chars: Vec<char> = strings[self.below(strings.len())].chars().collect();
+            if self.below(2) == 0 {
+                let extension = self.string(&ALPHABET, max_len / 2);
+                strings.push(chars.into_iter().chain(extension.chars()).collect());
+                continue;
+            }
+            for _ in 0..=self.below(3) {
+                let at = self.below(chars.len() + 1);
+                match self.below(3) {
+                    0 if at < chars.len() => drop(chars.remove(at)),
+                    1 if at < chars.len() => chars[at] = ALPHABET[self.below(ALPHABET.len())],
+                    _ => chars.insert(at, ALPHABET[self.below(ALPHABET.len())]),
+                }
+            }
+            strings.push(chars.into_iter().collect());
+        }
+        strings
+    }
+}
+
+/// A `Preprocessor` running the steps named `steps`, as Python builds one.
+#[cfg(feature = "python")]
+pub(crate) fn preprocessor(steps: &[&str]) -> Preprocessor {
+    Python::with_gil(|py| Preprocessor::new(Some(PyList::new(py, steps))).expect("known steps"))
+}
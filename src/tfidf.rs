@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::metric;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+use crate::tokens::check_ngram;
+use crate::tokens;
+use crate::pool::thread_pool;
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+
+// ------------------------------------------------------------------------
+//  Inverse Document Frequencies
+// ------------------------------------------------------------------------
+
+/// Token document frequencies gathered from a corpus.
+#[derive(Clone, Debug, Default)]
+pub struct DocumentFrequencies {
+    counts: HashMap<String, usize>,
+    n_documents: usize,
+}
+
+impl DocumentFrequencies {
+    /// Counts, for every token, the number of documents it appears in.
+    pub fn fit<'a, I: IntoIterator<Item = &'a str>>(corpus: I) -> Self {
+        let mut frequencies = Self::default();
+        for document in corpus {
+            let mut seen = tokens::tokenize(document);
+            seen.sort_unstable();
+            seen.dedup();
+            for token in seen {
+                *frequencies.counts.entry(token).or_insert(0) += 1;
+            }
+            frequencies.n_documents += 1;
+        }
+        frequencies
+    }
+
+    /// Smoothed inverse document frequency, `ln((1 + N) / (1 + df)) + 1`.
+    /// Tokens never seen in the corpus receive the largest weight.
+    pub fn idf(&self, token: &str) -> f64 {
+        let df = self.counts.get(token).copied().unwrap_or(0);
+        ((1.0 + self.n_documents as f64) / (1.0 + df as f64)).ln() + 1.0
+    }
+
+    /// Unit-length TF-IDF token weights of `s`, using `ln(tf + 1) * idf`.
+    pub fn weights(&self, s: &str) -> Vec<(String, f64)> {
+        let mut tf: HashMap<String, usize> = HashMap::new();
+        for token in tokens::tokenize(s) {
+            *tf.entry(token).or_insert(0) += 1;
+        }
+
+        let mut weights: Vec<(String, f64)> = tf
+            .into_iter()
+            .map(|(token, count)| {
+                let w = (count as f64 + 1.0).ln() * self.idf(&token);
+                (token, w)
+            })
+            .collect();
+
+        let norm = weights.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            weights.iter_mut().for_each(|(_, w)| *w /= norm);
+        }
+        weights
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Soft-TFIDF
+// ------------------------------------------------------------------------
+
+/// Soft-TFIDF similarity (Cohen, Ravikumar & Fienberg, 2003). Tokens are
+/// weighted by TF-IDF fit on a corpus, and tokens of `a` are paired with
+/// their most similar token of `b` under a secondary character metric. Pairs
+/// scoring below `threshold` contribute nothing.
+/// 
+/// # Arguments
+/// 
+/// * `corpus` - Documents used to fit the token IDF weights
+/// * `threshold` - Minimum secondary similarity for two tokens to match,
+///   between 0.0 and 1.0
+/// * `metric` - Name of the secondary similarity, e.g. `"jaro_winkler"`
+/// * `preprocessor` - `Preprocessor` applied to the corpus and to the
+///   compared strings before they are split into tokens, or `None`
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(corpus, /, threshold=0.9, metric='jaro_winkler', preprocessor=None)")
+)]
+pub struct SoftTfidf {
+    frequencies: DocumentFrequencies,
+    threshold: f64,
+    similarity: fn(&str, &str) -> f64,
+    preprocessor: Preprocessor,
+}
+
+impl SoftTfidf {
+    /// Fits the token weights on `corpus`. Tokens match when their
+    /// similarity under the metric named `metric` is at least `threshold`,
+    /// between 0.0 and 1.0.
+    pub fn new<'a, I: IntoIterator<Item = &'a str>>(corpus: I, threshold: f64, metric: &str) -> Result<Self> {
+        Self::with_preprocessor(corpus, threshold, metric, Preprocessor::default())
+    }
+
+    /// Fits the token weights on `corpus` after `preprocessor`, which is
+    /// then applied to the compared strings too.
+    pub(crate) fn with_preprocessor<'a, I: IntoIterator<Item = &'a str>>(
+        corpus: I,
+        threshold: f64,
+        metric: &str,
+        preprocessor: Preprocessor,
+    ) -> Result<Self> {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(Error::value("threshold must be between 0.0 and 1.0"));
+        }
+        let corpus: Vec<_> = corpus.into_iter().map(|document| preprocessor.apply(document)).collect();
+        Ok(Self {
+            frequencies: DocumentFrequencies::fit(corpus.iter().map(|document| &**document)),
+            threshold,
+            similarity: metric::similarity(metric)?,
+            preprocessor,
+        })
+    }
+
+    /// Soft-TFIDF similarity between `a` and `b`, between 0.0 and 1.0.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        self.score(&self.weights(a), &self.weights(b))
+    }
+
+    /// Parallel version of `similarity` against each of `bs`, on `n_threads`
+    /// threads, or on all available cores when `None`. The weights of `a`
+    /// are computed once.
+    pub fn similarities<S: AsRef<str> + Sync>(&self, a: &str, bs: &[S], n_threads: Option<usize>) -> Result<Vec<f64>> {
+        let weights_a = self.weights(a);
+        let output = thread_pool(n_threads)?.run_sized(bs.len(), 1, |counter, _| self.scores(counter, &weights_a, bs));
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Inverse document frequency of `token` in the fitted corpus.
+    pub fn idf(&self, token: &str) -> f64 {
+        self.frequencies.idf(&token.to_lowercase())
+    }
+
+    /// TF-IDF weights of the tokens of `s` after the preprocessor.
+    fn weights(&self, s: &str) -> Vec<(String, f64)> {
+        self.frequencies.weights(&self.preprocessor.apply(s))
+    }
+
+    /// Scores of the weights `weights_a` against each of `bs`, in parallel,
+    /// or `None` if the job was cancelled.
+    fn scores<S: AsRef<str> + Sync>(&self, counter: &Counter, weights_a: &[(String, f64)], bs: &[S]) -> Option<Vec<f64>> {
+        bs.par_iter()
+            .map(|b| (!counter.is_cancelled()).then(|| self.score(weights_a, &self.weights(b.as_ref()))))
+            .collect()
+    }
+
+    fn score(&self, a: &[(String, f64)], b: &[(String, f64)]) -> f64 {
+        let mut total = 0.0;
+        for (token_a, weight_a) in a {
+            let best = b
+                .iter()
+                .map(|(token_b, weight_b)| ((self.similarity)(token_a, token_b), *weight_b))
+                .fold(None, |best: Option<(f64, f64)>, (sim, w)| match best {
+                    Some((best_sim, _)) if best_sim >= sim => best,
+                    _ => Some((sim, w)),
+                });
+
+            if let Some((sim, weight_b)) = best {
+                if sim >= self.threshold {
+                    total += weight_a * weight_b * sim;
+                }
+            }
+        }
+        total.min(1.0)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SoftTfidf {
+    #[new]
+    #[args(threshold = "0.9", metric = "\"jaro_winkler\"", preprocessor = "None")]
+    fn py_new(corpus: Vec<&str>, threshold: f64, metric: &str, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        Ok(Self::with_preprocessor(corpus, threshold, metric, preprocessor.unwrap_or_default())?)
+    }
+
+    /// Calculates the Soft-TFIDF similarity between two strings. The returned
+    /// value is between 0.0 and 1.0 (higher value means more similar).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarity between `a` and `b`
+    #[pyo3(name = "similarity", text_signature = "($self, a, b, /)")]
+    fn py_similarity(&self, a: &str, b: &str) -> f64 {
+        self.similarity(a, b)
+    }
+
+    /// Parallel version of `similarity`. The weights of `a` are computed once.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `bs` - Secondary strings to compare to `a`
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Similarities between `a` and each `b` in `bs`
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "similarities", text_signature = "($self, a, bs, /, n_threads=None)")]
+    fn py_similarities(&self, a: &str, bs: Strings, n_threads: NThreads) -> PyResult<Vec<f64>> {
+        let bs = bs.as_strs()?;
+        let weights_a = self.weights(a);
+        let reporter = Reporter::new(None, Some(bs.len()));
+        let output = thread_pool(n_threads)?
+            .install_reporting(&reporter, |counter| self.scores(counter, &weights_a, &bs))?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Inverse document frequency of `token` in the fitted corpus.
+    #[pyo3(name = "idf", text_signature = "($self, token, /)")]
+    fn py_idf(&self, token: &str) -> f64 {
+        self.idf(token)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Character N-Gram TF-IDF Matching
+// ------------------------------------------------------------------------
+
+/// Character n-gram TF-IDF index over a list of choices. Queries are scored
+/// against every indexed choice by cosine similarity using an inverted index,
+/// so only choices sharing at least one n-gram with the query are visited.
+/// 
+/// # Arguments
+/// 
+/// * `ngram` - Number of characters per n-gram
+/// * `preprocessor` - `Preprocessor` applied to the choices and queries before
+///   they are split into n-grams, or `None`
+#[cfg_attr(feature = "python", pyclass(module = "strsim", text_signature = "(ngram=3, /, preprocessor=None)"))]
+pub struct TfidfMatcher {
+    ngram: usize,
+    preprocessor: Preprocessor,
+    vocabulary: HashMap<String, usize>,
+    idf: Vec<f64>,
+    postings: Vec<Vec<(usize, f64)>>,
+    n_choices: usize,
+}
+
+impl TfidfMatcher {
+    /// Makes an empty matcher over n-grams of `ngram` characters.
+    pub fn new(ngram: usize) -> Result<Self> {
+        check_ngram(ngram)?;
+        Ok(Self {
+            ngram,
+            preprocessor: Preprocessor::default(),
+            vocabulary: HashMap::new(),
+            idf: Vec::new(),
+            postings: Vec::new(),
+            n_choices: 0,
+        })
+    }
+
+    /// Builds the index from `choices`, replacing any previously fit index.
+    pub fn fit<I>(&mut self, choices: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.vocabulary.clear();
+        let counts: Vec<HashMap<String, usize>> = choices
+            .into_iter()
+            .map(|choice| self.term_counts(choice.as_ref()))
+            .collect();
+
+        let mut document_frequency: Vec<usize> = Vec::new();
+        for tf in &counts {
+            for gram in tf.keys() {
+                let next = self.vocabulary.len();
+                let term = *self.vocabulary.entry(gram.clone()).or_insert(next);
+                if term == document_frequency.len() {
+                    document_frequency.push(0);
+                }
+                document_frequency[term] += 1;
+            }
+        }
+
+        let n_choices = counts.len();
+        let n_documents = n_choices as f64;
+        self.idf = document_frequency
+            .iter()
+            .map(|&df| ((1.0 + n_documents) / (1.0 + df as f64)).ln() + 1.0)
+            .collect();
+
+        self.postings = vec![Vec::new(); self.vocabulary.len()];
+        for (choice, tf) in counts.into_iter().enumerate() {
+            let weights: Vec<(usize, f64)> = tf
+                .into_iter()
+                .map(|(gram, count)| {
+                    let term = self.vocabulary[&gram];
+                    (term, count as f64 * self.idf[term])
+                })
+                .collect();
+            let norm = weights.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+            for (term, weight) in weights {
+                self.postings[term].push((choice, weight / norm));
+            }
+        }
+        self.n_choices = n_choices;
+    }
+
+    /// Up to `top_k` choices scoring at least `threshold` against `s` by
+    /// TF-IDF cosine similarity, as pairs of a choice index and its score,
+    /// best match first.
+    pub fn query(&self, s: &str, top_k: usize, threshold: f64) -> Vec<(usize, f64)> {
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for (term, weight) in self.query_weights(s) {
+            for &(choice, choice_weight) in &self.postings[term] {
+                *scores.entry(choice).or_insert(0.0) += weight * choice_weight;
+            }
+        }
+
+        let mut matches: Vec<(usize, f64)> = scores
+            .into_iter()
+            .map(|(choice, score)| (choice, score.min(1.0)))
+            .filter(|&(_, score)| score >= threshold)
+            .collect();
+        matches.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        matches.truncate(top_k);
+        matches
+    }
+
+    /// Parallel version of `query` for many query strings, on `n_threads`
+    /// threads, or on all available cores when `None`.
+    pub fn query_many<S: AsRef<str> + Sync>(
+        &self,
+        strings: &[S],
+        top_k: usize,
+        threshold: f64,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<Vec<(usize, f64)>>> {
+        let output = thread_pool(n_threads)?.run_sized(strings.len(), 1, |counter, _| {
+            self.query_all(counter, strings, top_k, threshold)
+        });
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Number of choices in the fitted index.
+    pub fn len(&self) -> usize {
+        self.n_choices
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n_choices == 0
+    }
+
+    fn term_counts(&self, s: &str) -> HashMap<String, usize> {
+        let mut tf = HashMap::new();
+        for gram in tokens::char_ngrams(&self.preprocessor.apply(s), self.ngram) {
+            *tf.entry(gram).or_insert(0) += 1;
+        }
+        tf
+    }
+
+    fn query_weights(&self, s: &str) -> Vec<(usize, f64)> {
+        let mut weights: Vec<(usize, f64)> = self
+            .term_counts(s)
+            .into_iter()
+            .filter_map(|(gram, count)| {
+                self.vocabulary
+                    .get(&gram)
+                    .map(|&term| (term, count as f64 * self.idf[term]))
+            })
+            .collect();
+
+        let norm = weights.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            weights.iter_mut().for_each(|(_, w)| *w /= norm);
+        }
+        weights
+    }
+
+    /// `query` for each of `strings`, in parallel, or `None` if the job was
+    /// cancelled.
+    fn query_all<S: AsRef<str> + Sync>(
+        &self,
+        counter: &Counter,
+        strings: &[S],
+        top_k: usize,
+        threshold: f64,
+    ) -> Option<Vec<Vec<(usize, f64)>>> {
+        strings
+            .par_iter()
+            .map(|s| (!counter.is_cancelled()).then(|| self.query(s.as_ref(), top_k, threshold)))
+            .collect()
+    }
+}
+
+impl Persist for TfidfMatcher {
+    const KIND: &'static str = "TfidfMatcher";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.usize(self.ngram);
+        self.preprocessor.encode(encoder);
+        encoder.usize(self.n_choices);
+        let mut grams = vec![""; self.vocabulary.len()];
+        for (gram, &term) in &self.vocabulary {
+            grams[term] = gram;
+        }
+        encoder.strs(&grams);
+        for (&idf, postings) in self.idf.iter().zip(&self.postings) {
+            encoder.f64(idf);
+            let choices: Vec<usize> = postings.iter().map(|&(choice, _)| choice).collect();
+            encoder.ascending(&choices);
+            for &(_, weight) in postings {
+                encoder.f64(weight);
+            }
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let ngram = decoder.usize()?;
+        let mut matcher = Self { preprocessor: Preprocessor::decode(decoder)?, ..Self::new(ngram)? };
+        matcher.n_choices = decoder.usize()?;
+        let grams = decoder.strs()?;
+        let n_grams = grams.len();
+        matcher.vocabulary = grams.into_iter().enumerate().map(|(term, gram)| (gram, term)).collect();
+        decoder.check(matcher.vocabulary.len() == n_grams)?;
+        for _ in 0..n_grams {
+            matcher.idf.push(decoder.f64()?);
+            let choices = decoder.ascending(matcher.n_choices)?;
+            let postings = choices
+                .into_iter()
+                .map(|choice| Ok((choice, decoder.f64()?)))
+                .collect::<crate::Result<_>>()?;
+            matcher.postings.push(postings);
+        }
+        Ok(matcher)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl TfidfMatcher {
+    #[new]
+    #[args(ngram = "3", preprocessor = "None")]
+    fn py_new(ngram: usize, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        Ok(Self { preprocessor: preprocessor.unwrap_or_default(), ..Self::new(ngram)? })
+    }
+
+    /// Builds the index from `choices`, replacing any previously fit index.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `choices` - Strings that queries will be matched against
+    #[pyo3(name = "fit", text_signature = "($self, choices, /)")]
+    fn py_fit(&mut self, choices: Vec<&str>) {
+        self.fit(choices)
+    }
+
+    /// Finds the best matching choices for each query string by TF-IDF cosine
+    /// similarity. Each query yields at most `top_k` matches, sorted by
+    /// descending score, and only matches scoring at least `threshold`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `strings` - Query strings to match against the fitted choices
+    /// * `top_k` - Maximum number of matches per query
+    /// * `threshold` - Minimum cosine similarity for a match
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each query, a list of `(choice index, score)` pairs
+    #[pyo3(name = "query", text_signature = "($self, strings, /, top_k=1, threshold=0.0, n_threads=None)")]
+    #[args(top_k = "1", threshold = "0.0", n_threads = "NThreads::AUTO")]
+    fn py_query(
+        &self,
+        strings: Vec<&str>,
+        top_k: usize,
+        threshold: f64,
+        n_threads: NThreads,
+    ) -> PyResult<Vec<Vec<(usize, f64)>>> {
+        let reporter = Reporter::new(None, Some(strings.len().saturating_mul(self.n_choices)));
+        let output = thread_pool(n_threads)?
+            .install_reporting(&reporter, |counter| self.query_all(counter, &strings, top_k, threshold))?;
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Saves the matcher to a file in a compact binary format, to be restored
+    /// by `TfidfMatcher.load` without fitting it again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads a matcher written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved matcher
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of choices in the fitted index.
+    fn __len__(&self) -> usize {
+        self.n_choices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that both hold the same choices with the same scores, up to
+    /// the rounding of sums taken in hash order.
+    fn assert_close(found: &[Vec<(usize, f64)>], expected: &[Vec<(usize, f64)>]) {
+        assert_eq!(found.len(), expected.len());
+        for (found, expected) in found.iter().zip(expected) {
+            assert_eq!(found.len(), expected.len(), "{:?} != {:?}", found, expected);
+            for (&(i, x), &(j, y)) in found.iter().zip(expected) {
+                assert!(i == j && (x - y).abs() < 1e-12, "{:?} != {:?}", found, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn matcher_ranks_choices_by_cosine_similarity() {
+        let mut matcher = TfidfMatcher::new(3).unwrap();
+        matcher.fit(["new york", "new jersey", "york", "boston"]);
+        assert_eq!(matcher.len(), 4);
+        let found = matcher.query("new york city", 4, 0.1);
+        let choices: Vec<usize> = found.iter().map(|&(choice, _)| choice).collect();
+        assert_eq!(choices[..2], [0, 2]);
+        assert!(found.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_close(&[matcher.query("boston", 1, 0.0)], &[vec![(3, 1.0)]]);
+        assert!(matcher.query("zzz", 3, 0.0).is_empty());
+    }
+
+    /// Cosine similarities of `query` to each of `corpus` over dense
+    /// vectors of n-gram counts times the smoothed idf
+    /// `ln((1 + n) / (1 + df)) + 1`, as scikit-learn's `TfidfVectorizer`
+    /// weighs them.
+    fn dense_similarities(corpus: &[&str], query: &str, ngram: usize) -> Vec<f64> {
+        let grams: Vec<Vec<String>> = corpus.iter().map(|s| tokens::char_ngrams(s, ngram)).collect();
+        let mut vocabulary: Vec<&String> = grams.iter().flatten().collect();
+        vocabulary.sort_unstable();
+        vocabulary.dedup();
+        let n = corpus.len() as f64;
+        let idf: Vec<f64> = vocabulary
+            .iter()
+            .map(|&gram| ((1.0 + n) / (1.0 + grams.iter().filter(|g| g.contains(gram)).count() as f64)).ln() + 1.0)
+            .collect();
+        let vector = |grams: &[String]| -> Vec<f64> {
+            let v: Vec<f64> = vocabulary.iter().zip(&idf).map(|(&gram, idf)| grams.iter().filter(|&g| g == gram).count() as f64 * idf).collect();
+            let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            v.into_iter().map(|x| if norm > 0.0 { x / norm } else { 0.0 }).collect()
+        };
+        let q = vector(&tokens::char_ngrams(query, ngram));
+        grams.iter().map(|g| vector(g).iter().zip(&q).map(|(x, y)| x * y).sum()).collect()
+    }
+
+    #[test]
+    fn matcher_ranks_a_known_corpus_as_dense_tfidf() {
+        let corpus = ["new york", "new york city", "york", "newark", "new haven", "boston", "yorkshire", "new jersey"];
+        let queries = ["new york", "york city", "newark nj", "boston ma", "haven"];
+        let mut matcher = TfidfMatcher::new(3).unwrap();
+        matcher.fit(corpus);
+        let rankings: Vec<Vec<usize>> = queries
+            .iter()
+            .map(|query| matcher.query(query, corpus.len(), 0.0).into_iter().map(|(choice, _)| choice).collect())
+            .collect();
+        assert_eq!(rankings, [vec![0, 1, 2, 6, 4, 7, 3], vec![1, 2, 0, 6], vec![3, 1, 0, 4, 7], vec![5], vec![4]]);
+        for query in queries {
+            let dense = dense_similarities(&corpus, query, 3);
+            for (choice, score) in matcher.query(query, corpus.len(), 0.0) {
+                assert!((score - dense[choice]).abs() < 1e-12, "{:?} {}: {} != {}", query, choice, score, dense[choice]);
+            }
+            let matched = dense.iter().filter(|&&score| score > 0.0).count();
+            assert_eq!(matcher.query(query, corpus.len(), 0.0).len(), matched, "{:?}", query);
+        }
+        let loaded = TfidfMatcher::from_bytes(&matcher.to_bytes()).unwrap();
+        assert_close(&loaded.query_many(&queries, 3, 0.2, Some(2)).unwrap(), &queries.map(|q| matcher.query(q, 3, 0.2)));
+        assert!(TfidfMatcher::new(0).is_err());
+    }
+
+    #[test]
+    fn soft_tfidf_matches_similar_tokens() {
+        let corpus = ["john smith", "jon smith", "jane doe", "john doe"];
+        let soft = SoftTfidf::new(corpus, 0.9, "jaro_winkler").unwrap();
+        assert!((soft.similarity("john smith", "john smith") - 1.0).abs() < 1e-12);
+        assert!(soft.similarity("john smith", "jon smith") > soft.similarity("john smith", "jane doe"));
+        assert_eq!(soft.similarity("abc", "xyz"), 0.0);
+        let bs = ["jon smith", "jane doe", ""];
+        let expected: Vec<f64> = bs.iter().map(|b| soft.similarity("john smith", b)).collect();
+        let found = soft.similarities("john smith", &bs, Some(2)).unwrap();
+        assert!(found.iter().zip(&expected).all(|(x, y)| (x - y).abs() < 1e-12), "{:?} != {:?}", found, expected);
+        assert!(soft.idf("SMITH") < soft.idf("unseen"));
+        assert!(matches!(SoftTfidf::new(corpus, 1.5, "jaro"), Err(Error::Value(_))));
+        assert!(SoftTfidf::new(corpus, 0.9, "nope").is_err());
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn soft_tfidf_preprocesses_the_corpus_and_compared_strings() {
+        let corpus = ["Café Noir", "Café Crème", "Thé Vert"];
+        let preprocessor = crate::testing::preprocessor(&["strip_accents"]);
+        let soft = SoftTfidf::with_preprocessor(corpus, 0.9, "jaro_winkler", preprocessor).unwrap();
+        let plain = SoftTfidf::new(["Cafe Noir", "Cafe Creme", "The Vert"], 0.9, "jaro_winkler").unwrap();
+        assert_eq!(soft.similarity("CAFÉ NOIR", "cafe noir"), plain.similarity("cafe noir", "cafe noir"));
+        assert_eq!(soft.similarities("Thé", &["the vert", "Café"], None).unwrap(), plain.similarities("The", &["the vert", "Cafe"], None).unwrap());
+    }
+}
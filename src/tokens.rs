@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::sync::Mutex;
+
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyTypeError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use crate::error::{Error, Result};
+#[cfg(feature = "python")]
+use crate::error::InvalidParameterError;
+
+// ------------------------------------------------------------------------
+//  Tokenization Helpers
+// ------------------------------------------------------------------------
+
+/// Splits a string into lowercase alphanumeric tokens. Every character that
+/// is not alphanumeric acts as a separator and empty tokens are dropped.
+/// 
+/// # Arguments
+/// 
+/// * `s` - String to tokenize
+/// 
+/// # Returns
+/// 
+/// * `output` - Tokens of `s` in order of appearance
+pub fn tokenize(s: &str) -> Vec<String> {
+    s
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Splits a string into overlapping, lowercased character n-grams. Strings
+/// shorter than `n` produce a single gram holding the whole string.
+/// 
+/// # Arguments
+/// 
+/// * `s` - String to split
+/// * `n` - Number of characters per gram, at least 1
+/// 
+/// # Returns
+/// 
+/// * `output` - N-grams of `s` in order of appearance
+pub fn char_ngrams(s: &str, n: usize) -> Vec<String> {
+    let chars: Vec<char> = s.to_lowercase().chars().collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+    if chars.len() <= n {
+        return vec![chars.into_iter().collect()];
+    }
+    chars
+        .windows(n)
+        .map(|w| w.iter().collect())
+        .collect()
+}
+
+/// Fails unless `ngram`, a number of characters per gram, is at least 1.
+pub fn check_ngram(ngram: usize) -> Result<()> {
+    if ngram == 0 {
+        return Err(Error::value("the n-gram length must be at least 1"));
+    }
+    Ok(())
+}
+
+
+/// Most distinct strings a Python tokenizer remembers the tokens of during
+/// one call.
+#[cfg(feature = "python")]
+const TOKEN_CACHE_LIMIT: usize = 65_536;
+
+/// How the token metrics split a string into tokens, passed from Python as
+/// `None` for words, a delimiter string, a compiled regular expression whose
+/// matches are the tokens, or a callable returning the tokens of a string.
+/// Tokens are lowercased and empty tokens dropped whatever the tokenizer.
+pub enum Tokenizer {
+    /// Runs of letters and digits, as `tokenize`.
+    Words,
+    /// Pieces between occurrences of a delimiter, trimmed of whitespace.
+    Delimiter(String),
+    /// Tokens computed in Python.
+    #[cfg(feature = "python")]
+    Python(PyTokenizer),
+    /// Tokens of another tokenizer but for some stopwords.
+    Filtered(Box<Tokenizer>, Stopwords),
+}
+
+impl Tokenizer {
+    /// This tokenizer without the `stopwords`, if any.
+    pub fn without(self, stopwords: Option<Stopwords>) -> Self {
+        match stopwords {
+            Some(stopwords) => Self::Filtered(Box::new(self), stopwords),
+            None => self,
+        }
+    }
+
+    /// Tokens of `s`. A Python tokenizer that fails yields no tokens and
+    /// keeps the error for `check`.
+    pub fn tokenize(&self, s: &str) -> Vec<String> {
+        match self {
+            Self::Words => tokenize(s),
+            Self::Delimiter(delimiter) => s
+                .split(delimiter.as_str())
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_lowercase)
+                .collect(),
+            #[cfg(feature = "python")]
+            Self::Python(tokenizer) => tokenizer.tokenize(s),
+            Self::Filtered(tokenizer, stopwords) => {
+                let mut tokens = tokenizer.tokenize(s);
+                tokens.retain(|token| !stopwords.0.contains(token));
+                tokens
+            }
+        }
+    }
+
+    /// Raises the first error of a Python tokenizer, once the strings have
+    /// been compared.
+    pub fn check(&self) -> Result<()> {
+        match self {
+            #[cfg(feature = "python")]
+            Self::Python(tokenizer) => match tokenizer.error.lock().unwrap().take() {
+                Some(error) => Err(error.into()),
+                None => Ok(()),
+            },
+            Self::Filtered(tokenizer, _) => tokenizer.check(),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Tokenizer {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if let Ok(delimiter) = obj.extract::<String>() {
+            if delimiter.is_empty() {
+                return Err(InvalidParameterError::new_err("tokenizer delimiter must not be empty"));
+            }
+            return Ok(Self::Delimiter(delimiter));
+        }
+        let pattern = obj.py().import("re")?.getattr("Pattern")?.downcast()?;
+        if obj.is_instance(pattern)? {
+            Ok(Self::Python(PyTokenizer::new(obj.getattr("finditer")?, true)))
+        } else if obj.is_callable() {
+            Ok(Self::Python(PyTokenizer::new(obj, false)))
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "tokenizer must be a delimiter string, a compiled regular expression or a callable, got '{}'",
+                obj.get_type().name()?
+            )))
+        }
+    }
+}
+
+/// Tokens the token metrics ignore, such as the legal suffixes "inc" and
+/// "ltd" of company names, passed from Python as an iterable of strings and
+/// lowercased like the tokens.
+pub struct Stopwords(HashSet<String>);
+
+impl<S: AsRef<str>> FromIterator<S> for Stopwords {
+    fn from_iter<I: IntoIterator<Item = S>>(words: I) -> Self {
+        Self(words.into_iter().map(|word| word.as_ref().to_lowercase()).collect())
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Stopwords {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if obj.extract::<&str>().is_ok() {
+            return Err(PyTypeError::new_err("stopwords must be an iterable of strings, not a string"));
+        }
+        obj.iter()?.map(|word| word?.extract::<&str>()).collect()
+    }
+}
+
+/// A tokenizer implemented in Python, called with the GIL from whichever
+/// thread compares the strings. The tokens of each distinct string are
+/// remembered, up to `TOKEN_CACHE_LIMIT` strings, so that the string compared
+/// against every candidate crosses into Python once.
+#[cfg(feature = "python")]
+pub struct PyTokenizer {
+    function: PyObject,
+    matches: bool,
+    cache: Mutex<HashMap<String, Vec<String>>>,
+    error: Mutex<Option<PyErr>>,
+}
+
+#[cfg(feature = "python")]
+impl PyTokenizer {
+    /// `function` returns the tokens of a string, or match objects whose
+    /// whole match is a token if `matches`.
+    fn new(function: &PyAny, matches: bool) -> Self {
+        Self {
+            function: function.into(),
+            matches,
+            cache: Mutex::new(HashMap::new()),
+            error: Mutex::new(None),
+        }
+    }
+
+    fn tokenize(&self, s: &str) -> Vec<String> {
+        if let Some(tokens) = self.cache.lock().unwrap().get(s) {
+            return tokens.clone();
+        }
+        if self.error.lock().unwrap().is_some() {
+            return Vec::new();
+        }
+        match Python::with_gil(|py| self.call(py, s)) {
+            Ok(tokens) => {
+                let mut cache = self.cache.lock().unwrap();
+                if cache.len() < TOKEN_CACHE_LIMIT {
+                    cache.insert(s.to_string(), tokens.clone());
+                }
+                tokens
+            }
+            Err(error) => {
+                self.error.lock().unwrap().get_or_insert(error);
+                Vec::new()
+            }
+        }
+    }
+
+    fn call(&self, py: Python<'_>, s: &str) -> PyResult<Vec<String>> {
+        let mut tokens = Vec::new();
+        for item in self.function.as_ref(py).call1((s,))?.iter()? {
+            let item = item?;
+            let token: String = if self.matches {
+                item.call_method1("group", (0,))?.extract()?
+            } else {
+                item.extract()?
+            };
+            if !token.is_empty() {
+                tokens.push(token.to_lowercase());
+            }
+        }
+        Ok(tokens)
+    }
+}
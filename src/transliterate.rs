@@ -0,0 +1,207 @@
+use std::borrow::Cow;
+
+use unicode_normalization::char::{compose, decompose_canonical, is_combining_mark};
+
+// ------------------------------------------------------------------------
+//  Transliteration
+// ------------------------------------------------------------------------
+
+fn is_cyrillic(c: char) -> bool {
+    matches!(c as u32, 0x0400..=0x052f | 0x1c80..=0x1c8f | 0x2de0..=0x2dff | 0xa640..=0xa69f)
+}
+
+fn is_greek(c: char) -> bool {
+    matches!(c as u32, 0x0370..=0x03ff | 0x1f00..=0x1fff)
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Latin spelling of a lowercase Cyrillic letter, following the common
+/// English romanization of Russian and extended to the letters of Ukrainian,
+/// Belarusian, Serbian, Macedonian and Kazakh.
+fn cyrillic(c: char) -> Option<&'static str> {
+    Some(match c {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'д' => "d",
+        'е' | 'ё' | 'э' => "e",
+        'ж' => "zh",
+        'з' => "z",
+        'и' | 'і' => "i",
+        'й' | 'ы' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' | 'ў' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ю' => "yu",
+        'я' => "ya",
+        'ї' => "yi",
+        'є' => "ye",
+        'ґ' => "g",
+        'ђ' => "dj",
+        'ј' => "j",
+        'љ' => "lj",
+        'њ' => "nj",
+        'ћ' => "c",
+        'џ' | 'ѕ' => "dz",
+        'ѓ' => "gj",
+        'ќ' => "kj",
+        'ә' => "a",
+        'ғ' => "g",
+        'қ' => "q",
+        'ң' => "n",
+        'ө' => "o",
+        'ұ' | 'ү' => "u",
+        'һ' => "h",
+        _ => return None,
+    })
+}
+
+/// Latin spelling of a lowercase Greek letter without accents, after the
+/// ELOT 743 standard used on Greek passports.
+fn greek(c: char) -> Option<&'static str> {
+    Some(match c {
+        'α' => "a",
+        'β' => "v",
+        'γ' => "g",
+        'δ' => "d",
+        'ε' => "e",
+        'ζ' => "z",
+        'η' | 'ι' => "i",
+        'θ' => "th",
+        'κ' => "k",
+        'λ' => "l",
+        'μ' => "m",
+        'ν' => "n",
+        'ξ' => "x",
+        'ο' | 'ω' => "o",
+        'π' => "p",
+        'ρ' => "r",
+        'σ' | 'ς' => "s",
+        'τ' => "t",
+        'υ' => "y",
+        'φ' => "f",
+        'χ' => "ch",
+        'ψ' => "ps",
+        _ => return None,
+    })
+}
+
+/// A Greek letter without its accents and breathings, and whether it had a
+/// diaeresis, which keeps a vowel out of a digraph.
+fn greek_base(c: char) -> (char, bool) {
+    let mut base = None;
+    let mut diaeresis = false;
+    decompose_canonical(c, |d| {
+        if base.is_none() {
+            base = Some(d);
+        } else if d == '\u{308}' {
+            diaeresis = true;
+        }
+    });
+    (base.unwrap_or(c), diaeresis)
+}
+
+/// Latin spelling of the Cyrillic or Greek letter that starts `chars`, and
+/// the number of characters spelled, two for the Greek digraphs.
+fn latin(chars: &[char]) -> Option<(&'static str, usize)> {
+    let c = lower(chars[0]);
+    if is_cyrillic(c) {
+        return cyrillic(c).map(|latin| (latin, 1));
+    }
+    if !is_greek(c) {
+        return None;
+    }
+    let (base, _) = greek_base(c);
+    if let Some(&next) = chars.get(1) {
+        let (next, diaeresis) = greek_base(lower(next));
+        let digraph = match (base, next) {
+            _ if diaeresis => None,
+            ('ο', 'υ') => Some("ou"),
+            ('α', 'υ') => Some("av"),
+            ('ε', 'υ') => Some("ev"),
+            ('η', 'υ') => Some("iv"),
+            ('γ', 'γ') => Some("ng"),
+            _ => None,
+        };
+        if let Some(latin) = digraph {
+            return Some((latin, 2));
+        }
+    }
+    greek(base).map(|latin| (latin, 1))
+}
+
+/// `s` with its Cyrillic and Greek letters spelled in Latin ones, so that
+/// "Москва" becomes "Moskva" and "Αθήνα" becomes "Athina". Accents on the
+/// transliterated letters are dropped, capitals give a capitalized spelling,
+/// or an uppercase one within an uppercase word, and all other characters are
+/// kept.
+/// 
+/// # Arguments
+/// 
+/// * `s` - String to transliterate
+/// 
+/// # Returns
+/// 
+/// * `output` - `s` in Latin letters
+pub fn transliterate(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() || !s.chars().any(|c| is_cyrillic(c) || is_greek(c)) {
+        return Cow::Borrowed(s);
+    }
+    // Marks are composed onto the letters to transliterate, such as the breve
+    // of "й", and only those, so that other text keeps its normalization.
+    let mut chars: Vec<char> = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        let composed = chars
+            .last()
+            .filter(|&&letter| is_cyrillic(letter) || is_greek(letter))
+            .and_then(|&letter| compose(letter, c));
+        match composed {
+            Some(composed) => *chars.last_mut().unwrap() = composed,
+            None => chars.push(c),
+        }
+    }
+    let mut transliterated = String::with_capacity(s.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let Some((latin, len)) = latin(&chars[i..]) else {
+            transliterated.push(chars[i]);
+            i += 1;
+            continue;
+        };
+        let upper = chars[i].is_uppercase();
+        let shout = upper
+            && (chars.get(i + 1).is_some_and(|c| c.is_uppercase()) || i > 0 && chars[i - 1].is_uppercase());
+        let mut letters = latin.chars();
+        if let Some(first) = letters.next() {
+            transliterated.push(if upper { first.to_ascii_uppercase() } else { first });
+            if shout {
+                transliterated.extend(letters.map(|c| c.to_ascii_uppercase()));
+            } else {
+                transliterated.extend(letters);
+            }
+        }
+        i += len;
+        while i < chars.len() && is_combining_mark(chars[i]) {
+            i += 1;
+        }
+    }
+    Cow::Owned(transliterated)
+}
@@ -0,0 +1,317 @@
+use std::hash::Hash;
+
+use pyo3::prelude::*;
+use unicode_normalization::char::is_combining_mark;
+
+use crate::bytes::Seq;
+use crate::error::InvalidParameterError;
+use crate::pool::NThreads;
+
+// ------------------------------------------------------------------------
+//  Comparison Units
+// ------------------------------------------------------------------------
+
+/// What an edit distance counts as one symbol, passed from Python by its
+/// name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    /// Unicode scalar values, as Python indexes strings.
+    Char,
+    /// Extended grapheme clusters, what a reader sees as one character.
+    Grapheme,
+    /// Bytes of the UTF-8 encoding.
+    Byte,
+}
+
+impl Unit {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "char" => Ok(Self::Char),
+            "grapheme" => Ok(Self::Grapheme),
+            "byte" => Ok(Self::Byte),
+            _ => Err(InvalidParameterError::new_err(format!(
+                "unknown unit '{}', expected 'char', 'grapheme' or 'byte'",
+                name
+            ))),
+        }
+    }
+}
+
+impl<'py> FromPyObject<'py> for Unit {
+    fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        Self::from_name(obj.extract()?)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Grapheme Clusters
+// ------------------------------------------------------------------------
+
+/// Hangul syllable type of a character, for the rules keeping a syllable
+/// written in conjoining jamo together: leading consonant, vowel, trailing
+/// consonant, and precomposed syllable without or with a trailing consonant.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Hangul {
+    Leading,
+    Vowel,
+    Trailing,
+    Syllable,
+    SyllableTrailing,
+}
+
+fn hangul(c: char) -> Option<Hangul> {
+    match c as u32 {
+        0x1100..=0x115f | 0xa960..=0xa97c => Some(Hangul::Leading),
+        0x1160..=0x11a7 | 0xd7b0..=0xd7c6 => Some(Hangul::Vowel),
+        0x11a8..=0x11ff | 0xd7cb..=0xd7fb => Some(Hangul::Trailing),
+        syllable @ 0xac00..=0xd7a3 if (syllable - 0xac00) % 28 == 0 => Some(Hangul::Syllable),
+        0xac00..=0xd7a3 => Some(Hangul::SyllableTrailing),
+        _ => None,
+    }
+}
+
+const ZWJ: char = '\u{200d}';
+
+/// Characters that attach to the one before them: combining marks, the
+/// zero width joiner, variation selectors, emoji skin tone modifiers and
+/// the tag characters of subdivision flags.
+fn is_extend(c: char) -> bool {
+    is_combining_mark(c)
+        || matches!(c as u32, 0x200c | 0x200d | 0xfe00..=0xfe0f | 0x1f3fb..=0x1f3ff | 0xe0020..=0xe007f | 0xe0100..=0xe01ef)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1f1e6..=0x1f1ff)
+}
+
+/// Approximates the `Extended_Pictographic` property by the blocks emoji
+/// are drawn from.
+fn is_pictographic(c: char) -> bool {
+    matches!(
+        c as u32,
+        0xa9 | 0xae | 0x203c | 0x2049 | 0x2122 | 0x2139 | 0x2194..=0x21aa | 0x2300..=0x23ff | 0x25aa..=0x27bf
+            | 0x2934 | 0x2935 | 0x2b00..=0x2bff | 0x3030 | 0x303d | 0x3297 | 0x3299 | 0x1f000..=0x1faff
+            | 0x1fc00..=0x1fffd
+    )
+}
+
+/// Virama that join the consonants around them into one conjunct, as in
+/// Devanagari "क्ष".
+fn is_indic_linker(c: char) -> bool {
+    matches!(c as u32, 0x094d | 0x09cd | 0x0acd | 0x0b4d | 0x0c4d | 0x0d4d)
+}
+
+/// Splits `s` into extended grapheme clusters following the rules of
+/// Unicode Standard Annex #29: combining marks, joined emoji sequences, flag
+/// pairs, Hangul syllables and Indic conjuncts each form one cluster. The
+/// character properties are approximated by their main ranges, which covers
+/// the text met in practice.
+/// 
+/// # Arguments
+/// 
+/// * `s` - String to split
+/// 
+/// # Returns
+/// 
+/// * `output` - Clusters of `s`, in order
+pub fn graphemes(s: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<char> = None;
+    // Regional indicators in the current run, pictographic character seen
+    // before the trailing extenders, and linker seen after a letter.
+    let mut regional = 0;
+    let mut emoji = false;
+    let mut linked = false;
+
+    for (i, c) in s.char_indices() {
+        let joins = match previous {
+            None => true,
+            Some(p) => {
+                if p == '\r' && c == '\n' {
+                    true
+                } else if p.is_control() || c.is_control() {
+                    false
+                } else if let (Some(p), Some(c)) = (hangul(p), hangul(c)) {
+                    matches!(
+                        (p, c),
+                        (Hangul::Leading, Hangul::Leading | Hangul::Vowel | Hangul::Syllable | Hangul::SyllableTrailing)
+                            | (Hangul::Syllable | Hangul::Vowel, Hangul::Vowel | Hangul::Trailing)
+                            | (Hangul::SyllableTrailing | Hangul::Trailing, Hangul::Trailing)
+                    )
+                } else if is_extend(c) || p == ZWJ && emoji && is_pictographic(c) {
+                    true
+                } else if is_regional_indicator(p) && is_regional_indicator(c) {
+                    regional % 2 == 1
+                } else {
+                    linked && c.is_alphabetic()
+                }
+            }
+        };
+        if !joins {
+            clusters.push(&s[start..i]);
+            start = i;
+            regional = 0;
+            emoji = false;
+            linked = false;
+        }
+
+        if is_regional_indicator(c) {
+            regional += 1;
+        }
+        if is_pictographic(c) {
+            emoji = true;
+        } else if !is_extend(c) {
+            emoji = false;
+        }
+        if is_indic_linker(c) {
+            linked = true;
+        } else if !is_combining_mark(c) {
+            linked = false;
+        }
+        previous = Some(c);
+    }
+    if start < s.len() {
+        clusters.push(&s[start..]);
+    }
+    clusters
+}
+
+
+// ------------------------------------------------------------------------
+//  Metrics over Units
+// ------------------------------------------------------------------------
+
+fn levenshtein_of<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    strsim::generic_levenshtein(&Seq(a), &Seq(b))
+}
+
+fn damerau_levenshtein_of<T: Eq + Hash + Clone>(a: &[T], b: &[T]) -> usize {
+    strsim::generic_damerau_levenshtein(a, b)
+}
+
+/// Optimal string alignment distance between two sequences: Levenshtein
+/// with transpositions of adjacent symbols, no substring edited twice.
+fn osa_distance_of<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() || b.is_empty() {
+        return a.len().max(b.len());
+    }
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+fn normalized<T>(distance: fn(&[T], &[T]) -> usize, a: &[T], b: &[T]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    1.0 - distance(a, b) as f64 / a.len().max(b.len()) as f64
+}
+
+fn jaro_of<T: PartialEq>(a: &[T], b: &[T]) -> f64 {
+    strsim::generic_jaro(&Seq(a), &Seq(b))
+}
+
+fn jaro_winkler_of<T: PartialEq>(a: &[T], b: &[T]) -> f64 {
+    strsim::generic_jaro_winkler(&Seq(a), &Seq(b))
+}
+
+/// Levenshtein distance counted in `unit`s.
+pub fn levenshtein(unit: Unit) -> fn(&str, &str) -> usize {
+    match unit {
+        Unit::Char => crate::scratch::levenshtein,
+        Unit::Grapheme => |a, b| levenshtein_of(&graphemes(a), &graphemes(b)),
+        Unit::Byte => |a, b| levenshtein_of(a.as_bytes(), b.as_bytes()),
+    }
+}
+
+/// Levenshtein similarity over `unit`s, between 0.0 and 1.0.
+pub fn normalized_levenshtein(unit: Unit) -> fn(&str, &str) -> f64 {
+    match unit {
+        Unit::Char => crate::scratch::normalized_levenshtein,
+        Unit::Grapheme => |a, b| normalized(levenshtein_of, &graphemes(a), &graphemes(b)),
+        Unit::Byte => |a, b| normalized(levenshtein_of, a.as_bytes(), b.as_bytes()),
+    }
+}
+
+/// Damerau-Levenshtein distance counted in `unit`s.
+pub fn damerau_levenshtein(unit: Unit) -> fn(&str, &str) -> usize {
+    match unit {
+        Unit::Char => crate::scratch::damerau_levenshtein,
+        Unit::Grapheme => |a, b| damerau_levenshtein_of(&graphemes(a), &graphemes(b)),
+        Unit::Byte => |a, b| damerau_levenshtein_of(a.as_bytes(), b.as_bytes()),
+    }
+}
+
+/// Damerau-Levenshtein similarity over `unit`s, between 0.0 and 1.0.
+pub fn normalized_damerau_levenshtein(unit: Unit) -> fn(&str, &str) -> f64 {
+    match unit {
+        Unit::Char => crate::scratch::normalized_damerau_levenshtein,
+        Unit::Grapheme => |a, b| normalized(damerau_levenshtein_of, &graphemes(a), &graphemes(b)),
+        Unit::Byte => |a, b| normalized(damerau_levenshtein_of, a.as_bytes(), b.as_bytes()),
+    }
+}
+
+/// Optimal string alignment distance counted in `unit`s.
+pub fn osa_distance(unit: Unit) -> fn(&str, &str) -> usize {
+    match unit {
+        Unit::Char => crate::scratch::osa_distance,
+        Unit::Grapheme => |a, b| osa_distance_of(&graphemes(a), &graphemes(b)),
+        Unit::Byte => |a, b| osa_distance_of(a.as_bytes(), b.as_bytes()),
+    }
+}
+
+/// Jaro similarity over `unit`s.
+pub fn jaro(unit: Unit) -> fn(&str, &str) -> f64 {
+    match unit {
+        Unit::Char => crate::scratch::jaro,
+        Unit::Grapheme => |a, b| jaro_of(&graphemes(a), &graphemes(b)),
+        Unit::Byte => |a, b| jaro_of(a.as_bytes(), b.as_bytes()),
+    }
+}
+
+/// Jaro-Winkler similarity over `unit`s.
+pub fn jaro_winkler(unit: Unit) -> fn(&str, &str) -> f64 {
+    match unit {
+        Unit::Char => crate::scratch::jaro_winkler,
+        Unit::Grapheme => |a, b| jaro_winkler_of(&graphemes(a), &graphemes(b)),
+        Unit::Byte => |a, b| jaro_winkler_of(a.as_bytes(), b.as_bytes()),
+    }
+}
+
+/// Levenshtein distance counted in `unit`s, computed on `n_threads` threads
+/// by `wavefront::levenshtein`, along with the length in `unit`s of the
+/// longer string.
+pub fn levenshtein_long(unit: Unit, a: &str, b: &str, n_threads: NThreads) -> PyResult<(usize, usize)> {
+    fn of<T: PartialEq + Sync>(a: &[T], b: &[T], n_threads: NThreads) -> PyResult<(usize, usize)> {
+        Ok((crate::wavefront::levenshtein(a, b, n_threads.get())?, a.len().max(b.len())))
+    }
+    match unit {
+        Unit::Char => of(&a.chars().collect::<Vec<_>>(), &b.chars().collect::<Vec<_>>(), n_threads),
+        Unit::Grapheme => of(&graphemes(a), &graphemes(b), n_threads),
+        Unit::Byte => of(a.as_bytes(), b.as_bytes(), n_threads),
+    }
+}
+
+/// Fails unless `unit` is `Unit::Char`, for options defined over characters
+/// only.
+pub fn check_chars(unit: Unit, option: &str) -> PyResult<()> {
+    if unit != Unit::Char {
+        return Err(InvalidParameterError::new_err(format!("{} requires unit='char'", option)));
+    }
+    Ok(())
+}
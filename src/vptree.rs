@@ -0,0 +1,456 @@
+use std::collections::BinaryHeap;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use rayon::prelude::*;
+
+use crate::bktree::Distance;
+use crate::error::Result;
+#[cfg(feature = "python")]
+use crate::input::Strings;
+use crate::persist::{Decoder, Encoder, Persist};
+use crate::pool::{thread_pool, SERIAL_LIMIT};
+#[cfg(feature = "python")]
+use crate::pool::NThreads;
+use crate::preprocess::Preprocessor;
+use crate::progress::Counter;
+#[cfg(feature = "python")]
+use crate::progress::Reporter;
+
+// ------------------------------------------------------------------------
+//  Vantage-Point Tree
+// ------------------------------------------------------------------------
+
+struct Node {
+    /// Index of the vantage point among the words.
+    item: usize,
+    /// Largest distance from the vantage point of the words in `inside`.
+    radius: usize,
+    inside: Option<usize>,
+    outside: Option<usize>,
+}
+
+/// Vantage-point tree over a fixed list of words under an edit distance.
+/// Every node splits the words below it at the median distance from a
+/// vantage point into those inside that radius and those outside, so by the
+/// triangle inequality searches skip whole halves of the tree. Unlike a
+/// BK-tree, it also answers nearest neighbour queries efficiently.
+/// 
+/// # Arguments
+/// 
+/// * `words` - Words to index
+/// * `metric` - `"levenshtein"` or `"damerau_levenshtein"`
+/// * `n_threads` - Number of threads to use while building, or `None` or 0
+///   for all available cores
+/// * `preprocessor` - `Preprocessor` applied to the words and queries before
+///   they are compared, or `None`. Matches are returned as they were given
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "strsim", text_signature = "(words, /, metric='levenshtein', n_threads=None, preprocessor=None)")
+)]
+pub struct VPTree {
+    distance: Distance,
+    preprocessor: Preprocessor,
+    words: Vec<String>,
+    chars: Vec<Vec<char>>,
+    nodes: Vec<Node>,
+}
+
+impl VPTree {
+    /// Builds the tree over `words` under the metric named `metric`,
+    /// `"levenshtein"` or `"damerau_levenshtein"`, on `n_threads` threads, or
+    /// on all available cores when `None`.
+    pub fn new<S: AsRef<str>>(words: &[S], metric: &str, n_threads: Option<usize>) -> Result<Self> {
+        Self::preprocessed(words, metric, Preprocessor::default(), n_threads)
+    }
+
+    fn preprocessed<S: AsRef<str>>(
+        words: &[S],
+        metric: &str,
+        preprocessor: Preprocessor,
+        n_threads: Option<usize>,
+    ) -> Result<Self> {
+        let words: Vec<String> = words.iter().map(|word| word.as_ref().to_owned()).collect();
+        let mut tree = Self {
+            distance: Distance::from_name(metric)?,
+            chars: words.iter().map(|word| preprocessor.apply(word).chars().collect()).collect(),
+            preprocessor,
+            words,
+            nodes: Vec::new(),
+        };
+        let mut items: Vec<usize> = (0..tree.words.len()).collect();
+        let nodes = thread_pool(n_threads)?.install(|| {
+            tree.build(&mut items);
+            std::mem::take(&mut tree.nodes)
+        });
+        tree.nodes = nodes;
+        Ok(tree)
+    }
+
+    /// Finds the `k` words nearest to `query`, as pairs of the word and its
+    /// distance, closest first and ties broken by the order of the words.
+    pub fn knn(&self, query: &str, k: usize) -> Vec<(&str, usize)> {
+        self.named(self.nearest(&self.prepare(query), k))
+    }
+
+    /// Finds every word within `max_distance` of `query`, as pairs of the
+    /// word and its distance, closest first.
+    pub fn radius(&self, query: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        self.named(self.within(&self.prepare(query), max_distance))
+    }
+
+    /// Parallel version of `knn` for many queries, on `n_threads` threads, or
+    /// on all available cores when `None`.
+    pub fn knn_many<S: AsRef<str> + Sync>(
+        &self,
+        queries: &[S],
+        k: usize,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<Vec<(&str, usize)>>> {
+        let found = thread_pool(n_threads)?
+            .run_sized(queries.len(), 1, |counter, min_len| self.nearest_many(counter, queries, k, min_len));
+        Ok(found.unwrap_or_default().into_iter().map(|found| self.named(found)).collect())
+    }
+
+    /// Number of words in the tree.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Characters of `query` after preprocessing.
+    fn prepare(&self, query: &str) -> Vec<char> {
+        self.preprocessor.apply(query).chars().collect()
+    }
+
+    /// Builds the subtree over `items`, taking the first as vantage point,
+    /// and returns its root.
+    fn build(&mut self, items: &mut [usize]) -> Option<usize> {
+        let (&mut item, rest) = items.split_first_mut()?;
+        let vantage = &self.chars[item];
+        let chars = &self.chars;
+        let distance = self.distance;
+        let mut by_distance: Vec<(usize, usize)> = if rest.len() < SERIAL_LIMIT {
+            rest.iter().map(|&other| (distance.distance(vantage, &chars[other]), other)).collect()
+        } else {
+            rest.par_iter().map(|&other| (distance.distance(vantage, &chars[other]), other)).collect()
+        };
+
+        let index = self.nodes.len();
+        self.nodes.push(Node { item, radius: 0, inside: None, outside: None });
+        if by_distance.is_empty() {
+            return Some(index);
+        }
+
+        let median = (by_distance.len() - 1) / 2;
+        by_distance.select_nth_unstable(median);
+        let radius = by_distance[median].0;
+        let (mut inside, mut outside): (Vec<usize>, Vec<usize>) = (Vec::new(), Vec::new());
+        for (d, other) in by_distance {
+            if d <= radius {
+                inside.push(other);
+            } else {
+                outside.push(other);
+            }
+        }
+
+        let inside = self.build(&mut inside);
+        let outside = self.build(&mut outside);
+        self.nodes[index] = Node { item, radius, inside, outside };
+        Some(index)
+    }
+
+    /// Indices of the `k` words nearest to `query` and their distances,
+    /// closest first and then in the order of the words.
+    fn nearest(&self, query: &[char], k: usize) -> Vec<(usize, usize)> {
+        let mut best: BinaryHeap<(usize, usize)> = BinaryHeap::new();
+        if k == 0 {
+            return Vec::new();
+        }
+        // Nodes to visit, each with a lower bound on the distance from the
+        // query to the words below it.
+        let mut stack: Vec<(usize, usize)> = self.nodes.first().map(|_| (0, 0)).into_iter().collect();
+        while let Some((current, bound)) = stack.pop() {
+            let tau = |best: &BinaryHeap<(usize, usize)>| match best.peek() {
+                Some(&(d, _)) if best.len() == k => d,
+                _ => usize::MAX,
+            };
+            if bound > tau(&best) {
+                continue;
+            }
+            let node = &self.nodes[current];
+            let d = self.distance.distance(query, &self.chars[node.item]);
+            if best.len() < k {
+                best.push((d, node.item));
+            } else if (d, node.item) < *best.peek().expect("k > 0 words are kept") {
+                best.pop();
+                best.push((d, node.item));
+            }
+
+            let tau = tau(&best);
+            let inside = (node.inside, d.saturating_sub(node.radius));
+            let outside = (node.outside, (node.radius + 1).saturating_sub(d));
+            // Push the likelier side last, so that it is searched first.
+            let sides = if d <= node.radius { [outside, inside] } else { [inside, outside] };
+            for (child, bound) in sides {
+                if let (Some(child), true) = (child, bound <= tau) {
+                    stack.push((child, bound));
+                }
+            }
+        }
+        let mut best = best.into_vec();
+        best.sort_unstable();
+        best.into_iter().map(|(d, item)| (item, d)).collect()
+    }
+
+    /// Indices of the words within `max_distance` of `query` and their
+    /// distances, closest first and then in the order of the words.
+    fn within(&self, query: &[char], max_distance: usize) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.nodes.first().map(|_| 0).into_iter().collect();
+        while let Some(current) = stack.pop() {
+            let node = &self.nodes[current];
+            let d = self.distance.distance(query, &self.chars[node.item]);
+            if d <= max_distance {
+                found.push((node.item, d));
+            }
+            if let (Some(inside), true) = (node.inside, d.saturating_sub(max_distance) <= node.radius) {
+                stack.push(inside);
+            }
+            if let (Some(outside), true) = (node.outside, d.saturating_add(max_distance) > node.radius) {
+                stack.push(outside);
+            }
+        }
+        found.sort_unstable_by_key(|&(item, d)| (d, item));
+        found
+    }
+
+    /// `nearest` for each of `queries` in parallel, or `None` if the job was
+    /// cancelled.
+    fn nearest_many<S: AsRef<str> + Sync>(
+        &self,
+        counter: &Counter,
+        queries: &[S],
+        k: usize,
+        min_len: usize,
+    ) -> Option<Vec<Vec<(usize, usize)>>> {
+        queries
+            .par_iter()
+            .with_min_len(min_len)
+            .map(|query| {
+                (!counter.is_cancelled()).then(|| self.nearest(&self.prepare(query.as_ref()), k))
+            })
+            .collect()
+    }
+
+    fn named(&self, found: Vec<(usize, usize)>) -> Vec<(&str, usize)> {
+        found.into_iter().map(|(item, d)| (self.words[item].as_str(), d)).collect()
+    }
+}
+
+impl Persist for VPTree {
+    const KIND: &'static str = "VPTree";
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.str(self.distance.name());
+        self.preprocessor.encode(encoder);
+        encoder.strs(&self.words);
+        encoder.usize(self.nodes.len());
+        for node in &self.nodes {
+            encoder.usize(node.item);
+            encoder.usize(node.radius);
+            encoder.index(node.inside);
+            encoder.index(node.outside);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> crate::Result<Self> {
+        let distance = Distance::from_name(&decoder.str()?)?;
+        let preprocessor = Preprocessor::decode(decoder)?;
+        let words = decoder.strs()?;
+        let n_nodes = decoder.length()?;
+        decoder.check(n_nodes == words.len())?;
+        let nodes = (0..n_nodes)
+            .map(|_| {
+                let item = decoder.usize()?;
+                decoder.check(item < words.len())?;
+                Ok(Node {
+                    item,
+                    radius: decoder.usize()?,
+                    inside: decoder.index(n_nodes)?,
+                    outside: decoder.index(n_nodes)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let chars = words.iter().map(|word| preprocessor.apply(word).chars().collect()).collect();
+        Ok(Self { distance, preprocessor, words, chars, nodes })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl VPTree {
+    #[new]
+    #[args(metric = "\"levenshtein\"", n_threads = "NThreads::AUTO", preprocessor = "None")]
+    fn py_new(words: Strings, metric: &str, n_threads: NThreads, preprocessor: Option<Preprocessor>) -> PyResult<Self> {
+        Ok(Self::preprocessed(&words.as_strs()?, metric, preprocessor.unwrap_or_default(), n_threads.get())?)
+    }
+
+    /// Finds the `k` words nearest to `query`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - Word to look up
+    /// * `k` - Number of neighbours to return
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Up to `k` pairs of a word and its distance, closest first,
+    ///   ties broken by the order of the words
+    #[pyo3(name = "knn", text_signature = "($self, query, k, /)")]
+    fn py_knn(&self, query: &str, k: usize) -> Vec<(&str, usize)> {
+        self.knn(query, k)
+    }
+
+    /// Finds every word within `max_distance` of `query`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `query` - Word to look up
+    /// * `max_distance` - Largest distance of a match
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Pairs of a matching word and its distance, closest first
+    #[pyo3(name = "radius", text_signature = "($self, query, max_distance, /)")]
+    fn py_radius(&self, query: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        self.radius(query, max_distance)
+    }
+
+    /// Parallel version of `knn` for many queries.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `queries` - Words to look up
+    /// * `k` - Number of neighbours to return per query
+    /// * `n_threads` - Number of threads to use, or `None` or 0 for all available cores
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - For each query, up to `k` pairs of a word and its
+    ///   distance, closest first
+    #[args(n_threads = "NThreads::AUTO")]
+    #[pyo3(name = "knn_many", text_signature = "($self, queries, k, /, n_threads=None)")]
+    fn py_knn_many(&self, queries: Strings, k: usize, n_threads: NThreads) -> PyResult<Vec<Vec<(&str, usize)>>> {
+        let queries = queries.as_strs()?;
+        let reporter = Reporter::new(None, Some(queries.len().saturating_mul(self.words.len())));
+        let found = thread_pool(n_threads)?.install_sized(&reporter, queries.len(), 1, |counter, min_len| {
+            self.nearest_many(counter, &queries, k, min_len)
+        })?;
+        Ok(found.unwrap_or_default().into_iter().map(|found| self.named(found)).collect())
+    }
+
+    /// Saves the tree to a file in a compact binary format, to be restored
+    /// by `VPTree.load` without building it again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to write
+    #[pyo3(text_signature = "($self, path, /)")]
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.write_file(&path)?)
+    }
+
+    /// Loads a tree written by `save`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `path` - File to read
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - The saved tree
+    #[staticmethod]
+    #[pyo3(text_signature = "(path, /)")]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::read_file(&path)?)
+    }
+
+    fn __getnewargs__(&self) -> (Vec<String>,) {
+        (Vec::new(),)
+    }
+
+    fn __getstate__(slf: PyRef<'_, Self>) -> Py<PyBytes> {
+        PyBytes::new(slf.py(), &slf.to_bytes()).into()
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Number of words in the tree.
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    /// Every word with its distance from `query`, closest first and then in
+    /// the order of the words.
+    fn ranked<'w>(words: &'w [String], metric: &str, query: &str) -> Vec<(&'w str, usize)> {
+        let distance = |word: &str| match metric {
+            "levenshtein" => strsim::levenshtein(query, word),
+            _ => strsim::damerau_levenshtein(query, word),
+        };
+        let mut ranked: Vec<(usize, usize)> = words.iter().enumerate().map(|(i, word)| (distance(word), i)).collect();
+        ranked.sort_unstable();
+        ranked.into_iter().map(|(d, i)| (words[i].as_str(), d)).collect()
+    }
+
+    #[test]
+    fn searches_match_brute_force() {
+        let mut rng = Rng::new(592);
+        let words = rng.strings(400, 8);
+        let queries = rng.strings(30, 8);
+        for metric in ["levenshtein", "damerau_levenshtein"] {
+            let tree = VPTree::new(&words, metric, Some(2)).unwrap();
+            for query in queries.iter().chain(words.iter().take(10)) {
+                let ranked = ranked(&words, metric, query);
+                for k in [0, 1, 5, 37] {
+                    assert_eq!(tree.knn(query, k), ranked[..k], "{} {:?} {}", metric, query, k);
+                }
+                for max_distance in 0..=3 {
+                    let within: Vec<_> = ranked.iter().copied().filter(|&(_, d)| d <= max_distance).collect();
+                    assert_eq!(tree.radius(query, max_distance), within, "{} {:?} {}", metric, query, max_distance);
+                }
+            }
+            let found = tree.knn_many(&queries, 5, Some(2)).unwrap();
+            assert!(found.iter().zip(&queries).all(|(found, query)| *found == tree.knn(query, 5)));
+        }
+    }
+
+    #[test]
+    fn knn_breaks_ties_by_word_order_and_stops_at_the_tree_size() {
+        let words: Vec<String> = ["cat", "bat", "cat", "hat", "cart", "at", "dog"].map(String::from).into();
+        let tree = VPTree::new(&words, "levenshtein", None).unwrap();
+        assert_eq!(tree.knn("cat", 4), [("cat", 0), ("cat", 0), ("bat", 1), ("hat", 1)]);
+        assert_eq!(tree.knn("mat", 10), ranked(&words, "levenshtein", "mat"));
+        assert_eq!(tree.knn("mat", 10).len(), 7);
+        assert_eq!(tree.radius("xyz", 1), []);
+        let empty = VPTree::new(&[] as &[&str], "levenshtein", None).unwrap();
+        assert_eq!((empty.knn("cat", 3), empty.radius("cat", 5)), (vec![], vec![]));
+    }
+}
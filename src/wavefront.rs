@@ -0,0 +1,85 @@
+use std::sync::{Mutex, MutexGuard};
+
+use rayon::prelude::*;
+
+use crate::error::Result;
+use crate::pool::thread_pool;
+
+// ------------------------------------------------------------------------
+//  Wavefront Levenshtein
+// ------------------------------------------------------------------------
+
+/// Side of the square tiles the distance matrix is split into. A tile is
+/// the work of one task, large enough to outweigh scheduling it.
+const TILE: usize = 256;
+
+/// Computes the tile of the distance matrix between rows `rows` of `a` and
+/// columns `columns` of `b`. On entry `top` holds the row above the tile,
+/// without its corner, and `left` the column left of it, starting with the
+/// corner. On return `top` holds the last row of the tile and `left` its
+/// last column, starting with the last entry of the row above: the edges
+/// the tiles below and to the right of it need.
+fn tile<T: PartialEq>(a: &[T], b: &[T], top: &mut [usize], left: &mut [usize]) {
+    let Some(&corner) = top.last() else {
+        return;
+    };
+    let mut diagonal = std::mem::replace(&mut left[0], corner);
+    for (i, x) in a.iter().enumerate() {
+        let mut west = left[i + 1];
+        let mut northwest = diagonal;
+        diagonal = west;
+        for (cell, y) in top.iter_mut().zip(b) {
+            let north = *cell;
+            west = (north + 1).min(west + 1).min(northwest + usize::from(x != y));
+            northwest = north;
+            *cell = west;
+        }
+        left[i + 1] = west;
+    }
+}
+
+/// Levenshtein distance between two sequences, computed on `n_threads`
+/// threads, or on one per available core when `None`, for sequences long
+/// enough that a single comparison is worth sharing out.
+/// 
+/// The distance matrix is split into square tiles, and the tiles along each
+/// anti-diagonal, which only depend on the tiles above and to the left of
+/// them, are computed in parallel. Memory stays linear in the lengths of the
+/// sequences. Sequences shorter than a few tiles are compared on the calling
+/// thread.
+pub fn levenshtein<T: PartialEq + Sync>(a: &[T], b: &[T], n_threads: Option<usize>) -> Result<usize> {
+    if a.is_empty() || b.is_empty() {
+        return Ok(a.len().max(b.len()));
+    }
+    if a.len().min(b.len()) < 2 * TILE {
+        let mut top: Vec<usize> = (1..=b.len()).collect();
+        let mut left: Vec<usize> = (0..=a.len()).collect();
+        tile(a, b, &mut top, &mut left);
+        return Ok(left[a.len()]);
+    }
+
+    let rows: Vec<&[T]> = a.chunks(TILE).collect();
+    let columns: Vec<&[T]> = b.chunks(TILE).collect();
+    let edge = |start: usize, len: usize| Mutex::new((start..start + len).collect::<Vec<usize>>());
+    let tops: Vec<Mutex<Vec<usize>>> = (0..columns.len()).map(|j| edge(j * TILE + 1, columns[j].len())).collect();
+    let lefts: Vec<Mutex<Vec<usize>>> = (0..rows.len()).map(|i| edge(i * TILE, rows[i].len() + 1)).collect();
+
+    thread_pool(n_threads)?.install(|| {
+        for diagonal in 0..rows.len() + columns.len() - 1 {
+            let first = diagonal.saturating_sub(columns.len() - 1);
+            let last = diagonal.min(rows.len() - 1);
+            (first..=last).into_par_iter().for_each(|i| {
+                let j = diagonal - i;
+                tile(rows[i], columns[j], &mut lock(&tops[j]), &mut lock(&lefts[i]));
+            });
+        }
+    });
+    let last = lock(&lefts[rows.len() - 1]);
+    Ok(last[last.len() - 1])
+}
+
+/// Locks the edge of a tile. The tiles of one anti-diagonal never share an
+/// edge, so the lock is never contended.
+fn lock(edge: &Mutex<Vec<usize>>) -> MutexGuard<'_, Vec<usize>> {
+    edge.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
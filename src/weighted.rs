@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// ------------------------------------------------------------------------
+//  Weighted Edit Distance
+// ------------------------------------------------------------------------
+
+/// Substitution costs for a weighted Levenshtein distance. Single character
+/// substitutions are looked up in a table, and multi-character rules such as
+/// `rn` -> `m` replace a whole run of characters at once. Insertions,
+/// deletions, and substitutions not covered by a rule cost 1.0.
+#[derive(Clone, Debug, Default)]
+pub struct CostProfile {
+    substitutions: HashMap<(char, char), f64>,
+    rules: Vec<(Vec<char>, Vec<char>, f64)>,
+}
+
+impl CostProfile {
+    /// Builds a profile from `(from, to, cost)` entries. Every entry applies
+    /// in both directions.
+    pub fn new(entries: &[(&str, &str, f64)]) -> Self {
+        let mut profile = Self::default();
+        for &(from, to, cost) in entries {
+            let from: Vec<char> = from.chars().collect();
+            let to: Vec<char> = to.chars().collect();
+            if from.len() == 1 && to.len() == 1 {
+                profile.substitutions.insert((from[0], to[0]), cost);
+                profile.substitutions.insert((to[0], from[0]), cost);
+            } else {
+                profile.rules.push((from.clone(), to.clone(), cost));
+                profile.rules.push((to, from, cost));
+            }
+        }
+        profile
+    }
+
+    fn substitution(&self, x: char, y: char) -> f64 {
+        if x == y {
+            0.0
+        } else {
+            self.substitutions.get(&(x, y)).copied().unwrap_or(1.0)
+        }
+    }
+
+    /// Calculates the weighted edit distance between two strings.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` - First string to compare
+    /// * `b` - Secondary string to compare to `a`
+    /// 
+    /// # Returns
+    /// 
+    /// * `output` - Weighted distance between `a` and `b`
+    pub fn distance(&self, a: &str, b: &str) -> f64 {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let width = b.len() + 1;
+        let mut matrix = vec![0.0f64; (a.len() + 1) * width];
+        for (j, cell) in matrix.iter_mut().take(width).enumerate() {
+            *cell = j as f64;
+        }
+
+        for i in 1..=a.len() {
+            matrix[i * width] = i as f64;
+            for j in 1..=b.len() {
+                let mut best = (matrix[(i - 1) * width + j - 1] + self.substitution(a[i - 1], b[j - 1]))
+                    .min(matrix[(i - 1) * width + j] + 1.0)
+                    .min(matrix[i * width + j - 1] + 1.0);
+
+                for (from, to, cost) in &self.rules {
+                    if from.len() <= i
+                        && to.len() <= j
+                        && a[i - from.len()..i] == from[..]
+                        && b[j - to.len()..j] == to[..]
+                    {
+                        best = best.min(matrix[(i - from.len()) * width + j - to.len()] + cost);
+                    }
+                }
+                matrix[i * width + j] = best;
+            }
+        }
+        matrix[a.len() * width + b.len()]
+    }
+}
+
+
+/// Calculates a Levenshtein distance where every edit costs `decay^k`, with
+/// `k` the earlier of the positions of the edit in the two strings. With a
+/// `decay` below 1.0 errors near the start of the strings cost more than
+/// errors near the end, and a `decay` of 1.0 gives the plain Levenshtein
+/// distance.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// * `decay` - Factor applied to the cost of each later position
+/// 
+/// # Returns
+/// 
+/// * `output` - Weighted distance between `a` and `b`
+pub fn position_weighted_levenshtein(a: &str, b: &str, decay: f64) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_len = a.len().max(b.len());
+    let mut weights = Vec::with_capacity(max_len + 1);
+    let mut weight = 1.0;
+    for _ in 0..=max_len {
+        weights.push(weight);
+        weight *= decay;
+    }
+
+    let mut previous: Vec<f64> = Vec::with_capacity(b.len() + 1);
+    previous.push(0.0);
+    for j in 0..b.len() {
+        previous.push(previous[j] + weights[0]);
+    }
+    let mut current = vec![0.0; b.len() + 1];
+
+    for (i, &x) in a.iter().enumerate() {
+        current[0] = previous[0] + weights[0];
+        for (j, &y) in b.iter().enumerate() {
+            let cost = weights[i.min(j)];
+            let substitution = previous[j] + if x == y { 0.0 } else { cost };
+            let deletion = previous[j + 1] + weights[i.min(j + 1)];
+            let insertion = current[j] + weights[(i + 1).min(j)];
+            current[j + 1] = substitution.min(deletion).min(insertion);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+// ------------------------------------------------------------------------
+//  Built-in Profiles
+// ------------------------------------------------------------------------
+
+/// Common OCR confusions and their costs.
+const OCR_CONFUSIONS: [(&str, &str, f64); 24] = [
+    ("0", "O", 0.2),
+    ("0", "o", 0.3),
+    ("0", "D", 0.4),
+    ("O", "Q", 0.4),
+    ("1", "l", 0.2),
+    ("1", "I", 0.2),
+    ("l", "I", 0.2),
+    ("1", "i", 0.4),
+    ("1", "7", 0.5),
+    ("5", "S", 0.3),
+    ("5", "s", 0.4),
+    ("2", "Z", 0.4),
+    ("8", "B", 0.3),
+    ("6", "G", 0.4),
+    ("6", "b", 0.4),
+    ("9", "g", 0.4),
+    ("9", "q", 0.4),
+    ("u", "v", 0.4),
+    ("c", "e", 0.4),
+    ("rn", "m", 0.3),
+    ("cl", "d", 0.4),
+    ("vv", "w", 0.3),
+    ("VV", "W", 0.3),
+    ("ii", "u", 0.5),
+];
+
+/// Cost profile for errors typically produced by optical character
+/// recognition, such as `0`/`O`, `1`/`l`/`I`, and `rn`/`m`.
+pub fn ocr_profile() -> &'static CostProfile {
+    static PROFILE: OnceLock<CostProfile> = OnceLock::new();
+    PROFILE.get_or_init(|| CostProfile::new(&OCR_CONFUSIONS))
+}
+
+/// Calculates the weighted edit distance between two strings using the
+/// built-in OCR confusion profile.
+/// 
+/// # Arguments
+/// 
+/// * `a` - First string to compare
+/// * `b` - Secondary string to compare to `a`
+/// 
+/// # Returns
+/// 
+/// * `output` - Weighted distance between `a` and `b`
+pub fn ocr_levenshtein(a: &str, b: &str) -> f64 {
+    ocr_profile().distance(a, b)
+}
@@ -0,0 +1,616 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+
+use crate::error::InvalidParameterError;
+use crate::gzip::crc32_update;
+
+// ------------------------------------------------------------------------
+//  Output Formats
+// ------------------------------------------------------------------------
+
+/// File format results are written in, passed from Python by its name or
+/// taken from the extension of the output path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A NumPy `.npy` array.
+    Npy,
+    /// A NumPy `.npz` archive of arrays, stored without compression.
+    Npz,
+    /// A Parquet table, written through `pyarrow`.
+    Parquet,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "npy" => Ok(Self::Npy),
+            "npz" => Ok(Self::Npz),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(InvalidParameterError::new_err(format!(
+                "unknown format '{}', expected 'npy', 'npz' or 'parquet'",
+                name
+            ))),
+        }
+    }
+
+    /// The format named `format`, or else the one of the extension of `path`.
+    pub fn of(path: &Path, format: Option<&str>) -> PyResult<Self> {
+        if let Some(format) = format {
+            return Self::from_name(format);
+        }
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("npy") => Ok(Self::Npy),
+            Some("npz") => Ok(Self::Npz),
+            Some("parquet" | "pq") => Ok(Self::Parquet),
+            _ => Err(InvalidParameterError::new_err(format!(
+                "cannot tell the format of '{}' from its extension, pass format='npy', 'npz' or 'parquet'",
+                path.display()
+            ))),
+        }
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  NumPy Arrays
+// ------------------------------------------------------------------------
+
+/// Length of the `.npy` headers written here, which leaves room for any
+/// shape of up to two dimensions, so that a header written before the shape
+/// is known can be overwritten in place.
+const NPY_HEADER_LEN: usize = 128;
+
+/// `.npy` version 1.0 header of a C-ordered array of the NumPy type `descr`,
+/// such as `<f8`, and of shape `shape`.
+fn npy_header(descr: &str, shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [len] => format!("({},)", len),
+        _ => format!("({})", shape.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")),
+    };
+    let dict = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape);
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&((NPY_HEADER_LEN - 10) as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header.resize(NPY_HEADER_LEN - 1, b' ');
+    header.push(b'\n');
+    header
+}
+
+/// A one-dimensional `.npy` array of 8 byte values appended to a file as
+/// they come, whose length is filled into its header once all are written.
+pub struct Spool {
+    path: PathBuf,
+    descr: &'static str,
+    file: BufWriter<File>,
+    len: usize,
+}
+
+impl Spool {
+    fn create(path: PathBuf, descr: &'static str) -> PyResult<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path)?;
+        let mut file = BufWriter::new(file);
+        file.write_all(&npy_header(descr, &[0]))?;
+        Ok(Self { path, descr, file, len: 0 })
+    }
+
+    fn push(&mut self, value: [u8; 8]) -> PyResult<()> {
+        self.file.write_all(&value)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Fills in the header and returns the finished file, read from its
+    /// start.
+    fn finish(mut self) -> PyResult<File> {
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&npy_header(self.descr, &[self.len]))?;
+        let mut file = self.file.into_inner().map_err(|err| err.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(file)
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Zip Archives
+// ------------------------------------------------------------------------
+
+/// Version 4.5 of the zip format, the first with 64-bit sizes.
+const ZIP64_VERSION: u16 = 45;
+
+/// Date field of 1980-01-01, the earliest a zip archive can record.
+const ZIP_DATE: u16 = (1 << 5) | 1;
+
+/// An entry of a zip archive, as recorded in its central directory.
+struct ZipEntry {
+    name: String,
+    offset: u64,
+    crc: u32,
+    size: u64,
+}
+
+/// Writes a zip archive of uncompressed entries, one after the other, as
+/// `numpy.load` reads `.npz` files. Every entry carries 64-bit sizes, so
+/// entries may exceed 4 GiB, and its checksum and size are filled into its
+/// header once its data is written.
+pub struct ZipWriter {
+    file: BufWriter<File>,
+    position: u64,
+    entries: Vec<ZipEntry>,
+}
+
+impl ZipWriter {
+    fn create(path: &Path) -> PyResult<Self> {
+        Ok(Self { file: BufWriter::new(File::create(path)?), position: 0, entries: Vec::new() })
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> PyResult<()> {
+        self.file.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Starts the entry `name`, to which `write` then appends.
+    fn start(&mut self, name: &str) -> PyResult<()> {
+        self.entries.push(ZipEntry { name: name.to_string(), offset: self.position, crc: 0, size: 0 });
+        let mut header = Vec::with_capacity(50 + name.len());
+        header.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        header.extend_from_slice(&ZIP64_VERSION.to_le_bytes());
+        header.extend_from_slice(&[0; 6]);
+        header.extend_from_slice(&ZIP_DATE.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&[0xff; 8]);
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&16u16.to_le_bytes());
+        header.extend_from_slice(&[0; 16]);
+        self.write_raw(&header)
+    }
+
+    /// Appends `bytes` to the current entry.
+    fn write(&mut self, bytes: &[u8]) -> PyResult<()> {
+        let entry = self.entries.last_mut().expect("an entry was started");
+        entry.crc = crc32_update(entry.crc, bytes);
+        entry.size += bytes.len() as u64;
+        self.write_raw(bytes)
+    }
+
+    /// Appends everything `reader` holds to the current entry.
+    fn copy(&mut self, mut reader: impl Read) -> PyResult<()> {
+        let mut buffer = vec![0; 1 << 16];
+        loop {
+            let n = reader.read(&mut buffer)?;
+            if n == 0 {
+                return Ok(());
+            }
+            self.write(&buffer[..n])?;
+        }
+    }
+
+    /// Fills the checksum and size of the current entry into its header.
+    fn finish_entry(&mut self) -> PyResult<()> {
+        let entry = self.entries.last().expect("an entry was started");
+        let (offset, crc, size) = (entry.offset, entry.crc, entry.size);
+        let name_len = entry.name.len() as u64;
+        self.file.seek(SeekFrom::Start(offset + 14))?;
+        self.file.write_all(&crc.to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(offset + 30 + name_len + 4))?;
+        self.file.write_all(&size.to_le_bytes())?;
+        self.file.write_all(&size.to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(self.position))?;
+        Ok(())
+    }
+
+    /// Writes the central directory and its zip64 end records.
+    fn finish(mut self) -> PyResult<()> {
+        let directory_offset = self.position;
+        let entries = std::mem::take(&mut self.entries);
+        for entry in &entries {
+            let mut header = Vec::with_capacity(74 + entry.name.len());
+            header.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            header.extend_from_slice(&ZIP64_VERSION.to_le_bytes());
+            header.extend_from_slice(&ZIP64_VERSION.to_le_bytes());
+            header.extend_from_slice(&[0; 6]);
+            header.extend_from_slice(&ZIP_DATE.to_le_bytes());
+            header.extend_from_slice(&entry.crc.to_le_bytes());
+            header.extend_from_slice(&[0xff; 8]);
+            header.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&28u16.to_le_bytes());
+            header.extend_from_slice(&[0; 10]);
+            header.extend_from_slice(&[0xff; 4]);
+            header.extend_from_slice(entry.name.as_bytes());
+            header.extend_from_slice(&1u16.to_le_bytes());
+            header.extend_from_slice(&24u16.to_le_bytes());
+            header.extend_from_slice(&entry.size.to_le_bytes());
+            header.extend_from_slice(&entry.size.to_le_bytes());
+            header.extend_from_slice(&entry.offset.to_le_bytes());
+            self.write_raw(&header)?;
+        }
+        let directory_size = self.position - directory_offset;
+        let end_offset = self.position;
+        let count = entries.len() as u64;
+
+        let mut end = Vec::with_capacity(98);
+        end.extend_from_slice(&0x0606_4b50u32.to_le_bytes());
+        end.extend_from_slice(&44u64.to_le_bytes());
+        end.extend_from_slice(&ZIP64_VERSION.to_le_bytes());
+        end.extend_from_slice(&ZIP64_VERSION.to_le_bytes());
+        end.extend_from_slice(&[0; 8]);
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&directory_size.to_le_bytes());
+        end.extend_from_slice(&directory_offset.to_le_bytes());
+
+        end.extend_from_slice(&0x0706_4b50u32.to_le_bytes());
+        end.extend_from_slice(&0u32.to_le_bytes());
+        end.extend_from_slice(&end_offset.to_le_bytes());
+        end.extend_from_slice(&1u32.to_le_bytes());
+
+        end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        end.extend_from_slice(&[0; 4]);
+        end.extend_from_slice(&(count.min(0xffff) as u16).to_le_bytes());
+        end.extend_from_slice(&(count.min(0xffff) as u16).to_le_bytes());
+        end.extend_from_slice(&(directory_size.min(0xffff_ffff) as u32).to_le_bytes());
+        end.extend_from_slice(&[0xff; 4]);
+        end.extend_from_slice(&0u16.to_le_bytes());
+        self.write_raw(&end)?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Parquet Tables
+// ------------------------------------------------------------------------
+
+/// A Parquet file of `(row, column, score)` rows written through
+/// `pyarrow.parquet.ParquetWriter`, one row group per batch. The columns of
+/// each batch are handed to Arrow as buffers, without a Python object per
+/// value.
+pub struct ParquetWriter {
+    writer: PyObject,
+    schema: PyObject,
+}
+
+impl ParquetWriter {
+    fn create(py: Python<'_>, path: &Path) -> PyResult<Self> {
+        let pyarrow = py.import("pyarrow")?;
+        let fields = vec![
+            ("row", pyarrow.call_method0("uint64")?),
+            ("column", pyarrow.call_method0("uint64")?),
+            ("score", pyarrow.call_method0("float64")?),
+        ];
+        let schema = pyarrow.call_method1("schema", (fields,))?;
+        let writer = py.import("pyarrow.parquet")?.call_method1("ParquetWriter", (path, schema))?;
+        Ok(Self { writer: writer.into(), schema: schema.into() })
+    }
+
+    fn write(&self, py: Python<'_>, rows: &[u64], columns: &[u64], scores: &[f64]) -> PyResult<()> {
+        let arrays = vec![
+            crate::vectorized_arrow::uint64_array(py, rows)?,
+            crate::vectorized_arrow::uint64_array(py, columns)?,
+            crate::vectorized_arrow::float64_array(py, scores)?,
+        ];
+        let kwargs = [("schema", self.schema.clone_ref(py))].into_py_dict(py);
+        let table = py.import("pyarrow")?.getattr("Table")?.call_method("from_arrays", (arrays,), Some(kwargs))?;
+        self.writer.call_method1(py, "write_table", (table,))?;
+        Ok(())
+    }
+
+    fn finish(self, py: Python<'_>) -> PyResult<()> {
+        self.writer.call_method0(py, "close")?;
+        Ok(())
+    }
+}
+
+
+// ------------------------------------------------------------------------
+//  Result Writers
+// ------------------------------------------------------------------------
+
+/// Sibling of `path` for a temporary file named after `part`.
+fn part_path(path: &Path, part: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", part));
+    path.with_file_name(name)
+}
+
+/// Destination of a dense matrix of scores, handed over one tile at a time
+/// in row-major order: a 2D `.npy` array, an `.npz` archive holding it as
+/// `scores`, or a Parquet table of one row per cell.
+pub enum DenseWriter {
+    Npy(BufWriter<File>),
+    Npz(ZipWriter),
+    Parquet(ParquetWriter),
+}
+
+impl DenseWriter {
+    pub fn create(py: Python<'_>, path: &Path, format: Format, rows: usize, columns: usize) -> PyResult<Self> {
+        Ok(match format {
+            Format::Npy => {
+                let mut file = BufWriter::new(File::create(path)?);
+                file.write_all(&npy_header("<f8", &[rows, columns]))?;
+                Self::Npy(file)
+            }
+            Format::Npz => {
+                let mut zip = ZipWriter::create(path)?;
+                zip.start("scores.npy")?;
+                zip.write(&npy_header("<f8", &[rows, columns]))?;
+                Self::Npz(zip)
+            }
+            Format::Parquet => Self::Parquet(ParquetWriter::create(py, path)?),
+        })
+    }
+
+    /// Writes the tile of `width` columns whose top-left cell is at `row` and
+    /// `column`, which must follow the previous tile in row-major order.
+    pub fn write(&mut self, py: Python<'_>, row: usize, column: usize, width: usize, tile: &[f64]) -> PyResult<()> {
+        let bytes = || tile.iter().flat_map(|score| score.to_le_bytes()).collect::<Vec<u8>>();
+        match self {
+            Self::Npy(file) => file.write_all(&bytes())?,
+            Self::Npz(zip) => zip.write(&bytes())?,
+            Self::Parquet(parquet) => {
+                let rows: Vec<u64> = (0..tile.len()).map(|k| (row + k / width) as u64).collect();
+                let columns: Vec<u64> = (0..tile.len()).map(|k| (column + k % width) as u64).collect();
+                parquet.write(py, &rows, &columns, tile)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(self, py: Python<'_>) -> PyResult<()> {
+        match self {
+            Self::Npy(mut file) => file.flush()?,
+            Self::Npz(mut zip) => {
+                zip.finish_entry()?;
+                zip.finish()?;
+            }
+            Self::Parquet(parquet) => parquet.finish(py)?,
+        }
+        Ok(())
+    }
+}
+
+/// Destination of the `(row, column, score)` triples of a sparse matrix,
+/// handed over one batch at a time: an `.npz` archive of the arrays `row`,
+/// `column` and `score`, or a Parquet table of one row per triple. The
+/// arrays of an archive are spooled to temporary files next to it until
+/// their lengths are known.
+pub enum SparseWriter {
+    Npz { path: PathBuf, spools: Box<[Spool; 3]> },
+    Parquet(ParquetWriter),
+}
+
+impl SparseWriter {
+    pub fn create(py: Python<'_>, path: &Path, format: Format) -> PyResult<Self> {
+        Ok(match format {
+            Format::Npy => {
+                return Err(InvalidParameterError::new_err(
+                    "sparse results are three arrays, which need an 'npz' or 'parquet' file",
+                ))
+            }
+            Format::Npz => Self::Npz {
+                path: path.to_path_buf(),
+                spools: Box::new([
+                    Spool::create(part_path(path, "row"), "<u8")?,
+                    Spool::create(part_path(path, "column"), "<u8")?,
+                    Spool::create(part_path(path, "score"), "<f8")?,
+                ]),
+            },
+            Format::Parquet => Self::Parquet(ParquetWriter::create(py, path)?),
+        })
+    }
+
+    pub fn write(&mut self, py: Python<'_>, rows: &[u64], columns: &[u64], scores: &[f64]) -> PyResult<()> {
+        match self {
+            Self::Npz { spools, .. } => {
+                let [row_spool, column_spool, score_spool] = &mut **spools;
+                for ((&row, &column), &score) in rows.iter().zip(columns).zip(scores) {
+                    row_spool.push(row.to_le_bytes())?;
+                    column_spool.push(column.to_le_bytes())?;
+                    score_spool.push(score.to_le_bytes())?;
+                }
+            }
+            Self::Parquet(parquet) => parquet.write(py, rows, columns, scores)?,
+        }
+        Ok(())
+    }
+
+    pub fn finish(self, py: Python<'_>) -> PyResult<()> {
+        match self {
+            Self::Npz { path, spools } => {
+                let mut zip = ZipWriter::create(&path)?;
+                for (spool, name) in (*spools).into_iter().zip(["row.npy", "column.npy", "score.npy"]) {
+                    let part = spool.path.clone();
+                    zip.start(name)?;
+                    zip.copy(spool.finish()?)?;
+                    zip.finish_entry()?;
+                    fs::remove_file(part)?;
+                }
+                zip.finish()
+            }
+            Self::Parquet(parquet) => parquet.finish(py),
+        }
+    }
+
+    /// Removes the temporary files of an archive that will not be finished.
+    pub fn discard(self) {
+        if let Self::Npz { spools, .. } = self {
+            for spool in *spools {
+                let _ = fs::remove_file(&spool.path);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(data: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes(data[at..at + 2].try_into().unwrap())
+    }
+
+    fn u32_at(data: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+    }
+
+    fn u64_at(data: &[u8], at: usize) -> u64 {
+        u64::from_le_bytes(data[at..at + 8].try_into().unwrap())
+    }
+
+    /// Path in the temporary directory unique to this process and `name`.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("py-strsim-{}-{}", std::process::id(), name))
+    }
+
+    /// Checks the `.npy` header at the start of `data` and returns its
+    /// dictionary and the data after it.
+    fn parse_npy(data: &[u8]) -> (&str, &[u8]) {
+        assert_eq!(&data[..8], b"\x93NUMPY\x01\x00");
+        let end = 10 + u16_at(data, 8) as usize;
+        assert_eq!(end % 64, 0, "data must start aligned to 64 bytes");
+        assert_eq!(data[end - 1], b'\n');
+        (std::str::from_utf8(&data[10..end]).unwrap().trim_end(), &data[end..])
+    }
+
+    /// Checks the records of a zip archive written by `ZipWriter` and returns
+    /// the name and data of each of its entries.
+    fn parse_zip(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        // End of central directory, then the zip64 locator and end record.
+        let end = data.len() - 22;
+        assert_eq!(u32_at(data, end), 0x0605_4b50);
+        assert_eq!(u32_at(data, end + 16), 0xffff_ffff);
+        let locator = end - 20;
+        assert_eq!(u32_at(data, locator), 0x0706_4b50);
+        let record = u64_at(data, locator + 8) as usize;
+        assert_eq!(record, locator - 56);
+        assert_eq!(u32_at(data, record), 0x0606_4b50);
+        let count = u64_at(data, record + 32) as usize;
+        assert_eq!(u16_at(data, end + 10) as usize, count);
+        let directory_size = u64_at(data, record + 40) as usize;
+        let mut at = u64_at(data, record + 48) as usize;
+        assert_eq!(at + directory_size, record);
+
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(data, at), 0x0201_4b50);
+            assert_eq!(u16_at(data, at + 6), ZIP64_VERSION);
+            let crc = u32_at(data, at + 16);
+            assert_eq!((u32_at(data, at + 20), u32_at(data, at + 24)), (0xffff_ffff, 0xffff_ffff));
+            let name_len = u16_at(data, at + 28) as usize;
+            assert_eq!(u32_at(data, at + 42), 0xffff_ffff);
+            let name = String::from_utf8(data[at + 46..at + 46 + name_len].to_vec()).unwrap();
+            let extra = at + 46 + name_len;
+            assert_eq!((u16_at(data, extra), u16_at(data, extra + 2)), (1, 24));
+            let size = u64_at(data, extra + 4);
+            assert_eq!(u64_at(data, extra + 12), size);
+            let offset = u64_at(data, extra + 20) as usize;
+            at = extra + 28;
+
+            assert_eq!(u32_at(data, offset), 0x0403_4b50);
+            assert_eq!(u32_at(data, offset + 14), crc);
+            assert_eq!(u16_at(data, offset + 26) as usize, name_len);
+            let extra = offset + 30 + name_len;
+            assert_eq!((u16_at(data, extra), u16_at(data, extra + 2)), (1, 16));
+            assert_eq!((u64_at(data, extra + 4), u64_at(data, extra + 12)), (size, size));
+            let contents = data[extra + 20..extra + 20 + size as usize].to_vec();
+            assert_eq!(crc32_update(0, &contents), crc, "checksum of {}", name);
+            entries.push((name, contents));
+        }
+        assert_eq!(at, record);
+        entries
+    }
+
+    /// Reads the archive at `path` with Python's `zipfile`, which checks the
+    /// checksum of each entry, and returns the names of its entries.
+    fn zipfile_names(path: &Path) -> Vec<String> {
+        Python::with_gil(|py| {
+            let archive = py.import("zipfile").unwrap().getattr("ZipFile").unwrap().call1((path,)).unwrap();
+            assert!(archive.call_method0("testzip").unwrap().is_none());
+            archive.call_method0("namelist").unwrap().extract().unwrap()
+        })
+    }
+
+    #[test]
+    fn npy_header_is_aligned_and_readable() {
+        for shape in [&[0][..], &[7], &[3, 4], &[usize::MAX, usize::MAX]] {
+            let header = npy_header("<f8", shape);
+            assert_eq!(header.len(), NPY_HEADER_LEN);
+            let (dict, rest) = parse_npy(&header);
+            assert!(rest.is_empty());
+            assert!(dict.starts_with("{'descr': '<f8', 'fortran_order': False, 'shape': ("));
+        }
+        assert!(parse_npy(&npy_header("<u8", &[7])).0.contains("'shape': (7,)"));
+        assert!(parse_npy(&npy_header("<f8", &[3, 4])).0.contains("'shape': (3, 4)"));
+    }
+
+    #[test]
+    fn dense_npz_holds_the_matrix() {
+        let path = temp_path("dense.npz");
+        Python::with_gil(|py| {
+            let mut writer = DenseWriter::create(py, &path, Format::Npz, 2, 3).unwrap();
+            writer.write(py, 0, 0, 3, &[1.0, 2.0, 3.0]).unwrap();
+            writer.write(py, 1, 0, 3, &[4.0, 5.0, f64::NAN]).unwrap();
+            writer.finish(py).unwrap();
+        });
+        let data = fs::read(&path).unwrap();
+        let entries = parse_zip(&data);
+        assert_eq!(zipfile_names(&path), ["scores.npy"]);
+        fs::remove_file(&path).unwrap();
+
+        let (dict, values) = parse_npy(&entries[0].1);
+        assert!(dict.contains("'shape': (2, 3)"));
+        let values: Vec<f64> = values.chunks(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(values[..5], [1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(values[5].is_nan());
+    }
+
+    #[test]
+    fn sparse_npz_holds_the_triples() {
+        let path = temp_path("sparse.npz");
+        let (rows, columns, scores) = ([0, 0, 5], [1, 2, 3], [0.5, 0.25, 1.0]);
+        Python::with_gil(|py| {
+            let mut writer = SparseWriter::create(py, &path, Format::Npz).unwrap();
+            writer.write(py, &rows[..2], &columns[..2], &scores[..2]).unwrap();
+            writer.write(py, &rows[2..], &columns[2..], &scores[2..]).unwrap();
+            writer.finish(py).unwrap();
+        });
+        let data = fs::read(&path).unwrap();
+        let entries = parse_zip(&data);
+        assert_eq!(zipfile_names(&path), ["row.npy", "column.npy", "score.npy"]);
+        fs::remove_file(&path).unwrap();
+        assert!(!part_path(&path, "row").exists());
+
+        let arrays: Vec<(&str, Vec<[u8; 8]>)> = entries
+            .iter()
+            .map(|(_, data)| {
+                let (dict, values) = parse_npy(data);
+                assert!(dict.contains("'shape': (3,)"), "{}", dict);
+                (dict, values.chunks(8).map(|b| b.try_into().unwrap()).collect())
+            })
+            .collect();
+        assert!(arrays[0].0.contains("'<u8'") && arrays[2].0.contains("'<f8'"));
+        assert_eq!(arrays[0].1, rows.map(u64::to_le_bytes));
+        assert_eq!(arrays[1].1, columns.map(u64::to_le_bytes));
+        assert_eq!(arrays[2].1, scores.map(f64::to_le_bytes));
+    }
+
+    #[test]
+    fn empty_archive_has_only_end_records() {
+        let path = temp_path("empty.zip");
+        ZipWriter::create(&path).unwrap().finish().unwrap();
+        let data = fs::read(&path).unwrap();
+        assert!(parse_zip(&data).is_empty());
+        assert!(zipfile_names(&path).is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
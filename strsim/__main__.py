@@ -0,0 +1,165 @@
+"""Command-line interface to `strsim`, run as `python -m strsim`.
+
+Every command does its comparisons in the Rust extension, in parallel on
+`--threads` threads. Files of strings hold one string per line, and `-`
+reads standard input.
+"""
+
+import argparse
+import csv
+import sys
+
+from . import StrSimError, vectorized
+
+
+def read_lines(path):
+    if path == '-':
+        return sys.stdin.read().splitlines()
+    with open(path, encoding='utf-8') as f:
+        return f.read().splitlines()
+
+
+def read_table(path):
+    if path == '-':
+        return list(csv.DictReader(sys.stdin))
+    with open(path, encoding='utf-8', newline='') as f:
+        return list(csv.DictReader(f))
+
+
+def format_score(score):
+    return repr(int(score)) if float(score).is_integer() else repr(score)
+
+
+def score(args):
+    [value] = vectorized.score_pairs(args.metric, [(args.a, args.b)])
+    print(format_score(value))
+
+
+def search(args):
+    candidates = read_lines(args.candidates)
+    if args.threshold is not None:
+        matches = vectorized.threshold(args.metric, args.query, candidates, args.threshold, sort=True, n_threads=args.threads)
+        matches = matches[:args.top_k] if args.top_k is not None else matches
+    elif args.top_k is not None:
+        matches = vectorized.top_k(args.metric, args.query, candidates, args.top_k, n_threads=args.threads)
+    else:
+        matches = vectorized.ranked(args.metric, args.query, candidates, n_threads=args.threads)
+    for index, value in matches:
+        print(f'{format_score(value)}\t{candidates[index]}')
+
+
+def dedupe(args):
+    strings = read_lines(args.strings)
+    groups = vectorized.dedupe(strings, args.metric, args.threshold, n_threads=args.threads)
+    for index, (string, group) in enumerate(zip(strings, groups)):
+        if args.groups:
+            print(f'{group}\t{string}')
+        elif group == index:
+            print(string)
+
+
+def join(args):
+    left, right = read_table(args.left), read_table(args.right)
+    left_on, right_on = args.left_on or args.on, args.right_on or args.on
+    if left_on is None or right_on is None:
+        raise SystemExit('join: give the key column with --on, or with --left-on and --right-on')
+    for rows, column, path in ((left, left_on, args.left), (right, right_on, args.right)):
+        if rows and column not in rows[0]:
+            raise SystemExit(f"join: {path} has no column '{column}'")
+
+    joined = vectorized.fuzzy_join(
+        [row[left_on] for row in left],
+        [row[right_on] for row in right],
+        args.metric,
+        args.threshold,
+        how=args.how,
+        top_k=args.top_k,
+        n_threads=args.threads,
+    )
+    left_columns = list(left[0]) if left else []
+    right_columns = list(right[0]) if right else []
+    # Columns present in both tables are told apart as pandas.merge does.
+    shared = set(left_columns) & set(right_columns)
+    header = [f'{c}_x' if c in shared else c for c in left_columns]
+    header += [f'{c}_y' if c in shared else c for c in right_columns]
+    writer = csv.writer(sys.stdout, lineterminator='\n')
+    writer.writerow(header + ['score'])
+    for i, j, value in joined:
+        row = [left[i][c] for c in left_columns] if i is not None else [''] * len(left_columns)
+        row += [right[j][c] for c in right_columns] if j is not None else [''] * len(right_columns)
+        writer.writerow(row + ['' if value is None else format_score(value)])
+
+
+def parser(prog='python -m strsim'):
+    root = argparse.ArgumentParser(prog=prog, description=__doc__.splitlines()[0])
+    threads = argparse.ArgumentParser(add_help=False)
+    threads.add_argument('--threads', type=int, default=None, help='number of threads, all cores by default or with 0')
+    commands = root.add_subparsers(dest='command', required=True)
+
+    command = commands.add_parser('score', help='score two strings')
+    command.add_argument('metric', help='name of the metric, e.g. levenshtein')
+    command.add_argument('a')
+    command.add_argument('b')
+    command.set_defaults(run=score)
+
+    command = commands.add_parser(
+        'search',
+        parents=[threads],
+        help='score a query against a file of candidates',
+        description='Prints the score and the text of the matching candidates, best match first.',
+    )
+    command.add_argument('metric', help='name of the metric, e.g. levenshtein')
+    command.add_argument('query')
+    command.add_argument('candidates', help='file of candidates, one per line, or - for stdin')
+    command.add_argument('-k', '--top-k', type=int, default=None, help='number of matches to print')
+    command.add_argument('-t', '--threshold', type=float, default=None, help='worst score of a match')
+    command.set_defaults(run=search)
+
+    command = commands.add_parser(
+        'dedupe',
+        parents=[threads],
+        help='drop the near-duplicate lines of a file',
+        description='Prints the first line of each group of near-duplicates, in the order of the file.',
+    )
+    command.add_argument('metric', help='name of the metric, e.g. levenshtein')
+    command.add_argument('threshold', type=float, help='worst score of two near-duplicates')
+    command.add_argument('strings', help='file of strings, one per line, or - for stdin')
+    command.add_argument(
+        '--groups',
+        action='store_true',
+        help='print every line after the index of the first line of its group instead',
+    )
+    command.set_defaults(run=dedupe)
+
+    command = commands.add_parser(
+        'join',
+        parents=[threads],
+        help='fuzzy-join two CSV files on a key column',
+        description='Writes the joined rows as CSV, with the score of each match in a last column.',
+    )
+    command.add_argument('metric', help='name of the metric, e.g. levenshtein')
+    command.add_argument('threshold', type=float, help='worst score of two matching keys')
+    command.add_argument('left', help='first CSV file with a header, or - for stdin')
+    command.add_argument('right', help='second CSV file with a header')
+    command.add_argument('--on', help='key column of both files')
+    command.add_argument('--left-on', help='key column of the first file')
+    command.add_argument('--right-on', help='key column of the second file')
+    command.add_argument('--how', choices=['inner', 'left', 'right', 'outer'], default='left')
+    command.add_argument('-k', '--top-k', type=int, default=1, help='matches kept per key of the first file')
+    command.set_defaults(run=join)
+    return root
+
+
+def main(argv=None, prog='python -m strsim'):
+    args = parser(prog).parse_args(argv)
+    try:
+        args.run(args)
+    except BrokenPipeError:
+        # Piping into `head` closes stdout early, which is not an error.
+        sys.stderr.close()
+    except (StrSimError, ValueError, OSError) as error:
+        raise SystemExit(f'{args.command}: {error}')
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,94 @@
+"""Tests of the command-line interface, run with `python -m unittest` once the
+extension is built."""
+
+import contextlib
+import io
+import os
+import subprocess
+import sys
+import tempfile
+import unittest
+
+from strsim.__main__ import main
+
+
+class CommandLineTest(unittest.TestCase):
+    def setUp(self):
+        directory = tempfile.TemporaryDirectory()
+        self.addCleanup(directory.cleanup)
+        self.directory = directory.name
+
+    def write(self, name, text):
+        path = os.path.join(self.directory, name)
+        with open(path, 'w', encoding='utf-8') as f:
+            f.write(text)
+        return path
+
+    def run_main(self, *argv):
+        output = io.StringIO()
+        with contextlib.redirect_stdout(output):
+            main(list(argv))
+        return output.getvalue()
+
+    def test_score(self):
+        self.assertEqual(self.run_main('score', 'levenshtein', 'kitten', 'sitting'), '3\n')
+        self.assertEqual(self.run_main('score', 'jaro', 'abc', 'abc'), '1\n')
+        self.assertEqual(self.run_main('score', 'normalized_levenshtein', 'ab', 'ac'), '0.5\n')
+
+    def test_search(self):
+        cities = self.write('cities.txt', 'new york\nnewark\nyork\nnew yolk\n')
+        self.assertEqual(
+            self.run_main('search', 'levenshtein', 'new yrok', cities, '-k', '2', '--threads', '1'),
+            '2\tnew york\n2\tnew yolk\n',
+        )
+        self.assertEqual(
+            self.run_main('search', 'normalized_levenshtein', 'york', cities, '--threshold', '0.5'),
+            '1\tyork\n0.5\tnew york\n',
+        )
+        self.assertEqual(len(self.run_main('search', 'levenshtein', 'york', cities).splitlines()), 4)
+
+    def test_dedupe(self):
+        names = self.write('names.txt', 'Jon\nJohn\nMary\nJon\nMarie\n')
+        self.assertEqual(self.run_main('dedupe', 'levenshtein', '1', names), 'Jon\nMary\nMarie\n')
+        self.assertEqual(
+            self.run_main('dedupe', 'levenshtein', '1', names, '--groups'),
+            '0\tJon\n0\tJohn\n2\tMary\n0\tJon\n4\tMarie\n',
+        )
+
+    def test_join(self):
+        left = self.write('left.csv', 'name,id\nAcme Inc,1\nGlobex,2\n')
+        right = self.write('right.csv', 'name,city\nACME Inc.,Springfield\nInitech,Austin\n')
+        self.assertEqual(
+            self.run_main('join', 'levenshtein', '4', left, right, '--on', 'name').splitlines(),
+            ['name_x,id,name_y,city,score', 'Acme Inc,1,ACME Inc.,Springfield,4', 'Globex,2,,,'],
+        )
+        self.assertEqual(
+            self.run_main('join', 'levenshtein', '4', left, right, '--on', 'name', '--how', 'inner').splitlines()[1:],
+            ['Acme Inc,1,ACME Inc.,Springfield,4'],
+        )
+        with self.assertRaisesRegex(SystemExit, "has no column 'key'"):
+            self.run_main('join', 'levenshtein', '3', left, right, '--on', 'key')
+
+    def test_errors_exit_with_the_command_name(self):
+        with self.assertRaisesRegex(SystemExit, '^score: .*no_such_metric'):
+            self.run_main('score', 'no_such_metric', 'a', 'b')
+        with self.assertRaisesRegex(SystemExit, '^search: '):
+            self.run_main('search', 'levenshtein', 'a', os.path.join(self.directory, 'missing.txt'))
+
+    def test_runs_as_a_module_under_both_names(self):
+        environment = dict(os.environ, PYTHONPATH=os.pathsep.join(sys.path))
+        for module in ['strsim', 'py_strsim']:
+            result = subprocess.run(
+                [sys.executable, '-m', module, 'score', 'levenshtein', 'kitten', 'sitting'],
+                capture_output=True,
+                text=True,
+                env=environment,
+                check=True,
+            )
+            self.assertEqual(result.stdout, '3\n')
+            usage = subprocess.run([sys.executable, '-m', module], capture_output=True, text=True, env=environment)
+            self.assertIn(f'usage: python -m {module}', usage.stderr)
+
+
+if __name__ == '__main__':
+    unittest.main()